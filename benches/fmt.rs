@@ -0,0 +1,97 @@
+//! Formatting of a recorded dump, as in "-a -full" on 192 threads.
+//! "buffer" vs "string_per_result" measures the outer buffer of `write_*_fmt`,
+//! the decoders of "-parse" (`src/parse`) write into the same buffer.
+//! `cargo bench --bench fmt`
+#![allow(dead_code)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use libcpuid_dump::{CpuidResult, CpuVendor, FormatConfig};
+
+#[path = "../src/raw_cpuid.rs"]
+mod raw_cpuid;
+pub use raw_cpuid::*;
+
+#[path = "../src/parse/mod.rs"]
+mod parse;
+pub use parse::*;
+
+type FmtFunc = fn(&RawCpuid, &CpuVendor, &FormatConfig, &mut String);
+
+const THREADS: usize = 192;
+/* `cpuid_dump --reference zen3_ryzen5_5600g -r -full` */
+const SNAPSHOT: &str = include_str!("zen3_ryzen5_5600g_raw.txt");
+
+fn snapshot() -> Vec<RawCpuid> {
+    SNAPSHOT
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.is_empty())
+        .map(|line| RawCpuid::from_raw_line(line).unwrap())
+        .collect()
+}
+
+fn fmt_benchmark(c: &mut Criterion) {
+    let pool = snapshot();
+    let vendor = CpuVendor::AuthenticAMD;
+    let cfg = FormatConfig::default();
+
+    let fmt_funcs: [(&str, FmtFunc); 3] = [
+        ("parse", RawCpuid::write_parse_fmt),
+        ("raw", RawCpuid::write_raw_fmt),
+        ("bin", RawCpuid::write_bin_fmt),
+    ];
+
+    for (name, fmt_func) in fmt_funcs {
+        let mut group = c.benchmark_group(name);
+
+        /* the previous path, a `String` per result collected into a `String` per thread */
+        group.bench_function("string_per_result", |b| b.iter(|| {
+            let s: String = (0..THREADS).map(|_| {
+                pool.iter().map(|rawcpuid| {
+                    let mut s = String::new();
+                    fmt_func(rawcpuid, &vendor, &cfg, &mut s);
+                    s
+                }).collect::<String>()
+            }).collect();
+
+            black_box(s)
+        }));
+
+        group.bench_function("buffer", |b| b.iter(|| {
+            let mut buf = String::with_capacity(pool.len() * TOTAL_WIDTH * 2 * THREADS);
+
+            for _ in 0..THREADS {
+                for rawcpuid in &pool {
+                    fmt_func(rawcpuid, &vendor, &cfg, &mut buf);
+                }
+            }
+
+            black_box(buf)
+        }));
+
+        group.finish();
+    }
+}
+
+/* per-leaf decoders of "-parse", the results per second */
+fn parse_benchmark(c: &mut Criterion) {
+    let pool = snapshot();
+    let vendor = CpuVendor::AuthenticAMD;
+    let cfg = FormatConfig::default();
+    let mut group = c.benchmark_group("parse_leaf");
+
+    group.throughput(Throughput::Elements(pool.len() as u64));
+    group.bench_function("zen3_ryzen5_5600g", |b| b.iter(|| {
+        let mut buf = String::with_capacity(pool.len() * TOTAL_WIDTH * 2);
+
+        for rawcpuid in &pool {
+            rawcpuid.write_parse_fmt(&vendor, &cfg, &mut buf);
+        }
+
+        black_box(buf)
+    }));
+
+    group.finish();
+}
+
+criterion_group!(benches, fmt_benchmark, parse_benchmark);
+criterion_main!(benches);
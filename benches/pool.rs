@@ -0,0 +1,90 @@
+//! Leaf pool execution, and the diff path of "-a" on a recorded dump.
+//! `cargo bench --bench pool`
+#![allow(dead_code)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use libcpuid_dump::{CpuidResult, CpuVendor, FormatConfig};
+
+#[path = "../src/raw_cpuid.rs"]
+mod raw_cpuid;
+pub use raw_cpuid::*;
+
+#[path = "../src/parse/mod.rs"]
+mod parse;
+pub use parse::*;
+
+#[path = "../src/leaf_pool.rs"]
+mod leaf_pool;
+use leaf_pool::leaf_pool;
+
+const THREADS: u32 = 192;
+/* `cpuid_dump --reference zen3_ryzen5_5600g -r -full` */
+const SNAPSHOT: &str = include_str!("zen3_ryzen5_5600g_raw.txt");
+
+fn snapshot() -> Vec<RawCpuid> {
+    SNAPSHOT
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.is_empty())
+        .map(|line| RawCpuid::from_raw_line(line).unwrap())
+        .collect()
+}
+
+/* The results of the thread, only the APIC IDs differ from the first thread */
+fn thread_snapshot(first: &[RawCpuid], apic_id: u32) -> Vec<RawCpuid> {
+    first.iter().map(|rawcpuid| {
+        let mut rawcpuid = *rawcpuid;
+        let result = &mut rawcpuid.result;
+
+        match rawcpuid.leaf {
+            0x1 => result.ebx = (result.ebx & 0x00FF_FFFF) | (apic_id << 24),
+            0xB | 0x8000_001E => result.edx = apic_id,
+            _ => {},
+        }
+
+        rawcpuid
+    }).collect()
+}
+
+fn leaf_pool_benchmark(c: &mut Criterion) {
+    c.bench_function("leaf_pool", |b| b.iter(|| black_box(leaf_pool())));
+
+    let pool = leaf_pool();
+
+    c.bench_function("exe_leaf_pool", |b| b.iter(|| {
+        let rawcpuid_pool: Vec<RawCpuid> = pool
+            .iter()
+            .map(|(leaf, sub_leaf)| RawCpuid::exe(*leaf, *sub_leaf))
+            .collect();
+
+        black_box(rawcpuid_pool)
+    }));
+}
+
+fn diff_benchmark(c: &mut Criterion) {
+    let first = snapshot();
+    let vendor = CpuVendor::AuthenticAMD;
+    let cfg = FormatConfig::default();
+    let threads: Vec<Vec<RawCpuid>> = (1..THREADS).map(|apic_id| thread_snapshot(&first, apic_id)).collect();
+
+    c.bench_function("diff_all_threads", |b| b.iter(|| {
+        let mut buf = String::with_capacity(first.len() * TOTAL_WIDTH * 2);
+
+        for rawcpuid in &first {
+            rawcpuid.write_parse_fmt(&vendor, &cfg, &mut buf);
+        }
+
+        for pool in &threads {
+            let mut pool = pool.clone();
+            retain_diff(&mut pool, &first);
+
+            for rawcpuid in &pool {
+                rawcpuid.write_parse_fmt(&vendor, &cfg, &mut buf);
+            }
+        }
+
+        black_box(buf)
+    }));
+}
+
+criterion_group!(benches, leaf_pool_benchmark, diff_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,21 @@
+/* The build metadata of "--version -json" */
+use std::process::Command;
+
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=CPUID_DUMP_GIT_COMMIT={commit}");
+    println!("cargo:rustc-env=CPUID_DUMP_TARGET={}", std::env::var("TARGET").unwrap_or_default());
+    /* a new commit or a checkout of another branch */
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    println!("cargo:rerun-if-changed=build.rs");
+}
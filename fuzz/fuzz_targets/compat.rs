@@ -0,0 +1,38 @@
+//! Arbitrary text to the parser of the `cpuid -r` compatible format, used by "--reference".
+//! `cargo +nightly fuzz run compat`
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+use libcpuid_dump::{cpuid, CpuidResult, CpuVendor, FormatConfig, Vendor};
+
+#[path = "../../src/raw_cpuid.rs"]
+mod raw_cpuid;
+pub use raw_cpuid::*;
+
+#[path = "../../src/parse/mod.rs"]
+mod parse;
+pub use parse::*;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(dump) = std::str::from_utf8(data) else { return };
+
+    let pool: Vec<RawCpuid> = dump
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(RawCpuid::from_compat_line)
+        .collect();
+
+    let vendor = pool
+        .iter()
+        .find(|rawcpuid| rawcpuid.leaf == 0x0 && rawcpuid.sub_leaf == 0x0)
+        .map_or(CpuVendor::Unknown(Vendor { ebx: 0x0, ecx: 0x0, edx: 0x0 }), |rawcpuid| CpuVendor::from(&rawcpuid.result));
+
+    for rawcpuid in &pool {
+        let mut buf = String::new();
+        rawcpuid.write_compat_fmt(&vendor, &FormatConfig::DEFAULT, &mut buf);
+        assert_eq!(RawCpuid::from_compat_line(&buf), Some(*rawcpuid));
+
+        rawcpuid.write_parse_fmt(&vendor, &FormatConfig::DEFAULT, &mut buf);
+    }
+});
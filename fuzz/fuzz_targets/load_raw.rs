@@ -0,0 +1,42 @@
+//! Arbitrary text to the loader of "--load", the dump saved with "-r" and the CPU-Z/HWiNFO reports.
+//! `cargo +nightly fuzz run load_raw`
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+use libcpuid_dump::{cpuid, CpuidResult, CpuVendor, FormatConfig};
+
+#[path = "../../src/raw_cpuid.rs"]
+mod raw_cpuid;
+pub use raw_cpuid::*;
+
+#[path = "../../src/parse/mod.rs"]
+mod parse;
+pub use parse::*;
+
+#[path = "../../src/load_report.rs"]
+mod load_report;
+use load_report::{parse_report, ReportKind};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(dump) = std::str::from_utf8(data) else { return };
+    let pools = match ReportKind::detect(dump) {
+        Some(_) => parse_report(dump),
+        None => split_raw_dump(dump),
+    };
+    let Ok(pools) = pools else { return };
+
+    for (_thread_id, pool) in &pools {
+        /* same as `CpuidDump::from_rawcpuid_pool` */
+        let Some(leaf_00h) = pool.iter().find(|rawcpuid| rawcpuid.leaf == 0x0 && rawcpuid.sub_leaf == 0x0) else {
+            continue;
+        };
+        let vendor = CpuVendor::from(&leaf_00h.result);
+        let mut buf = String::new();
+
+        for rawcpuid in pool {
+            rawcpuid.write_parse_fmt(&vendor, &FormatConfig::DEFAULT, &mut buf);
+            rawcpuid.write_bin_fmt(&vendor, &FormatConfig::DEFAULT, &mut buf);
+        }
+    }
+});
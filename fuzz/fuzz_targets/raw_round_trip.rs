@@ -0,0 +1,46 @@
+//! Near-valid dumps: the arbitrary results formatted with "-r", then loaded again.
+//! Every decoder of "-parse" gets the arbitrary registers, for the vendors of the decoders.
+//! `cargo +nightly fuzz run raw_round_trip`
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+use libcpuid_dump::{cpuid, CpuidResult, CpuVendor, FormatConfig, Vendor};
+
+#[path = "../../src/raw_cpuid.rs"]
+mod raw_cpuid;
+pub use raw_cpuid::*;
+
+#[path = "../../src/parse/mod.rs"]
+mod parse;
+pub use parse::*;
+
+fuzz_target!(|input: Vec<(u32, u32, [u32; 4])>| {
+    let pool: Vec<RawCpuid> = input.into_iter().map(|(leaf, sub_leaf, [eax, ebx, ecx, edx])| {
+        RawCpuid { leaf, sub_leaf, result: CpuidResult { eax, ebx, ecx, edx } }
+    }).collect();
+
+    let vendors = [
+        CpuVendor::GenuineIntel,
+        CpuVendor::AuthenticAMD,
+        CpuVendor::HygonGenuine,
+        CpuVendor::CentaurHauls,
+        CpuVendor::Unknown(Vendor { ebx: 0x0, ecx: 0x0, edx: 0x0 }),
+    ];
+    let mut dump = String::from("# [Pkg: 000, Core: 000, SMT: 000, x2APIC: 000, Thread: 000]\n");
+
+    for rawcpuid in &pool {
+        rawcpuid.write_raw_fmt(&CpuVendor::AuthenticAMD, &FormatConfig::DEFAULT, &mut dump);
+    }
+
+    let pools = split_raw_dump(&dump).unwrap();
+    assert_eq!(pools, vec![(Some(0), pool.clone())]);
+
+    for vendor in &vendors {
+        let mut buf = String::new();
+
+        for rawcpuid in &pool {
+            rawcpuid.write_parse_fmt(vendor, &FormatConfig::DEFAULT, &mut buf);
+        }
+    }
+});
@@ -1,23 +1,69 @@
-use crate::{CpuidResult};
+use crate::{CpuidResult, Reg};
 
-/// Physical/Virtual Addresses size (bit) available from `CPUID.(EAX=8000_0008h):EAX`
+/// Physical/Virtual Addresses size (bit) available from `CPUID.(EAX=8000_0008h):EAX`,
+/// with the support of ignoring the upper address bits from the other leaves
 #[derive(Debug, Clone)]
 pub struct AddressSize {
     pub physical: u8,
     pub virtual_: u8,
+    /// GuestPhysAddrSize: EAX[23:16] (AMD), 0 if it is the same as `physical`
+    pub guest_physical: u8,
+    /// Intel LAM (Linear Address Masking): CPUID.(EAX=07h, ECX=1):EAX[26]
+    pub lam: bool,
+    /// AMD UAI (Upper Address Ignore): CPUID.(EAX=8000_0021h):EAX[7]
+    pub uai: bool,
 }
 
 impl From<&CpuidResult> for AddressSize {
     fn from(cpuid: &CpuidResult) -> Self {
         Self {
             physical: (cpuid.eax & 0xFF) as u8,
-            virtual_: ((cpuid.eax >> 8) & 0xFF) as u8,
+            virtual_: cpuid.eax.bits(8..16) as u8,
+            guest_physical: cpuid.eax.bits(16..24) as u8,
+            lam: false,
+            uai: false,
         }
     }
 }
 
 impl AddressSize {
+    /// `[80_08_x0, 00_07_x1, 80_21_x0]`
+    pub fn from_cpuid_array(cpuid_array: [CpuidResult; 3]) -> Self {
+        let [cpuid_80_08, cpuid_00_07_x1, cpuid_80_21] = cpuid_array;
+
+        Self {
+            lam: cpuid_00_07_x1.eax.bit(26),
+            uai: cpuid_80_21.eax.bit(7),
+            ..Self::from(&cpuid_80_08)
+        }
+    }
+
+    /// Guest physical address size, `physical` if GuestPhysAddrSize is 0
+    pub fn guest_physical_or_physical(&self) -> u8 {
+        if self.guest_physical == 0 { self.physical } else { self.guest_physical }
+    }
+
+    #[cfg(target_arch = "x86_64")]
     pub fn get() -> Self {
-        Self::from(&cpuid!(0x8000_0008, 0x0))
+        const ZERO: CpuidResult = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+
+        Self::from_cpuid_array([
+            cpuid!(0x8000_0008, 0x0),
+            try_cpuid!(0x7, 0x1).unwrap_or(ZERO),
+            try_cpuid!(0x8000_0021, 0x0).unwrap_or(ZERO),
+        ])
     }
 }
+
+#[test]
+fn test_addr_size() {
+    /* EPYC 9654 */
+    let cpuid_80_08 = CpuidResult { eax: 0x00003034, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+    let cpuid_80_21 = CpuidResult { eax: 0x00062FCF, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+    let zero = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+    let addr_size = AddressSize::from_cpuid_array([cpuid_80_08, zero, cpuid_80_21]);
+
+    assert_eq!((addr_size.physical, addr_size.virtual_, addr_size.guest_physical), (52, 48, 0));
+    assert_eq!(addr_size.guest_physical_or_physical(), 52);
+    assert!(addr_size.uai && !addr_size.lam);
+}
@@ -1,7 +1,8 @@
-use crate::{cpuid, CpuidResult};
+use crate::{CpuidResult, Reg};
 #[cfg(feature = "std")]
 use std::fmt;
 
+#[cfg(target_arch = "x86_64")]
 const LEAF: u32 = 0x8000_0026;
 
 /* from AMD Zen 4 */
@@ -47,7 +48,7 @@ impl From<u8> for AmdTopoLevelType {
 
 impl From<&CpuidResult> for AmdTopoLevelType {
     fn from(cpuid: &CpuidResult) -> Self {
-        Self::from(((cpuid.ecx >> 8) & 0xFF) as u8)
+        Self::from(cpuid.ecx.bits(8..16) as u8)
     }
 }
 
@@ -84,7 +85,7 @@ impl From<u8> for AmdCoreType {
 
 impl From<&CpuidResult> for AmdCoreType {
     fn from(cpuid: &CpuidResult) -> Self {
-        AmdCoreType::from(((cpuid.ebx >> 28) & 0xF) as u8)
+        AmdCoreType::from(cpuid.ebx.bits(28..32) as u8)
     }
 }
 
@@ -115,7 +116,7 @@ impl From<u8> for AmdNativeModelId {
 
 impl From<&CpuidResult> for AmdNativeModelId {
     fn from(cpuid: &CpuidResult) -> Self {
-        Self::from(((cpuid.ebx >> 24) & 0xF) as u8)
+        Self::from(cpuid.ebx.bits(24..28) as u8)
     }
 }
 
@@ -135,14 +136,14 @@ impl From<&CpuidResult> for AmdExtTopo {
         let level_type = AmdTopoLevelType::from(cpuid);
         let next_level = (cpuid.eax & 0xF) as u8;
 
-        let asymmetric_cores = ((cpuid.eax >> 31) & 0b1) == 1;
-        let hetero_cores = ((cpuid.eax >> 30) & 0b1) == 1;
+        let asymmetric_cores = cpuid.eax.bit(31);
+        let hetero_cores = cpuid.eax.bit(30);
 
-        let eff_rank_available = level_type.is_core() && (((cpuid.eax >> 29) & 0b1) == 1);
+        let eff_rank_available = level_type.is_core() && cpuid.eax.bit(29);
         let core_type = level_type.is_core().then_some(AmdCoreType::from(cpuid));
         // TODO: need AmdCoreType?
         let native_model_id = level_type.is_core().then_some(AmdNativeModelId::from(cpuid));
-        let eff_rank = level_type.is_core().then_some(((cpuid.ebx >> 16) & 0xFF) as u8);
+        let eff_rank = level_type.is_core().then_some(cpuid.ebx.bits(16..24) as u8);
 
         let num_proc = (cpuid.ebx & 0xFFFF) as u16;
         let _input_ecx = (cpuid.ecx & 0xFF) as u8;
@@ -165,10 +166,12 @@ impl From<&CpuidResult> for AmdExtTopo {
 }
 
 impl AmdExtTopo {
+    #[cfg(target_arch = "x86_64")]
     pub fn get(sub_leaf: u32) -> Self {
         Self::from(&cpuid!(LEAF, sub_leaf))
     }
 
+    #[cfg(target_arch = "x86_64")]
     pub fn is_supported() -> bool {
         const INPUT_ECX: u32 = 0x1;
         let cpuid = cpuid!(LEAF, INPUT_ECX);
@@ -0,0 +1,92 @@
+use crate::{CpuidResult, Reg};
+
+/// Extended Feature Extensions ID available from `CPUID.(EAX=8000_0008h):EBX`, AMD CPU only
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmdFtrExtId {
+    pub clzero: bool,
+    /// Instruction Retired Counter MSR
+    pub inst_ret_cnt_msr: bool,
+    /// FP Error Pointers Restored by XRSTOR
+    pub rstr_fp_err_ptrs: bool,
+    pub invlpgb: bool,
+    pub rdpru: bool,
+    pub mcommit: bool,
+    pub wbnoinvd: bool,
+    pub ibpb: bool,
+    pub int_wbinvd: bool,
+    pub ibrs: bool,
+    pub stibp: bool,
+    pub ibrs_always_on: bool,
+    pub stibp_always_on: bool,
+    pub ibrs_preferred: bool,
+    pub ibrs_same_mode: bool,
+    pub efer_lmsle_unsupported: bool,
+    pub invlpgb_nested_pages: bool,
+    pub ppin: bool,
+    pub ssbd: bool,
+    pub ssbd_virt_spec_ctrl: bool,
+    pub ssbd_not_required: bool,
+    pub cppc: bool,
+    pub psfd: bool,
+    pub btc_no: bool,
+    /// IBPB also clears the return address predictor
+    pub ibpb_ret: bool,
+}
+
+impl From<u32> for AmdFtrExtId {
+    fn from(ebx: u32) -> Self {
+        let bit = |pos: u32| -> bool { ebx.bit(pos) };
+
+        Self {
+            clzero: bit(0),
+            inst_ret_cnt_msr: bit(1),
+            rstr_fp_err_ptrs: bit(2),
+            invlpgb: bit(3),
+            rdpru: bit(4),
+            mcommit: bit(8),
+            wbnoinvd: bit(9),
+            ibpb: bit(12),
+            int_wbinvd: bit(13),
+            ibrs: bit(14),
+            stibp: bit(15),
+            ibrs_always_on: bit(16),
+            stibp_always_on: bit(17),
+            ibrs_preferred: bit(18),
+            ibrs_same_mode: bit(19),
+            efer_lmsle_unsupported: bit(20),
+            invlpgb_nested_pages: bit(21),
+            ppin: bit(23),
+            ssbd: bit(24),
+            ssbd_virt_spec_ctrl: bit(25),
+            ssbd_not_required: bit(26),
+            cppc: bit(27),
+            psfd: bit(28),
+            btc_no: bit(29),
+            ibpb_ret: bit(30),
+        }
+    }
+}
+
+impl From<&CpuidResult> for AmdFtrExtId {
+    fn from(cpuid: &CpuidResult) -> Self {
+        Self::from(cpuid.ebx)
+    }
+}
+
+impl AmdFtrExtId {
+    #[cfg(target_arch = "x86_64")]
+    pub fn get() -> Self {
+        Self::from(&cpuid!(0x8000_0008, 0x0))
+    }
+}
+
+#[test]
+fn test_amd_ftr_ext_id() {
+    /* Ryzen 5 5600G, 0x8000_0008 */
+    let cpuid = CpuidResult { eax: 0x00003030, ebx: 0x191EF657, ecx: 0x0000400B, edx: 0x00010000 };
+    let ftr = AmdFtrExtId::from(&cpuid);
+
+    assert!(ftr.clzero && ftr.inst_ret_cnt_msr && ftr.rstr_fp_err_ptrs);
+    assert!(ftr.ibpb && ftr.ibrs && ftr.stibp && ftr.ssbd && ftr.psfd);
+    assert!(!ftr.ibrs_always_on && !ftr.ssbd_virt_spec_ctrl);
+}
@@ -0,0 +1,58 @@
+use crate::{CpuidResult, Reg};
+
+/// AMD Secure Encryption capabilities available from `CPUID.(EAX=8000_001Fh)`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmdMemEncrypt {
+    pub sme: bool,
+    pub sev: bool,
+    pub sev_es: bool,
+    pub sev_snp: bool,
+    pub vmpl: bool,
+    /// C-bit (encryption bit) location in page table entry
+    pub c_bit: u8,
+    /// Reduction of physical address space in bits when memory encryption is enabled
+    pub phys_addr_reduction: u8,
+    pub num_vmpl: u8,
+    /// Number of encrypted guests supported simultaneously (ASIDs)
+    pub num_encrypted_guests: u32,
+    /// Minimum ASID value for an SEV enabled, SEV-ES disabled guest
+    pub min_sev_no_es_asid: u32,
+}
+
+impl From<&CpuidResult> for AmdMemEncrypt {
+    fn from(cpuid: &CpuidResult) -> Self {
+        let bit = |pos: u32| -> bool { cpuid.eax.bit(pos) };
+
+        Self {
+            sme: bit(0),
+            sev: bit(1),
+            sev_es: bit(3),
+            sev_snp: bit(4),
+            vmpl: bit(5),
+            c_bit: (cpuid.ebx & 0x3F) as u8,
+            phys_addr_reduction: cpuid.ebx.bits(6..12) as u8,
+            num_vmpl: cpuid.ebx.bits(12..16) as u8,
+            num_encrypted_guests: cpuid.ecx,
+            min_sev_no_es_asid: cpuid.edx,
+        }
+    }
+}
+
+impl AmdMemEncrypt {
+    #[cfg(target_arch = "x86_64")]
+    pub fn get() -> Self {
+        Self::from(&cpuid!(0x8000_001F, 0x0))
+    }
+}
+
+#[test]
+fn test_amd_mem_encrypt() {
+    /* AMD Ryzen 5 2600 */
+    let cpuid = CpuidResult { eax: 0x0000_000F, ebx: 0x0000_016F, ecx: 0x0000_000F, edx: 0x0 };
+    let enc = AmdMemEncrypt::from(&cpuid);
+
+    assert!(enc.sme && enc.sev && enc.sev_es);
+    assert!(!enc.sev_snp);
+    assert_eq!((enc.c_bit, enc.phys_addr_reduction), (47, 5));
+    assert_eq!(enc.num_encrypted_guests, 15);
+}
@@ -1,4 +1,4 @@
-use crate::{cpuid, CpuidResult, FamModStep};
+use crate::{CpuidResult, FamModStep, Reg};
 
 /* Leaf: 0x8000_0001, AMD CPU only */
 /* ref: https://en.wikipedia.org/wiki/List_of_AMD_CPU_microarchitectures */
@@ -62,7 +62,7 @@ impl fmt::Display for AmdPkgType {
 impl From<&CpuidResult> for AmdPkgType {
     fn from(cpuid: &CpuidResult) -> Self {
         let fms = FamModStep::from(cpuid);
-        let pkg_type = cpuid.ebx >> 28;
+        let pkg_type = cpuid.ebx.bits(28..32);
 
         match fms {
             FamModStep { syn_fam: 0x10, .. } => match pkg_type {
@@ -211,6 +211,7 @@ impl From<&CpuidResult> for AmdPkgType {
 }
 
 impl AmdPkgType {
+    #[cfg(target_arch = "x86_64")]
     pub fn get() -> Self {
         Self::from(&cpuid!(0x8000_0001, 0x0))
     }
@@ -0,0 +1,82 @@
+use crate::{leaf, CpuidResult, CpuidSource, Reg};
+
+/// Memory bandwidth enforcement of the L3 cache, available from `CPUID.(EAX=8000_0020h, ECX=1 or 2)`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmdBwEnforcement {
+    /// Size of the bandwidth specifier field in bits
+    pub bw_len: u32,
+    /// Number of the classes of service (COS)
+    pub cos_count: u32,
+}
+
+impl From<&CpuidResult> for AmdBwEnforcement {
+    fn from(cpuid: &CpuidResult) -> Self {
+        Self {
+            bw_len: cpuid.eax,
+            cos_count: cpuid.edx + 1,
+        }
+    }
+}
+
+impl AmdBwEnforcement {
+    /// Maximum bandwidth limit, in the granularity of 1/8 GB/s
+    pub fn max_bw(&self) -> u64 {
+        1u64.checked_shl(self.bw_len).unwrap_or(u64::MAX)
+    }
+}
+
+/// AMD PQoS Extended Features available from `CPUID.(EAX=8000_0020h)`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmdPqos {
+    /// L3 Memory Bandwidth Enforcement
+    pub l3_mbe: Option<AmdBwEnforcement>,
+    /// L3 Slow Memory Bandwidth Enforcement
+    pub l3_smbe: Option<AmdBwEnforcement>,
+    /// Number of the configurable events of BMEC (Bandwidth Monitoring Event Configuration)
+    pub bmec_events: Option<u8>,
+}
+
+impl AmdPqos {
+    /// `None` if the leaf is not supported or no feature of the leaf is supported
+    pub fn from_source<S: CpuidSource + ?Sized>(src: &S) -> Option<Self> {
+        if src.cpuid(0x8000_0000, 0x0).eax < leaf::AMD_PQOS_EXT {
+            return None;
+        }
+
+        let ebx = src.cpuid(leaf::AMD_PQOS_EXT, 0x0).ebx;
+
+        if ebx.bits(1..4) == 0x0 {
+            return None;
+        }
+
+        let sub_leaf = |bit: u32| if ebx.bit(bit) { Some(src.cpuid(leaf::AMD_PQOS_EXT, bit)) } else { None };
+
+        Some(Self {
+            l3_mbe: sub_leaf(1).as_ref().map(AmdBwEnforcement::from),
+            l3_smbe: sub_leaf(2).as_ref().map(AmdBwEnforcement::from),
+            bmec_events: sub_leaf(3).map(|cpuid| cpuid.ebx.bits(0..8) as u8),
+        })
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn get() -> Option<Self> {
+        Self::from_source(&crate::NativeCpuid)
+    }
+}
+
+#[test]
+fn test_amd_pqos() {
+    /* AMD Ryzen 5 5600G */
+    let cpuids = [
+        (0x8000_0000, 0x0, CpuidResult { eax: 0x8000_0023, ebx: 0x0, ecx: 0x0, edx: 0x0 }),
+        (0x8000_0020, 0x0, CpuidResult { eax: 0x0, ebx: 0x2, ecx: 0x0, edx: 0x0 }),
+        (0x8000_0020, 0x1, CpuidResult { eax: 0xB, ebx: 0x0, ecx: 0x0, edx: 0xF }),
+    ];
+    let pqos = AmdPqos::from_source(&cpuids[..]).unwrap();
+    let mbe = pqos.l3_mbe.as_ref().unwrap();
+
+    assert_eq!((mbe.bw_len, mbe.cos_count, mbe.max_bw()), (11, 16, 2048));
+    assert!(pqos.l3_smbe.is_none() && pqos.bmec_events.is_none());
+
+    assert!(AmdPqos::from_source(&cpuids[1..]).is_none());
+}
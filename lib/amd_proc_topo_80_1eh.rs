@@ -1,4 +1,4 @@
-use crate::CpuidResult;
+use crate::{CpuidResult, Reg};
 
 /// Information available from `CPUID.(EAX=8000_001Eh)`, AMD CPU only
 #[derive(Debug, Clone)]
@@ -13,7 +13,7 @@ pub struct AmdProcTopo {
 impl From<&CpuidResult> for AmdProcTopo {
     fn from(cpuid: &CpuidResult) -> Self {
         let ext_apic_id = cpuid.eax;
-        let threads_per_core = (((cpuid.ebx >> 8) & 0xFF) as u8).saturating_add(1);
+        let threads_per_core = (cpuid.ebx.bits(8..16) as u8).saturating_add(1);
         let core_id = (cpuid.ebx & 0xFF) as u8;
         let nodes_per_processor = (cpuid.ecx & 0b111) as u8;
         let node_id = (cpuid.ecx & 0xFF) as u8;
@@ -29,6 +29,7 @@ impl From<&CpuidResult> for AmdProcTopo {
 }
 
 impl AmdProcTopo {
+    #[cfg(target_arch = "x86_64")]
     pub fn get() -> Self {
         Self::from(&cpuid!(0x8000_001E, 0x0))
     }
@@ -1,4 +1,4 @@
-use crate::{cpuid, CpuidResult};
+use crate::{CpuidResult, Reg};
 
 /// Information available from `CPUID.(EAX=8000_0001h)`, AMD CPU only
 #[derive(Debug, Clone)]
@@ -13,10 +13,10 @@ pub struct AmdSizeId {
 
 impl From<&CpuidResult> for AmdSizeId {
     fn from(cpuid: &CpuidResult) -> Self {
-        let perf_tsc_size = 40 + ((cpuid.ecx >> 16) & 0b11) as u8 * 8;
-        let apic_id_size = ((cpuid.ecx >> 12) & 0xF) as u8;
+        let perf_tsc_size = 40 + cpuid.ecx.bits(16..18) as u8 * 8;
+        let apic_id_size = cpuid.ecx.bits(12..16) as u8;
         let num_thread = ((cpuid.ecx & 0xFF) as u8).saturating_add(1);
-        let rdpru_max_input = (cpuid.edx >> 16) as u16;
+        let rdpru_max_input = cpuid.edx.bits(16..32) as u16;
         let invlpgb_max_page = (cpuid.edx & 0xFFFF) as u16;
 
         Self {
@@ -30,6 +30,7 @@ impl From<&CpuidResult> for AmdSizeId {
 }
 
 impl AmdSizeId {
+    #[cfg(target_arch = "x86_64")]
     pub fn get() -> Self {
         Self::from(&cpuid!(0x8000_0008, 0x0))
     }
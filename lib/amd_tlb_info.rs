@@ -39,19 +39,19 @@ impl fmt::Display for TlbAssoc {
 }
 
 #[derive(Debug, Clone)]
-pub struct TlbInfo {
+pub struct TlbEntries {
     pub size: u16,
     pub assoc: TlbAssoc,
 }
 
 #[cfg(feature = "std")]
-impl fmt::Display for TlbInfo {
+impl fmt::Display for TlbEntries {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:>4}_entry, {:>6}_way", self.size, self.assoc)
     }
 }
 
-impl TlbInfo {
+impl TlbEntries {
     pub(crate) fn half_size(&self) -> Self {
         Self {
             size: self.size / 2,
@@ -103,18 +103,18 @@ impl TlbInfo {
 #[derive(Debug, Clone)]
 pub struct Tlb {
     pub type_: TlbType,
-    pub page_4k: TlbInfo,
-    pub page_2m: TlbInfo,
-    pub page_4m: TlbInfo,
+    pub page_4k: TlbEntries,
+    pub page_2m: TlbEntries,
+    pub page_4m: TlbEntries,
 }
 
 impl Tlb {
     pub fn reg(type_: TlbType, reg_4k: u16, reg_2m4m: u16) -> Self {
         let [page_4k, page_2m] = match type_ {
             TlbType::L1d |
-            TlbType::L1i => [TlbInfo::from_reg_l1(reg_4k), TlbInfo::from_reg_l1(reg_2m4m)],
+            TlbType::L1i => [TlbEntries::from_reg_l1(reg_4k), TlbEntries::from_reg_l1(reg_2m4m)],
             TlbType::L2d |
-            TlbType::L2i => [TlbInfo::from_reg_l2(reg_4k), TlbInfo::from_reg_l2(reg_2m4m)],
+            TlbType::L2i => [TlbEntries::from_reg_l2(reg_4k), TlbEntries::from_reg_l2(reg_2m4m)],
         };
         let page_4m = page_2m.half_size();
 
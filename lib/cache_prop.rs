@@ -1,4 +1,6 @@
-use crate::{cpuid, CpuidResult, CpuVendor};
+use crate::{CpuidResult, Reg};
+#[cfg(target_arch = "x86_64")]
+use crate::CpuVendor;
 
 /// Used for [CacheProp]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -86,25 +88,44 @@ pub struct CacheProp {
     pub set: u32,
     pub size: u32,
     pub size_unit: Unit,
+    /// Maximum number of addressable IDs for logical processors sharing this cache, EAX[25:14]+1
     pub share_thread: u32,
+    /// Maximum number of addressable IDs for processor cores in the physical package, EAX[31:26]+1,
+    /// reserved (always 1) in `0x8000_001D`
+    pub core_ids: u32,
+    /// Self initializing cache level (does not need SW initialization), EAX[8]
+    pub self_init: bool,
+    /// Fully associative cache, EAX[9]
+    pub fully_assoc: bool,
+    /// WBINVD/INVD is not guaranteed to act upon the lower level caches of the non-originating threads, EDX[0]
+    pub wbinvd_no_lower: bool,
+    /// Cache is inclusive of the lower cache levels, EDX[1]
     pub inclusive: bool,
+    /// Complex function is used to index the cache, EDX[2]
+    pub complex_index: bool,
 }
 
 impl From<&CpuidResult> for CacheProp {
     fn from(cpuid: &CpuidResult) -> Self {
         let cache_type = CacheType::from(*cpuid);
 
-        let level = (cpuid.eax >> 5) & 0b111;
+        let level = cpuid.eax.bits(5..8);
         let line_size = (cpuid.ebx & 0xFFF) + 1;
-        let way = (cpuid.ebx >> 22) + 1;
+        let way = cpuid.ebx.bits(22..32) + 1;
         let set = cpuid.ecx.saturating_add(1);
         let size = line_size.saturating_mul(way).saturating_mul(set);
 
-        let share_thread = ((cpuid.eax >> 14) & 0xFFF) + 1;
+        let share_thread = cpuid.eax.bits(14..26) + 1;
+        let core_ids = cpuid.eax.bits(26..32) + 1;
 
         let size_unit = Unit::from(size);
 
+        let self_init = cpuid.eax.bit(8);
+        let fully_assoc = cpuid.eax.bit(9);
+
+        let wbinvd_no_lower = (cpuid.edx & 0b1) != 0;
         let inclusive = (cpuid.edx & 0b10) == 0b10;
+        let complex_index = (cpuid.edx & 0b100) != 0;
 
         Self {
             cache_type,
@@ -115,17 +136,24 @@ impl From<&CpuidResult> for CacheProp {
             size,
             size_unit,
             share_thread,
+            core_ids,
+            self_init,
+            fully_assoc,
+            wbinvd_no_lower,
             inclusive,
+            complex_index,
         }
     }
 }
 
 impl CacheProp {
+    #[cfg(target_arch = "x86_64")]
     pub fn get_cache_prop_leaf() -> Option<u32> {
         match CpuVendor::get() {
-            CpuVendor::AuthenticAMD => {
+            CpuVendor::AuthenticAMD |
+            CpuVendor::HygonGenuine => {
                 /* AMD TopologyExtensions: CPUID[Leaf=0x8000_0001, SubLeaf=0x0].ECX[22] */
-                let amd_topo_ext = ((cpuid!(0x8000_0001, 0x0).ecx >> 22) & 0b1) != 0;
+                let amd_topo_ext = cpuid!(0x8000_0001, 0x0).ecx.bit(22);
 
                 if amd_topo_ext {
                     Some(0x8000_001D)
@@ -159,6 +187,7 @@ impl CacheProp {
         value / 1024f32
     }
 
+    #[cfg(target_arch = "x86_64")]
     pub fn get(level: u32, cache_type: CacheType) -> Option<Self> {
         let leaf = Self::get_cache_prop_leaf()?;
 
@@ -199,9 +228,37 @@ fn test_cache_prop() {
             size: 16 * unit.to_byte(),
             size_unit: unit,
             share_thread: 12,
+            core_ids: 1,
+            self_init: true,
+            fully_assoc: false,
+            wbinvd_no_lower: true,
             inclusive: false,
+            complex_index: false,
         }
     };
 
     assert_eq!(cache, test);
 }
+
+#[test]
+fn test_cache_prop_intel() {
+    /* CPUID Xeon Platinum 8380 (Ice Lake-SP), 0x4_x3 */
+    let cpuid = CpuidResult {
+        eax: 0xFC3FC163,
+        ebx: 0x02C0003F,
+        ecx: 0x0000BFFF,
+        edx: 0x00000004,
+    };
+
+    let cache = CacheProp::from(&cpuid);
+
+    assert_eq!(cache.level, 3);
+    assert_eq!(cache.way, 12);
+    assert_eq!(cache.share_thread, 256);
+    assert_eq!(cache.core_ids, 64);
+    assert!(cache.self_init);
+    assert!(!cache.fully_assoc);
+    assert!(!cache.wbinvd_no_lower);
+    assert!(!cache.inclusive);
+    assert!(cache.complex_index);
+}
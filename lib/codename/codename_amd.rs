@@ -1,4 +1,4 @@
-use crate::{CpuVendor, ProcInfo, CpuCodename, CpuMicroArch, CpuStepping, ProcessNode};
+use crate::{CpuVendor, ProcInfo, CpuCodename, CpuMicroArch, CpuStepping, MarketSegment, ProcessNode};
 #[cfg(feature = "std")]
 use std::fmt;
 /* ref: https://github.com/illumos/illumos-gate/blob/master/usr/src/uts/intel/os/cpuid_subr.c */
@@ -573,6 +573,59 @@ impl fmt::Display for AmdCodename {
     }
 }
 
+impl AmdCodename {
+    pub fn segment(&self) -> MarketSegment {
+        use MarketSegment::*;
+
+        match self {
+            Self::DR |
+            Self::RB |
+            Self::HY |
+            Self::Naples |
+            Self::Rome |
+            Self::Milan |
+            Self::Trento |
+            Self::Genoa => Server,
+            Self::BL |
+            Self::DA |
+            Self::PH |
+            Self::Orochi |
+            Self::Godavari |
+            Self::PinnacleRidge |
+            Self::Matisse |
+            Self::Chagall |
+            Self::Vermeer |
+            Self::Raphael |
+            Self::GraniteRidge => Desktop,
+            Self::Griffin |
+            Self::Ontario_Zacate |
+            Self::Carrizo |
+            Self::StoneyRidge |
+            Self::Kabini_Temash |
+            Self::Beema_Mullins |
+            Self::Raven2 |
+            Self::Lucienne |
+            Self::VanGogh |
+            Self::Mendocino |
+            Self::Rembrandt => Mobile,
+            Self::Llano |
+            Self::Trinity |
+            Self::Richland |
+            Self::Kaveri |
+            Self::BristolRidge |
+            Self::RavenRidge |
+            Self::Picasso |
+            Self::Renoir |
+            Self::Cezanne_Barcelo |
+            Self::Phoenix |
+            Self::Phoenix2 => Client,
+            Self::MI300 => Accelerator,
+            Self::Fam10h |
+            Self::Cato => Unknown,
+        }
+    }
+}
+
 /// List of AMD micro-architectures
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum AmdMicroArch {
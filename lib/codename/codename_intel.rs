@@ -1,4 +1,4 @@
-use crate::{CpuVendor, ProcInfo, CpuCodename, CpuMicroArch, CpuStepping, ProcessNode};
+use crate::{CpuVendor, ProcInfo, CpuCodename, CpuMicroArch, CpuStepping, MarketSegment, ProcessNode};
 #[cfg(feature = "std")]
 use std::fmt;
 
@@ -445,7 +445,7 @@ impl ProcInfo {
             /* Bonnell */
             0x1C | 0x26 => {
                 let codename = match m {
-                    0x16 => IntelCodename::Bonnell, // Diamondville, Pineview
+                    0x1C => IntelCodename::Bonnell, // Diamondville, Pineview
                     0x26 => IntelCodename::Bonnell_MID, // Silverthorne, Lincroft
                     _ => unreachable!(),
                 };
@@ -865,6 +865,103 @@ impl fmt::Display for IntelCodename {
     }
 }
 
+impl IntelCodename {
+    pub fn segment(&self) -> MarketSegment {
+        use MarketSegment::*;
+
+        match self {
+            Self::Dunnington |
+            Self::Nehalem_EP |
+            Self::Nehalem_EX |
+            Self::Westmere_EP |
+            Self::Westmere_EX |
+            Self::SandyBridge_X |
+            Self::IvyBridge_X |
+            Self::Haswell_X |
+            Self::Broadwell_X |
+            Self::SkyLake_X |
+            Self::CascadeLake_X |
+            Self::CooperLake_X |
+            Self::IceLake_X |
+            Self::SapphireRapids_X |
+            Self::EmeraldRapids_X |
+            Self::GraniteRapids_X |
+            Self::SierraForest_X => Server,
+            Self::Quark_X1000 |
+            Self::Broadwell_D |
+            Self::IceLake_D |
+            Self::GraniteRapids_D |
+            Self::Silvermont_D |
+            Self::Airmont_NP |
+            Self::CougarMountain |
+            Self::Denverton |
+            Self::SnowRidge |
+            Self::ElkhartLake |
+            Self::GrandRidge => Embedded,
+            Self::Yonah |
+            Self::Haswell_L |
+            Self::SkyLake_L |
+            Self::KabyLake_L |
+            Self::AmberLake_L |
+            Self::CoffeeLake_L |
+            Self::WhiskeyLake_L |
+            Self::CometLake_L |
+            Self::CannonLake_L |
+            Self::IceLake_L |
+            Self::TigerLake_L |
+            Self::TigerLake_H |
+            Self::Bonnell_MID |
+            Self::Saltwell_MID |
+            Self::Saltwell_TABLET |
+            Self::Silvermont_MID |
+            Self::Airmont_MID |
+            Self::XMM7272 |
+            Self::SoFIA_3G |
+            Self::Lakefield |
+            Self::AlderLake_L |
+            Self::RaptorLake_P |
+            Self::MeteorLake_L |
+            Self::LunarLake_M |
+            Self::ArrowLake_H => Mobile,
+            Self::SkyLake_S |
+            Self::KabyLake_S |
+            Self::CoffeeLake_S |
+            Self::CometLake_S |
+            Self::IceLake_S |
+            Self::RocketLake_S |
+            Self::AlderLake_S |
+            Self::RaptorLake_S |
+            Self::RaptorLake_S_BFH |
+            Self::MeteorLake_S |
+            Self::MeteorLake_B5H |
+            Self::ArrowLake_S => Desktop,
+            Self::Merom |
+            Self::Merom_L |
+            Self::Penryn |
+            Self::Nehalem |
+            Self::Nehalem_G |
+            Self::Westmere |
+            Self::SandyBridge |
+            Self::IvyBridge |
+            Self::Haswell |
+            Self::Haswell_G |
+            Self::Broadwell |
+            Self::Broadwell_G |
+            Self::Bonnell |
+            Self::Saltwell |
+            Self::Silvermont |
+            Self::Airmont |
+            Self::ApolloLake |
+            Self::GeminiLake |
+            Self::JasperLake |
+            Self::AlderLake_N => Client,
+            Self::IceLake_NNPI |
+            Self::KnightsLanding |
+            Self::KnightsMill => Accelerator,
+        }
+    }
+}
+
 /// List of Intel micro-architectures
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
@@ -1,4 +1,4 @@
-use crate::{CpuVendor, ProcInfo, CpuCodename, CpuMicroArch, CpuStepping, ProcessNode};
+use crate::{CpuVendor, ProcInfo, CpuCodename, CpuMicroArch, CpuStepping, MarketSegment, ProcessNode};
 #[cfg(feature = "std")]
 use std::fmt;
 /* ref: https://github.com/google/cpu_features/pull/218/ */
@@ -73,6 +73,18 @@ impl fmt::Display for ZhaoxinCodename {
     }
 }
 
+impl ZhaoxinCodename {
+    pub fn segment(&self) -> MarketSegment {
+        match self {
+            Self::ZX_C_4000 => MarketSegment::Desktop,
+            /* KX: desktop, KH: server */
+            Self::KX5000_KH20000 |
+            Self::KX6000_KH30000 => MarketSegment::Unknown,
+            Self::KH40000 => MarketSegment::Server,
+        }
+    }
+}
+
 /// List of Zhaoxin micro-architectures
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ZhaoxinMicroArch {
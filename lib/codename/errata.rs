@@ -0,0 +1,144 @@
+use crate::{CpuVendor, FamModStep};
+/* ref: https://github.com/torvalds/linux/blob/master/arch/x86/kernel/cpu/amd.c */
+/* ref: https://github.com/torvalds/linux/blob/master/arch/x86/kernel/cpu/common.c */
+
+/// Notable published errata and quirks, matched by the vendor and the Family/Model/Stepping.
+/// The list is not exhaustive, it covers the ones that change the behavior visible from software.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Erratum {
+    /// Erratum ID or CVE
+    pub id: &'static str,
+    pub title: &'static str,
+    /// Workaround or the effect of the microcode update
+    pub note: &'static str,
+    vendor: CpuVendor,
+    family: u32,
+    /// Inclusive ranges of the model
+    models: &'static [(u32, u32)],
+    /// Inclusive range of the stepping
+    steps: (u32, u32),
+}
+
+impl Erratum {
+    const fn new(
+        id: &'static str,
+        title: &'static str,
+        note: &'static str,
+        vendor: CpuVendor,
+        family: u32,
+        models: &'static [(u32, u32)],
+    ) -> Self {
+        Self { id, title, note, vendor, family, models, steps: (0x0, 0xF) }
+    }
+
+    const fn steps(self, first: u32, last: u32) -> Self {
+        Self { steps: (first, last), ..self }
+    }
+
+    pub fn matches(&self, vendor: &CpuVendor, fms: &FamModStep) -> bool {
+        self.vendor == *vendor
+            && self.family == fms.syn_fam
+            && self.models.iter().any(|(first, last)| (*first..=*last).contains(&fms.syn_mod))
+            && (self.steps.0..=self.steps.1).contains(&fms.step)
+    }
+
+    /// Errata of the list that match the vendor and the Family/Model/Stepping
+    pub fn lookup<'a>(vendor: &'a CpuVendor, fms: &'a FamModStep) -> impl Iterator<Item = &'static Erratum> + 'a {
+        ERRATA.iter().filter(move |e| e.matches(vendor, fms))
+    }
+}
+
+const ERRATA: &[Erratum] = &[
+    /* Intel */
+    Erratum::new(
+        "HSD136/BDM53",
+        "Software using TSX may result in unpredictable system behavior",
+        "TSX (HLE, RTM) is disabled by the microcode update",
+        CpuVendor::GenuineIntel, 0x6,
+        &[(0x3C, 0x3C), (0x3F, 0x3F), (0x45, 0x46), (0x3D, 0x3D), (0x47, 0x47), (0x4F, 0x4F), (0x56, 0x56)],
+    ),
+    Erratum::new(
+        "SKL105",
+        "TSX memory ordering, TSX deprecated on client processors",
+        "RTM always aborts after the microcode update (CPUID.(EAX=7h, ECX=0h):EDX[11] RTM_ALWAYS_ABORT)",
+        CpuVendor::GenuineIntel, 0x6,
+        &[(0x4E, 0x4E), (0x5E, 0x5E), (0x8E, 0x8E), (0x9E, 0x9E)],
+    ),
+    Erratum::new(
+        "CVE-2022-40982",
+        "Gather Data Sampling (Downfall)",
+        "Mitigated by the microcode update, with the performance impact on the gather instructions",
+        CpuVendor::GenuineIntel, 0x6,
+        &[
+            (0x4E, 0x4E), (0x55, 0x55), (0x5E, 0x5E), (0x6A, 0x6A), (0x6C, 0x6C), (0x7D, 0x7E),
+            (0x8C, 0x8E), (0x9E, 0x9E), (0xA5, 0xA5), (0xA7, 0xA7),
+        ],
+    ),
+    Erratum::new(
+        "ADL AVX-512",
+        "AVX-512 is not supported on the hybrid processors",
+        "AVX-512 of the P-cores is fused off or disabled by the microcode update and BIOS",
+        CpuVendor::GenuineIntel, 0x6,
+        &[(0x97, 0x97), (0x9A, 0x9A)],
+    ),
+    Erratum::new(
+        "RPL Vmin Shift",
+        "Vmin shift instability on the 13th/14th Gen desktop processors",
+        "Fixed by the microcode update 0x129 or later (0x12B)",
+        CpuVendor::GenuineIntel, 0x6,
+        &[(0xB7, 0xB7), (0xBF, 0xBF)],
+    ),
+    /* AMD */
+    Erratum::new(
+        "RDRAND after resume",
+        "RDRAND may return 0xFFFF_FFFF after suspend/resume",
+        "Linux clears the RDRAND CPUID bit if the BIOS does not fix it",
+        CpuVendor::AuthenticAMD, 0x15,
+        &[(0x00, 0xFF)],
+    ),
+    Erratum::new(
+        "RDRAND after resume",
+        "RDRAND may return 0xFFFF_FFFF after suspend/resume",
+        "Linux clears the RDRAND CPUID bit if the BIOS does not fix it",
+        CpuVendor::AuthenticAMD, 0x16,
+        &[(0x00, 0xFF)],
+    ),
+    Erratum::new(
+        "Ryzen segfault",
+        "Performance marginality under the heavy compile workloads on the early Summit Ridge",
+        "Fixed in the processors manufactured after week 25 of 2017",
+        CpuVendor::AuthenticAMD, 0x17,
+        &[(0x01, 0x01)],
+    ).steps(0x1, 0x1),
+    Erratum::new(
+        "Matisse RDRAND",
+        "RDRAND always returns 0xFFFF_FFFF with the early BIOS",
+        "Fixed by the AGESA update (ABBA)",
+        CpuVendor::AuthenticAMD, 0x17,
+        &[(0x71, 0x71)],
+    ),
+    Erratum::new(
+        "CVE-2023-20593",
+        "Zenbleed: VZEROUPPER misprediction leaks the register contents",
+        "Fixed by the microcode update or the chicken bit DE_CFG[9]",
+        CpuVendor::AuthenticAMD, 0x17,
+        &[(0x30, 0x4F), (0x60, 0x7F), (0x90, 0x91), (0xA0, 0xAF)],
+    ),
+];
+
+#[test]
+fn test_errata_lookup() {
+    /* Ryzen 5 5600G */
+    let fms = FamModStep::from(0x00A50F00);
+    assert_eq!(Erratum::lookup(&CpuVendor::AuthenticAMD, &fms).count(), 0);
+
+    /* Ryzen 9 3900X */
+    let fms = FamModStep::from(0x00870F10);
+    let ids: Vec<&str> = Erratum::lookup(&CpuVendor::AuthenticAMD, &fms).map(|e| e.id).collect();
+    assert_eq!(ids, ["Matisse RDRAND", "CVE-2023-20593"]);
+
+    /* Ryzen 7 1800X, B1 */
+    assert_eq!(Erratum::lookup(&CpuVendor::AuthenticAMD, &FamModStep::from(0x00800F11)).count(), 1);
+    assert_eq!(Erratum::lookup(&CpuVendor::AuthenticAMD, &FamModStep::from(0x00800F12)).count(), 0);
+    assert_eq!(Erratum::lookup(&CpuVendor::GenuineIntel, &FamModStep::from(0x00800F11)).count(), 0);
+}
@@ -1,4 +1,4 @@
-use crate::{cpuid, CpuidResult, CpuVendor};
+use crate::{CpuidResult, CpuVendor, Reg};
 use crate::codename::{AmdCodename, IntelCodename, ZhaoxinCodename};
 use crate::codename::{AmdMicroArch, IntelMicroArch, ZhaoxinMicroArch};
 #[cfg(feature = "std")]
@@ -44,7 +44,7 @@ impl ProcInfo {
                 0x7 => Self::zhaoxin_fam07h(m, s),
                 _ => unknown!(vendor, f, m, s),
             },
-            CpuVendor::Unknown(_) => Self {
+            _ => Self {
                 codename: CpuCodename::Unknown(vendor, f, m),
                 archname: CpuMicroArch::Unknown,
                 step_info: CpuStepping::Unknown(s),
@@ -84,6 +84,95 @@ impl fmt::Display for CpuCodename {
     }
 }
 
+impl CpuCodename {
+    /// Vendor of the codename, the vendor ID for the unknown codenames
+    pub fn vendor(&self) -> CpuVendor {
+        match self {
+            Self::Amd(_) => CpuVendor::AuthenticAMD,
+            Self::Intel(_) => CpuVendor::GenuineIntel,
+            Self::Zhaoxin(_) => CpuVendor::Shanghai,
+            Self::Unknown(vendor, _, _) => *vendor,
+        }
+    }
+
+    /// Market segment of the codename
+    pub fn segment(&self) -> MarketSegment {
+        match self {
+            Self::Amd(codename) => codename.segment(),
+            Self::Intel(codename) => codename.segment(),
+            Self::Zhaoxin(codename) => codename.segment(),
+            Self::Unknown(..) => MarketSegment::Unknown,
+        }
+    }
+}
+
+/// Market segment of the codename
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MarketSegment {
+    Desktop,
+    Mobile,
+    /// Both of desktop and mobile (e.g. the APUs for the both)
+    Client,
+    Server,
+    Embedded,
+    Accelerator,
+    /// Unknown, or the codenames of the several segments (e.g. the same die for desktop and server)
+    Unknown,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for MarketSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Codename with the micro-architecture, the market segment and the vendor,
+/// to match without comparing the strings.
+/// `Display` is the same as [CpuCodename], like "AMD Raphael".
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Codename {
+    pub codename: CpuCodename,
+    pub arch: CpuMicroArch,
+    pub segment: MarketSegment,
+    pub vendor: CpuVendor,
+}
+
+impl Codename {
+    pub fn from_fms(fms: &FamModStep, vendor: &CpuVendor) -> Self {
+        Self::from(&ProcInfo::from_fms(fms, vendor))
+    }
+
+    /// `None` for the unknown Family/Model
+    pub fn option_from_fms(fms: &FamModStep, vendor: &CpuVendor) -> Option<Self> {
+        let codename = Self::from_fms(fms, vendor);
+
+        if let CpuCodename::Unknown(..) = codename.codename {
+            None
+        } else {
+            Some(codename)
+        }
+    }
+}
+
+impl From<&ProcInfo> for Codename {
+    fn from(info: &ProcInfo) -> Self {
+        Self {
+            codename: info.codename,
+            arch: info.archname.clone(),
+            segment: info.codename.segment(),
+            vendor: info.codename.vendor(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Codename {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.codename.fmt(f)
+    }
+}
+
 /// CPU micro-architectures by vendor
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum CpuMicroArch {
@@ -196,8 +285,8 @@ pub struct FamModStep {
 impl From<u32> for FamModStep {
     fn from(eax: u32) -> Self {
         Self {
-            syn_fam: ((eax >> 8) & 0xF) + ((eax >> 20) & 0xFF),
-            syn_mod: ((eax >> 4) & 0xF) + ((eax >> 12) & 0xF0),
+            syn_fam: eax.bits(8..12) + eax.bits(20..28),
+            syn_mod: eax.bits(4..8) + (eax.bits(16..20) << 4),
             step: eax & 0xF,
             raw_eax: eax,
         }
@@ -211,7 +300,138 @@ impl From<&CpuidResult> for FamModStep {
 }
 
 impl FamModStep {
+    #[cfg(target_arch = "x86_64")]
     pub fn get() -> Self {
         Self::from(&cpuid!(0x1))
     }
+
+    /// From `CPUID.(EAX=01h):EAX`
+    pub fn from_cpuid(cpuid: &CpuidResult) -> Self {
+        Self::from(cpuid.eax)
+    }
+
+    /// From the displayed family, model and stepping, `raw_eax` is encoded with the extended family/model
+    pub fn new(family: u32, model: u32, stepping: u32) -> Self {
+        let [base_fam, ext_fam] = if 0xF <= family { [0xF, family - 0xF] } else { [family, 0x0] };
+        let eax = ((ext_fam & 0xFF) << 20)
+            | ((model & 0xF0) << 12)
+            | ((base_fam & 0xF) << 8)
+            | ((model & 0xF) << 4)
+            | (stepping & 0xF);
+
+        Self { syn_fam: family, syn_mod: model, step: stepping, raw_eax: eax }
+    }
+
+    pub fn family(&self) -> u32 {
+        self.syn_fam
+    }
+
+    pub fn model(&self) -> u32 {
+        self.syn_mod
+    }
+
+    pub fn stepping(&self) -> u32 {
+        self.step
+    }
+
+    pub fn raw_eax(&self) -> u32 {
+        self.raw_eax
+    }
+}
+
+/// "F:M:S" in hex, like "19:61:2"
+#[cfg(feature = "std")]
+impl fmt::Display for FamModStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:X}:{:X}:{:X}", self.syn_fam, self.syn_mod, self.step)
+    }
+}
+
+/// Error of parsing [FamModStep]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ParseFmsError {
+    /// Not in the form of "F:M:S"
+    Format,
+    /// Not a hex number
+    Value,
+    /// Family > 0x10E, Model > 0xFF or Stepping > 0xF
+    Range,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ParseFmsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Self::Format => "expected \"Family:Model:Stepping\" like \"19:61:2\"",
+            Self::Value => "Family, Model and Stepping must be hex numbers",
+            Self::Range => "Family must be 0x0..=0x10E, Model 0x0..=0xFF and Stepping 0x0..=0xF",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseFmsError {}
+
+/// From "F:M:S" in hex, like "19:61:2" or "0x19:0x61:0x2"
+impl core::str::FromStr for FamModStep {
+    type Err = ParseFmsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut values = [0u32; 3];
+        let mut split = s.trim().split(':');
+
+        for value in values.iter_mut() {
+            let v = split.next().ok_or(ParseFmsError::Format)?.trim();
+            let v = v.strip_prefix("0x").or_else(|| v.strip_prefix("0X")).unwrap_or(v);
+
+            *value = u32::from_str_radix(v, 16).map_err(|_| ParseFmsError::Value)?;
+        }
+
+        if split.next().is_some() {
+            return Err(ParseFmsError::Format);
+        }
+
+        let [family, model, stepping] = values;
+
+        if 0xF + 0xFF < family || 0xFF < model || 0xF < stepping {
+            return Err(ParseFmsError::Range);
+        }
+
+        Ok(Self::new(family, model, stepping))
+    }
+}
+
+#[test]
+fn test_codename() {
+    /* Ryzen 9 7950X */
+    let codename = Codename::from_fms(&FamModStep::from(0x00A60F12), &CpuVendor::AuthenticAMD);
+
+    assert_eq!(codename.codename, CpuCodename::Amd(AmdCodename::Raphael));
+    assert_eq!(codename.arch, CpuMicroArch::Amd(AmdMicroArch::Zen4));
+    assert_eq!(codename.segment, MarketSegment::Desktop);
+    assert_eq!(codename.vendor, CpuVendor::AuthenticAMD);
+    assert_eq!(codename.to_string(), "AMD Raphael");
+
+    assert!(Codename::option_from_fms(&FamModStep::from(0x00A60F12), &CpuVendor::GenuineIntel).is_none());
+}
+
+#[test]
+fn test_fms_parse() {
+    /* Ryzen 5 5600G */
+    let fms: FamModStep = "19:50:0".parse().unwrap();
+
+    assert_eq!(fms, FamModStep::from(0x00A50F00));
+    assert_eq!(fms.to_string(), "19:50:0");
+
+    /* Core i7-6700K */
+    let fms: FamModStep = "0x6:0x5E:0x3".parse().unwrap();
+
+    assert_eq!(fms.raw_eax(), 0x000506E3);
+    assert_eq!((fms.family(), fms.model(), fms.stepping()), (0x6, 0x5E, 0x3));
+
+    assert_eq!("19:61".parse::<FamModStep>(), Err(ParseFmsError::Format));
+    assert_eq!("19:6G:2".parse::<FamModStep>(), Err(ParseFmsError::Value));
+    assert_eq!("19:61:10".parse::<FamModStep>(), Err(ParseFmsError::Range));
 }
@@ -9,3 +9,6 @@ pub use codename_intel::*;
 
 mod codename_zhaoxin;
 pub use codename_zhaoxin::*;
+
+mod errata;
+pub use errata::*;
@@ -0,0 +1,77 @@
+use crate::CpuidResult;
+
+/// Backend of the CPUID execution.
+/// Replace `NativeCpuid` with a closure or a recorded dump to decode synthetic register values,
+/// e.g. in the unit tests on a VM or a non-x86 host.
+pub trait CpuidSource {
+    fn cpuid(&self, leaf: u32, sub_leaf: u32) -> CpuidResult;
+
+    /// `None` if `leaf` is above the max leaf of its range (`CPUID.(EAX=0h, 4000_0000h or 8000_0000h):EAX`).
+    /// Intel CPUs return the data of the highest basic leaf for the leaf out of the range, not zeros.
+    fn try_cpuid(&self, leaf: u32, sub_leaf: u32) -> Option<CpuidResult> {
+        let base = leaf & 0xFFFF_0000;
+        let max_leaf = self.cpuid(base, 0x0).eax;
+        /* the max leaf of the other ranges is in the same range, e.g. 0x8000_0008 */
+        let valid_range = base == 0x0 || (max_leaf & 0xFFFF_0000) == base;
+
+        if valid_range && leaf <= max_leaf {
+            Some(self.cpuid(leaf, sub_leaf))
+        } else {
+            None
+        }
+    }
+}
+
+/// The CPUID instruction of the current CPU
+#[cfg(target_arch = "x86_64")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NativeCpuid;
+
+#[cfg(target_arch = "x86_64")]
+impl CpuidSource for NativeCpuid {
+    fn cpuid(&self, leaf: u32, sub_leaf: u32) -> CpuidResult {
+        cpuid!(leaf, sub_leaf)
+    }
+}
+
+/// `|leaf, sub_leaf| -> CpuidResult`
+impl<F: Fn(u32, u32) -> CpuidResult> CpuidSource for F {
+    fn cpuid(&self, leaf: u32, sub_leaf: u32) -> CpuidResult {
+        self(leaf, sub_leaf)
+    }
+}
+
+/// Recorded results of `(leaf, sub_leaf, result)`, zero for the leaves not in the dump
+impl CpuidSource for [(u32, u32, CpuidResult)] {
+    fn cpuid(&self, leaf: u32, sub_leaf: u32) -> CpuidResult {
+        self.iter()
+            .find(|(l, s, _)| *l == leaf && *s == sub_leaf)
+            .map_or(CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 }, |(_, _, result)| *result)
+    }
+}
+
+#[cfg(feature = "std")]
+impl CpuidSource for Vec<(u32, u32, CpuidResult)> {
+    fn cpuid(&self, leaf: u32, sub_leaf: u32) -> CpuidResult {
+        self.as_slice().cpuid(leaf, sub_leaf)
+    }
+}
+
+#[test]
+fn test_try_cpuid() {
+    let zero = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+    /* Pentium 4: max std leaf 0x2, max ext leaf 0x8000_0004 */
+    let src = [
+        (0x0, 0x0, CpuidResult { eax: 0x2, ..zero }),
+        (0x2, 0x0, CpuidResult { eax: 0x665B5001, ..zero }),
+        (0x8000_0000, 0x0, CpuidResult { eax: 0x8000_0004, ..zero }),
+    ];
+    let src: &[(u32, u32, CpuidResult)] = &src;
+
+    assert_eq!(src.try_cpuid(0x2, 0x0).map(|cpuid| cpuid.eax), Some(0x665B5001));
+    assert!(src.try_cpuid(0x7, 0x0).is_none());
+    assert!(src.try_cpuid(0x8000_0001, 0x0).is_some());
+    assert!(src.try_cpuid(0x8000_0008, 0x0).is_none());
+    /* the ext range not reported */
+    assert!(src.try_cpuid(0x4000_0001, 0x0).is_none());
+}
@@ -0,0 +1,104 @@
+//! C API, enabled with the `ffi` feature.
+//! The declarations are in `include/cpuid_dump.h`, generated by cbindgen (`tests/ffi_header.rs`).
+
+use core::ffi::{c_char, c_int, CStr};
+use crate::{MicroArchLevel, NativeCpuid, TopoId, Vendor};
+
+/// `CpuidResult` with the C layout
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuidDumpResult {
+    pub eax: u32,
+    pub ebx: u32,
+    pub ecx: u32,
+    pub edx: u32,
+}
+
+/// `TopoId` with the C layout, `node_id` is `u32::MAX` if not available
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuidDumpTopoId {
+    pub smt_id: u32,
+    pub core_id: u32,
+    pub pkg_id: u32,
+    pub x2apic_id: u32,
+    pub node_id: u32,
+}
+
+/// Execute CPUID with `leaf` and `sub_leaf` on the current thread
+#[no_mangle]
+pub extern "C" fn cpuid_dump_query(leaf: u32, sub_leaf: u32) -> CpuidDumpResult {
+    let cpuid = cpuid!(leaf, sub_leaf);
+
+    CpuidDumpResult {
+        eax: cpuid.eax,
+        ebx: cpuid.ebx,
+        ecx: cpuid.ecx,
+        edx: cpuid.edx,
+    }
+}
+
+/// Write the vendor ID string (e.g. "AuthenticAMD") and NUL to `buf`, returns 0 on success,
+/// -1 if `buf` is NULL or `len` < 13
+///
+/// # Safety
+/// `buf` must be valid for writes of `len` bytes
+#[no_mangle]
+pub unsafe extern "C" fn cpuid_dump_vendor(buf: *mut c_char, len: usize) -> c_int {
+    if buf.is_null() || len < 13 {
+        return -1;
+    }
+
+    let Vendor { ebx, ecx, edx } = Vendor::get();
+    let mut s = [0u8; 13];
+
+    for (i, reg) in [ebx, edx, ecx].iter().enumerate() {
+        s[(i*4)..(i*4+4)].copy_from_slice(&reg.to_le_bytes());
+    }
+
+    core::ptr::copy_nonoverlapping(s.as_ptr(), buf as *mut u8, s.len());
+
+    0
+}
+
+/// Write the topology ID of the current thread to `topo`, returns 0 on success,
+/// -1 if `topo` is NULL or the topology is not available
+///
+/// # Safety
+/// `topo` must be valid for writes of `CpuidDumpTopoId`
+#[no_mangle]
+pub unsafe extern "C" fn cpuid_dump_topology(topo: *mut CpuidDumpTopoId) -> c_int {
+    let Some(topo) = topo.as_mut() else { return -1 };
+    let Some(topo_id) = TopoId::get_topo_info() else { return -1 };
+
+    *topo = CpuidDumpTopoId {
+        smt_id: topo_id.smt_id,
+        core_id: topo_id.core_id,
+        pkg_id: topo_id.pkg_id,
+        x2apic_id: topo_id.x2apic_id,
+        node_id: topo_id.node_id.unwrap_or(u32::MAX),
+    };
+
+    0
+}
+
+/// Check the feature (e.g. "AVX2", "sha_ni"), the names and aliases of `FTR_BITS` or the psABI level features,
+/// ignoring the case and the punctuation,
+/// returns 1 if supported, 0 if not supported, -1 if the name is unknown or not UTF-8
+///
+/// # Safety
+/// `name` must be a NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn cpuid_dump_has_feature(name: *const c_char) -> c_int {
+    if name.is_null() {
+        return -1;
+    }
+
+    let Ok(name) = CStr::from_ptr(name).to_str() else { return -1 };
+
+    match crate::has_feature(&NativeCpuid, name).or_else(|| MicroArchLevel::has_feature(name)) {
+        Some(true) => 1,
+        Some(false) => 0,
+        None => -1,
+    }
+}
@@ -0,0 +1,51 @@
+/// Layout of the text format of `cpuid_dump -parse`:
+/// the input (leaf, sub-leaf) and the registers in the fixed columns,
+/// followed by the decoded fields wrapped at `total_width`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatConfig {
+    pub total_width: usize,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl FormatConfig {
+    /// `"  0x00000000 0x0:  "`
+    pub const INPUT_WIDTH: usize = 19;
+    /// `"0x00000000 "` * 4
+    pub const OUTPUT_WIDTH: usize = 44;
+    /// The columns before the decoded fields, `INPUT_WIDTH + OUTPUT_WIDTH + " ".len()`
+    pub const PAD_WIDTH: usize = Self::INPUT_WIDTH + Self::OUTPUT_WIDTH + 1;
+    pub const DEFAULT_TOTAL_WIDTH: usize = 100;
+    /// 80 columns, the decoded fields of 16 columns
+    pub const MIN_TOTAL_WIDTH: usize = Self::PAD_WIDTH + 16;
+
+    pub const DEFAULT: Self = Self { total_width: Self::DEFAULT_TOTAL_WIDTH };
+
+    /// `None` if `total_width` is less than `MIN_TOTAL_WIDTH`
+    pub fn new(total_width: usize) -> Option<Self> {
+        (Self::MIN_TOTAL_WIDTH <= total_width).then_some(Self { total_width })
+    }
+
+    /// Width of the decoded fields
+    pub fn parse_width(&self) -> usize {
+        self.total_width.saturating_sub(Self::PAD_WIDTH)
+    }
+
+    /// The ruler line under the column headers
+    #[cfg(feature = "std")]
+    pub fn line(&self) -> String {
+        "=".repeat(self.total_width)
+    }
+}
+
+#[test]
+fn test_format_config() {
+    assert_eq!(FormatConfig::default().parse_width(), 36);
+    assert_eq!(FormatConfig::new(120).unwrap().parse_width(), 56);
+    assert_eq!(FormatConfig::new(FormatConfig::MIN_TOTAL_WIDTH).unwrap().parse_width(), 16);
+    assert!(FormatConfig::new(60).is_none());
+}
@@ -0,0 +1,608 @@
+use crate::{CpuidResult, CpuidSource};
+
+/// Register of the CPUID result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    EAX,
+    EBX,
+    ECX,
+    EDX,
+}
+use Register::*;
+
+impl Register {
+    pub const fn index(&self) -> usize {
+        match self {
+            EAX => 0,
+            EBX => 1,
+            ECX => 2,
+            EDX => 3,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            EAX => "EAX",
+            EBX => "EBX",
+            ECX => "ECX",
+            EDX => "EDX",
+        }
+    }
+}
+
+/// A feature bit: `CPUID.(EAX=leaf, ECX=sub_leaf):reg[bit]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FtrBit {
+    pub leaf: u32,
+    pub sub_leaf: u32,
+    pub reg: Register,
+    pub bit: u32,
+    /// Feature name in this crate
+    pub name: &'static str,
+    /// "Intel, AMD", "Intel" or "AMD"
+    pub vendor: &'static str,
+    /// Short description for `--describe`
+    pub desc: &'static str,
+}
+
+/// The leaves of the feature flags only defined by AMD
+pub const fn amd_only_leaf(leaf: u32) -> bool {
+    matches!(leaf, 0x8000_000A | 0x8000_001A | 0x8000_001B | 0x8000_001F | 0x8000_0020 | 0x8000_0021)
+}
+
+const fn ftr(leaf: u32, sub_leaf: u32, reg: Register, bit: u32, name: &'static str) -> FtrBit {
+    let vendor = if amd_only_leaf(leaf) { "AMD" } else { "Intel, AMD" };
+
+    FtrBit { leaf, sub_leaf, reg, bit, name, vendor, desc: "" }
+}
+
+impl FtrBit {
+    const fn desc(self, vendor: &'static str, desc: &'static str) -> Self {
+        Self { vendor, desc, ..self }
+    }
+}
+
+/// All feature bits, in the display order of each leaf.
+/// The feature tables of `cpuid_dump` (`--describe`, the parsed flags) are derived from this.
+pub const FTR_BITS: &[FtrBit] = &[
+    /* CPUID.(EAX=1h, ECX=0h):EDX */
+    ftr(0x1, 0x0, EDX, 0, "FPU").desc("Intel, AMD", "x87 Floating Point Unit on-chip"),
+    ftr(0x1, 0x0, EDX, 1, "VME").desc("Intel, AMD", "Virtual-8086 Mode Enhancements"),
+    ftr(0x1, 0x0, EDX, 2, "DE").desc("Intel, AMD", "Debugging Extensions (I/O breakpoints, CR4.DE)"), // DebugExt
+    ftr(0x1, 0x0, EDX, 3, "PSE").desc("Intel, AMD", "Page Size Extension (4-MiB pages)"),
+    ftr(0x1, 0x0, EDX, 4, "TSC").desc("Intel, AMD", "Time Stamp Counter and RDTSC instruction"),
+    ftr(0x1, 0x0, EDX, 5, "MSR").desc("Intel, AMD", "RDMSR/WRMSR instructions and Model Specific Registers"),
+    ftr(0x1, 0x0, EDX, 6, "PAE").desc("Intel, AMD", "Physical Address Extension"),
+    ftr(0x1, 0x0, EDX, 7, "MCE").desc("Intel, AMD", "Machine Check Exception"),
+    ftr(0x1, 0x0, EDX, 8, "CX8").desc("Intel, AMD", "CMPXCHG8B instruction"),
+    ftr(0x1, 0x0, EDX, 9, "APIC").desc("Intel, AMD", "APIC on-chip"),
+    ftr(0x1, 0x0, EDX, 11, "SEP").desc("Intel, AMD", "SYSENTER/SYSEXIT instructions"),
+    ftr(0x1, 0x0, EDX, 12, "MTRR").desc("Intel, AMD", "Memory Type Range Registers"),
+    ftr(0x1, 0x0, EDX, 13, "PGE").desc("Intel, AMD", "Page Global Enable (global pages in CR4.PGE)"),
+    ftr(0x1, 0x0, EDX, 14, "MCA").desc("Intel, AMD", "Machine Check Architecture"),
+    ftr(0x1, 0x0, EDX, 15, "CMOV").desc("Intel, AMD", "Conditional move instructions"),
+    ftr(0x1, 0x0, EDX, 16, "PAT").desc("Intel, AMD", "Page Attribute Table"),
+    ftr(0x1, 0x0, EDX, 17, "PSE36").desc("Intel, AMD", "36-bit Page Size Extension"),
+    ftr(0x1, 0x0, EDX, 18, "PSN").desc("Intel", "Processor Serial Number"),
+    ftr(0x1, 0x0, EDX, 19, "CLFLUSH").desc("Intel, AMD", "CLFLUSH instruction"),
+    ftr(0x1, 0x0, EDX, 21, "DS").desc("Intel", "Debug Store (branch trace store, PEBS)"),
+    ftr(0x1, 0x0, EDX, 22, "ACPI").desc("Intel", "Thermal Monitor and Software Controlled Clock Facilities"),
+    ftr(0x1, 0x0, EDX, 23, "MMX").desc("Intel, AMD", "MMX technology"),
+    ftr(0x1, 0x0, EDX, 24, "FXSR").desc("Intel, AMD", "FXSAVE/FXRSTOR instructions"),
+    ftr(0x1, 0x0, EDX, 25, "SSE").desc("Intel, AMD", "Streaming SIMD Extensions"),
+    ftr(0x1, 0x0, EDX, 26, "SSE2").desc("Intel, AMD", "Streaming SIMD Extensions 2"),
+    ftr(0x1, 0x0, EDX, 27, "SS").desc("Intel", "Self Snoop"), // Self Snoop
+    ftr(0x1, 0x0, EDX, 28, "HTT").desc("Intel, AMD", "Max APIC IDs reserved field is valid (Hyper-Threading/multi-core)"),
+    ftr(0x1, 0x0, EDX, 29, "TM").desc("Intel", "Thermal Monitor (automatic thermal control)"), // Thermal Monitor
+    ftr(0x1, 0x0, EDX, 31, "PBE").desc("Intel", "Pending Break Enable (FERR#/PBE# pin)"), // Pending Break Enable
+
+    /* CPUID.(EAX=1h, ECX=0h):ECX */
+    ftr(0x1, 0x0, ECX, 0, "SSE3").desc("Intel, AMD", "Streaming SIMD Extensions 3"),
+    ftr(0x1, 0x0, ECX, 1, "PCLMULQDQ").desc("Intel, AMD", "Carry-less multiplication instruction"),
+    ftr(0x1, 0x0, ECX, 2, "DTES64").desc("Intel", "64-bit Debug Store area"), // 64-bit DS Area
+    ftr(0x1, 0x0, ECX, 3, "MONITOR").desc("Intel, AMD", "MONITOR/MWAIT instructions"), // MONITOR/MWAIT
+    ftr(0x1, 0x0, ECX, 4, "DS-CPL").desc("Intel", "CPL Qualified Debug Store"), // CPL Qualified Debug Store
+    ftr(0x1, 0x0, ECX, 5, "VMX").desc("Intel", "Intel Virtual Machine Extensions"), // Virtual Machine Extensions
+    ftr(0x1, 0x0, ECX, 6, "SMX").desc("Intel", "Safer Mode Extensions (Intel TXT)"), // Safer Mode Extensions
+    ftr(0x1, 0x0, ECX, 7, "EST").desc("Intel", "Enhanced Intel SpeedStep Technology"), // Enhanced Intel SpeedStep Technology
+    ftr(0x1, 0x0, ECX, 8, "TM2").desc("Intel", "Thermal Monitor 2"), // Thermal Monito 2
+    ftr(0x1, 0x0, ECX, 9, "SSSE3").desc("Intel, AMD", "Supplemental Streaming SIMD Extensions 3"),
+    ftr(0x1, 0x0, ECX, 10, "CNXT-ID").desc("Intel", "L1 Context ID (adaptive or shared L1 data cache mode)"), // L1 Context ID
+    ftr(0x1, 0x0, ECX, 11, "SDBG").desc("Intel", "Silicon Debug interface (IA32_DEBUG_INTERFACE MSR)"), // Silicon Debug
+    ftr(0x1, 0x0, ECX, 12, "FMA").desc("Intel, AMD", "Fused multiply-add with 3 operands (FMA3)"),
+    ftr(0x1, 0x0, ECX, 13, "CX16").desc("Intel, AMD", "CMPXCHG16B instruction"), // CMPXCHG16B
+    ftr(0x1, 0x0, ECX, 14, "xTPR Update Control").desc("Intel", "xTPR Update Control (IA32_MISC_ENABLE[23])"),
+    ftr(0x1, 0x0, ECX, 15, "PDCM").desc("Intel", "Perfmon and Debug Capability (IA32_PERF_CAPABILITIES MSR)"), // Perfmon and Debug Capability
+    ftr(0x1, 0x0, ECX, 17, "PCID").desc("Intel, AMD", "Process-context identifiers"),
+    ftr(0x1, 0x0, ECX, 18, "DCA").desc("Intel", "Direct Cache Access (prefetch from a memory mapped device)"),
+    ftr(0x1, 0x0, ECX, 19, "SSE4.1").desc("Intel, AMD", "Streaming SIMD Extensions 4.1"),
+    ftr(0x1, 0x0, ECX, 20, "SSE4.2").desc("Intel, AMD", "Streaming SIMD Extensions 4.2, CRC32 and PCMPxSTRx"),
+    ftr(0x1, 0x0, ECX, 21, "x2APIC").desc("Intel, AMD", "x2APIC mode"),
+    ftr(0x1, 0x0, ECX, 22, "MOVBE").desc("Intel, AMD", "MOVBE instruction (move data after swapping bytes)"),
+    ftr(0x1, 0x0, ECX, 23, "POPCNT").desc("Intel, AMD", "POPCNT instruction"),
+    ftr(0x1, 0x0, ECX, 24, "TSC-Deadline").desc("Intel, AMD", "TSC deadline mode of the local APIC timer"),
+    ftr(0x1, 0x0, ECX, 25, "AES").desc("Intel, AMD", "AES-NI instructions"), // AESNI
+    ftr(0x1, 0x0, ECX, 26, "XSAVE").desc("Intel, AMD", "XSAVE/XRSTOR/XSETBV/XGETBV instructions and XCR0"), // XSAVE/XRSTOR
+    ftr(0x1, 0x0, ECX, 27, "OSXSAVE").desc("Intel, AMD", "The OS has set CR4.OSXSAVE to enable XSAVE"), // XSETBV/XGETBV
+    ftr(0x1, 0x0, ECX, 28, "AVX").desc("Intel, AMD", "Advanced Vector Extensions"),
+    ftr(0x1, 0x0, ECX, 29, "F16C").desc("Intel, AMD", "16-bit floating-point conversion instructions"),
+    ftr(0x1, 0x0, ECX, 30, "RDRAND").desc("Intel, AMD", "RDRAND instruction (on-chip random number generator)"),
+
+    /* CPUID.(EAX=6h, ECX=0h):EAX */
+    ftr(0x6, 0x0, EAX, 0, "DigitalTempSensor").desc("Intel, AMD", "Digital Temperature Sensor"),
+    ftr(0x6, 0x0, EAX, 1, "TurboBoost").desc("Intel", "Intel Turbo Boost Technology"),
+    ftr(0x6, 0x0, EAX, 2, "ARAT").desc("Intel, AMD", "APIC timer always running (not affected by the C-states)"), // APIC-Timer-always-running, always running APIC timer
+    ftr(0x6, 0x0, EAX, 4, "PLN").desc("Intel", "Power Limit Notification controls"), // Power Limit Management
+    ftr(0x6, 0x0, EAX, 5, "ECMD").desc("Intel", "Clock modulation duty cycle extension"), // Clock modulation duty cycle extension
+    ftr(0x6, 0x0, EAX, 6, "PTM").desc("Intel", "Package Thermal Management"), // Package Thermal Management
+    ftr(0x6, 0x0, EAX, 7, "HWP").desc("Intel", "Hardware-controlled Performance states (Intel Speed Shift)"),
+    ftr(0x6, 0x0, EAX, 8, "HWP_Notification").desc("Intel", "HWP Notification (IA32_HWP_INTERRUPT MSR)"),
+    ftr(0x6, 0x0, EAX, 9, "HWP_Activity_Window").desc("Intel", "HWP Activity Window control"),
+    ftr(0x6, 0x0, EAX, 10, "HWP_Energy_Performance_Preference").desc("Intel", "HWP Energy Performance Preference control"),
+    ftr(0x6, 0x0, EAX, 11, "HWP_Package_Level_Request").desc("Intel", "HWP Package Level Request (IA32_HWP_REQUEST_PKG MSR)"),
+    ftr(0x6, 0x0, EAX, 13, "HDC").desc("Intel", "Hardware Duty Cycling"),
+    ftr(0x6, 0x0, EAX, 14, "TurboBoostMax").desc("Intel", "Intel Turbo Boost Max Technology 3.0"),
+    ftr(0x6, 0x0, EAX, 15, "HWP_Capabilities").desc("Intel", "HWP Capabilities changes are notified (Highest Performance change)"),
+    ftr(0x6, 0x0, EAX, 16, "HWP_PECI").desc("Intel", "HWP PECI override"),
+    ftr(0x6, 0x0, EAX, 17, "Flexible_HWP").desc("Intel", "Flexible HWP"),
+    ftr(0x6, 0x0, EAX, 18, "FastAccessMode").desc("Intel", "Fast access mode for IA32_HWP_REQUEST MSR"),
+    ftr(0x6, 0x0, EAX, 19, "HFI").desc("Intel", "Hardware Feedback Interface"), // Hardware Feedback Interface
+    // bit 20: Ignoring Idle Logical Processor HWP req
+    ftr(0x6, 0x0, EAX, 23, "EHFI").desc("Intel", "Enhanced Hardware Feedback Interface (Intel Thread Director)"),
+
+    /* CPUID.(EAX=7h, ECX=0h):EBX */
+    ftr(0x7, 0x0, EBX, 0, "FSGSBASE").desc("Intel, AMD", "RDFSBASE/RDGSBASE/WRFSBASE/WRGSBASE instructions"),
+    ftr(0x7, 0x0, EBX, 1, "TSC_Adjust").desc("Intel, AMD", "IA32_TSC_ADJUST MSR"),
+    ftr(0x7, 0x0, EBX, 2, "SGX").desc("Intel", "Intel Software Guard Extensions"),
+    ftr(0x7, 0x0, EBX, 3, "BMI1").desc("Intel, AMD", "Bit Manipulation Instruction set 1"),
+    ftr(0x7, 0x0, EBX, 4, "HLE").desc("Intel", "Hardware Lock Elision (Intel TSX)"),
+    ftr(0x7, 0x0, EBX, 5, "AVX2").desc("Intel, AMD", "Advanced Vector Extensions 2"),
+    ftr(0x7, 0x0, EBX, 6, "FDP_EXCPTN_ONLY").desc("Intel", "x87 FPU Data Pointer updated only on x87 exceptions"),
+    ftr(0x7, 0x0, EBX, 7, "SMEP").desc("Intel, AMD", "Supervisor-Mode Execution Prevention"),
+    ftr(0x7, 0x0, EBX, 8, "BMI2").desc("Intel, AMD", "Bit Manipulation Instruction set 2"),
+    ftr(0x7, 0x0, EBX, 9, "ERMS").desc("Intel, AMD", "Enhanced REP MOVSB/STOSB"), // Enhanced REP MOVSB/STOSB
+    ftr(0x7, 0x0, EBX, 10, "INVPCID").desc("Intel, AMD", "INVPCID instruction"),
+    ftr(0x7, 0x0, EBX, 11, "RTM").desc("Intel", "Restricted Transactional Memory (Intel TSX)"),
+    ftr(0x7, 0x0, EBX, 12, "PQM").desc("Intel, AMD", "Resource Director Technology Monitoring (AMD: Platform QoS Monitoring)"), // AMD: Platform QoS Monitoring, Intel: RDT-M (Resource Director Technology - Monitoring)
+    ftr(0x7, 0x0, EBX, 13, "FPU_CS_DS").desc("Intel", "FPU CS and FPU DS values deprecated"), // Deprecates FPU CS and FPU DS
+    ftr(0x7, 0x0, EBX, 14, "MemoryProtectionExtensions").desc("Intel", "Memory Protection Extensions (bounds checking)"),
+    ftr(0x7, 0x0, EBX, 15, "PQE").desc("Intel, AMD", "Resource Director Technology Allocation (AMD: Platform QoS Enforcement)"), // AMD: PQE (Cache Allocation Technology, Platform QoS Allocation?), Intel: RTD-A (Allocation),
+    ftr(0x7, 0x0, EBX, 16, "AVX512F").desc("Intel, AMD", "AVX-512 Foundation"),
+    ftr(0x7, 0x0, EBX, 17, "AVX512DQ").desc("Intel, AMD", "AVX-512 Doubleword and Quadword instructions"),
+    ftr(0x7, 0x0, EBX, 18, "RDSEED").desc("Intel, AMD", "RDSEED instruction"),
+    ftr(0x7, 0x0, EBX, 19, "ADX").desc("Intel, AMD", "Multi-precision add-carry instructions (ADCX/ADOX)"),
+    ftr(0x7, 0x0, EBX, 20, "SMAP").desc("Intel, AMD", "Supervisor-Mode Access Prevention"),
+    ftr(0x7, 0x0, EBX, 21, "AVX512IFMA").desc("Intel, AMD", "AVX-512 Integer Fused Multiply-Add"),
+    ftr(0x7, 0x0, EBX, 23, "CLFLUSHOPT").desc("Intel, AMD", "CLFLUSHOPT instruction"),
+    ftr(0x7, 0x0, EBX, 24, "CLWB").desc("Intel, AMD", "CLWB instruction (cache line write back)"),
+    ftr(0x7, 0x0, EBX, 25, "ProcessorTrace").desc("Intel", "Intel Processor Trace"),
+    ftr(0x7, 0x0, EBX, 26, "AVX512PF").desc("Intel", "AVX-512 Prefetch instructions (Xeon Phi)"),
+    ftr(0x7, 0x0, EBX, 27, "AVX512ER").desc("Intel", "AVX-512 Exponential and Reciprocal instructions (Xeon Phi)"),
+    ftr(0x7, 0x0, EBX, 28, "AVX512CD").desc("Intel, AMD", "AVX-512 Conflict Detection"),
+    ftr(0x7, 0x0, EBX, 29, "SHA").desc("Intel, AMD", "SHA-1/SHA-256 instruction extensions (SHA-NI)"),
+    ftr(0x7, 0x0, EBX, 30, "AVX512BW").desc("Intel, AMD", "AVX-512 Byte and Word instructions"),
+    ftr(0x7, 0x0, EBX, 31, "AVX512VL").desc("Intel, AMD", "AVX-512 Vector Length extensions"),
+
+    /* CPUID.(EAX=7h, ECX=0h):ECX */
+    ftr(0x7, 0x0, ECX, 0, "PREFETCHWT1").desc("Intel", "PREFETCHWT1 instruction (Xeon Phi)"), // Intel Xeon Phi only
+    ftr(0x7, 0x0, ECX, 1, "AVX512_VBMI").desc("Intel, AMD", "AVX-512 Vector Bit Manipulation Instructions"),
+    ftr(0x7, 0x0, ECX, 2, "UMIP").desc("Intel, AMD", "User-Mode Instruction Prevention"),
+    ftr(0x7, 0x0, ECX, 3, "PKU").desc("Intel, AMD", "Protection Keys for user-mode pages"),
+    ftr(0x7, 0x0, ECX, 4, "OSPKE").desc("Intel, AMD", "The OS has set CR4.PKE to enable Protection Keys"),
+    ftr(0x7, 0x0, ECX, 5, "WAITPKG").desc("Intel", "UMONITOR/UMWAIT/TPAUSE instructions"),
+    ftr(0x7, 0x0, ECX, 6, "AVX512_VBMI2").desc("Intel, AMD", "AVX-512 Vector Bit Manipulation Instructions 2"),
+    ftr(0x7, 0x0, ECX, 7, "CET_SS").desc("Intel, AMD", "CET Shadow Stack"),
+    ftr(0x7, 0x0, ECX, 8, "GFNI").desc("Intel, AMD", "Galois Field New Instructions"),
+    ftr(0x7, 0x0, ECX, 9, "VAES").desc("Intel, AMD", "Vector AES instructions"),
+    ftr(0x7, 0x0, ECX, 10, "VPCLMULQDQ").desc("Intel, AMD", "Vector carry-less multiplication"),
+    ftr(0x7, 0x0, ECX, 11, "AVX512_VNNI").desc("Intel, AMD", "AVX-512 Vector Neural Network Instructions"),
+    ftr(0x7, 0x0, ECX, 12, "AVX512_BITALG").desc("Intel, AMD", "AVX-512 VPOPCNTB/W and VPSHUFBITQMB"),
+    ftr(0x7, 0x0, ECX, 13, "TME_EN").desc("Intel", "Total Memory Encryption"),
+    ftr(0x7, 0x0, ECX, 14, "AVX512_VPOPCNTDQ").desc("Intel, AMD", "AVX-512 VPOPCNTD/Q"),
+    ftr(0x7, 0x0, ECX, 16, "LA57").desc("Intel, AMD", "5-level paging (57-bit linear address)"), // 57-bit linear addresses
+    // bit 17-21: The value of MAWAU used by the BNDLDX and BNDSTX instructions in 64-bit mode.
+    ftr(0x7, 0x0, ECX, 22, "RDPID").desc("Intel, AMD", "RDPID instruction"),
+    ftr(0x7, 0x0, ECX, 23, "KL").desc("Intel", "Key Locker (AES keys wrapped by handles)"), // Key Locker
+    ftr(0x7, 0x0, ECX, 24, "BUS_LOCK_DETECT").desc("Intel, AMD", "OS bus-lock detection (#DB after a split lock)"),
+    ftr(0x7, 0x0, ECX, 25, "CLDEMOTE").desc("Intel", "CLDEMOTE instruction (cache line demote)"),
+    ftr(0x7, 0x0, ECX, 27, "MOVDIRI").desc("Intel, AMD", "MOVDIRI instruction (direct store)"),
+    ftr(0x7, 0x0, ECX, 28, "MOVDIRI64B").desc("Intel, AMD", "MOVDIR64B instruction (64-byte direct store)"),
+    ftr(0x7, 0x0, ECX, 29, "ENQCMD").desc("Intel", "ENQCMD/ENQCMDS instructions (enqueue stores)"), // Enqueue Stores
+    ftr(0x7, 0x0, ECX, 30, "SGX_LC").desc("Intel", "SGX Launch Configuration (flexible launch control)"), // SGX Launch Configuration
+    ftr(0x7, 0x0, ECX, 31, "PKS").desc("Intel", "Protection Keys for supervisor-mode pages"), // protection keys for supervisor-mode pages
+
+    /* CPUID.(EAX=7h, ECX=0h):EDX */
+    ftr(0x7, 0x0, EDX, 2, "AVX512_4VNNIW").desc("Intel", "AVX-512 4-iteration Vector Neural Network Instructions (Xeon Phi)"), // Intel Xeon Phi only
+    ftr(0x7, 0x0, EDX, 3, "AVX512_4FMAPS").desc("Intel", "AVX-512 4-iteration single-precision FMA (Xeon Phi)"), // Intel Xeon Phi only
+    ftr(0x7, 0x0, EDX, 4, "FSRM").desc("Intel, AMD", "Fast Short REP MOV"), // Fast Short REP MOV
+    ftr(0x7, 0x0, EDX, 5, "UINTR").desc("Intel", "User Interrupts"), // the processor supports user interrupts
+    ftr(0x7, 0x0, EDX, 8, "AVX512_VP2INTERSECT").desc("Intel", "AVX-512 VP2INTERSECTD/Q"),
+    ftr(0x7, 0x0, EDX, 9, "SRBDS_CTRL").desc("Intel", "SRBDS mitigation control (IA32_MCU_OPT_CTRL MSR)"),
+    ftr(0x7, 0x0, EDX, 10, "MD_CLEAR").desc("Intel", "VERW clears the CPU buffers (MDS mitigation)"),
+    ftr(0x7, 0x0, EDX, 11, "RTM_ALWAYS_ABORT").desc("Intel", "RTM transactions always abort"),
+    ftr(0x7, 0x0, EDX, 13, "RTM_FORCE_ABORT").desc("Intel", "RTM_FORCE_ABORT bit of TSX_FORCE_ABORT MSR"),
+    ftr(0x7, 0x0, EDX, 14, "SERIALIZE").desc("Intel, AMD", "SERIALIZE instruction"),
+    ftr(0x7, 0x0, EDX, 15, "Hybrid").desc("Intel", "Hybrid part (different core types)"),
+    ftr(0x7, 0x0, EDX, 16, "TSXLDTRK").desc("Intel", "TSX suspend load address tracking (XSUSLDTRK/XRESLDTRK)"), // Intel TSX suspend load address tracking
+    ftr(0x7, 0x0, EDX, 18, "PCONFIG").desc("Intel", "PCONFIG instruction (platform configuration, MKTME)"),
+    ftr(0x7, 0x0, EDX, 19, "ArchitecturalLBR").desc("Intel", "Architectural Last Branch Records"),
+    ftr(0x7, 0x0, EDX, 20, "CET_IBT").desc("Intel", "CET Indirect Branch Tracking"),
+    ftr(0x7, 0x0, EDX, 22, "AMX-BF16").desc("Intel", "AMX tile computational operations on bfloat16"),
+    ftr(0x7, 0x0, EDX, 23, "AVX512_FP16").desc("Intel, AMD", "AVX-512 half-precision floating-point"),
+    ftr(0x7, 0x0, EDX, 24, "AMX-TILE").desc("Intel", "Advanced Matrix Extensions, tile architecture"),
+    ftr(0x7, 0x0, EDX, 25, "AMX-INT8").desc("Intel", "AMX tile computational operations on 8-bit integers"),
+    ftr(0x7, 0x0, EDX, 26, "IBRS").desc("Intel, AMD", "Indirect Branch Restricted Speculation"),
+    ftr(0x7, 0x0, EDX, 27, "STIBP").desc("Intel, AMD", "Single Thread Indirect Branch Predictors"),
+    ftr(0x7, 0x0, EDX, 28, "L1D_FLUSH").desc("Intel", "L1D_FLUSH command of IA32_FLUSH_CMD MSR"),
+    // 0x7, 0x0, EDX, 29, "IA32_ARCH_CAPABILITIES"
+    // 0x7, 0x0, EDX, 30, "IA32_CORE_CAPABILITIES"
+    ftr(0x7, 0x0, EDX, 31, "SSBD").desc("Intel, AMD", "Speculative Store Bypass Disable"),
+
+    /* CPUID.(EAX=7h, ECX=1h):EAX */
+    ftr(0x7, 0x1, EAX, 3, "RAO-INT").desc("Intel", "RAO-INT instructions (remote atomic operations)"),
+    ftr(0x7, 0x1, EAX, 4, "AVX-VNNI").desc("Intel, AMD", "VEX-encoded Vector Neural Network Instructions"),
+    ftr(0x7, 0x1, EAX, 5, "AVX512_BF16").desc("Intel, AMD", "AVX-512 bfloat16 instructions"),
+    ftr(0x7, 0x1, EAX, 6, "LASS").desc("Intel", "Linear Address Space Separation"), // Linear Address Space Separation.
+    ftr(0x7, 0x1, EAX, 7, "CMPCCXADD").desc("Intel", "CMPccXADD instructions"),
+    ftr(0x7, 0x1, EAX, 8, "ArchPerfmonExt").desc("Intel, AMD", "Architectural performance monitoring extended leaf (0x23)"),
+    ftr(0x7, 0x1, EAX, 10, "FZRM").desc("Intel, AMD", "Fast zero-length REP MOVSB"), // fast zero-length MOVSB
+    ftr(0x7, 0x1, EAX, 11, "FSRS").desc("Intel, AMD", "Fast short REP STOSB"), // fast short STOSB
+    ftr(0x7, 0x1, EAX, 12, "FSRC").desc("Intel, AMD", "Fast short REP CMPSB/SCASB"), // fast short CMPSB, SCASB
+    /* https://lore.kernel.org/lkml/20221006154041.13001-2-xin3.li@intel.com/ */
+    ftr(0x7, 0x1, EAX, 18, "LKGS").desc("Intel", "LKGS instruction (load the kernel GS base)"), // Load "kernel" (userspace) gs
+    ftr(0x7, 0x1, EAX, 19, "WRMSRNS").desc("Intel", "WRMSRNS instruction (non-serializing WRMSR)"), // Non-Serializing WRMSR
+    ftr(0x7, 0x1, EAX, 21, "AMX-FP16").desc("Intel", "AMX tile computational operations on FP16"),
+    ftr(0x7, 0x1, EAX, 22, "HRESET").desc("Intel", "HRESET instruction (history reset)"),
+    ftr(0x7, 0x1, EAX, 23, "AVX-IFMA").desc("Intel", "AVX (VEX-encoded) Integer Fused Multiply-Add"),
+    ftr(0x7, 0x1, EAX, 26, "LAM").desc("Intel", "Linear Address Masking"), // Linear Address Masking
+    ftr(0x7, 0x1, EAX, 27, "MSRLIST").desc("Intel", "RDMSRLIST/WRMSRLIST instructions"), // [RD,WR]MSRLIST
+
+    /* CPUID.(EAX=7h, ECX=1h):EDX */
+    ftr(0x7, 0x1, EDX, 4, "AVX-VNNI-INT8").desc("Intel", "AVX (VEX-encoded) VNNI on 8-bit integers"),
+    ftr(0x7, 0x1, EDX, 5, "AVX-NE-CONVERT").desc("Intel", "AVX (VEX-encoded) BF16/FP16 conversion instructions"),
+    ftr(0x7, 0x1, EDX, 14, "PREFETCHITI").desc("Intel", "PREFETCHIT0/PREFETCHIT1 instructions (code prefetch)"),
+    ftr(0x7, 0x1, EDX, 18, "CET_SSS").desc("Intel", "CET supervisor shadow stack"), // CET Supervisor Shadow Stack
+
+    /* CPUID.(EAX=7h, ECX=2h):EDX */
+    ftr(0x7, 0x2, EDX, 0, "PSFD").desc("Intel", "PSFD bit of IA32_SPEC_CTRL (disable Fast Store Forwarding Predictor)"), // Fast Store Forwarding Predictor without disabling Speculative Store Bypass
+    ftr(0x7, 0x2, EDX, 1, "IPRED_CTRL").desc("Intel", "IPRED_DIS bits of IA32_SPEC_CTRL (restrict indirect predictions)"),
+    ftr(0x7, 0x2, EDX, 2, "RRSBA_CTRL").desc("Intel", "RRSBA_DIS bits of IA32_SPEC_CTRL (restrict RSB alternate predictions)"),
+    ftr(0x7, 0x2, EDX, 3, "DDPD_U").desc("Intel", "DDPD_U bit of IA32_SPEC_CTRL (disable Data Dependent Prefetcher)"), // Data Dependent Prefetche
+    ftr(0x7, 0x2, EDX, 4, "BHI_CTRL").desc("Intel", "BHI_DIS_S bit of IA32_SPEC_CTRL (Branch History Injection mitigation)"),
+    ftr(0x7, 0x2, EDX, 5, "MCDT_NO").desc("Intel", "No MXCSR Configuration Dependent Timing"), // MXCSR Configuration Dependent Timing
+
+    /* Ref: https://github.com/torvalds/linux/blob/master/arch/x86/kernel/fpu/xstate.c */
+    /* CPUID.(EAX=Dh, ECX=0h):EAX */
+    ftr(0xD, 0x0, EAX, 0, "X87").desc("Intel, AMD", "XSAVE state component: x87 state"),
+    ftr(0xD, 0x0, EAX, 1, "SSE").desc("Intel, AMD", "XSAVE state component: SSE state (XMM, MXCSR)"),
+    ftr(0xD, 0x0, EAX, 2, "AVX256").desc("Intel, AMD", "XSAVE state component: AVX state (upper halves of YMM)"),
+    ftr(0xD, 0x0, EAX, 3, "MPX bounds").desc("Intel", "XSAVE state component: MPX bound registers"),
+    ftr(0xD, 0x0, EAX, 4, "MPX CSR").desc("Intel", "XSAVE state component: MPX configuration and status registers"),
+    ftr(0xD, 0x0, EAX, 5, "AVX512 opmask").desc("Intel, AMD", "XSAVE state component: AVX-512 opmask registers"), // KREGS
+    ftr(0xD, 0x0, EAX, 6, "AVX512 Hi256").desc("Intel, AMD", "XSAVE state component: upper halves of ZMM0-15"),
+    ftr(0xD, 0x0, EAX, 7, "AVX512 ZMM_Hi256").desc("Intel, AMD", "XSAVE state component: ZMM16-31"),
+    ftr(0xD, 0x0, EAX, 8, "Processor Trace").desc("Intel", "XSAVES state component: Processor Trace"), // unused
+    ftr(0xD, 0x0, EAX, 9, "Protection Key User").desc("Intel, AMD", "XSAVE state component: PKRU register"),
+    ftr(0xD, 0x0, EAX, 10, "PASID").desc("Intel", "XSAVES state component: PASID"),
+    ftr(0xD, 0x0, EAX, 11, "CET User").desc("Intel, AMD", "XSAVES state component: CET user state"),
+    ftr(0xD, 0x0, EAX, 12, "CET Supervisor").desc("Intel, AMD", "XSAVES state component: CET supervisor state"),
+    ftr(0xD, 0x0, EAX, 13, "HDC").desc("Intel", "XSAVES state component: Hardware Duty Cycling"),
+    ftr(0xD, 0x0, EAX, 14, "UINTR").desc("Intel", "XSAVES state component: User Interrupts"),
+    ftr(0xD, 0x0, EAX, 15, "LBR").desc("Intel", "XSAVES state component: Architectural LBR"),
+    ftr(0xD, 0x0, EAX, 16, "HWP").desc("Intel", "XSAVES state component: HWP request"),
+    ftr(0xD, 0x0, EAX, 17, "AMX Tile config").desc("Intel", "XSAVE state component: AMX tile configuration (TILECFG)"),
+    ftr(0xD, 0x0, EAX, 18, "AMX Tile data").desc("Intel", "XSAVE state component: AMX tile data (TILEDATA)"),
+    ftr(0xD, 0x0, EAX, 19, "APX").desc("Intel", "XSAVE state component: APX extended GPRs (R16-R31)"),
+
+    /* CPUID.(EAX=Dh, ECX=1h):EAX */
+    ftr(0xD, 0x1, EAX, 0, "XSAVEOPT").desc("Intel, AMD", "XSAVEOPT instruction"),
+    ftr(0xD, 0x1, EAX, 1, "XSAVEC").desc("Intel, AMD", "XSAVEC instruction (compacted format)"),
+    ftr(0xD, 0x1, EAX, 2, "XGETBV").desc("Intel, AMD", "XGETBV with ECX = 1 (XINUSE)"),
+    ftr(0xD, 0x1, EAX, 3, "XSAVES").desc("Intel, AMD", "XSAVES/XRSTORS instructions and IA32_XSS"),
+    ftr(0xD, 0x1, EAX, 4, "XFD").desc("Intel", "Extended Feature Disable"),
+
+    /* CPUID.(EAX=Dh, ECX=1h):ECX */
+    ftr(0xD, 0x1, ECX, 11, "CET User").desc("Intel, AMD", "CET user state is supported in IA32_XSS"),
+    ftr(0xD, 0x1, ECX, 12, "CET SuperVisor").desc("Intel, AMD", "CET supervisor state is supported in IA32_XSS"),
+
+    /* CPUID.(EAX=80000001h, ECX=0h):ECX */
+    ftr(0x8000_0001, 0x0, ECX, 0, "LAHF/SAHF").desc("Intel, AMD", "LAHF/SAHF instructions in 64-bit mode"),
+    ftr(0x8000_0001, 0x0, ECX, 1, "CmpLegacy").desc("AMD", "Core multi-processing legacy mode"),
+    ftr(0x8000_0001, 0x0, ECX, 2, "SVM").desc("AMD", "AMD Secure Virtual Machine (AMD-V)"),
+    ftr(0x8000_0001, 0x0, ECX, 3, "Ext_APIC_Space").desc("AMD", "Extended APIC register space"),
+    ftr(0x8000_0001, 0x0, ECX, 4, "Alt_MOV_CR8").desc("AMD", "LOCK MOV CR0 means MOV CR8"),
+    ftr(0x8000_0001, 0x0, ECX, 5, "ABM").desc("Intel, AMD", "Advanced Bit Manipulation (LZCNT)"),
+    ftr(0x8000_0001, 0x0, ECX, 6, "SSE4A").desc("AMD", "AMD SSE4a instructions (EXTRQ/INSERTQ/MOVNTSD/MOVNTSS)"),
+    ftr(0x8000_0001, 0x0, ECX, 7, "MisAlignSSE").desc("AMD", "Misaligned SSE mode"),
+    ftr(0x8000_0001, 0x0, ECX, 8, "3DNowPrefetch").desc("Intel, AMD", "PREFETCH/PREFETCHW instructions"),
+    ftr(0x8000_0001, 0x0, ECX, 9, "OSVW").desc("AMD", "OS Visible Workaround"), // OS visible workaround
+    ftr(0x8000_0001, 0x0, ECX, 10, "IBS").desc("AMD", "Instruction Based Sampling"), // Instruction based sampling
+    ftr(0x8000_0001, 0x0, ECX, 11, "XOP").desc("AMD", "AMD eXtended Operations"), // Extended operation
+    ftr(0x8000_0001, 0x0, ECX, 12, "SKINIT").desc("AMD", "SKINIT and STGI instructions"), // SKINIT and STGI
+    ftr(0x8000_0001, 0x0, ECX, 13, "WDT").desc("AMD", "Watchdog timer"), // Watchdog timer
+    ftr(0x8000_0001, 0x0, ECX, 15, "LWP").desc("AMD", "Lightweight profiling"), // Lightweight profiling
+    ftr(0x8000_0001, 0x0, ECX, 16, "FMA4").desc("AMD", "Fused multiply-add with 4 operands"),
+    ftr(0x8000_0001, 0x0, ECX, 17, "TCE").desc("AMD", "Translation Cache Extension"), // Translation Cache Extension
+    ftr(0x8000_0001, 0x0, ECX, 21, "TBM").desc("AMD", "AMD Trailing Bit Manipulation"), // Trailing bit manipulation
+    ftr(0x8000_0001, 0x0, ECX, 22, "TopologyExtensions").desc("AMD", "AMD topology extensions (0x8000_001D, 0x8000_001E)"), // CPUID Fn8000_001D_EAX_x[N:0]-CPUID Fn8000_001E_EDX
+    ftr(0x8000_0001, 0x0, ECX, 23, "PerfCtrExtCore").desc("AMD", "Core performance counter extensions"), // Processor performance counter extensions
+    ftr(0x8000_0001, 0x0, ECX, 24, "PerfCtrExtNB").desc("AMD", "Northbridge (Data Fabric) performance counter extensions"), // NB performance counter extensions
+    ftr(0x8000_0001, 0x0, ECX, 26, "DataBkptExt").desc("AMD", "Data access breakpoint extension (address mask)"), // Data access breakpoint extension
+    ftr(0x8000_0001, 0x0, ECX, 27, "PerfTSC").desc("AMD", "Performance time-stamp counter"), // Performance time-stamp counter
+    ftr(0x8000_0001, 0x0, ECX, 28, "PerfCtrExtLLC").desc("AMD", "L3 performance counter extension"), // L3 performance counter extension
+    ftr(0x8000_0001, 0x0, ECX, 29, "MONITORX").desc("AMD", "MONITORX/MWAITX instructions"), // MWAITX/MONITORX
+    ftr(0x8000_0001, 0x0, ECX, 30, "AddrMaskExt").desc("AMD", "Extended address masking for instruction breakpoints"),
+
+    /* CPUID.(EAX=80000001h, ECX=0h):EDX */
+    ftr(0x8000_0001, 0x0, EDX, 11, "SYSCALL/SYSRET").desc("Intel, AMD", "SYSCALL/SYSRET instructions"),
+    ftr(0x8000_0001, 0x0, EDX, 20, "NXbit").desc("Intel, AMD", "No-execute page protection"),
+    ftr(0x8000_0001, 0x0, EDX, 26, "Page1GB").desc("Intel, AMD", "1-GByte pages"),
+    ftr(0x8000_0001, 0x0, EDX, 27, "RDTSCP").desc("Intel, AMD", "RDTSCP instruction and IA32_TSC_AUX"),
+    ftr(0x8000_0001, 0x0, EDX, 29, "LongMode").desc("Intel, AMD", "Long mode (x86-64)"),
+    ftr(0x8000_0001, 0x0, EDX, 30, "3DNow!Ext").desc("AMD", "AMD extensions to 3DNow! instructions"),
+    ftr(0x8000_0001, 0x0, EDX, 31, "3DNow!").desc("AMD", "3DNow! instructions"),
+
+    /* CPUID.(EAX=80000008h, ECX=0h):EBX */
+    ftr(0x8000_0008, 0x0, EBX, 0, "CLZERO").desc("AMD", "CLZERO instruction"),
+    ftr(0x8000_0008, 0x0, EBX, 1, "InstRetCntMsr").desc("AMD", "Instructions retired count MSR (IRPerf)"),
+    ftr(0x8000_0008, 0x0, EBX, 2, "RstrFpErrPtrs").desc("AMD", "FXSAVE/XSAVE always save the error pointers"),
+    ftr(0x8000_0008, 0x0, EBX, 3, "INVLPGB").desc("AMD", "INVLPGB and TLBSYNC instructions (broadcast TLB invalidation)"), // INVLPGB and TLBSYNC instruction
+    ftr(0x8000_0008, 0x0, EBX, 4, "RDPRU").desc("AMD", "RDPRU instruction"),
+    ftr(0x8000_0008, 0x0, EBX, 8, "MCOMMIT").desc("AMD", "MCOMMIT instruction"),
+    ftr(0x8000_0008, 0x0, EBX, 9, "WBNOINVD").desc("Intel, AMD", "WBNOINVD instruction"),
+    ftr(0x8000_0008, 0x0, EBX, 12, "IBPB").desc("Intel, AMD", "Indirect Branch Prediction Barrier"),
+    ftr(0x8000_0008, 0x0, EBX, 13, "INT_WBINVD").desc("AMD", "WBINVD/WBNOINVD are interruptible"),
+    ftr(0x8000_0008, 0x0, EBX, 14, "IBRS").desc("Intel, AMD", "Indirect Branch Restricted Speculation"),
+    ftr(0x8000_0008, 0x0, EBX, 15, "STIBP").desc("Intel, AMD", "Single Thread Indirect Branch Predictors"),
+    ftr(0x8000_0008, 0x0, EBX, 16, "IBRS_Always_On").desc("AMD", "Processor prefers that IBRS is always on"),
+    ftr(0x8000_0008, 0x0, EBX, 17, "STIBP_Always_On").desc("AMD", "Processor prefers that STIBP is always on"),
+    ftr(0x8000_0008, 0x0, EBX, 18, "IBRS_Preferred").desc("AMD", "IBRS is preferred over software mitigations"),
+    ftr(0x8000_0008, 0x0, EBX, 19, "IBRS_Same_Mode").desc("AMD", "IBRS provides the same-mode protection"),
+    ftr(0x8000_0008, 0x0, EBX, 20, "EFER_LMSLE_Unsupported").desc("AMD", "EFER.LMSLE is not supported"),
+    ftr(0x8000_0008, 0x0, EBX, 21, "INVLPGB_Nested_Pages").desc("AMD", "INVLPGB of the nested translations"),
+    ftr(0x8000_0008, 0x0, EBX, 23, "PPIN").desc("AMD", "Protected Processor Inventory Number"), // Protected Processor Inventory Number
+    ftr(0x8000_0008, 0x0, EBX, 24, "SSBD").desc("Intel, AMD", "Speculative Store Bypass Disable"), // Speculative Store Bypass Disable
+    ftr(0x8000_0008, 0x0, EBX, 25, "SSBD_Virt_Spec_Ctrl").desc("AMD", "SSBD through VIRT_SPEC_CTRL MSR"),
+    ftr(0x8000_0008, 0x0, EBX, 26, "SSBD_Not_Required").desc("AMD", "SSBD is not needed"),
+    ftr(0x8000_0008, 0x0, EBX, 27, "CPPC").desc("AMD", "Collaborative Processor Performance Control"), // Collaborative Processor Performance Control
+    ftr(0x8000_0008, 0x0, EBX, 28, "PSFD").desc("AMD", "Predictive Store Forwarding Disable"), // Predictive Store Forward Disable
+    ftr(0x8000_0008, 0x0, EBX, 29, "BTC_NO").desc("AMD", "Not affected by Branch Type Confusion"), // Branch Type Confusion
+    ftr(0x8000_0008, 0x0, EBX, 30, "IBPB_RET").desc("AMD", "IBPB also clears the return address predictor"), // IBPB also clears the return address predictor
+
+    /* CPUID.(EAX=80000007h, ECX=0h):EDX */
+    ftr(0x8000_0007, 0x0, EDX, 0, "TS").desc("AMD", "Temperature sensor"), // Temperature Sensor
+    ftr(0x8000_0007, 0x0, EDX, 3, "TTP").desc("AMD", "THERMTRIP"), // THERMTRIP
+    ftr(0x8000_0007, 0x0, EDX, 4, "TM").desc("AMD", "Hardware thermal control"), // Hardware thermal control
+    ftr(0x8000_0007, 0x0, EDX, 6, "100MHzSteps").desc("AMD", "100 MHz multiplier control"),
+    ftr(0x8000_0007, 0x0, EDX, 7, "HwPstate").desc("AMD", "Hardware P-state control"),
+    ftr(0x8000_0007, 0x0, EDX, 8, "TscInvariant").desc("Intel, AMD", "Invariant TSC (constant rate in all the P/C-states)"),
+    ftr(0x8000_0007, 0x0, EDX, 9, "CPB").desc("AMD", "AMD Core Performance Boost"), // Core Performance Boost
+    ftr(0x8000_0007, 0x0, EDX, 10, "EffFreqRO").desc("AMD", "Read-only effective frequency interface (MPERF/APERF RO)"), // read-only effective frequency interface
+    ftr(0x8000_0007, 0x0, EDX, 11, "ProcFeedbackInterface").desc("AMD", "Processor feedback interface"),
+    ftr(0x8000_0007, 0x0, EDX, 12, "ProcPowerReporting").desc("AMD", "Core power reporting"),
+    ftr(0x8000_0007, 0x0, EDX, 13, "ConnectedStandby").desc("AMD", "Connected standby"),
+    ftr(0x8000_0007, 0x0, EDX, 14, "RAPL").desc("AMD", "Running Average Power Limit"),
+
+    /* CPUID.(EAX=8000000Ah, ECX=0h):EDX */
+    ftr(0x8000_000A, 0x0, EDX, 0, "NestedPaging").desc("AMD", "AMD Nested Paging (RVI)"),
+    ftr(0x8000_000A, 0x0, EDX, 1, "LbrVirt").desc("AMD", "LBR virtualization"),
+    ftr(0x8000_000A, 0x0, EDX, 2, "SVM_Lock").desc("AMD", "SVM lock (SVMDIS bit of VM_CR MSR)"),
+    ftr(0x8000_000A, 0x0, EDX, 3, "NRIP_Save").desc("AMD", "Next RIP save on #VMEXIT"),
+    ftr(0x8000_000A, 0x0, EDX, 4, "TSC_Rate_MSR").desc("AMD", "MSR-based TSC rate control"),
+    ftr(0x8000_000A, 0x0, EDX, 5, "VMCB_Clean").desc("AMD", "VMCB clean bits"),
+    ftr(0x8000_000A, 0x0, EDX, 6, "FlushByASID").desc("AMD", "Flush by ASID"),
+    ftr(0x8000_000A, 0x0, EDX, 7, "DecodeAssists").desc("AMD", "Decode assists"),
+    ftr(0x8000_000A, 0x0, EDX, 10, "PauseFilter").desc("AMD", "PAUSE intercept filter"),
+    ftr(0x8000_000A, 0x0, EDX, 12, "PauseFilterThreshold").desc("AMD", "PAUSE filter cycle count threshold"),
+    ftr(0x8000_000A, 0x0, EDX, 13, "AVIC").desc("AMD", "AMD Advanced Virtual Interrupt Controller"), // AMD virtual interrupt controller
+    ftr(0x8000_000A, 0x0, EDX, 15, "V_VMSAVE_VMLOAD").desc("AMD", "Virtualized VMSAVE/VMLOAD"),
+    ftr(0x8000_000A, 0x0, EDX, 16, "vGIF").desc("AMD", "Virtualized Global Interrupt Flag"), // Virtualized GIF
+    ftr(0x8000_000A, 0x0, EDX, 17, "GMET").desc("AMD", "Guest Mode Execute Trap"), // Guest Mode Execute Trap
+    ftr(0x8000_000A, 0x0, EDX, 18, "x2AVIC").desc("AMD", "x2APIC mode of AVIC"),
+    ftr(0x8000_000A, 0x0, EDX, 19, "SupervisorShadowStack").desc("AMD", "SVM supervisor shadow stack restrictions"),
+    ftr(0x8000_000A, 0x0, EDX, 20, "GuestSpecCtrl").desc("AMD", "SPEC_CTRL virtualization of the guest"),
+    ftr(0x8000_000A, 0x0, EDX, 21, "ROGPT").desc("AMD", "Read-Only Guest Page Table"), // Read-Only Guest Page Table
+    ftr(0x8000_000A, 0x0, EDX, 23, "HOST_MCE_OVERRIDE").desc("AMD", "Host MCE override (#MC intercept)"),
+    ftr(0x8000_000A, 0x0, EDX, 25, "VNMI").desc("AMD", "NMI virtualization"), // NMI Virtualization
+    ftr(0x8000_000A, 0x0, EDX, 26, "IbsVirt").desc("AMD", "IBS virtualization"),
+
+    /* CPUID.(EAX=8000001Ah, ECX=0h):EAX */
+    ftr(0x8000_001A, 0x0, EAX, 0, "FP128").desc("AMD", "128-bit SSE full-width execution"),
+    ftr(0x8000_001A, 0x0, EAX, 1, "MOVU").desc("AMD", "MOVU SSE instructions are more efficient than MOVL/MOVH"),
+    ftr(0x8000_001A, 0x0, EAX, 2, "FP256").desc("AMD", "256-bit AVX full-width execution"),
+
+    /* CPUID.(EAX=8000001Bh, ECX=0h):EAX */
+    ftr(0x8000_001B, 0x0, EAX, 0, "IBSFFV").desc("AMD", "IBS feature flags valid"), // IBS feature flags valid
+    ftr(0x8000_001B, 0x0, EAX, 1, "FetchSam").desc("AMD", "IBS fetch sampling"), // IBS fetch sampling
+    ftr(0x8000_001B, 0x0, EAX, 2, "OpSam").desc("AMD", "IBS execution sampling"), // IBS execution sampling
+    ftr(0x8000_001B, 0x0, EAX, 3, "RdWrOpCnt").desc("AMD", "Read/write of the IBS op counter"), // Read write of op counter
+    ftr(0x8000_001B, 0x0, EAX, 4, "OpCnt").desc("AMD", "IBS op counting mode"), // Op counting mode
+    ftr(0x8000_001B, 0x0, EAX, 5, "BrnTrgt").desc("AMD", "IBS branch target address reporting"), // Branch target address reporting
+    ftr(0x8000_001B, 0x0, EAX, 6, "OpCntExt").desc("AMD", "IBS op counters extended by 7 bits"), // IbsOpCurCnt and IbsOpMaxCnt extend by 7 bits
+    ftr(0x8000_001B, 0x0, EAX, 7, "RipInvalidChk").desc("AMD", "IBS invalid RIP indication"), // Invalid RIP indication
+    ftr(0x8000_001B, 0x0, EAX, 8, "OpBrnFuse").desc("AMD", "IBS fused branch micro-op indication"), // Fused branch micro-op indication
+    ftr(0x8000_001B, 0x0, EAX, 11, "IbsL3MissFiltering").desc("AMD", "IBS L3 miss filtering"),
+
+    /* CPUID.(EAX=8000001Fh, ECX=0h):EAX */
+    ftr(0x8000_001F, 0x0, EAX, 0, "SME").desc("AMD", "AMD Secure Memory Encryption"), // Secure Memory Encryption
+    ftr(0x8000_001F, 0x0, EAX, 1, "SEV").desc("AMD", "AMD Secure Encrypted Virtualization"), // Secure Encrypted Virtualization
+    ftr(0x8000_001F, 0x0, EAX, 2, "PageFlushMSR").desc("AMD", "Page Flush MSR"),
+    ftr(0x8000_001F, 0x0, EAX, 3, "SEV-ES").desc("AMD", "SEV Encrypted State"), // SEV Encrypted State
+    ftr(0x8000_001F, 0x0, EAX, 4, "SEV-SNP").desc("AMD", "SEV Secure Nested Paging"), // SEV Secure Nested Paging
+    ftr(0x8000_001F, 0x0, EAX, 5, "VMPL").desc("AMD", "SEV-SNP VM Permission Levels"), // VM Permission Levels
+    ftr(0x8000_001F, 0x0, EAX, 8, "SecureTSC").desc("AMD", "Secure TSC for SEV-SNP guests"),
+    ftr(0x8000_001F, 0x0, EAX, 10, "HwEnfCacheCoh").desc("AMD", "Hardware cache coherency across encryption domains"), // Hardware cache coherency across encryption domains enforced
+    ftr(0x8000_001F, 0x0, EAX, 11, "64BitHost").desc("AMD", "SEV guests only run in 64-bit mode hosts"),
+    ftr(0x8000_001F, 0x0, EAX, 12, "RestrictedInjection").desc("AMD", "Restricted interrupt injection for SEV-ES guests"),
+    ftr(0x8000_001F, 0x0, EAX, 13, "AlternateInjection").desc("AMD", "Alternate interrupt injection for SEV-ES guests"),
+    ftr(0x8000_001F, 0x0, EAX, 14, "DebugSwap").desc("AMD", "Full debug state swap for SEV-ES guests"),
+    ftr(0x8000_001F, 0x0, EAX, 15, "PreventHostIBS").desc("AMD", "Host IBS is disallowed for SEV-ES guests"),
+    ftr(0x8000_001F, 0x0, EAX, 16, "VTE").desc("AMD", "Virtual Transparent Encryption"), // Virtual Transparent Encryption
+    ftr(0x8000_001F, 0x0, EAX, 17, "VmgexitParameter").desc("AMD", "VMGEXIT parameter"),
+    ftr(0x8000_001F, 0x0, EAX, 18, "VirtualTomMsr").desc("AMD", "Virtual TOM MSR"),
+    ftr(0x8000_001F, 0x0, EAX, 19, "IbsVirtGuestCtl").desc("AMD", "IBS virtualization for SEV-ES guests"),
+    /* .. */
+    ftr(0x8000_001F, 0x0, EAX, 24, "VMSARegProt").desc("AMD", "VMSA register protection"), // VMSA Register Protection
+    ftr(0x8000_001F, 0x0, EAX, 25, "SmtProtection").desc("AMD", "SMT protection"),
+    /* .. */
+    ftr(0x8000_001F, 0x0, EAX, 28, "SvsmCommPageMSR").desc("AMD", "SVSM communication page MSR"), // SVSM Communication Page MSR
+    ftr(0x8000_001F, 0x0, EAX, 29, "NestedVirtSnpMsr").desc("AMD", "Nested virtualization of the SNP MSRs"),
+
+    /* CPUID.(EAX=80000020h, ECX=0h):EBX */
+    ftr(0x8000_0020, 0x0, EBX, 1, "L3MBE").desc("AMD", "L3 Memory Bandwidth Enforcement"), // L3 Memory Bandwidth Enforcement
+    ftr(0x8000_0020, 0x0, EBX, 2, "L3SMBE").desc("AMD", "L3 Slow Memory Bandwidth Enforcement"), // L3 Slow Memory Bandwidth Enforcement
+    ftr(0x8000_0020, 0x0, EBX, 3, "BMEC").desc("AMD", "Bandwidth Monitoring Event Configuration"), // Bandwidth Monitoring Event Configuration
+    ftr(0x8000_0020, 0x0, EBX, 5, "ABMC").desc("AMD", "Assignable Bandwidth Monitoring Counters"), // Assignable Bandwidth Monitoring Counters
+
+    /* CPUID.(EAX=80000021h, ECX=0h):EAX */
+    ftr(0x8000_0021, 0x0, EAX, 0, "NoNestedDataBp").desc("AMD", "Nested data breakpoints are ignored"), // Processor ignores nested data breakpoints
+    ftr(0x8000_0021, 0x0, EAX, 1, "FsGsKernelGsBaseNonSerializing").desc("AMD", "WRMSR to FS_BASE/GS_BASE/KernelGSBase is non-serializing"),
+    ftr(0x8000_0021, 0x0, EAX, 2, "LFenceAlwaysSerializing").desc("AMD", "LFENCE is always dispatch serializing"),
+    ftr(0x8000_0021, 0x0, EAX, 3, "SmmPgCfgLock").desc("AMD", "SMM paging configuration lock"),
+    ftr(0x8000_0021, 0x0, EAX, 6, "NullSelectClearsBase").desc("AMD", "Null segment selector loads also clear the base"),
+    ftr(0x8000_0021, 0x0, EAX, 7, "UpperAddressIgnore").desc("AMD", "Upper Address Ignore"),
+    ftr(0x8000_0021, 0x0, EAX, 8, "AutomaticIBRS").desc("AMD", "AMD Automatic IBRS"),
+    ftr(0x8000_0021, 0x0, EAX, 9, "NoSmmCtlMSR").desc("AMD", "SMM_CTL MSR is not present"),
+    ftr(0x8000_0021, 0x0, EAX, 10, "FSRS").desc("AMD", "Fast short REP STOSB"), // Fast Short Rep Stosb
+    ftr(0x8000_0021, 0x0, EAX, 11, "FSRC").desc("AMD", "Fast short REPE CMPSB"), // Fast Short Repe Cmpsb
+    ftr(0x8000_0021, 0x0, EAX, 13, "PrefetchCtlMsr").desc("AMD", "Prefetch control MSR"),
+    /* */
+    ftr(0x8000_0021, 0x0, EAX, 17, "CpuidUserDis").desc("AMD", "CPUID disable for the non-privileged software (#GP)"), // GpOnUserCpuid
+    ftr(0x8000_0021, 0x0, EAX, 18, "EPSF").desc("AMD", "Enhanced Predictive Store Forwarding"), // Enhanced Predictive Store Forwarding
+    ftr(0x8000_0021, 0x0, EAX, 22, "Workload_Class").desc("AMD", "Workload-based heuristic feedback to the OS"),
+];
+
+/// Revision of `FTR_BITS`, bump on an added, renamed or removed feature bit
+pub const FTR_REVISION: u32 = 3;
+
+/// FNV-1a of the leaf, sub-leaf, register, bit and name of `FTR_BITS`,
+/// tells the registries apart even if `FTR_REVISION` is not bumped
+pub fn ftr_digest() -> u32 {
+    FTR_BITS.iter().fold(0x811C_9DC5, |hash, f| {
+        [f.leaf, f.sub_leaf, f.reg.index() as u32, f.bit]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .chain(f.name.bytes())
+            .fold(hash, |hash, b| (hash ^ u32::from(b)).wrapping_mul(0x0100_0193))
+    })
+}
+
+/// Feature table of the register, the empty string for unnamed bits
+pub const fn reg_ftr(leaf: u32, sub_leaf: u32, reg: Register) -> [&'static str; 32] {
+    let mut ftr = [""; 32];
+    let mut i = 0;
+
+    while i < FTR_BITS.len() {
+        let f = &FTR_BITS[i];
+
+        if f.leaf == leaf && f.sub_leaf == sub_leaf && f.reg.index() == reg.index() {
+            ftr[f.bit as usize] = f.name;
+        }
+
+        i += 1;
+    }
+
+    ftr
+}
+
+/// Other names of the features: (name in `FTR_BITS`, aliases).
+/// The case and the punctuation are ignored in the lookup (`normalize_ftr_name`),
+/// e.g. "avx512vnni" is "AVX512_VNNI" and "sse4_2" is "SSE4.2", only the other words are listed.
+pub const FTR_ALIASES: &[(&str, &[&str])] = &[
+    ("HTT", &["HyperThreading"]),
+    ("DigitalTempSensor", &["DiditalTempSensor", "DTS"]),
+    ("TurboBoost", &["Turbo"]),
+    ("MemoryProtectionExtensions", &["MPX"]),
+    ("ProcessorTrace", &["PT"]),
+    ("HLE", &["TSX_HLE"]),
+    ("RTM", &["TSX", "TSX_RTM"]),
+    ("ERMS", &["Enhanced_REP_MOVSB"]),
+    ("FSRM", &["Fast_Short_REP_MOVSB"]),
+    ("PKU", &["MPK", "Protection_Keys"]),
+    ("KL", &["KeyLocker"]),
+    ("SGX_LC", &["FLC"]),
+    ("SHA", &["SHA_NI", "SHA-NI"]),
+    ("ABM", &["LZCNT"]),
+    ("LAHF/SAHF", &["LAHF"]),
+    ("SYSCALL/SYSRET", &["SYSCALL", "SCE"]),
+    ("NXbit", &["NX", "XD", "XD_bit"]),
+    ("Page1GB", &["1GB_pages"]),
+    ("LongMode", &["LM", "x86-64", "AMD64", "EM64T", "Intel64"]),
+    ("NestedPaging", &["NPT", "NP"]),
+    ("CPB", &["Core_Performance_Boost"]),
+    ("SEV-SNP", &["SNP"]),
+];
+
+/// Name for the lookup: lowercase, without '_', '-', '.', '/', ' '
+#[cfg(feature = "std")]
+pub fn normalize_ftr_name(name: &str) -> String {
+    name
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-' | '.' | '/' | ' '))
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Aliases of the feature in `FTR_ALIASES`
+pub fn ftr_aliases(name: &str) -> &'static [&'static str] {
+    FTR_ALIASES.iter().find(|(n, _)| *n == name).map_or(&[], |(_, aliases)| aliases)
+}
+
+/// Feature bits with the name, case-insensitive
+pub fn find_ftr(name: &str) -> impl Iterator<Item = &'static FtrBit> + '_ {
+    FTR_BITS.iter().filter(move |f| f.name.eq_ignore_ascii_case(name))
+}
+
+/// Feature bits of the name or its alias, ignoring the case and the punctuation (`normalize_ftr_name`)
+#[cfg(feature = "std")]
+pub fn ftr_bits_by_name(query: &str) -> impl Iterator<Item = &'static FtrBit> {
+    let query = normalize_ftr_name(query);
+
+    FTR_BITS.iter().filter(move |f| {
+        core::iter::once(f.name)
+            .chain(ftr_aliases(f.name).iter().copied())
+            .any(|name| normalize_ftr_name(name) == query)
+    })
+}
+
+impl FtrBit {
+    /// Whether the bit is set in the result of `src`, `false` for the leaf above the max leaf
+    pub fn is_set<S: CpuidSource + ?Sized>(&self, src: &S) -> bool {
+        src.try_cpuid(self.leaf, self.sub_leaf).is_some_and(|CpuidResult { eax, ebx, ecx, edx }| {
+            ([eax, ebx, ecx, edx][self.reg.index()] >> self.bit) & 0b1 == 0b1
+        })
+    }
+}
+
+/// Whether the feature of `FTR_BITS` (the name or its alias, e.g. "AVX2", "sha_ni") is supported,
+/// `None` for the unknown name
+#[cfg(feature = "std")]
+pub fn has_feature<S: CpuidSource + ?Sized>(src: &S, name: &str) -> Option<bool> {
+    let mut bits = ftr_bits_by_name(name).peekable();
+
+    bits.peek()?;
+
+    Some(bits.any(|f| f.is_set(src)))
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_has_feature() {
+    let zero = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+    /* Ryzen 5 5600G */
+    let src = [
+        (0x0, 0x0, CpuidResult { eax: 0x10, ..zero }),
+        (0x7, 0x0, CpuidResult { ebx: 0x219C_97A9, ecx: 0x0040_068C, ..zero }),
+        (0x8000_0000, 0x0, CpuidResult { eax: 0x8000_0020, ..zero }),
+        (0x8000_0001, 0x0, CpuidResult { ecx: 0x75C2_37FF, edx: 0x2FD3_FBFF, ..zero }),
+    ];
+    let src: &[(u32, u32, CpuidResult)] = &src;
+
+    assert_eq!(has_feature(src, "AVX2"), Some(true));
+    assert_eq!(has_feature(src, "sha_ni"), Some(true));
+    assert_eq!(has_feature(src, "SHA-NI"), Some(true));
+    assert_eq!(has_feature(src, "avx512f"), Some(false));
+    assert_eq!(has_feature(src, "SYSCALL"), Some(true));
+    assert_eq!(has_feature(src, "NoSuchFeature"), None);
+    assert_eq!(ftr_bits_by_name("lzcnt").next().map(|f| f.name), Some("ABM"));
+}
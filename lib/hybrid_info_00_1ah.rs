@@ -1,4 +1,4 @@
-use crate::{cpuid, CpuidResult};
+use crate::{CpuidResult, Reg};
 
 /* https://github.com/slimbootloader/slimbootloader/blob/master/Platform/AlderlakeBoardPkg/Library/Stage2BoardInitLib/CpuInfoLib.c */
 
@@ -22,7 +22,7 @@ impl std::fmt::Display for HybridCoreType {
 
 impl From<&CpuidResult> for HybridCoreType {
     fn from(cpuid: &CpuidResult) -> Self {
-        match cpuid.eax >> 24 {
+        match cpuid.eax.bits(24..32) {
             0x10 => HybridCoreType::_Reserved1,
             0x20 => HybridCoreType::Atom,
             0x30 => HybridCoreType::_Reserved2,
@@ -92,6 +92,7 @@ impl std::fmt::Display for IntelNativeModelId {
 pub struct HybridInfo;
 
 impl HybridInfo {
+    #[cfg(target_arch = "x86_64")]
     pub fn get_hybrid_info() -> (Option<HybridCoreType>, IntelNativeModelId) {
         Self::get_hybrid_info_from_cpuid(&cpuid!(0x1A, 0x0))
     }
@@ -1,5 +1,5 @@
 #[cfg(feature = "std")]
-use crate::{cpuid, CacheType, CacheProp, HybridCoreType, HybridInfo};
+use crate::{CacheType, CacheProp, HybridCoreType, HybridInfo, Reg};
 use crate::util::*;
 
 use std::sync::Arc;
@@ -271,9 +271,7 @@ pub struct TopoPartInfo {
 
 impl TopoPartInfo {
     pub fn check_hybrid_flag() -> bool {
-        let cpuid = (cpuid!(0x7, 0x0).edx >> 15) & 0b1;
-
-        cpuid == 0b1
+        cpuid!(0x7, 0x0).edx.bit(15)
     }
 
     fn get_core_type_only_list(core_type: HybridCoreType) -> Vec<usize> {
@@ -0,0 +1,73 @@
+/* C API of libcpuid_dump, build with `cargo rustc --release --features ffi --crate-type staticlib` */
+
+#ifndef CPUID_DUMP_H
+#define CPUID_DUMP_H
+
+/* Generated by cbindgen from ffi.rs, do not edit */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * `CpuidResult` with the C layout
+ */
+typedef struct CpuidDumpResult {
+  uint32_t eax;
+  uint32_t ebx;
+  uint32_t ecx;
+  uint32_t edx;
+} CpuidDumpResult;
+
+/**
+ * `TopoId` with the C layout, `node_id` is `u32::MAX` if not available
+ */
+typedef struct CpuidDumpTopoId {
+  uint32_t smt_id;
+  uint32_t core_id;
+  uint32_t pkg_id;
+  uint32_t x2apic_id;
+  uint32_t node_id;
+} CpuidDumpTopoId;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Execute CPUID with `leaf` and `sub_leaf` on the current thread
+ */
+struct CpuidDumpResult cpuid_dump_query(uint32_t leaf, uint32_t sub_leaf);
+
+/**
+ * Write the vendor ID string (e.g. "AuthenticAMD") and NUL to `buf`, returns 0 on success,
+ * -1 if `buf` is NULL or `len` < 13
+ *
+ * # Safety
+ * `buf` must be valid for writes of `len` bytes
+ */
+int cpuid_dump_vendor(char *buf, size_t len);
+
+/**
+ * Write the topology ID of the current thread to `topo`, returns 0 on success,
+ * -1 if `topo` is NULL or the topology is not available
+ *
+ * # Safety
+ * `topo` must be valid for writes of `CpuidDumpTopoId`
+ */
+int cpuid_dump_topology(struct CpuidDumpTopoId *topo);
+
+/**
+ * Check the feature (e.g. "AVX2", "sha_ni"), the names and aliases of `FTR_BITS` or the psABI level features,
+ * ignoring the case and the punctuation,
+ * returns 1 if supported, 0 if not supported, -1 if the name is unknown or not UTF-8
+ *
+ * # Safety
+ * `name` must be a NUL-terminated string
+ */
+int cpuid_dump_has_feature(const char *name);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CPUID_DUMP_H */
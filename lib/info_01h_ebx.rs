@@ -1,4 +1,4 @@
-use crate::{cpuid, CpuidResult};
+use crate::{CpuidResult, Reg};
 
 /// Information available from `CPUID.(EAX=01h):EBX`
 #[derive(Debug, Clone)]
@@ -12,9 +12,9 @@ pub struct Info01h {
 impl From<u32> for Info01h {
     fn from(ebx: u32) -> Self {
         Self {
-            local_apic_id: ((ebx >> 24) & 0xFF) as u8,
-            max_apic_id: ((ebx >> 16) & 0xFF) as u8,
-            clflush_size: (((ebx >> 8) & 0xFF) as u8).saturating_mul(8),
+            local_apic_id: ebx.bits(24..32) as u8,
+            max_apic_id: ebx.bits(16..24) as u8,
+            clflush_size: (ebx.bits(8..16) as u8).saturating_mul(8),
             brand_id: (ebx & 0xFF) as u8,
         }
     }
@@ -27,6 +27,7 @@ impl From<&CpuidResult> for Info01h {
 }
 
 impl Info01h {
+    #[cfg(target_arch = "x86_64")]
     pub fn get() -> Self {
         Self::from(&cpuid!(0x1, 0x0))
     }
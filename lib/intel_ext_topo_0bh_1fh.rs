@@ -1,4 +1,4 @@
-use crate::CpuidResult;
+use crate::{CpuidResult, Reg};
 
 /// Used for [IntelExtTopo]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,7 +28,7 @@ impl From<u8> for TopoLevelType {
 
 impl From<u32> for TopoLevelType {
     fn from(ecx: u32) -> Self {
-        Self::from(((ecx >> 8) & 0xFF) as u8)
+        Self::from(ecx.bits(8..16) as u8)
     }
 }
 
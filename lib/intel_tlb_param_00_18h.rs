@@ -1,4 +1,4 @@
-use crate::CpuidResult;
+use crate::{CpuidResult, Reg};
 
 #[derive(Debug, Clone)]
 pub struct IntelTlbParam {
@@ -18,16 +18,16 @@ pub struct IntelTlbParam {
 impl From<&CpuidResult> for IntelTlbParam {
     fn from(cpuid: &CpuidResult) -> Self {
         let cache_type = IntelTlbType::from(cpuid);
-        let cache_level = ((cpuid.edx >> 5) & 0b111) as u8;
+        let cache_level = cpuid.edx.bits(5..8) as u8;
         let set = cpuid.ecx;
-        let way = (cpuid.ebx >> 16) as u16;
-        let partitioning = ((cpuid.ebx >> 8) & 0b111) as u8;
+        let way = cpuid.ebx.bits(16..32) as u16;
+        let partitioning = cpuid.ebx.bits(8..11) as u8;
         let support_4k = (cpuid.ebx & 0b1) == 0b1;
         let support_2m = (cpuid.ebx & 0b10) == 0b10;
         let support_4m = (cpuid.ebx & 0b100) == 0b100;
         let support_1g = (cpuid.ebx & 0b1000) == 0b1000;
         let fully_assoc = (cpuid.edx & 0b1000_0000) == 0b1000_0000;
-        let max_shared_thread = ((cpuid.edx >> 14) & 0xFFF) as u16;
+        let max_shared_thread = cpuid.edx.bits(14..26) as u16;
 
         Self {
             cache_type,
@@ -46,6 +46,7 @@ impl From<&CpuidResult> for IntelTlbParam {
 }
 
 impl IntelTlbParam {
+    #[cfg(target_arch = "x86_64")]
     #[cfg(feature = "std")]
     pub fn get() -> Vec<Self> {
         let max_sub_leaf = cpuid!(0x18, 0).eax;
@@ -0,0 +1,121 @@
+//! Leaf (InputEAX) numbers of CPUID, e.g. `cpuid!(leaf::EXT_TOPOLOGY_V2, 0x0)`.
+//! The leaves with `AMD_` are AMD (and Hygon) only, the others are Intel or common.
+
+/* Standard */
+/// Largest Standard Function and Vendor ID
+pub const VENDOR_ID: u32 = 0x0;
+/// Processor Signature and Feature Flags
+pub const SIGNATURE_FEATURES: u32 = 0x1;
+/// TLB/Cache/Prefetch Descriptors, Intel
+pub const CACHE_DESCRIPTORS: u32 = 0x2;
+/// Processor Serial Number, Intel
+pub const SERIAL_NUMBER: u32 = 0x3;
+/// Deterministic Cache Parameters, Intel
+pub const CACHE_PROPERTIES: u32 = 0x4;
+pub const MONITOR_MWAIT: u32 = 0x5;
+/// Thermal and Power Management
+pub const THERMAL_POWER: u32 = 0x6;
+/// Structured Extended Feature Flags
+pub const STRUCTURED_EXT_FEATURES: u32 = 0x7;
+/// Direct Cache Access, Intel
+pub const DIRECT_CACHE_ACCESS: u32 = 0x9;
+/// Architectural Performance Monitoring, Intel
+pub const ARCH_PERF_MON: u32 = 0xA;
+/// Extended Topology Enumeration
+pub const EXT_TOPOLOGY: u32 = 0xB;
+/// Processor Extended State Enumeration
+pub const XSTATE: u32 = 0xD;
+/// RDT Monitoring (Intel), PQoS Monitoring (AMD)
+pub const RDT_MONITORING: u32 = 0xF;
+/// RDT Allocation (Intel), PQoS Enforcement (AMD)
+pub const RDT_ALLOCATION: u32 = 0x10;
+/// SGX Capability Enumeration, Intel
+pub const SGX: u32 = 0x12;
+/// Processor Trace, Intel
+pub const PROCESSOR_TRACE: u32 = 0x14;
+/// TSC and Core Crystal Clock, Intel
+pub const TSC_CRYSTAL_CLOCK: u32 = 0x15;
+/// Processor Frequency, Intel
+pub const PROCESSOR_FREQUENCY: u32 = 0x16;
+/// SoC Vendor Attribute, Intel
+pub const SOC_VENDOR: u32 = 0x17;
+/// Deterministic Address Translation Parameters, Intel
+pub const TLB_PARAMETERS: u32 = 0x18;
+/// Key Locker, Intel
+pub const KEY_LOCKER: u32 = 0x19;
+/// Hybrid Information, Intel
+pub const HYBRID_INFO: u32 = 0x1A;
+/// PCONFIG Information, Intel
+pub const PCONFIG: u32 = 0x1B;
+/// Last Branch Records, Intel
+pub const LAST_BRANCH_RECORDS: u32 = 0x1C;
+/// Tile Information, Intel
+pub const TILE_INFO: u32 = 0x1D;
+/// TMUL Information, Intel
+pub const TMUL_INFO: u32 = 0x1E;
+/// V2 Extended Topology Enumeration, Intel
+pub const EXT_TOPOLOGY_V2: u32 = 0x1F;
+/// Processor History Reset, Intel
+pub const HISTORY_RESET: u32 = 0x20;
+/// Architectural Performance Monitoring Extended, Intel
+pub const ARCH_PERF_MON_EXT: u32 = 0x23;
+/// AVX10 Converged Vector ISA, Intel
+pub const AVX10: u32 = 0x24;
+
+/* Hypervisor */
+/// Hypervisor Vendor ID and Largest Function
+pub const HYPERVISOR_VENDOR: u32 = 0x4000_0000;
+pub const HYPERVISOR_INTERFACE: u32 = 0x4000_0001;
+/// Generic timing information (VMware, KVM)
+pub const HYPERVISOR_TIMING: u32 = 0x4000_0010;
+
+/* Extended */
+/// Largest Extended Function
+pub const EXT_MAX: u32 = 0x8000_0000;
+/// Extended Processor Signature and Feature Flags
+pub const EXT_SIGNATURE_FEATURES: u32 = 0x8000_0001;
+/// Processor Name String, the first 16 bytes
+pub const PROC_NAME_0: u32 = 0x8000_0002;
+pub const PROC_NAME_1: u32 = 0x8000_0003;
+/// Processor Name String, the last 16 bytes
+pub const PROC_NAME_2: u32 = 0x8000_0004;
+/// L1 Cache and TLB, AMD
+pub const AMD_L1_CACHE_TLB: u32 = 0x8000_0005;
+/// L2/L3 Cache and TLB (AMD), L2 Cache (Intel)
+pub const L2_L3_CACHE_TLB: u32 = 0x8000_0006;
+/// Advanced Power Management
+pub const ADV_POWER_MGMT: u32 = 0x8000_0007;
+/// Address Sizes and Extended Feature IDs
+pub const ADDR_SIZES: u32 = 0x8000_0008;
+/// SVM Revision and Feature, AMD
+pub const AMD_SVM: u32 = 0x8000_000A;
+/// 1 GiB Page TLB, AMD
+pub const AMD_TLB_1G: u32 = 0x8000_0019;
+/// Performance Optimization, AMD
+pub const AMD_PERF_OPT: u32 = 0x8000_001A;
+/// Instruction-Based Sampling, AMD
+pub const AMD_IBS: u32 = 0x8000_001B;
+/// Lightweight Profiling, AMD
+pub const AMD_LWP: u32 = 0x8000_001C;
+/// Cache Topology, AMD, same format as `CACHE_PROPERTIES`
+pub const AMD_CACHE_PROPERTIES: u32 = 0x8000_001D;
+/// Processor Topology, AMD
+pub const AMD_PROC_TOPOLOGY: u32 = 0x8000_001E;
+/// Encrypted Memory Capabilities (SME, SEV), AMD
+pub const SEV: u32 = 0x8000_001F;
+/// PQoS Extended Features, AMD
+pub const AMD_PQOS_EXT: u32 = 0x8000_0020;
+/// Extended Feature Identification 2, AMD
+pub const AMD_EXT_FEATURES_2: u32 = 0x8000_0021;
+/// Extended Performance Monitoring and Debug, AMD
+pub const AMD_EXT_PERF_MON: u32 = 0x8000_0022;
+/// Multi-Key Encrypted Memory Capabilities, AMD
+pub const AMD_MKTME: u32 = 0x8000_0023;
+/// Extended CPU Topology, AMD
+pub const AMD_EXT_TOPOLOGY: u32 = 0x8000_0026;
+
+/* Centaur/Zhaoxin */
+/// Largest Centaur Function
+pub const CENTAUR_MAX: u32 = 0xC000_0000;
+/// Centaur Feature Flags
+pub const CENTAUR_FEATURES: u32 = 0xC000_0001;
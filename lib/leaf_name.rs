@@ -0,0 +1,76 @@
+use crate::{leaf, CpuVendor};
+
+/// Short name of the CPUID leaf
+pub struct LeafName;
+
+impl LeafName {
+    /// `None` for the unknown leaves and the leaves reserved on the vendor
+    pub fn from_leaf(leaf: u32, vendor: &CpuVendor) -> Option<&'static str> {
+        let amd = matches!(vendor, CpuVendor::AuthenticAMD | CpuVendor::HygonGenuine);
+        let intel = !amd;
+
+        let name = match leaf {
+            leaf::VENDOR_ID => "Largest Standard Function and Vendor ID",
+            leaf::SIGNATURE_FEATURES => "Processor Signature and Feature Flags",
+            leaf::CACHE_DESCRIPTORS if intel => "TLB/Cache/Prefetch Descriptors",
+            leaf::SERIAL_NUMBER if intel => "Processor Serial Number",
+            leaf::CACHE_PROPERTIES if intel => "Deterministic Cache Parameters",
+            leaf::MONITOR_MWAIT => "MONITOR/MWAIT",
+            leaf::THERMAL_POWER => "Thermal and Power Management",
+            leaf::STRUCTURED_EXT_FEATURES => "Structured Extended Feature Flags",
+            leaf::DIRECT_CACHE_ACCESS if intel => "Direct Cache Access",
+            leaf::ARCH_PERF_MON if intel => "Architectural Performance Monitoring",
+            leaf::EXT_TOPOLOGY => "Extended Topology Enumeration",
+            leaf::XSTATE => "Processor Extended State Enumeration",
+            leaf::RDT_MONITORING if amd => "PQoS Monitoring",
+            leaf::RDT_MONITORING => "RDT Monitoring",
+            leaf::RDT_ALLOCATION if amd => "PQoS Enforcement",
+            leaf::RDT_ALLOCATION => "RDT Allocation",
+            leaf::SGX if intel => "SGX Capability Enumeration",
+            leaf::PROCESSOR_TRACE if intel => "Processor Trace",
+            leaf::TSC_CRYSTAL_CLOCK if intel => "TSC and Core Crystal Clock",
+            leaf::PROCESSOR_FREQUENCY if intel => "Processor Frequency",
+            leaf::SOC_VENDOR if intel => "SoC Vendor Attribute",
+            leaf::TLB_PARAMETERS if intel => "Deterministic Address Translation Parameters",
+            leaf::KEY_LOCKER if intel => "Key Locker",
+            leaf::HYBRID_INFO if intel => "Hybrid Information",
+            leaf::PCONFIG if intel => "PCONFIG Information",
+            leaf::LAST_BRANCH_RECORDS if intel => "Last Branch Records",
+            leaf::TILE_INFO if intel => "Tile Information",
+            leaf::TMUL_INFO if intel => "TMUL Information",
+            leaf::EXT_TOPOLOGY_V2 if intel => "V2 Extended Topology Enumeration",
+            leaf::HISTORY_RESET if intel => "Processor History Reset",
+            leaf::ARCH_PERF_MON_EXT if intel => "Architectural Performance Monitoring Extended",
+            leaf::AVX10 if intel => "AVX10 Converged Vector ISA",
+            leaf::HYPERVISOR_VENDOR => "Hypervisor Vendor ID and Largest Function",
+            leaf::HYPERVISOR_INTERFACE => "Hypervisor Interface",
+            leaf::HYPERVISOR_TIMING => "Hypervisor Timing Information",
+            leaf::EXT_MAX => "Largest Extended Function",
+            leaf::EXT_SIGNATURE_FEATURES => "Extended Processor Signature and Feature Flags",
+            leaf::PROC_NAME_0..=leaf::PROC_NAME_2 => "Processor Name String",
+            leaf::AMD_L1_CACHE_TLB if amd => "L1 Cache and TLB",
+            leaf::L2_L3_CACHE_TLB if amd => "L2/L3 Cache and TLB",
+            leaf::L2_L3_CACHE_TLB => "L2 Cache",
+            leaf::ADV_POWER_MGMT => "Advanced Power Management",
+            leaf::ADDR_SIZES => "Address Sizes and Extended Feature IDs",
+            leaf::AMD_SVM if amd => "SVM Revision and Feature",
+            leaf::AMD_TLB_1G if amd => "1 GiB Page TLB",
+            leaf::AMD_PERF_OPT if amd => "Performance Optimization",
+            leaf::AMD_IBS if amd => "Instruction-Based Sampling",
+            leaf::AMD_LWP if amd => "Lightweight Profiling",
+            leaf::AMD_CACHE_PROPERTIES if amd => "Cache Topology",
+            leaf::AMD_PROC_TOPOLOGY if amd => "Processor Topology",
+            leaf::SEV if amd => "Encrypted Memory Capabilities",
+            leaf::AMD_PQOS_EXT if amd => "PQoS Extended Features",
+            leaf::AMD_EXT_FEATURES_2 if amd => "Extended Feature Identification 2",
+            leaf::AMD_EXT_PERF_MON if amd => "Extended Performance Monitoring and Debug",
+            leaf::AMD_MKTME if amd => "Multi-Key Encrypted Memory Capabilities",
+            leaf::AMD_EXT_TOPOLOGY if amd => "Extended CPU Topology",
+            leaf::CENTAUR_MAX if !amd => "Largest Centaur Function",
+            leaf::CENTAUR_FEATURES if !amd => "Centaur Feature Flags",
+            _ => return None,
+        };
+
+        Some(name)
+    }
+}
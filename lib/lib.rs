@@ -1,10 +1,22 @@
 #![doc = include_str!("./README.md")]
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use core::arch::x86_64::CpuidResult;
+#[cfg(target_arch = "x86_64")]
+pub use core::arch::x86_64::CpuidResult;
+
+/// Same layout as `core::arch::x86_64::CpuidResult`, for decoding dumps on non-x86 targets (e.g. `wasm32-unknown-unknown`)
+#[cfg(not(target_arch = "x86_64"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CpuidResult {
+    pub eax: u32,
+    pub ebx: u32,
+    pub ecx: u32,
+    pub edx: u32,
+}
 
 // pub const _AX: u32 = 0x8000_0000;
 
+#[cfg(target_arch = "x86_64")]
 #[macro_export]
 macro_rules! cpuid {
     ($leaf: expr) => {
@@ -15,8 +27,38 @@ macro_rules! cpuid {
     };
 }
 
+/// `cpuid!` with the check of the max leaf, `None` for the leaf not supported by the CPU.
+/// See [`CpuidSource::try_cpuid`].
+#[cfg(target_arch = "x86_64")]
+#[macro_export]
+macro_rules! try_cpuid {
+    ($leaf: expr) => {
+        $crate::CpuidSource::try_cpuid(&$crate::NativeCpuid, $leaf, 0x0)
+    };
+    ($leaf: expr, $sub_leaf: expr) => {
+        $crate::CpuidSource::try_cpuid(&$crate::NativeCpuid, $leaf, $sub_leaf)
+    };
+}
+
 #[macro_use]
 pub mod util;
+
+pub mod leaf;
+
+mod cpuid_source;
+pub use cpuid_source::*;
+
+mod raw_cpuid;
+pub use raw_cpuid::*;
+
+mod ftr_bits;
+pub use ftr_bits::*;
+
+mod format_config;
+pub use format_config::*;
+
+mod reg;
+pub use reg::*;
 // pub use util::*;
 
 mod codename;
@@ -33,6 +75,9 @@ mod proc_name;
 #[cfg(feature = "std")]
 pub use proc_name::*;
 
+mod leaf_name;
+pub use leaf_name::*;
+
 mod cache_prop;
 pub use cache_prop::*;
 
@@ -42,6 +87,15 @@ pub use info_01h_ebx::*;
 mod monitor_mwait_00_05h;
 pub use monitor_mwait_00_05h::*;
 
+mod xsave_00_0dh;
+pub use xsave_00_0dh::*;
+
+mod sgx_epc_00_12h;
+pub use sgx_epc_00_12h::*;
+
+mod rdt_00_0fh_10h;
+pub use rdt_00_0fh_10h::*;
+
 mod intel_ext_topo_0bh_1fh;
 pub use intel_ext_topo_0bh_1fh::*;
 
@@ -51,15 +105,18 @@ pub use amd_tlb_info::*;
 mod intel_tlb_param_00_18h;
 pub use intel_tlb_param_00_18h::*;
 
+mod tlb_info;
+pub use tlb_info::*;
+
 mod hybrid_info_00_1ah;
 pub use hybrid_info_00_1ah::*;
 
 mod topo_info;
 pub use topo_info::*;
 
-#[cfg(feature = "std")]
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
 mod hybrid_topology;
-#[cfg(feature = "std")]
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
 pub use hybrid_topology::*;
 
 mod addr_size_80_08h_eax;
@@ -68,6 +125,9 @@ pub use addr_size_80_08h_eax::*;
 mod amd_size_id_80_08h_ecx;
 pub use amd_size_id_80_08h_ecx::*;
 
+mod amd_ftr_ext_id_80_08h_ebx;
+pub use amd_ftr_ext_id_80_08h_ebx::*;
+
 mod amd_pkg_type_80_01h;
 pub use amd_pkg_type_80_01h::*;
 
@@ -76,3 +136,21 @@ pub use amd_proc_topo_80_1eh::*;
 
 mod amd_ext_topo_80_26h;
 pub use amd_ext_topo_80_26h::*;
+
+mod amd_mem_encrypt_80_1fh;
+pub use amd_mem_encrypt_80_1fh::*;
+
+mod amd_pqos_80_20h;
+pub use amd_pqos_80_20h::*;
+
+mod mem_encrypt_tme;
+pub use mem_encrypt_tme::*;
+
+mod security_features;
+pub use security_features::*;
+
+#[cfg(all(feature = "ffi", target_arch = "x86_64"))]
+pub mod ffi;
+
+#[cfg(all(feature = "python", target_arch = "x86_64"))]
+mod python;
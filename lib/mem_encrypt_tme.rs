@@ -0,0 +1,78 @@
+use crate::{CpuidResult, Reg};
+
+/// Total Memory Encryption (TME) / Multi-Key TME capabilities, aggregated from
+/// `CPUID.(EAX=07h, ECX=0)`, `CPUID.(EAX=1Bh, ECX=n)` (PCONFIG) and `CPUID.(EAX=8000_0023h)` (AMD)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemEncryptTme {
+    /// TME_EN: CPUID.(EAX=07h, ECX=0):ECX[13]
+    pub tme: bool,
+    /// PCONFIG: CPUID.(EAX=07h, ECX=0):EDX[18]
+    pub pconfig: bool,
+    /// PCONFIG supports the MKTME target
+    pub pconfig_mktme: bool,
+    /// AMD MemHmk: Secure Host Multi-Key Memory Encryption, CPUID.(EAX=8000_0023h):EAX[0]
+    pub amd_mem_hmk: bool,
+    /// AMD MaxMemHmkEncrKeyID: CPUID.(EAX=8000_0023h):EBX[15:0]
+    pub amd_max_key_id: u16,
+}
+
+impl MemEncryptTme {
+    /// PCONFIG target identifier for MKTME
+    const PCONFIG_TARGET_MKTME: u32 = 0x1;
+
+    /// `[00_07_x0, 80_23_x0]`, `pconfig_sub_leaves`: `00_1B_x{n}`
+    pub fn from_cpuid_array(cpuid_array: [CpuidResult; 2], pconfig_sub_leaves: &[CpuidResult]) -> Self {
+        let [cpuid_00_07, cpuid_80_23] = cpuid_array;
+
+        let pconfig_mktme = pconfig_sub_leaves
+            .iter()
+            /* EAX[11:0] = 1: Target Identifier sub-leaf */
+            .filter(|cpuid| (cpuid.eax & 0xFFF) == 0x1)
+            .any(|cpuid| {
+                [cpuid.ebx, cpuid.ecx, cpuid.edx].contains(&Self::PCONFIG_TARGET_MKTME)
+            });
+
+        Self {
+            tme: cpuid_00_07.ecx.bit(13),
+            pconfig: cpuid_00_07.edx.bit(18),
+            pconfig_mktme,
+            amd_mem_hmk: (cpuid_80_23.eax & 0b1) == 0b1,
+            amd_max_key_id: (cpuid_80_23.ebx & 0xFFFF) as u16,
+        }
+    }
+
+    /// The number of MKTME keys (`MK_TME_MAX_KEYS`, bits 50:36)
+    /// and KeyID bits (`MK_TME_MAX_KEYID_BITS`, bits 35:32) from IA32_TME_CAPABILITY (MSR 0x981),
+    /// the MSR is not enumerated by CPUID
+    pub fn mktme_keys_from_tme_capability(tme_capability: u64) -> (u16, u8) {
+        let max_keys = ((tme_capability >> 36) & 0x7FFF) as u16;
+        let keyid_bits = ((tme_capability >> 32) & 0xF) as u8;
+
+        (max_keys, keyid_bits)
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn get() -> Self {
+        const ZERO: CpuidResult = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+        let max_std_leaf = cpuid!(0x0, 0x0).eax;
+        let max_ext_leaf = cpuid!(0x8000_0000, 0x0).eax;
+
+        let mut pconfig_sub_leaves = [ZERO; 0x10];
+
+        if 0x1B <= max_std_leaf {
+            for (sub_leaf, cpuid) in pconfig_sub_leaves.iter_mut().enumerate() {
+                *cpuid = cpuid!(0x1B, sub_leaf as u32);
+
+                if (cpuid.eax & 0xFFF) == 0x0 { break }
+            }
+        }
+
+        let cpuid_80_23 = if 0x8000_0023 <= max_ext_leaf {
+            cpuid!(0x8000_0023, 0x0)
+        } else {
+            ZERO
+        };
+
+        Self::from_cpuid_array([cpuid!(0x7, 0x0), cpuid_80_23], &pconfig_sub_leaves)
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{cpuid, CpuidResult};
+use crate::{CpuidResult, CpuidSource, Reg};
+use core::convert::TryFrom;
 
 /// Micro-architecture level defined by the x86-64 psABI
 #[allow(non_camel_case_types)]
@@ -13,137 +14,239 @@ pub enum MicroArchLevel {
 }
 
 impl MicroArchLevel {
-    const MASK_BASE_LINE: u32 = {
-        /* 00_01_EDX */
-        const FPU: u32  = 1 <<  0;
-        const CX8: u32  = 1 <<  8;
-        const SCE: u32  = 1 << 11;
-        const CMOV: u32 = 1 << 15;
-        const MMX: u32  = 1 << 23;
-        const FXSR: u32 = 1 << 24;
-        const SSE: u32  = 1 << 25;
-        const SSE2: u32 = 1 << 26;
-
-        FPU | CX8 | SCE | CMOV | MMX | FXSR | SSE | SSE2
-    };
-
-    const MASK_X86_64_V2: [u32; 2] = { 
-        /* 00_01_ECX */
-        const SSE3: u32       = 1 << 0;
-        const SSSE3: u32      = 1 << 9;
-        const CMPXCHG16B: u32 = 1 << 13;
-        const SSE4_1: u32     = 1 << 19;
-        const SSE4_2: u32     = 1 << 20;
-        const POPCNT: u32     = 1 << 23;
-        /* 80_01_ECX */
-        const LAHF_SAHF: u32 = 1 << 0;
+    /* zeros for Leaf 0x7 and 0x8000_0001 not supported, e.g. on the old CPUs */
+    #[cfg(target_arch = "x86_64")]
+    fn set_cpuid() -> [CpuidResult; 3] {
+        const ZERO: CpuidResult = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
 
         [
-            SSE3 | SSSE3 | CMPXCHG16B | SSE4_1 | SSE4_2 | POPCNT,
-            LAHF_SAHF,
+            cpuid!(0x1, 0x0),
+            try_cpuid!(0x7, 0x0).unwrap_or(ZERO),
+            try_cpuid!(0x8000_0001, 0x0).unwrap_or(ZERO),
         ]
-    };
+    }
 
-    const MASK_X86_64_V3: [u32; 3] = {
-        /* 00_01_ECX */
-        const FMA: u32     = 1 << 12;
-        const MOVBE: u32   = 1 << 22;
-        const OSXSAVE: u32 = 1 << 27;
-        const AVX: u32     = 1 << 28;
-        const F16C: u32    = 1 << 29;
+    /// `[00_01_x0, 00_07_x0, 80_01_x0]` of the source
+    pub fn cpuid_array_from<S: CpuidSource + ?Sized>(src: &S) -> [CpuidResult; 3] {
+        [
+            src.cpuid(0x1, 0x0),
+            src.cpuid(0x7, 0x0),
+            src.cpuid(0x8000_0001, 0x0),
+        ]
+    }
 
-        /* 00_07_EBX */
-        const BMI1: u32 = 1 << 3;
-        const AVX2: u32 = 1 << 5;
-        const BMI2: u32 = 1 << 8;
+    /// Level of the source (e.g. a loaded dump) without executing the CPUID instruction
+    pub fn from_source<S: CpuidSource + ?Sized>(src: &S) -> Self {
+        Self::from_cpuid_array(Self::cpuid_array_from(src))
+    }
 
-        /* 80_01_ECX */
-        const ABM_LZCNT: u32 = 1 << 5;
+    /// `[00_01_x0, 00_07_x0, 80_01_x0]`
+    pub fn from_cpuid_array(cpuid_array: [CpuidResult; 3]) -> Self {
+        let features = Self::features_from_cpuid_array(cpuid_array);
+
+        /* the highest level whose features and the features of the lower levels are all supported */
+        [Self::X86_64_V1, Self::X86_64_V2, Self::X86_64_V3, Self::X86_64_V4]
+            .iter()
+            .copied()
+            .take_while(|level| level.level_features(&features).all(|ftr| ftr.present))
+            .last()
+            .unwrap_or(Self::X86_64_V0)
+    }
 
-        [
-            FMA | MOVBE | OSXSAVE | AVX | F16C,
-            BMI1 | AVX2 | BMI2,
-            ABM_LZCNT,
-        ]
-    };
-
-    const MASK_X86_64_V4: u32 = {
-        /* 00_07_EBX */
-        const AVX512F: u32  = 1 << 16;
-        const AVX512DQ: u32 = 1 << 17;
-        const AVX512CD: u32 = 1 << 28;
-        const AVX512BW: u32 = 1 << 30;
-        const AVX512VL: u32 = 1 << 31;
-
-        AVX512F | AVX512DQ | AVX512CD | AVX512BW | AVX512VL 
-    };
-    
-    fn set_cpuid() -> [CpuidResult; 3] {
-        [
-            cpuid!(0x1, 0x0),
-            cpuid!(0x7, 0x0),
-            cpuid!(0x8000_0001, 0x0),
-        ]
+    #[cfg(target_arch = "x86_64")]
+    pub fn check() -> Self {
+        let cpuid_array = Self::set_cpuid();
+
+        Self::from_cpuid_array(cpuid_array)
     }
 
-    fn from_cpuid_array(cpuid_array: [CpuidResult; 3]) -> Self {
+    /* (Level, Name, Register index of `regs`, Bit) */
+    const LEVEL_FTR: [(Self, &'static str, usize, u32); 29] = [
+        (Self::X86_64_V1, "FPU", 0, 0),
+        (Self::X86_64_V1, "CX8", 0, 8),
+        (Self::X86_64_V1, "SCE", 4, 11),
+        (Self::X86_64_V1, "CMOV", 0, 15),
+        (Self::X86_64_V1, "MMX", 0, 23),
+        (Self::X86_64_V1, "FXSR", 0, 24),
+        (Self::X86_64_V1, "SSE", 0, 25),
+        (Self::X86_64_V1, "SSE2", 0, 26),
+        (Self::X86_64_V2, "SSE3", 1, 0),
+        (Self::X86_64_V2, "SSSE3", 1, 9),
+        (Self::X86_64_V2, "CMPXCHG16B", 1, 13),
+        (Self::X86_64_V2, "SSE4_1", 1, 19),
+        (Self::X86_64_V2, "SSE4_2", 1, 20),
+        (Self::X86_64_V2, "POPCNT", 1, 23),
+        (Self::X86_64_V2, "LAHF-SAHF", 3, 0),
+        (Self::X86_64_V3, "FMA", 1, 12),
+        (Self::X86_64_V3, "MOVBE", 1, 22),
+        (Self::X86_64_V3, "OSXSAVE", 1, 27),
+        (Self::X86_64_V3, "AVX", 1, 28),
+        (Self::X86_64_V3, "F16C", 1, 29),
+        (Self::X86_64_V3, "BMI1", 2, 3),
+        (Self::X86_64_V3, "AVX2", 2, 5),
+        (Self::X86_64_V3, "BMI2", 2, 8),
+        (Self::X86_64_V3, "LZCNT", 3, 5),
+        (Self::X86_64_V4, "AVX512F", 2, 16),
+        (Self::X86_64_V4, "AVX512DQ", 2, 17),
+        (Self::X86_64_V4, "AVX512CD", 2, 28),
+        (Self::X86_64_V4, "AVX512BW", 2, 30),
+        (Self::X86_64_V4, "AVX512VL", 2, 31),
+    ];
+
+    /* [00_01_EDX, 00_01_ECX, 00_07_EBX, 80_01_ECX, 80_01_EDX] */
+    fn regs(cpuid_array: [CpuidResult; 3]) -> [u32; 5] {
         let [cpuid_00_01, cpuid_00_07, cpuid_80_01] = cpuid_array;
 
-        let mask = |bitmask: &[u32], cpuid: &[u32]| -> bool {
-            for (bitmask, cpuid) in bitmask.iter().zip(cpuid) {
-                if (bitmask & cpuid) != *bitmask {
-                    return false;
-                }
-            }
-
-            true
-        };
-
-        let [base_line, x86_64_v2, x86_64_v3, x86_64_v4] = [
-            mask(&[Self::MASK_BASE_LINE], &[cpuid_00_01.edx]),
-            mask(&Self::MASK_X86_64_V2, &[cpuid_00_01.ecx, cpuid_80_01.ecx]),
-            mask(&Self::MASK_X86_64_V3, &[cpuid_00_01.ecx, cpuid_00_07.ebx, cpuid_80_01.ecx]),
-            mask(&[Self::MASK_X86_64_V4], &[cpuid_00_07.ebx]),
-        ];
-
-        let mut level = if base_line {
-            1u8
-        } else {
-            return Self::X86_64_V0
-        };
-
-        if x86_64_v2 { level |= 1 << 1 }
-        if x86_64_v3 { level |= 1 << 2 }
-        if x86_64_v4 { level |= 1 << 3 }
+        [cpuid_00_01.edx, cpuid_00_01.ecx, cpuid_00_07.ebx, cpuid_80_01.ecx, cpuid_80_01.edx]
+    }
+
+    /// All features required by the levels, in the order of the level,
+    /// with whether each feature is supported
+    pub fn features_from_cpuid_array(cpuid_array: [CpuidResult; 3]) -> [LevelFeature; 29] {
+        let regs = Self::regs(cpuid_array);
+
+        Self::LEVEL_FTR.map(|(level, name, idx, bit)| LevelFeature {
+            level,
+            name,
+            present: regs[idx].bit(bit),
+        })
+    }
+
+    pub fn features_from_source<S: CpuidSource + ?Sized>(src: &S) -> [LevelFeature; 29] {
+        Self::features_from_cpuid_array(Self::cpuid_array_from(src))
+    }
+
+    /// Features required by this level itself, not including the lower levels
+    pub fn level_features(self, features: &[LevelFeature]) -> impl Iterator<Item = LevelFeature> + '_ {
+        features.iter().copied().filter(move |ftr| ftr.level == self)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn missing_features_from_cpuid_array(
+        target: Self,
+        cpuid_array: [CpuidResult; 3],
+    ) -> Vec<&'static str> {
+        Self::features_from_cpuid_array(cpuid_array)
+            .iter()
+            .filter(|ftr| ftr.level <= target && !ftr.present)
+            .map(|ftr| ftr.name)
+            .collect()
+    }
+
+    /// Whether the feature of psABI level (e.g. "AVX2") is supported, `None` for an unknown name
+    pub fn has_feature_from_cpuid_array(
+        name: &str,
+        cpuid_array: [CpuidResult; 3],
+    ) -> Option<bool> {
+        let regs = Self::regs(cpuid_array);
+
+        Self::LEVEL_FTR
+            .iter()
+            .find(|(_, ftr, _, _)| ftr.eq_ignore_ascii_case(name))
+            .map(|(_, _, idx, bit)| regs[*idx].bit(*bit))
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn has_feature(name: &str) -> Option<bool> {
+        Self::has_feature_from_cpuid_array(name, Self::set_cpuid())
+    }
+
+    /// List of features required by `target` level that are not supported by the current CPU
+    #[cfg(target_arch = "x86_64")]
+    #[cfg(feature = "std")]
+    pub fn missing_features(target: Self) -> Vec<&'static str> {
+        let cpuid_array = Self::set_cpuid();
+
+        Self::missing_features_from_cpuid_array(target, cpuid_array)
+    }
+}
 
+/// A feature required by the x86-64 micro-architecture level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelFeature {
+    /// The lowest level requiring the feature
+    pub level: MicroArchLevel,
+    pub name: &'static str,
+    /// Supported by the CPU (or the dump)
+    pub present: bool,
+}
+
+impl TryFrom<u8> for MicroArchLevel {
+    type Error = u8;
+
+    fn try_from(level: u8) -> Result<Self, Self::Error> {
         match level {
-            0b1 => Self::X86_64_V1,
-            0b11 => Self::X86_64_V2,
-            0b111 => Self::X86_64_V3,
-            0b1111 => Self::X86_64_V4,
-            _ => Self::X86_64_V0,
+            0 => Ok(Self::X86_64_V0),
+            1 => Ok(Self::X86_64_V1),
+            2 => Ok(Self::X86_64_V2),
+            3 => Ok(Self::X86_64_V3),
+            4 => Ok(Self::X86_64_V4),
+            _ => Err(level),
         }
     }
-    pub fn check() -> Self {
-        let cpuid_array = Self::set_cpuid();
+}
 
-        Self::from_cpuid_array(cpuid_array)
+#[cfg(feature = "std")]
+impl std::fmt::Display for MicroArchLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "x86-64-v{}", *self as u8)
     }
 }
 
+/* Ryzen 5 5600G: [00_01, 00_07, 80_01] */
+#[cfg(test)]
+const RYZEN_5_5600G: [CpuidResult; 3] = [
+    CpuidResult { eax: 0x00A50F00, ebx: 0x0A0C0800, ecx: 0x7EF8320B, edx: 0x178BFBFF },
+    CpuidResult { eax: 0x00000000, ebx: 0x219C97A9, ecx: 0x0040068C, edx: 0x00000010 },
+    CpuidResult { eax: 0x00A50F00, ebx: 0x20000000, ecx: 0x75C237FF, edx: 0x2FD3FBFF },
+];
+
 #[test]
 fn test_micro_arch_level() {
-    let cpuid_array = [
-        /* Ryzen 5 5600G */
-        /* 00_01 */
-        CpuidResult { eax: 0x00A50F00, ebx: 0x0A0C0800, ecx: 0x7EF8320B, edx: 0x178BFBFF },
-        /* 00_07 */
-        CpuidResult { eax: 0x00000000, ebx: 0x219C97A9, ecx: 0x0040068C, edx: 0x00000010 },
-        /* 80_01 */
-        CpuidResult { eax: 0x00A50F00, ebx: 0x20000000, ecx: 0x75C237FF, edx: 0x2FD3FBFF },
-    ];
-
-    let level = MicroArchLevel::from_cpuid_array(cpuid_array);
+    let level = MicroArchLevel::from_cpuid_array(RYZEN_5_5600G);
 
     assert_eq!(MicroArchLevel::X86_64_V3, level);
+
+    /* SCE (SYSCALL/SYSRET) is 80_01_EDX[11], not SEP of 00_01_EDX[11] */
+    let mut no_syscall = RYZEN_5_5600G;
+    no_syscall[2].edx &= !(1 << 11);
+
+    assert_eq!(MicroArchLevel::from_cpuid_array(no_syscall), MicroArchLevel::X86_64_V0);
+}
+
+
+#[test]
+fn test_missing_features() {
+    let v3 = MicroArchLevel::missing_features_from_cpuid_array(MicroArchLevel::X86_64_V3, RYZEN_5_5600G);
+    let v4 = MicroArchLevel::missing_features_from_cpuid_array(MicroArchLevel::X86_64_V4, RYZEN_5_5600G);
+
+    assert!(v3.is_empty());
+    assert_eq!(v4, ["AVX512F", "AVX512DQ", "AVX512CD", "AVX512BW", "AVX512VL"]);
+}
+
+#[test]
+fn test_has_feature() {
+    assert_eq!(MicroArchLevel::has_feature_from_cpuid_array("avx2", RYZEN_5_5600G), Some(true));
+    assert_eq!(MicroArchLevel::has_feature_from_cpuid_array("AVX512F", RYZEN_5_5600G), Some(false));
+    assert_eq!(MicroArchLevel::has_feature_from_cpuid_array("AMX-TILE", RYZEN_5_5600G), None);
+}
+
+#[test]
+fn test_level_features() {
+    let src: Vec<(u32, u32, CpuidResult)> = [0x1, 0x7, 0x8000_0001]
+        .iter()
+        .zip(RYZEN_5_5600G)
+        .map(|(&leaf, cpuid)| (leaf, 0x0, cpuid))
+        .collect();
+    let src: &[(u32, u32, CpuidResult)] = &src;
+
+    assert_eq!(MicroArchLevel::from_source(src), MicroArchLevel::X86_64_V3);
+
+    let features = MicroArchLevel::features_from_source(src);
+    let v2: Vec<_> = MicroArchLevel::X86_64_V2.level_features(&features).collect();
+
+    assert_eq!(v2.len(), 7);
+    assert!(v2.iter().all(|ftr| ftr.present));
+    assert_eq!(v2[6], LevelFeature { level: MicroArchLevel::X86_64_V2, name: "LAHF-SAHF", present: true });
+    assert!(MicroArchLevel::X86_64_V4.level_features(&features).all(|ftr| !ftr.present));
 }
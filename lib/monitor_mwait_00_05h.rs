@@ -1,4 +1,4 @@
-use crate::{cpuid, CpuidResult};
+use crate::{CpuidResult};
 
 /// Information available from `CPUID.(EAX=05h)`
 #[derive(Debug, Clone)]
@@ -39,6 +39,7 @@ impl From<&CpuidResult> for MonitorMwait {
 }
 
 impl MonitorMwait {
+    #[cfg(target_arch = "x86_64")]
     pub fn get() -> Self {
         Self::from(&cpuid!(0x5, 0x0))
     }
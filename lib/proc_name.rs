@@ -1,8 +1,25 @@
-use crate::{cpuid, CpuidResult};
+use crate::CpuidResult;
+#[cfg(target_arch = "x86_64")]
+use crate::leaf;
 
 /// Processor name
 pub struct ProcName;
 
+/// Trimming policy of the processor name, used for [ProcName::trim_with]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NameTrim {
+    /// Collapse the internal runs of spaces into a space
+    pub collapse_spaces: bool,
+    /// Strip the "(R)", "(TM)" and "(tm)" markers
+    pub strip_marks: bool,
+}
+
+impl NameTrim {
+    pub const ALL: Self = Self { collapse_spaces: true, strip_marks: true };
+}
+
+const MARKS: &[&str] = &["(R)", "(r)", "(TM)", "(tm)"];
+
 impl ProcName {
     pub(crate) fn check_reg(reg: u32) -> [u8; 4] {
         let mut bytes = reg.to_le_bytes();
@@ -27,29 +44,88 @@ impl ProcName {
         total
     }
 
+    #[cfg(target_arch = "x86_64")]
     fn set_cpuid() -> [CpuidResult; 3] {
         [
-            cpuid!(0x8000_0002, 0x0),
-            cpuid!(0x8000_0003, 0x0),
-            cpuid!(0x8000_0004, 0x0),
+            cpuid!(leaf::PROC_NAME_0, 0x0),
+            cpuid!(leaf::PROC_NAME_1, 0x0),
+            cpuid!(leaf::PROC_NAME_2, 0x0),
         ]
     }
     
-    fn from_cpuid_array(array: [CpuidResult; 3]) -> String {
+    pub fn from_cpuid_array(array: [CpuidResult; 3]) -> String {
         /* 4 (0x8000_0002 .. 0x8000_0004) * u32 ([u8; 4]) * 4 (E{A,B,C,D}X) */
         let name: Vec<u8> = array.iter().flat_map(Self::dec_cpuid).collect();
 
-        String::from_utf8(name).unwrap()
+        String::from_utf8_lossy(&name).into_owned()
+    }
+
+    /// The brand string bytes as returned by CPUID, without replacing the control characters
+    pub fn raw_bytes(array: [CpuidResult; 3]) -> [u8; 48] {
+        let mut bytes = [0u8; 48];
+
+        for (i, reg) in array.iter().flat_map(|cpuid| [cpuid.eax, cpuid.ebx, cpuid.ecx, cpuid.edx]).enumerate() {
+            bytes[(i*4)..(i*4+4)].copy_from_slice(&reg.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// The name up to the NUL terminator, for the truncated or the garbage brand strings (e.g. by the hypervisors).
+    /// The control characters are replaced with spaces and the invalid UTF-8 with U+FFFD.
+    pub fn from_raw_bytes(bytes: &[u8]) -> String {
+        let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
+        let name: Vec<u8> = bytes[..end]
+            .iter()
+            .map(|byte| if char::from(*byte).is_control() { 0x20 } else { *byte })
+            .collect();
+
+        String::from_utf8_lossy(&name).into_owned()
+    }
+
+    /// Trim the leading and the trailing spaces, and the internal ones or the markers by the policy
+    pub fn trim_with(name: &str, policy: NameTrim) -> String {
+        let mut name = name.to_string();
+
+        if policy.strip_marks {
+            for mark in MARKS {
+                name = name.replace(mark, "");
+            }
+        }
+
+        if policy.collapse_spaces {
+            name = name.split_whitespace().collect::<Vec<&str>>().join(" ");
+        }
+
+        name.trim().to_string()
+    }
+
+    /// The name for the file names, like "Intel_Core_i9-12900K":
+    /// the markers are stripped, the characters other than ASCII alphanumerics, '-' and '.' are replaced with '_'
+    pub fn sanitized(name: &str) -> String {
+        let name: String = Self::trim_with(name, NameTrim::ALL)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+            .collect();
+
+        name.split('_').filter(|s| !s.is_empty()).collect::<Vec<&str>>().join("_")
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn get_raw_bytes() -> [u8; 48] {
+        Self::raw_bytes(Self::set_cpuid())
     }
     
+    #[cfg(target_arch = "x86_64")]
     pub fn get_name() -> String {
         let cpuid = Self::set_cpuid();
 
         Self::from_cpuid_array(cpuid)
     }
     
+    #[cfg(target_arch = "x86_64")]
     pub fn get_trim_name() -> String {
-        Self::get_name()
+        Self::from_raw_bytes(&Self::get_raw_bytes())
             .trim()
             .to_string()
     }
@@ -66,3 +142,18 @@ fn test_proc_name() {
 
     assert_eq!(name, ProcName::from_cpuid_array(cpuid));
 }
+
+#[test]
+fn test_proc_name_trim() {
+    /* truncated by the hypervisor, the garbage after NUL */
+    let bytes = b"QEMU Virtual CPU\0\xFF\xFE garbage";
+
+    assert_eq!(ProcName::from_raw_bytes(bytes), "QEMU Virtual CPU");
+    assert_eq!(ProcName::from_raw_bytes(b"Bad \xC3\x28 name\x07"), "Bad \u{FFFD}( name ");
+
+    let name = "  Intel(R) Xeon(R) CPU E5-2680 0 @ 2.70GHz";
+
+    assert_eq!(ProcName::trim_with(name, NameTrim::default()), "Intel(R) Xeon(R) CPU E5-2680 0 @ 2.70GHz");
+    assert_eq!(ProcName::trim_with(name, NameTrim::ALL), "Intel Xeon CPU E5-2680 0 @ 2.70GHz");
+    assert_eq!(ProcName::sanitized(name), "Intel_Xeon_CPU_E5-2680_0_2.70GHz");
+}
@@ -0,0 +1,97 @@
+//! Python extension module, enabled with the `python` feature.
+//! Build with `cargo rustc --release --features python --crate-type cdylib`,
+//! and rename `liblibcpuid_dump.so` to `libcpuid_dump.so`.
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use crate::{CpuidResult, NativeCpuid, RawCpuid, Reg};
+
+/* (Leaf, SubLeaf, EAX, EBX, ECX, EDX) */
+type Leaf = (u32, u32, u32, u32, u32, u32);
+
+fn to_tuple(leaf: u32, sub_leaf: u32, cpuid: &CpuidResult) -> Leaf {
+    (leaf, sub_leaf, cpuid.eax, cpuid.ebx, cpuid.ecx, cpuid.edx)
+}
+
+/* `(sub_leaf, result)`, without the zero results except for leaf 0xD */
+fn sub_leaves(leaf: u32) -> Vec<(u32, CpuidResult)> {
+    let x0 = cpuid!(leaf, 0x0);
+    let is_zero = |cpuid: &CpuidResult| cpuid.eax == 0 && cpuid.ebx == 0 && cpuid.ecx == 0 && cpuid.edx == 0;
+
+    let end_of = |sub_leaf: u32, cpuid: &CpuidResult| -> bool {
+        match leaf {
+            /* Cache Type == Null */
+            0x4 | 0x8000_001D => (cpuid.eax & 0x1F) == 0,
+            0x7 => x0.eax < sub_leaf,
+            /* Level Type == Invalid */
+            0xB | 0x1F => cpuid.ecx.bits(8..16) == 0,
+            0xD => 0x13 < sub_leaf,
+            _ => 0 < sub_leaf,
+        }
+    };
+
+    let mut sub_leaves = vec![(0x0, x0)];
+
+    for sub_leaf in 1.. {
+        let cpuid = cpuid!(leaf, sub_leaf);
+
+        if end_of(sub_leaf, &cpuid) { break }
+
+        sub_leaves.push((sub_leaf, cpuid));
+    }
+
+    if leaf != 0xD {
+        sub_leaves.retain(|(_, cpuid)| !is_zero(cpuid));
+    }
+
+    sub_leaves
+}
+
+/// Execute CPUID on the current thread, returns the list of `(leaf, sub_leaf, eax, ebx, ecx, edx)`
+#[pyfunction]
+fn capture() -> Vec<Leaf> {
+    let max_std_leaf = cpuid!(0x0, 0x0).eax;
+    let max_ext_leaf = cpuid!(0x8000_0000, 0x0).eax;
+
+    (0x0..=max_std_leaf)
+        .chain(0x8000_0000..=max_ext_leaf)
+        .flat_map(|leaf| {
+            sub_leaves(leaf)
+                .iter()
+                .map(|(sub_leaf, cpuid)| to_tuple(leaf, *sub_leaf, cpuid))
+                .collect::<Vec<Leaf>>()
+        })
+        .collect()
+}
+
+/// Parse the dump of `cpuid_dump -r` or `cpuid -r` (`cpuid_dump -c`),
+/// returns the list of `(leaf, sub_leaf, eax, ebx, ecx, edx)` of all threads
+#[pyfunction]
+fn parse(dump_bytes: &[u8]) -> PyResult<Vec<Leaf>> {
+    let dump = std::str::from_utf8(dump_bytes)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let pool = crate::parse_dump(dump).map_err(PyValueError::new_err)?;
+
+    Ok(pool
+        .iter()
+        .map(|RawCpuid { leaf, sub_leaf, result }| to_tuple(*leaf, *sub_leaf, result))
+        .collect())
+}
+
+/// Whether the current CPU supports the feature (e.g. "AVX2", "sha_ni"), the names and aliases of `FTR_BITS`,
+/// raises `ValueError` for an unknown name
+#[pyfunction]
+fn supports(name: &str) -> PyResult<bool> {
+    crate::has_feature(&NativeCpuid, name)
+        .ok_or_else(|| PyValueError::new_err(format!("unknown feature: {name}")))
+}
+
+#[pymodule]
+#[pyo3(name = "libcpuid_dump")]
+fn py_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(capture, m)?)?;
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_function(wrap_pyfunction!(supports, m)?)?;
+
+    Ok(())
+}
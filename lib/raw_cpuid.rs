@@ -0,0 +1,276 @@
+use crate::{CpuidResult, CpuidSource};
+
+/// The input (leaf, sub-leaf) and the result of CPUID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawCpuid {
+    pub leaf: u32,
+    pub sub_leaf: u32,
+    pub result: CpuidResult,
+}
+
+/* `CpuidResult` has no `Hash` */
+impl RawCpuid {
+    fn key(&self) -> [u32; 6] {
+        let CpuidResult { eax, ebx, ecx, edx } = self.result;
+
+        [self.leaf, self.sub_leaf, eax, ebx, ecx, edx]
+    }
+}
+
+impl core::hash::Hash for RawCpuid {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// Ordered by leaf, sub-leaf, then EAX, EBX, ECX, EDX
+impl Ord for RawCpuid {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl PartialOrd for RawCpuid {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl RawCpuid {
+    /// Execute CPUID on the current thread
+    #[cfg(target_arch = "x86_64")]
+    pub fn exe(leaf: u32, sub_leaf: u32) -> Self {
+        Self {
+            leaf,
+            sub_leaf,
+            result: cpuid!(leaf, sub_leaf),
+        }
+    }
+
+    /// `exe` on the backend, e.g. a recorded dump in the unit tests
+    pub fn exe_from(src: &dyn CpuidSource, leaf: u32, sub_leaf: u32) -> Self {
+        Self {
+            leaf,
+            sub_leaf,
+            result: src.cpuid(leaf, sub_leaf),
+        }
+    }
+
+    pub fn check_result_zero(&self) -> bool {
+        self.result == CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 }
+    }
+
+    /// Parse the line of `cpuid -r` (`cpuid_dump -c`),
+    /// like "   0x00000000 0x00: eax=0x00000010 ebx=0x68747541 ecx=0x444d4163 edx=0x69746e65"
+    pub fn from_compat_line(line: &str) -> Option<Self> {
+        let hex = |s: &str| -> Option<u32> {
+            u32::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+        };
+
+        let (input, regs) = line.trim().split_once(':')?;
+        let mut input = input.split_whitespace();
+        let leaf = hex(input.next()?)?;
+        let sub_leaf = hex(input.next()?)?;
+
+        let mut regs = regs.split_whitespace().filter_map(|reg| {
+            let (_, v) = reg.split_once('=')?;
+            hex(v)
+        });
+
+        let result = CpuidResult {
+            eax: regs.next()?,
+            ebx: regs.next()?,
+            ecx: regs.next()?,
+            edx: regs.next()?,
+        };
+
+        Some(Self { leaf, sub_leaf, result })
+    }
+}
+
+/// The raw line of `cpuid_dump -r` without the ASCII comment:
+/// `<leaf> <sub_leaf>: <eax> <ebx> <ecx> <edx>`, each "0x" and 8 upper hex digits
+impl core::fmt::Display for RawCpuid {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let [leaf, sub_leaf] = [self.leaf, self.sub_leaf];
+        let CpuidResult { eax, ebx, ecx, edx } = self.result;
+
+        write!(f, "{leaf:#010X} {sub_leaf:#010X}: {eax:#010X} {ebx:#010X} {ecx:#010X} {edx:#010X}")
+    }
+}
+
+/// Inverse of `Display`, the trailing `  # "<ASCII>"` of `cpuid_dump -r` is accepted
+#[cfg(feature = "std")]
+impl std::str::FromStr for RawCpuid {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        use std::convert::TryFrom;
+
+        let hex = |s: &str| -> Result<u32, String> {
+            let valid = matches!(
+                s.strip_prefix("0x"),
+                Some(digits) if digits.len() == 8 && digits.bytes().all(|b| matches!(b, b'0'..=b'9' | b'A'..=b'F'))
+            );
+
+            if !valid {
+                return Err(format!("\"{s}\" is not \"0x\" and 8 upper hex digits"));
+            }
+
+            u32::from_str_radix(&s[2..], 16).map_err(|e| e.to_string())
+        };
+
+        let Some((input, output)) = line.split_once(": ") else {
+            return Err("expected \"<leaf> <sub_leaf>: <eax> <ebx> <ecx> <edx>\"".to_string());
+        };
+        /* trailing comment */
+        let output = output.split_once("  #").map_or(output, |(output, _)| output);
+        let Some((leaf, sub_leaf)) = input.split_once(' ') else {
+            return Err("expected \"<leaf> <sub_leaf>\" before ':'".to_string());
+        };
+        let regs: Vec<u32> = output.split(' ').map(hex).collect::<Result<_, _>>()?;
+        let Ok([eax, ebx, ecx, edx]) = <[u32; 4]>::try_from(regs) else {
+            return Err("expected 4 registers after ':'".to_string());
+        };
+
+        Ok(Self {
+            leaf: hex(leaf)?,
+            sub_leaf: hex(sub_leaf)?,
+            result: CpuidResult { eax, ebx, ecx, edx },
+        })
+    }
+}
+
+/// Sort by leaf and sub-leaf, the results of the same input keep their order
+pub fn sort_by_leaf(pool: &mut [RawCpuid]) {
+    pool.sort_by_key(|rawcpuid| (rawcpuid.leaf, rawcpuid.sub_leaf));
+}
+
+/// Remove the repeated results of the same input and the same value, the first one is kept
+#[cfg(feature = "std")]
+pub fn dedup_identical(pool: &mut Vec<RawCpuid>) {
+    let mut seen = std::collections::HashSet::with_capacity(pool.len());
+
+    pool.retain(|rawcpuid| seen.insert(*rawcpuid));
+}
+
+/// Remove the results with E[ABCD]X all zero
+#[cfg(feature = "std")]
+pub fn retain_nonzero(pool: &mut Vec<RawCpuid>) {
+    pool.retain(|rawcpuid| !rawcpuid.check_result_zero());
+}
+
+/// The "Thread: <n>" of the topology line, and the results of the thread
+#[cfg(feature = "std")]
+pub type ThreadPool = (Option<usize>, Vec<RawCpuid>);
+
+/// Split the output of `cpuid_dump -r` into the results of each thread, one per "# [..]" topology line.
+/// Other lines starting with '#' and empty lines are ignored, any other line is an error.
+#[cfg(feature = "std")]
+pub fn split_raw_dump(dump: &str) -> Result<Vec<ThreadPool>, String> {
+    let mut pools: Vec<ThreadPool> = Vec::new();
+
+    for (i, line) in dump.lines().enumerate() {
+        if let Some(head) = line.strip_prefix("# [") {
+            /* like "# [Pkg: 000, Core: 000, SMT: 000, x2APIC: 000, Thread: 000]" */
+            let thread_id = head
+                .split(", ")
+                .find_map(|s| s.strip_prefix("Thread: "))
+                .and_then(|s| s.trim_end_matches(']').parse::<usize>().ok());
+
+            pools.push((thread_id, Vec::new()));
+            continue;
+        }
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let rawcpuid: RawCpuid = line.parse()
+            .map_err(|e| format!("line {}: invalid format: {e}: \"{line}\"", i+1))?;
+
+        match pools.last_mut() {
+            Some((_, pool)) => pool.push(rawcpuid),
+            None => pools.push((None, vec![rawcpuid])),
+        }
+    }
+
+    Ok(pools)
+}
+
+/// The results of all threads in the dump of `cpuid_dump -r` or `cpuid -r` (`cpuid_dump -c`).
+/// The format is detected by "eax=", the other lines of `cpuid -r` (e.g. "CPU 0:") are ignored.
+#[cfg(feature = "std")]
+pub fn parse_dump(dump: &str) -> Result<Vec<RawCpuid>, String> {
+    if dump.contains("eax=") {
+        return Ok(dump
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(RawCpuid::from_compat_line)
+            .collect());
+    }
+
+    Ok(split_raw_dump(dump)?.into_iter().flat_map(|(_, pool)| pool).collect())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_raw_line() {
+    /* Ryzen 5 5600G */
+    let pool = [
+        RawCpuid { leaf: 0x0, sub_leaf: 0x0, result: CpuidResult { eax: 0x10, ebx: 0x6874_7541, ecx: 0x444D_4163, edx: 0x6974_6E65 } },
+        RawCpuid { leaf: 0xD, sub_leaf: 0xB, result: CpuidResult { eax: 0x10, ebx: 0x0, ecx: 0x1, edx: 0x0 } },
+        RawCpuid { leaf: u32::MAX, sub_leaf: u32::MAX, result: CpuidResult { eax: u32::MAX, ebx: 0x0, ecx: u32::MAX, edx: 0x0 } },
+    ];
+
+    for rawcpuid in &pool {
+        assert_eq!(rawcpuid.to_string().parse::<RawCpuid>(), Ok(*rawcpuid));
+    }
+
+    assert_eq!(pool[0].to_string(), "0x00000000 0x00000000: 0x00000010 0x68747541 0x444D4163 0x69746E65");
+    assert_eq!(
+        "0x00000000 0x00000000: 0x00000010 0x68747541 0x444D4163 0x69746E65  # \"AuthenticAMD\"".parse::<RawCpuid>(),
+        Ok(pool[0]),
+    );
+    assert!("0x00000000 0x0: 0x00000010 0x68747541 0x444D4163 0x69746E65".parse::<RawCpuid>().is_err());
+    assert!("0x00000000 0x00000000: 0x00000010 0x68747541 0x444D4163".parse::<RawCpuid>().is_err());
+    assert!("0x00000000 0x00000000: 0x00000010 0x68747541 0x444D4163 0x69746e65".parse::<RawCpuid>().is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_pool_utils() {
+    let rawcpuid = |leaf: u32, sub_leaf: u32, eax: u32| RawCpuid { leaf, sub_leaf, result: CpuidResult { eax, ebx: 0x0, ecx: 0x0, edx: 0x0 } };
+    let mut pool = vec![rawcpuid(0xD, 0x1, 0x1), rawcpuid(0x1, 0x0, 0x0), rawcpuid(0xD, 0x0, 0x7), rawcpuid(0xD, 0x1, 0x1)];
+
+    sort_by_leaf(&mut pool);
+    assert_eq!(pool, [rawcpuid(0x1, 0x0, 0x0), rawcpuid(0xD, 0x0, 0x7), rawcpuid(0xD, 0x1, 0x1), rawcpuid(0xD, 0x1, 0x1)]);
+
+    dedup_identical(&mut pool);
+    assert_eq!(pool, [rawcpuid(0x1, 0x0, 0x0), rawcpuid(0xD, 0x0, 0x7), rawcpuid(0xD, 0x1, 0x1)]);
+
+    retain_nonzero(&mut pool);
+    assert_eq!(pool, [rawcpuid(0xD, 0x0, 0x7), rawcpuid(0xD, 0x1, 0x1)]);
+
+    assert!(rawcpuid(0x1, 0xFF, 0xFF) < rawcpuid(0x2, 0x0, 0x0));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_parse_dump() {
+    /* Ryzen 5 5600G */
+    let leaf_00h = RawCpuid { leaf: 0x0, sub_leaf: 0x0, result: CpuidResult { eax: 0x10, ebx: 0x6874_7541, ecx: 0x444D_4163, edx: 0x6974_6E65 } };
+    let leaf_0dh = RawCpuid { leaf: 0xD, sub_leaf: 0xB, result: CpuidResult { eax: 0x10, ebx: 0x0, ecx: 0x1, edx: 0x0 } };
+
+    let raw = "# [Pkg: 000, Core: 000, SMT: 000, x2APIC: 000, Thread: 000]\n\
+        0x00000000 0x00000000: 0x00000010 0x68747541 0x444D4163 0x69746E65  # \"AuthenticAMD\"\n\
+        0x0000000D 0x0000000B: 0x00000010 0x00000000 0x00000001 0x00000000\n";
+    let compat = "CPU 0:\n\
+        \x20  0x00000000 0x00: eax=0x00000010 ebx=0x68747541 ecx=0x444d4163 edx=0x69746e65\n\
+        \x20  0x0000000d 0x0b: eax=0x00000010 ebx=0x00000000 ecx=0x00000001 edx=0x00000000\n";
+
+    assert_eq!(split_raw_dump(raw), Ok(vec![(Some(0), vec![leaf_00h, leaf_0dh])]));
+    assert_eq!(parse_dump(raw), Ok(vec![leaf_00h, leaf_0dh]));
+    assert_eq!(parse_dump(compat), Ok(vec![leaf_00h, leaf_0dh]));
+    assert!(parse_dump("0x0 0x0: 0x10").is_err());
+}
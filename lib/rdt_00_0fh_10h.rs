@@ -0,0 +1,187 @@
+use crate::{leaf, CpuidResult, CpuidSource, Reg};
+
+/// Intel RDT Monitoring / AMD PQoS Monitoring capabilities available from `CPUID.(EAX=0Fh)`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RdtMonitoring {
+    /// Maximum RMID of all the resource types, 0-based
+    pub max_rmid: u32,
+    /// `None` if L3 cache monitoring is not supported
+    pub l3: Option<RdtL3Monitoring>,
+}
+
+/// L3 cache monitoring available from `CPUID.(EAX=0Fh, ECX=1)`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RdtL3Monitoring {
+    /// Maximum RMID of the L3 cache, 0-based
+    pub max_rmid: u32,
+    /// Conversion factor from the counter value (IA32_QM_CTR) to bytes
+    pub upscaling_factor: u32,
+    /// Width of the counter in bits
+    pub counter_width: u8,
+    /// L3 occupancy monitoring
+    pub occupancy: bool,
+    /// L3 total external bandwidth monitoring
+    pub total_bandwidth: bool,
+    /// L3 local external bandwidth monitoring
+    pub local_bandwidth: bool,
+}
+
+impl From<&CpuidResult> for RdtL3Monitoring {
+    fn from(cpuid: &CpuidResult) -> Self {
+        Self {
+            max_rmid: cpuid.ecx,
+            upscaling_factor: cpuid.ebx,
+            /* offset from 24 bits */
+            counter_width: 24 + cpuid.eax.bits(0..8) as u8,
+            occupancy: cpuid.edx.bit(0),
+            total_bandwidth: cpuid.edx.bit(1),
+            local_bandwidth: cpuid.edx.bit(2),
+        }
+    }
+}
+
+impl RdtMonitoring {
+    /// `None` if the leaf is not supported or no resource type is monitored
+    pub fn from_source<S: CpuidSource + ?Sized>(src: &S) -> Option<Self> {
+        if src.cpuid(0x0, 0x0).eax < leaf::RDT_MONITORING {
+            return None;
+        }
+
+        let sub0 = src.cpuid(leaf::RDT_MONITORING, 0x0);
+
+        if sub0.edx == 0x0 {
+            return None;
+        }
+
+        Some(Self {
+            max_rmid: sub0.ebx,
+            l3: if sub0.edx.bit(1) {
+                Some(RdtL3Monitoring::from(&src.cpuid(leaf::RDT_MONITORING, 0x1)))
+            } else {
+                None
+            },
+        })
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn get() -> Option<Self> {
+        Self::from_source(&crate::NativeCpuid)
+    }
+}
+
+/// Cache Allocation Technology of the L3/L2 cache, available from `CPUID.(EAX=10h, ECX=1 or 2)`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CatCapability {
+    /// Length of the capacity bit mask (CBM)
+    pub cbm_len: u8,
+    /// Bit-granular map of the allocation units shared with the other entities
+    pub shareable_mask: u32,
+    /// Number of the classes of service (COS)
+    pub cos_count: u32,
+    /// Code and Data Prioritization
+    pub cdp: bool,
+    /// Non-contiguous 1s value in the CBM
+    pub non_contiguous_cbm: bool,
+}
+
+impl From<&CpuidResult> for CatCapability {
+    fn from(cpuid: &CpuidResult) -> Self {
+        Self {
+            cbm_len: cpuid.eax.bits(0..5) as u8 + 1,
+            shareable_mask: cpuid.ebx,
+            cos_count: cpuid.edx.bits(0..16) + 1,
+            cdp: cpuid.ecx.bit(2),
+            non_contiguous_cbm: cpuid.ecx.bit(3),
+        }
+    }
+}
+
+/// Memory Bandwidth Allocation available from `CPUID.(EAX=10h, ECX=3)`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MbaCapability {
+    /// Maximum throttling value, the granularity of the delay values is `100 / max_throttle` %
+    pub max_throttle: u32,
+    /// The response of the delay values is linear
+    pub linear: bool,
+    /// Number of the classes of service (COS)
+    pub cos_count: u32,
+}
+
+impl From<&CpuidResult> for MbaCapability {
+    fn from(cpuid: &CpuidResult) -> Self {
+        Self {
+            max_throttle: cpuid.eax.bits(0..12) + 1,
+            linear: cpuid.ecx.bit(2),
+            cos_count: cpuid.edx.bits(0..16) + 1,
+        }
+    }
+}
+
+/// Intel RDT Allocation / AMD PQoS Enforcement capabilities available from `CPUID.(EAX=10h)`.
+/// AMD reports L3 allocation only, the bandwidth enforcement of AMD is [`AmdPqos`](crate::AmdPqos).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RdtAllocation {
+    pub l3: Option<CatCapability>,
+    pub l2: Option<CatCapability>,
+    pub mba: Option<MbaCapability>,
+}
+
+impl RdtAllocation {
+    /// `None` if the leaf is not supported or no resource type is allocated
+    pub fn from_source<S: CpuidSource + ?Sized>(src: &S) -> Option<Self> {
+        if src.cpuid(0x0, 0x0).eax < leaf::RDT_ALLOCATION {
+            return None;
+        }
+
+        let ebx = src.cpuid(leaf::RDT_ALLOCATION, 0x0).ebx;
+
+        if ebx.bits(1..4) == 0x0 {
+            return None;
+        }
+
+        let sub_leaf = |bit: u32| if ebx.bit(bit) { Some(src.cpuid(leaf::RDT_ALLOCATION, bit)) } else { None };
+
+        Some(Self {
+            l3: sub_leaf(1).as_ref().map(CatCapability::from),
+            l2: sub_leaf(2).as_ref().map(CatCapability::from),
+            mba: sub_leaf(3).as_ref().map(MbaCapability::from),
+        })
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn get() -> Option<Self> {
+        Self::from_source(&crate::NativeCpuid)
+    }
+}
+
+#[test]
+fn test_rdt() {
+    /* Intel Xeon Gold 6138 */
+    let cpuids = [
+        (0x0, 0x0, CpuidResult { eax: 0x16, ebx: 0x0, ecx: 0x0, edx: 0x0 }),
+        (0xF, 0x0, CpuidResult { eax: 0x0, ebx: 0xDF, ecx: 0x0, edx: 0x2 }),
+        (0xF, 0x1, CpuidResult { eax: 0x0, ebx: 0xE000, ecx: 0xDF, edx: 0x7 }),
+        (0x10, 0x0, CpuidResult { eax: 0x0, ebx: 0xA, ecx: 0x0, edx: 0x0 }),
+        (0x10, 0x1, CpuidResult { eax: 0xA, ebx: 0x600, ecx: 0x4, edx: 0xF }),
+        (0x10, 0x3, CpuidResult { eax: 0x59, ebx: 0x0, ecx: 0x4, edx: 0x7 }),
+    ];
+    let mon = RdtMonitoring::from_source(&cpuids[..]).unwrap();
+    let l3 = mon.l3.unwrap();
+
+    assert_eq!(mon.max_rmid, 0xDF);
+    assert_eq!((l3.max_rmid, l3.upscaling_factor, l3.counter_width), (0xDF, 0xE000, 24));
+    assert!(l3.occupancy && l3.total_bandwidth && l3.local_bandwidth);
+
+    let alloc = RdtAllocation::from_source(&cpuids[..]).unwrap();
+    let cat = alloc.l3.unwrap();
+    let mba = alloc.mba.unwrap();
+
+    assert!(alloc.l2.is_none());
+    assert_eq!((cat.cbm_len, cat.shareable_mask, cat.cos_count), (11, 0x600, 16));
+    assert!(cat.cdp && !cat.non_contiguous_cbm);
+    assert_eq!((mba.max_throttle, mba.cos_count), (90, 8));
+    assert!(mba.linear);
+
+    /* the max standard leaf is below 0xF */
+    assert!(RdtMonitoring::from_source(&cpuids[2..]).is_none());
+}
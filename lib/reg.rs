@@ -0,0 +1,54 @@
+use core::ops::Range;
+
+/// Bit-field extraction of the CPUID register,
+/// e.g. `ebx.bits(8..16)` for `EBX[15:8]`, `ecx.bit(22)` for `ECX[22]`
+pub trait Reg: Copy {
+    /// `self[range.end-1:range.start]`, shifted to the bit 0
+    fn bits(self, range: Range<u32>) -> u32;
+
+    /// `self[n]`
+    fn bit(self, n: u32) -> bool;
+
+    /// The bits of the contiguous `mask`, shifted to the bit 0, e.g. `eax.masked(0x3FFC000)` for `EAX[25:14]`
+    fn masked(self, mask: u32) -> u32;
+}
+
+impl Reg for u32 {
+    fn bits(self, range: Range<u32>) -> u32 {
+        debug_assert!(range.start < range.end && range.end <= 32, "invalid bit range: {:?}", range);
+
+        let width = range.end - range.start;
+        let mask = if width < 32 { (1 << width) - 1 } else { u32::MAX };
+
+        (self >> range.start) & mask
+    }
+
+    fn bit(self, n: u32) -> bool {
+        debug_assert!(n < 32, "invalid bit: {}", n);
+
+        ((self >> n) & 0b1) == 0b1
+    }
+
+    fn masked(self, mask: u32) -> u32 {
+        if mask == 0 {
+            return 0;
+        }
+
+        (self & mask) >> mask.trailing_zeros()
+    }
+}
+
+#[test]
+fn test_reg() {
+    /* CPUID.(EAX=01h):EBX of Ryzen 5 5600G */
+    let ebx: u32 = 0x0A0C0800;
+
+    assert_eq!(ebx.bits(8..16), 0x08);
+    assert_eq!(ebx.bits(16..24), 0x0C);
+    assert_eq!(ebx.bits(24..32), 0x0A);
+    assert_eq!(ebx.bits(0..32), ebx);
+    assert!(ebx.bit(11));
+    assert!(!ebx.bit(31));
+    assert_eq!(ebx.masked(0x00FF_0000), 0x0C);
+    assert_eq!(ebx.masked(0), 0);
+}
@@ -0,0 +1,93 @@
+use crate::{CpuidResult, AmdFtrExtId, Reg};
+
+/// Speculative execution mitigation features, aggregated from `CPUID.(EAX=07h, ECX=0):EDX`,
+/// `CPUID.(EAX=07h, ECX=2):EDX`, `CPUID.(EAX=8000_0008h):EBX` and `CPUID.(EAX=8000_0021h):EAX`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SecurityFeatures {
+    pub ibrs: bool,
+    pub ibpb: bool,
+    pub stibp: bool,
+    pub ssbd: bool,
+    pub l1d_flush: bool,
+    pub md_clear: bool,
+    pub srbds_ctrl: bool,
+    pub arch_capabilities: bool,
+    pub core_capabilities: bool,
+    /* 00_07_EDX_x2 */
+    pub psfd: bool,
+    pub ipred_ctrl: bool,
+    pub rrsba_ctrl: bool,
+    pub ddpd_u: bool,
+    pub bhi_ctrl: bool,
+    pub mcdt_no: bool,
+    /* AMD 80_08_EBX */
+    pub ibrs_always_on: bool,
+    pub stibp_always_on: bool,
+    pub ibrs_preferred: bool,
+    pub ibrs_same_mode: bool,
+    pub virt_ssbd: bool,
+    pub ssbd_not_required: bool,
+    pub btc_no: bool,
+    pub ibpb_ret: bool,
+    /* AMD 80_21_EAX */
+    pub automatic_ibrs: bool,
+}
+
+impl SecurityFeatures {
+    #[cfg(target_arch = "x86_64")]
+    fn set_cpuid() -> [CpuidResult; 4] {
+        const ZERO: CpuidResult = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+
+        [
+            try_cpuid!(0x7, 0x0).unwrap_or(ZERO),
+            try_cpuid!(0x7, 0x2).unwrap_or(ZERO),
+            try_cpuid!(0x8000_0008, 0x0).unwrap_or(ZERO),
+            try_cpuid!(0x8000_0021, 0x0).unwrap_or(ZERO),
+        ]
+    }
+
+    /// `[00_07_x0, 00_07_x2, 80_08_x0, 80_21_x0]`
+    pub fn from_cpuid_array(cpuid_array: [CpuidResult; 4]) -> Self {
+        let [cpuid_00_07_x0, cpuid_00_07_x2, cpuid_80_08, cpuid_80_21] = cpuid_array;
+
+        let [edx_x0, edx_x2, eax_80_21] = [
+            cpuid_00_07_x0.edx,
+            cpuid_00_07_x2.edx,
+            cpuid_80_21.eax,
+        ];
+        let amd = AmdFtrExtId::from(&cpuid_80_08);
+
+        Self {
+            /* Intel enumerates IBRS and IBPB with the same bit */
+            ibrs: edx_x0.bit(26) || amd.ibrs,
+            ibpb: edx_x0.bit(26) || amd.ibpb,
+            stibp: edx_x0.bit(27) || amd.stibp,
+            ssbd: edx_x0.bit(31) || amd.ssbd,
+            l1d_flush: edx_x0.bit(28),
+            md_clear: edx_x0.bit(10),
+            srbds_ctrl: edx_x0.bit(9),
+            arch_capabilities: edx_x0.bit(29),
+            core_capabilities: edx_x0.bit(30),
+            psfd: edx_x2.bit(0) || amd.psfd,
+            ipred_ctrl: edx_x2.bit(1),
+            rrsba_ctrl: edx_x2.bit(2),
+            ddpd_u: edx_x2.bit(3),
+            bhi_ctrl: edx_x2.bit(4),
+            mcdt_no: edx_x2.bit(5),
+            ibrs_always_on: amd.ibrs_always_on,
+            stibp_always_on: amd.stibp_always_on,
+            ibrs_preferred: amd.ibrs_preferred,
+            ibrs_same_mode: amd.ibrs_same_mode,
+            virt_ssbd: amd.ssbd_virt_spec_ctrl,
+            ssbd_not_required: amd.ssbd_not_required,
+            btc_no: amd.btc_no,
+            ibpb_ret: amd.ibpb_ret,
+            automatic_ibrs: eax_80_21.bit(8),
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn get() -> Self {
+        Self::from_cpuid_array(Self::set_cpuid())
+    }
+}
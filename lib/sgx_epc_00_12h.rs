@@ -0,0 +1,40 @@
+use crate::{CpuidResult};
+
+/// SGX EPC (Enclave Page Cache) section available from `CPUID.(EAX=12h, ECX=n)`, n > 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SgxEpcSection {
+    /// The physical address of the base of the EPC section
+    pub base: u64,
+    /// The size (in bytes) of the EPC section
+    pub size: u64,
+    /// 1: Confidentiality, Integrity and Replay protection,
+    /// 2: Confidentiality protection only
+    pub property: u8,
+}
+
+impl SgxEpcSection {
+    /// `None` if the sub-leaf is not a valid EPC section (`EAX[3:0]` != 1)
+    pub fn option_from_cpuid(cpuid: &CpuidResult) -> Option<Self> {
+        if (cpuid.eax & 0xF) != 0x1 {
+            return None;
+        }
+
+        let base = (((cpuid.ebx & 0xF_FFFF) as u64) << 32) | ((cpuid.eax & 0xFFFF_F000) as u64);
+        let size = (((cpuid.edx & 0xF_FFFF) as u64) << 32) | ((cpuid.ecx & 0xFFFF_F000) as u64);
+
+        Some(Self {
+            base,
+            size,
+            property: (cpuid.ecx & 0xF) as u8,
+        })
+    }
+
+    pub fn size_in_mib(&self) -> u64 {
+        self.size >> 20
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn get(sub_leaf: u32) -> Option<Self> {
+        Self::option_from_cpuid(&cpuid!(0x12, sub_leaf))
+    }
+}
@@ -0,0 +1,49 @@
+//! `include/cpuid_dump.h` is generated from `ffi.rs` with cbindgen, and checked by this test.
+//! `CPUID_DUMP_BLESS=1 cargo test --features ffi --test ffi_header` updates the header.
+#![cfg(feature = "ffi")]
+
+use std::path::Path;
+
+fn generate() -> String {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config {
+        header: Some("/* C API of libcpuid_dump, build with `cargo rustc --release --features ffi --crate-type staticlib` */".to_string()),
+        autogen_warning: Some("/* Generated by cbindgen from ffi.rs, do not edit */".to_string()),
+        include_guard: Some("CPUID_DUMP_H".to_string()),
+        language: cbindgen::Language::C,
+        style: cbindgen::Style::Both,
+        cpp_compat: true,
+        no_includes: true,
+        sys_includes: vec!["stddef.h".to_string(), "stdint.h".to_string()],
+        usize_is_size_t: true,
+        ..Default::default()
+    };
+    let mut header = Vec::new();
+
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(dir.join("ffi.rs"))
+        .generate()
+        .expect("cbindgen failed")
+        .write(&mut header);
+
+    String::from_utf8(header).unwrap()
+}
+
+#[test]
+fn test_ffi_header() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("include/cpuid_dump.h");
+    let header = generate();
+
+    if std::env::var_os("CPUID_DUMP_BLESS").is_some() {
+        std::fs::write(&path, &header).unwrap();
+        return;
+    }
+
+    let checked_in = std::fs::read_to_string(&path).unwrap();
+
+    assert!(
+        checked_in == header,
+        "include/cpuid_dump.h is out of date, run `CPUID_DUMP_BLESS=1 cargo test --features ffi --test ffi_header`",
+    );
+}
@@ -0,0 +1,264 @@
+use crate::{CpuidResult, IntelTlbParam, IntelTlbType, TlbAssoc};
+#[cfg(feature = "std")]
+use crate::TlbEntries;
+#[cfg(feature = "std")]
+use std::fmt;
+
+/// Kind of the TLB entries, used for [TlbInfo]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+pub enum TlbKind {
+    Instruction,
+    Data,
+    LoadOnly,
+    StoreOnly,
+    Unified,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for TlbKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Self::Instruction => "Instruction",
+            Self::Data => "Data",
+            Self::LoadOnly => "Load only",
+            Self::StoreOnly => "Store only",
+            Self::Unified => "Unified",
+        })
+    }
+}
+
+/// Page sizes of the TLB entries, used for [TlbInfo]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, PartialOrd, Ord)]
+pub struct TlbPages {
+    pub page_4k: bool,
+    pub page_2m: bool,
+    pub page_4m: bool,
+    pub page_1g: bool,
+}
+
+impl TlbPages {
+    pub const fn new(page_4k: bool, page_2m: bool, page_4m: bool, page_1g: bool) -> Self {
+        Self { page_4k, page_2m, page_4m, page_1g }
+    }
+}
+
+#[cfg(feature = "std")]
+impl TlbPages {
+    const P4K: Self = Self::new(true, false, false, false);
+    const P2M: Self = Self::new(false, true, false, false);
+    const P4M: Self = Self::new(false, false, true, false);
+    const P2M4M: Self = Self::new(false, true, true, false);
+    const P1G: Self = Self::new(false, false, false, true);
+    const P4K2M: Self = Self::new(true, true, false, false);
+    const P4K4M: Self = Self::new(true, false, true, false);
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for TlbPages {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pages: Vec<&str> = [
+            (self.page_4k, "4K"),
+            (self.page_2m, "2M"),
+            (self.page_4m, "4M"),
+            (self.page_1g, "1G"),
+        ].iter().filter(|(flag, _)| *flag).map(|(_, page)| *page).collect();
+
+        f.pad(&pages.join("/"))
+    }
+}
+
+/// A TLB of any vendor, from AMD `CPUID.(EAX=8000_0005h/8000_0006h/8000_0019h)`,
+/// Intel `CPUID.(EAX=18h)` or the descriptors of `CPUID.(EAX=02h)`.
+/// The entries for 4M pages use two entries for 2M pages on AMD.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TlbInfo {
+    pub level: u8,
+    pub kind: TlbKind,
+    pub pages: TlbPages,
+    pub entries: u32,
+    pub assoc: TlbAssoc,
+}
+
+/* (Descriptor, Level, Kind, Pages, Entries, Ways, 0: fully associative), Intel SDM Vol.2A Table 3-12 */
+#[cfg(feature = "std")]
+const TLB_DESCRIPTORS: &[(u8, u8, TlbKind, TlbPages, u32, u8)] = &[
+    (0x01, 1, TlbKind::Instruction, TlbPages::P4K, 32, 4),
+    (0x02, 1, TlbKind::Instruction, TlbPages::P4M, 2, 0),
+    (0x03, 1, TlbKind::Data, TlbPages::P4K, 64, 4),
+    (0x04, 1, TlbKind::Data, TlbPages::P4M, 8, 4),
+    (0x05, 1, TlbKind::Data, TlbPages::P4M, 32, 4),
+    (0x0B, 1, TlbKind::Instruction, TlbPages::P4M, 4, 4),
+    (0x55, 1, TlbKind::Instruction, TlbPages::P2M4M, 7, 0),
+    (0x56, 1, TlbKind::Data, TlbPages::P4M, 16, 4),
+    (0x57, 1, TlbKind::Data, TlbPages::P4K, 16, 4),
+    (0x59, 1, TlbKind::Data, TlbPages::P4K, 16, 0),
+    (0x5A, 1, TlbKind::Data, TlbPages::P2M4M, 32, 4),
+    (0x61, 1, TlbKind::Instruction, TlbPages::P4K, 48, 0),
+    (0x63, 1, TlbKind::Data, TlbPages::P2M4M, 32, 4),
+    (0x63, 1, TlbKind::Data, TlbPages::P1G, 4, 4),
+    (0x64, 1, TlbKind::Data, TlbPages::P4K, 512, 4),
+    (0x6A, 1, TlbKind::Data, TlbPages::P4K, 64, 8),
+    (0x6B, 1, TlbKind::Data, TlbPages::P4K, 256, 8),
+    (0x6C, 1, TlbKind::Data, TlbPages::P2M4M, 128, 8),
+    (0x6D, 1, TlbKind::Data, TlbPages::P1G, 16, 0),
+    (0x76, 1, TlbKind::Instruction, TlbPages::P2M4M, 8, 0),
+    (0xA0, 1, TlbKind::Data, TlbPages::P4K, 32, 0),
+    (0xB0, 1, TlbKind::Instruction, TlbPages::P4K, 128, 4),
+    (0xB1, 1, TlbKind::Instruction, TlbPages::P2M, 8, 4),
+    (0xB2, 1, TlbKind::Instruction, TlbPages::P4K, 64, 4),
+    (0xB3, 1, TlbKind::Data, TlbPages::P4K, 128, 4),
+    (0xB4, 1, TlbKind::Data, TlbPages::P4K, 256, 4),
+    (0xB5, 1, TlbKind::Instruction, TlbPages::P4K, 64, 8),
+    (0xB6, 1, TlbKind::Instruction, TlbPages::P4K, 128, 8),
+    (0xBA, 1, TlbKind::Data, TlbPages::P4K, 64, 4),
+    (0xC0, 1, TlbKind::Data, TlbPages::P4K4M, 8, 4),
+    (0xC1, 2, TlbKind::Unified, TlbPages::P4K2M, 1024, 8),
+    (0xC2, 1, TlbKind::Data, TlbPages::P4K2M, 16, 4),
+    (0xC3, 2, TlbKind::Unified, TlbPages::P4K2M, 1536, 6),
+    (0xC3, 2, TlbKind::Unified, TlbPages::P1G, 16, 4),
+    (0xC4, 1, TlbKind::Data, TlbPages::P2M4M, 32, 4),
+    (0xCA, 2, TlbKind::Unified, TlbPages::P4K, 512, 4),
+];
+
+impl TlbInfo {
+    #[cfg(feature = "std")]
+    fn from_entries(level: u8, kind: TlbKind, pages: TlbPages, tlb: TlbEntries) -> Option<Self> {
+        match tlb.assoc {
+            TlbAssoc::Disabled |
+            TlbAssoc::Invalid => None,
+            _ if tlb.size == 0 => None,
+            assoc => Some(Self { level, kind, pages, entries: tlb.size as u32, assoc }),
+        }
+    }
+
+    /// From `CPUID.(EAX=8000_0005h)`, `CPUID.(EAX=8000_0006h)` and `CPUID.(EAX=8000_0019h)` (AMD),
+    /// without the disabled TLBs
+    #[cfg(feature = "std")]
+    pub fn from_amd_cpuid_array(cpuid: [CpuidResult; 3]) -> Vec<Self> {
+        let [l1, l2, tlb_1g] = cpuid;
+        let lo = |reg: u32| (reg & 0xFFFF) as u16;
+        let hi = |reg: u32| (reg >> 16) as u16;
+
+        vec![
+            (1, TlbKind::Instruction, TlbPages::P4K, TlbEntries::from_reg_l1(lo(l1.ebx))),
+            (1, TlbKind::Instruction, TlbPages::P2M4M, TlbEntries::from_reg_l1(lo(l1.eax))),
+            (1, TlbKind::Instruction, TlbPages::P1G, TlbEntries::from_reg_l2(lo(tlb_1g.eax))),
+            (1, TlbKind::Data, TlbPages::P4K, TlbEntries::from_reg_l1(hi(l1.ebx))),
+            (1, TlbKind::Data, TlbPages::P2M4M, TlbEntries::from_reg_l1(hi(l1.eax))),
+            (1, TlbKind::Data, TlbPages::P1G, TlbEntries::from_reg_l2(hi(tlb_1g.eax))),
+            (2, TlbKind::Instruction, TlbPages::P4K, TlbEntries::from_reg_l2(lo(l2.ebx))),
+            (2, TlbKind::Instruction, TlbPages::P2M4M, TlbEntries::from_reg_l2(lo(l2.eax))),
+            (2, TlbKind::Instruction, TlbPages::P1G, TlbEntries::from_reg_l2(lo(tlb_1g.ebx))),
+            (2, TlbKind::Data, TlbPages::P4K, TlbEntries::from_reg_l2(hi(l2.ebx))),
+            (2, TlbKind::Data, TlbPages::P2M4M, TlbEntries::from_reg_l2(hi(l2.eax))),
+            (2, TlbKind::Data, TlbPages::P1G, TlbEntries::from_reg_l2(hi(tlb_1g.ebx))),
+        ]
+        .into_iter()
+        .filter_map(|(level, kind, pages, tlb)| Self::from_entries(level, kind, pages, tlb))
+        .collect()
+    }
+
+    /// From a sub-leaf of `CPUID.(EAX=18h)` (Intel), `None` for the null sub-leaf
+    pub fn from_intel_leaf_18h(cpuid: &CpuidResult) -> Option<Self> {
+        let param = IntelTlbParam::from(cpuid);
+        let kind = match param.cache_type {
+            IntelTlbType::Data => TlbKind::Data,
+            IntelTlbType::Instruction => TlbKind::Instruction,
+            IntelTlbType::Unified => TlbKind::Unified,
+            IntelTlbType::LoadOnly => TlbKind::LoadOnly,
+            IntelTlbType::StoreOnly => TlbKind::StoreOnly,
+            IntelTlbType::Null |
+            IntelTlbType::Reserved => return None,
+        };
+        let pages = TlbPages::new(param.support_4k, param.support_2m, param.support_4m, param.support_1g);
+        let assoc = if param.fully_assoc {
+            TlbAssoc::Full
+        } else {
+            TlbAssoc::Way(param.way.min(u8::MAX as u16) as u8)
+        };
+
+        Some(Self {
+            level: param.cache_level,
+            kind,
+            pages,
+            entries: (param.way as u32).saturating_mul(param.set),
+            assoc,
+        })
+    }
+
+    /// From the TLB descriptors of `CPUID.(EAX=02h)` (Intel), the other descriptors are ignored.
+    /// The descriptor `0xFF` means the TLBs are reported by `CPUID.(EAX=18h)`.
+    #[cfg(feature = "std")]
+    pub fn from_leaf_02h(cpuid: &CpuidResult) -> Vec<Self> {
+        Self::descriptors_02h(cpuid)
+            .iter()
+            .flat_map(|desc| TLB_DESCRIPTORS.iter().filter(move |tlb| tlb.0 == *desc))
+            .map(|&(_, level, kind, pages, entries, way)| Self {
+                level,
+                kind,
+                pages,
+                entries,
+                assoc: if way == 0 { TlbAssoc::Full } else { TlbAssoc::Way(way) },
+            })
+            .collect()
+    }
+
+    /// The descriptor bytes of `CPUID.(EAX=02h)`, without `EAX[7:0]` (always 0x01)
+    /// and the registers of which bit 31 is set (reserved)
+    #[cfg(feature = "std")]
+    pub fn descriptors_02h(cpuid: &CpuidResult) -> Vec<u8> {
+        [cpuid.eax, cpuid.ebx, cpuid.ecx, cpuid.edx]
+            .iter()
+            .enumerate()
+            .filter(|(_, reg)| (*reg >> 31) == 0)
+            .flat_map(|(i, reg)| {
+                let skip = if i == 0 { 1 } else { 0 };
+
+                reg.to_le_bytes().to_vec().into_iter().skip(skip)
+            })
+            .filter(|desc| *desc != 0)
+            .collect()
+    }
+}
+
+#[test]
+fn test_tlb_info() {
+    /* CPUID Ryzen 5 5600G, 0x8000_0005, 0x8000_0006, 0x8000_0019 */
+    let tlb = TlbInfo::from_amd_cpuid_array([
+        CpuidResult { eax: 0xFF40FF40, ebx: 0xFF40FF40, ecx: 0x20080140, edx: 0x20080140 },
+        CpuidResult { eax: 0x48002200, ebx: 0x68004200, ecx: 0x02006140, edx: 0x00809140 },
+        CpuidResult { eax: 0xF040F040, ebx: 0xF0400000, ecx: 0x0, edx: 0x0 },
+    ]);
+
+    assert_eq!(tlb.len(), 11);
+    assert_eq!(tlb[0], TlbInfo {
+        level: 1,
+        kind: TlbKind::Instruction,
+        pages: TlbPages::P4K,
+        entries: 64,
+        assoc: TlbAssoc::Full,
+    });
+    /* L2 DTLB 4K: 2048 entries, 8-15 ways */
+    assert!(tlb.contains(&TlbInfo {
+        level: 2,
+        kind: TlbKind::Data,
+        pages: TlbPages::P4K,
+        entries: 2048,
+        assoc: TlbAssoc::WayRange(8..16),
+    }));
+
+    /* Core 2, CPUID.(EAX=02h) */
+    let tlb = TlbInfo::from_leaf_02h(&CpuidResult { eax: 0x05B0B101, ebx: 0x005657F0, ecx: 0x0, edx: 0x2CB43049 });
+
+    assert_eq!(
+        tlb.iter().map(|tlb| (tlb.kind, tlb.entries)).collect::<Vec<_>>(),
+        [
+            (TlbKind::Instruction, 8),
+            (TlbKind::Instruction, 128),
+            (TlbKind::Data, 32),
+            (TlbKind::Data, 16),
+            (TlbKind::Data, 16),
+            (TlbKind::Data, 256),
+        ],
+    );
+}
@@ -1,4 +1,4 @@
-use crate::{cpuid, CpuidResult, TopoLevelType};
+use crate::{leaf, CpuidResult, CpuidSource, AmdProcTopo, AmdSizeId, TopoLevelType, Reg};
 
 /// Topology ID (SMT, Core, Pkg, X2APIC)
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -7,22 +7,29 @@ pub struct TopoId {
     pub core_id: u32,
     pub pkg_id: u32,
     pub x2apic_id: u32,
+    /// Available from `CPUID.(EAX=8000_001Eh)`, AMD CPU only
+    pub node_id: Option<u32>,
 }
 
 impl TopoId {
-    fn check_topology_leaf(leaf: u32) -> bool {
+    fn check_topology_leaf<S: CpuidSource + ?Sized>(src: &S, leaf: u32) -> bool {
         const SUB_LEAF: u32 = 0x1;
-        let cpuid = cpuid!(leaf, SUB_LEAF);
+        let cpuid = src.cpuid(leaf, SUB_LEAF);
 
         /* ECX[07-00]: Level number. Same value in ECX input (Sub_Leaf) */
         (cpuid.ecx & 0xFF) == SUB_LEAF
     }
 
+    #[cfg(target_arch = "x86_64")]
     pub(crate) fn get_topology_leaf() -> Option<u32> {
-        let topo_leaf = if Self::check_topology_leaf(0x1F) {
-            0x1F
-        } else if Self::check_topology_leaf(0xB) {
-            0xB
+        Self::topology_leaf_from(&crate::NativeCpuid)
+    }
+
+    fn topology_leaf_from<S: CpuidSource + ?Sized>(src: &S) -> Option<u32> {
+        let topo_leaf = if Self::check_topology_leaf(src, leaf::EXT_TOPOLOGY_V2) {
+            leaf::EXT_TOPOLOGY_V2
+        } else if Self::check_topology_leaf(src, leaf::EXT_TOPOLOGY) {
+            leaf::EXT_TOPOLOGY
         } else {
             return None;
         };
@@ -30,12 +37,13 @@ impl TopoId {
         Some(topo_leaf)
     }
 
-    pub(crate) fn get_cpuid_by_level_type(
+    fn get_cpuid_by_level_type<S: CpuidSource + ?Sized>(
+        src: &S,
         topo_leaf: u32,
         target_level_type: TopoLevelType
     ) -> Option<CpuidResult> {
         for sub_leaf in 0..(TopoLevelType::Die as u32) {
-            let cpuid = cpuid!(topo_leaf, sub_leaf);
+            let cpuid = src.cpuid(topo_leaf, sub_leaf);
             let level_type = TopoLevelType::from(&cpuid);
             
             if level_type == target_level_type {
@@ -69,19 +77,364 @@ impl TopoId {
             core_id,
             pkg_id,
             x2apic_id,
+            node_id: None,
         }
     }
 
+    /// For AMD CPUs (and VMs) without `CPUID.(EAX=0Bh)`,
+    /// from `CPUID.(EAX=8000_0008h):ECX` and `CPUID.(EAX=8000_001Eh)`
+    pub fn get_topo_info_with_amd_cpuid(
+        cpuid_80_08: &CpuidResult,
+        cpuid_80_1e: &CpuidResult,
+    ) -> Self {
+        let size_id = AmdSizeId::from(cpuid_80_08);
+        let proc_topo = AmdProcTopo::from(cpuid_80_1e);
+        let x2apic_id = proc_topo.ext_apic_id;
+
+        /* find last set bit */
+        let mask_width = |num: u32| -> u32 { num.next_power_of_two().trailing_zeros() };
+
+        let smt_mask_width = mask_width(proc_topo.threads_per_core as u32);
+        let smt_select_mask = !(u32::MAX << smt_mask_width);
+
+        /* ApicIdSize = 0 indicates that NC (num_thread) is used to determine the size */
+        let pkg_mask_width = if size_id.apic_id_size != 0 {
+            size_id.apic_id_size as u32
+        } else {
+            mask_width(size_id.num_thread as u32)
+        };
+
+        Self {
+            smt_id: x2apic_id & smt_select_mask,
+            core_id: proc_topo.core_id as u32,
+            pkg_id: x2apic_id.checked_shr(pkg_mask_width).unwrap_or(0),
+            x2apic_id,
+            node_id: Some(proc_topo.node_id as u32),
+        }
+    }
+
+    /// For the CPUs without `CPUID.(EAX=0Bh/1Fh)` and `CPUID.(EAX=8000_001Eh)` (e.g. old Xeon, AMD pre-Zen),
+    /// from the initial APIC ID and the logical processor count of `CPUID.(EAX=01h):EBX`,
+    /// and the cores per package of `CPUID.(EAX=04h):EAX[31:26]` (Intel) or `CPUID.(EAX=8000_0008h):ECX` (AMD).
+    /// Give zeros for the leaves not supported.
+    pub fn get_topo_info_with_legacy_cpuid(
+        cpuid_01: &CpuidResult,
+        cpuid_04: &CpuidResult,
+        cpuid_80_08: &CpuidResult,
+    ) -> Self {
+        let apic_id = cpuid_01.ebx.bits(24..32);
+        /* HTT: EDX[28], the logical processor count of EBX[23:16] is valid */
+        let htt = cpuid_01.edx.bit(28);
+        let logical = if htt { cpuid_01.ebx.bits(16..24).max(1) } else { 1 };
+
+        /* find last set bit */
+        let mask_width = |num: u32| -> u32 { num.next_power_of_two().trailing_zeros() };
+
+        /* Cache Type: EAX[4:0], 0 if there is no leaf 0x4 (AMD) */
+        let (cores, apic_id_size) = if (cpuid_04.eax & 0x1F) != 0 {
+            (cpuid_04.eax.bits(26..32) + 1, 0)
+        } else {
+            let size_id = AmdSizeId::from(cpuid_80_08);
+
+            (size_id.num_thread as u32, size_id.apic_id_size as u32)
+        };
+        let cores = cores.clamp(1, logical);
+
+        let smt_mask_width = mask_width(logical / cores);
+        let pkg_mask_width = if apic_id_size != 0 {
+            apic_id_size
+        } else {
+            smt_mask_width + mask_width(cores)
+        };
+        let core_mask_width = pkg_mask_width.saturating_sub(smt_mask_width);
+
+        Self {
+            smt_id: apic_id & !(u32::MAX << smt_mask_width),
+            core_id: (apic_id >> smt_mask_width) & !(u32::MAX << core_mask_width),
+            pkg_id: apic_id.checked_shr(pkg_mask_width).unwrap_or(0),
+            x2apic_id: apic_id,
+            node_id: None,
+        }
+    }
+
+    fn legacy_topo_from<S: CpuidSource + ?Sized>(src: &S) -> Option<Self> {
+        const ZERO: CpuidResult = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+        let max_std_leaf = src.cpuid(leaf::VENDOR_ID, 0x0).eax;
+        let max_ext_leaf = src.cpuid(leaf::EXT_MAX, 0x0).eax;
+
+        if max_std_leaf < leaf::SIGNATURE_FEATURES {
+            return None;
+        }
+
+        let cpuid_04 = if leaf::CACHE_PROPERTIES <= max_std_leaf { src.cpuid(leaf::CACHE_PROPERTIES, 0x0) } else { ZERO };
+        let cpuid_80_08 = if leaf::ADDR_SIZES <= max_ext_leaf { src.cpuid(leaf::ADDR_SIZES, 0x0) } else { ZERO };
+
+        Some(Self::get_topo_info_with_legacy_cpuid(&src.cpuid(leaf::SIGNATURE_FEATURES, 0x0), &cpuid_04, &cpuid_80_08))
+    }
+
+    fn check_amd_topo_ext<S: CpuidSource + ?Sized>(src: &S) -> bool {
+        /* AMD TopologyExtensions: CPUID[Leaf=0x8000_0001, SubLeaf=0x0].ECX[22] */
+        let max_ext_leaf = src.cpuid(leaf::EXT_MAX, 0x0).eax;
+        let topo_ext = src.cpuid(leaf::EXT_SIGNATURE_FEATURES, 0x0).ecx.bit(22);
+
+        leaf::AMD_PROC_TOPOLOGY <= max_ext_leaf && topo_ext
+    }
+
     /*
         Page 9: Detecting Hyper-Threading Technology - kuo-cputopology-rc1-rh1-final-256920.pdf
         https://www.intel.com/content/dam/develop/external/us/en/documents/kuo-cputopology-rc1-rh1-final-256920.pdf
     */
+    #[cfg(target_arch = "x86_64")]
     pub fn get_topo_info() -> Option<Self> {
-        let topo_leaf = Self::get_topology_leaf()?;
+        Self::get_topo_info_from(&crate::NativeCpuid)
+    }
 
-        let smt_cpuid = Self::get_cpuid_by_level_type(topo_leaf, TopoLevelType::SMT)?;
-        let core_cpuid = Self::get_cpuid_by_level_type(topo_leaf, TopoLevelType::Core)?;
+    pub fn get_topo_info_from<S: CpuidSource + ?Sized>(src: &S) -> Option<Self> {
+        let topo_leaf = match Self::topology_leaf_from(src) {
+            Some(leaf) => leaf,
+            None if Self::check_amd_topo_ext(src) => return Some(
+                Self::get_topo_info_with_amd_cpuid(
+                    &src.cpuid(leaf::ADDR_SIZES, 0x0),
+                    &src.cpuid(leaf::AMD_PROC_TOPOLOGY, 0x0),
+                )
+            ),
+            None => return Self::legacy_topo_from(src),
+        };
+
+        let smt_cpuid = Self::get_cpuid_by_level_type(src, topo_leaf, TopoLevelType::SMT)?;
+        let core_cpuid = Self::get_cpuid_by_level_type(src, topo_leaf, TopoLevelType::Core)?;
 
         Some(Self::get_topo_info_with_smt_core_cpuid(&smt_cpuid, &core_cpuid))
     }
 }
+
+/// Bit field of x2APIC ID, `[lo + width - 1:lo]`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ApicIdField {
+    /// "smt", "core", "module", "tile", "die" (Intel), "ccx", "ccd" (AMD) or "pkg"
+    pub name: &'static str,
+    pub lo: u32,
+    pub width: u32,
+}
+
+impl ApicIdField {
+    const EMPTY: Self = Self { name: "", lo: 0, width: 0 };
+
+    /// ID of the level, the bits of the field in `x2apic_id`
+    pub fn id(&self, x2apic_id: u32) -> u32 {
+        let mask = !u32::MAX.checked_shl(self.width).unwrap_or(0);
+
+        x2apic_id.checked_shr(self.lo).unwrap_or(0) & mask
+    }
+}
+
+/// Decomposition of x2APIC ID into the fields of the topology levels,
+/// from the shift values of `CPUID.(EAX=8000_0026h)` (AMD Zen 4 or later), `CPUID.(EAX=1Fh/0Bh)`,
+/// or `CPUID.(EAX=8000_001Eh)` and `CPUID.(EAX=8000_0008h):ECX` (AMD TopologyExtensions)
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ApicIdLayout {
+    /// The leaf of the shift values
+    pub leaf: u32,
+    fields: [ApicIdField; 8],
+    len: usize,
+}
+
+impl ApicIdLayout {
+    /* indexed by the level type of ECX[15:8] */
+    const INTEL_LEVEL_NAMES: [&'static str; 6] = ["", "smt", "core", "module", "tile", "die"];
+    const AMD_LEVEL_NAMES: [&'static str; 5] = ["", "smt", "core", "ccx", "ccd"];
+
+    /// The fields from the LSB, the last is "pkg"
+    pub fn fields(&self) -> &[ApicIdField] {
+        &self.fields[..self.len]
+    }
+
+    fn push(&mut self, name: &'static str, lo: u32, hi: u32) {
+        if self.len < self.fields.len() && lo < hi {
+            self.fields[self.len] = ApicIdField { name, lo, width: hi - lo };
+            self.len += 1;
+        }
+    }
+
+    fn from_levels<S: CpuidSource + ?Sized>(src: &S, topo_leaf: u32, names: &[&'static str]) -> Self {
+        let mut layout = Self { leaf: topo_leaf, fields: [ApicIdField::EMPTY; 8], len: 0 };
+        let mut lo = 0;
+
+        /* the last field is for the package */
+        for sub_leaf in 0..(layout.fields.len() as u32 - 1) {
+            let cpuid = src.cpuid(topo_leaf, sub_leaf);
+            let level_type = cpuid.ecx.bits(8..16) as usize;
+            /* EAX[4:0]: shift of x2APIC ID to the ID of the next level */
+            let shift = cpuid.eax.bits(0..5);
+
+            if level_type == 0 || shift < lo { break }
+
+            layout.push(names.get(level_type).copied().unwrap_or("unknown"), lo, shift);
+            lo = shift;
+        }
+
+        layout.push("pkg", lo, 32);
+        layout
+    }
+
+    fn from_amd_proc_topo<S: CpuidSource + ?Sized>(src: &S) -> Self {
+        let size_id = AmdSizeId::from(&src.cpuid(leaf::ADDR_SIZES, 0x0));
+        let proc_topo = AmdProcTopo::from(&src.cpuid(leaf::AMD_PROC_TOPOLOGY, 0x0));
+        let mask_width = |num: u32| -> u32 { num.next_power_of_two().trailing_zeros() };
+
+        let smt_mask_width = mask_width(proc_topo.threads_per_core as u32);
+        let pkg_mask_width = if size_id.apic_id_size != 0 {
+            size_id.apic_id_size as u32
+        } else {
+            mask_width(size_id.num_thread as u32)
+        };
+        let mut layout = Self { leaf: leaf::AMD_PROC_TOPOLOGY, fields: [ApicIdField::EMPTY; 8], len: 0 };
+
+        layout.push("smt", 0, smt_mask_width);
+        layout.push("core", smt_mask_width, pkg_mask_width);
+        layout.push("pkg", pkg_mask_width.max(smt_mask_width), 32);
+        layout
+    }
+
+    /// `None` if there is no leaf of the shift values
+    pub fn from_source<S: CpuidSource + ?Sized>(src: &S) -> Option<Self> {
+        let max_ext_leaf = src.cpuid(leaf::EXT_MAX, 0x0).eax;
+        let amd_ext_topo = leaf::AMD_EXT_TOPOLOGY <= max_ext_leaf && {
+            let cpuid = src.cpuid(leaf::AMD_EXT_TOPOLOGY, 0x0);
+
+            /* the first level is Core, with the logical processors */
+            cpuid.ecx.bits(8..16) == 0x1 && cpuid.ebx.bits(0..16) != 0x0
+        };
+
+        if amd_ext_topo {
+            return Some(Self::from_levels(src, leaf::AMD_EXT_TOPOLOGY, &Self::AMD_LEVEL_NAMES));
+        }
+
+        match TopoId::topology_leaf_from(src) {
+            Some(topo_leaf) => Some(Self::from_levels(src, topo_leaf, &Self::INTEL_LEVEL_NAMES)),
+            None if TopoId::check_amd_topo_ext(src) => Some(Self::from_amd_proc_topo(src)),
+            None => None,
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn get() -> Option<Self> {
+        Self::from_source(&crate::NativeCpuid)
+    }
+}
+
+#[test]
+fn test_topo_info_amd() {
+    /* Ryzen 5 5600G, Thread 7 */
+    let cpuid_80_08 = CpuidResult { eax: 0x00003030, ebx: 0x191EF657, ecx: 0x0000400B, edx: 0x00010000 };
+    let cpuid_80_1e = CpuidResult { eax: 0x00000003, ebx: 0x00000101, ecx: 0x00000000, edx: 0x00000000 };
+
+    let topo = TopoId::get_topo_info_with_amd_cpuid(&cpuid_80_08, &cpuid_80_1e);
+
+    assert_eq!(
+        topo,
+        TopoId { smt_id: 1, core_id: 1, pkg_id: 0, x2apic_id: 3, node_id: Some(0) },
+    );
+}
+
+#[test]
+fn test_topo_info_from_source() {
+    /* Ryzen 5 5600G, Thread 7: no Leaf 0xB/0x1F, TopologyExtensions */
+    let src = [
+        (0x8000_0000, 0x0, CpuidResult { eax: 0x80000020, ebx: 0x68747541, ecx: 0x444D4163, edx: 0x69746E65 }),
+        (0x8000_0001, 0x0, CpuidResult { eax: 0x00A50F00, ebx: 0x20000000, ecx: 0x75C237FF, edx: 0x2FD3FBFF }),
+        (0x8000_0008, 0x0, CpuidResult { eax: 0x00003030, ebx: 0x191EF657, ecx: 0x0000400B, edx: 0x00010000 }),
+        (0x8000_001E, 0x0, CpuidResult { eax: 0x00000003, ebx: 0x00000101, ecx: 0x00000000, edx: 0x00000000 }),
+    ];
+
+    assert_eq!(
+        TopoId::get_topo_info_from(&src[..]),
+        Some(TopoId { smt_id: 1, core_id: 1, pkg_id: 0, x2apic_id: 3, node_id: Some(0) }),
+    );
+
+    /* Leaf 0xB: 2 threads per core, 16 threads per package, x2APIC ID 0x13 */
+    let src = |leaf: u32, sub_leaf: u32| match (leaf, sub_leaf) {
+        (0xB, 0x0) => CpuidResult { eax: 0x1, ebx: 0x2, ecx: 0x100, edx: 0x13 },
+        (0xB, 0x1) => CpuidResult { eax: 0x4, ebx: 0x10, ecx: 0x201, edx: 0x13 },
+        _ => CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 },
+    };
+
+    assert_eq!(
+        TopoId::get_topo_info_from(&src),
+        Some(TopoId { smt_id: 1, core_id: 1, pkg_id: 1, x2apic_id: 0x13, node_id: None }),
+    );
+}
+
+#[test]
+fn test_topo_info_legacy() {
+    let zero = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+
+    /* Xeon E5-2680 (Sandy Bridge-EP) without Leaf 0xB: 8 cores, 16 threads, initial APIC ID 0x23 */
+    let cpuid_01 = CpuidResult { eax: 0x000206D7, ebx: 0x23200800, ecx: 0x1FBEE3FF, edx: 0xBFEBFBFF };
+    let cpuid_04 = CpuidResult { eax: 0x3C004121, ebx: 0x01C0003F, ecx: 0x0000003F, edx: 0x00000000 };
+
+    assert_eq!(
+        TopoId::get_topo_info_with_legacy_cpuid(&cpuid_01, &cpuid_04, &zero),
+        TopoId { smt_id: 1, core_id: 1, pkg_id: 1, x2apic_id: 0x23, node_id: None },
+    );
+
+    /* FX-8350 (Piledriver): 8 cores, ApicIdCoreIdSize 4, initial APIC ID 0x15 */
+    let cpuid_01 = CpuidResult { eax: 0x00600F20, ebx: 0x15080800, ecx: 0x3E98320B, edx: 0x178BFBFF };
+    let cpuid_80_08 = CpuidResult { eax: 0x00003030, ebx: 0x00000000, ecx: 0x00004007, edx: 0x00000000 };
+
+    assert_eq!(
+        TopoId::get_topo_info_with_legacy_cpuid(&cpuid_01, &zero, &cpuid_80_08),
+        TopoId { smt_id: 0, core_id: 5, pkg_id: 1, x2apic_id: 0x15, node_id: None },
+    );
+}
+
+#[test]
+fn test_apic_id_layout() {
+    let zero = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+    let names = |layout: &ApicIdLayout| -> [(&str, u32, u32); 3] {
+        let f = layout.fields();
+        [(f[0].name, f[0].lo, f[0].width), (f[1].name, f[1].lo, f[1].width), (f[2].name, f[2].lo, f[2].width)]
+    };
+
+    /* Leaf 0x1F: SMT shift 1, Core shift 6, x2APIC ID 0x34 */
+    let src = |leaf: u32, sub_leaf: u32| match (leaf, sub_leaf) {
+        (0x1F, 0x0) => CpuidResult { eax: 0x1, ebx: 0x2, ecx: 0x100, edx: 0x34 },
+        (0x1F, 0x1) => CpuidResult { eax: 0x6, ebx: 0x18, ecx: 0x201, edx: 0x34 },
+        _ => zero,
+    };
+    let layout = ApicIdLayout::from_source(&src).unwrap();
+    let f = layout.fields();
+
+    assert_eq!(layout.leaf, 0x1F);
+    assert_eq!(names(&layout), [("smt", 0, 1), ("core", 1, 5), ("pkg", 6, 26)]);
+    assert_eq!((f[2].id(0x34), f[1].id(0x34), f[0].id(0x34)), (0, 26, 0));
+
+    /* Leaf 0x8000_0026 (Ryzen 9 7950X): Core shift 1, Complex shift 4, Die (CCD) shift 4, Socket shift 7 */
+    let src = |leaf: u32, sub_leaf: u32| match (leaf, sub_leaf) {
+        (0x8000_0000, 0x0) => CpuidResult { eax: 0x8000_0028, ..zero },
+        (0x8000_0026, 0x0) => CpuidResult { eax: 0x1, ebx: 0x2, ecx: 0x100, edx: 0x1B },
+        (0x8000_0026, 0x1) => CpuidResult { eax: 0x4, ebx: 0x10, ecx: 0x201, edx: 0x1B },
+        (0x8000_0026, 0x2) => CpuidResult { eax: 0x4, ebx: 0x10, ecx: 0x302, edx: 0x1B },
+        (0x8000_0026, 0x3) => CpuidResult { eax: 0x7, ebx: 0x20, ecx: 0x403, edx: 0x1B },
+        _ => zero,
+    };
+    let layout = ApicIdLayout::from_source(&src).unwrap();
+    let f = layout.fields();
+
+    assert_eq!(layout.leaf, 0x8000_0026);
+    assert_eq!(f.len(), 4);
+    assert_eq!([f[0].name, f[1].name, f[2].name, f[3].name], ["smt", "core", "ccd", "pkg"]);
+    assert_eq!((f[2].lo, f[2].width, f[2].id(0x1B)), (4, 3, 1));
+
+    /* Ryzen 5 2600: TopologyExtensions only, 2 threads per core, ApicIdSize 4 */
+    let src = [
+        (0x8000_0000, 0x0, CpuidResult { eax: 0x8000001F, ebx: 0x68747541, ecx: 0x444D4163, edx: 0x69746E65 }),
+        (0x8000_0001, 0x0, CpuidResult { eax: 0x00800F82, ebx: 0x20000000, ecx: 0x35C233FF, edx: 0x2FD3FBFF }),
+        (0x8000_0008, 0x0, CpuidResult { eax: 0x00003030, ebx: 0x00001007, ecx: 0x0000400B, edx: 0x00000000 }),
+        (0x8000_001E, 0x0, CpuidResult { eax: 0x00000009, ebx: 0x00000104, ecx: 0x00000000, edx: 0x00000000 }),
+    ];
+    let layout = ApicIdLayout::from_source(&src[..]).unwrap();
+
+    assert_eq!(names(&layout), [("smt", 0, 1), ("core", 1, 3), ("pkg", 4, 28)]);
+    assert!(ApicIdLayout::from_source(&[(0x0, 0x0, zero)][..]).is_none());
+}
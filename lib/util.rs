@@ -1,7 +1,8 @@
-use crate::{cpuid, CacheProp, TopoId, TopoLevelType};
+#[cfg(target_arch = "x86_64")]
+use crate::{CacheProp, Reg, TopoId, TopoLevelType};
 
 /// Pin thread to CPU
-pub fn pin_thread(cpu: usize) -> Result<(), i32> {
+pub fn pin_thread(_cpu: usize) -> Result<(), i32> {
     #[cfg(unix)]
     unsafe {
         use libc::{
@@ -15,7 +16,7 @@ pub fn pin_thread(cpu: usize) -> Result<(), i32> {
 
         let mut set = core::mem::zeroed::<cpu_set_t>();
         CPU_ZERO(&mut set);
-        CPU_SET(cpu, &mut set);
+        CPU_SET(_cpu, &mut set);
 
         let status = sched_setaffinity(0, core::mem::size_of::<cpu_set_t>(), &set);
         if status == -1 {
@@ -29,15 +30,210 @@ pub fn pin_thread(cpu: usize) -> Result<(), i32> {
             GetCurrentThread,
             SetThreadAffinityMask,
         };
-        SetThreadAffinityMask(GetCurrentThread(), 1 << cpu);
+        SetThreadAffinityMask(GetCurrentThread(), 1 << _cpu);
     }
 
     Ok(())
 }
 
+/// Whether CPUID faulting is enabled for the current thread, using `arch_prctl(ARCH_GET_CPUID)`.
+/// With CPUID faulting, the CPUID instruction is trapped and the result may be emulated by the kernel.
+/// Returns `None` if not supported by the platform or the kernel.
+pub fn cpuid_faulting() -> Option<bool> {
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    unsafe {
+        const ARCH_GET_CPUID: libc::c_long = 0x1011;
+
+        let status = libc::syscall(libc::SYS_arch_prctl, ARCH_GET_CPUID, 0);
+
+        /* 1: CPUID is enabled, 0: CPUID faulting is enabled */
+        match status {
+            0 => Some(true),
+            1 => Some(false),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+    None
+}
+
+/// Read MSR with `/dev/cpu/<cpu>/msr` (requires the msr module and the permission),
+/// `None` if it is not available
+#[cfg(feature = "std")]
+pub fn read_msr(_cpu: usize, _msr: u32) -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::fs::File;
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut f = File::open(format!("/dev/cpu/{_cpu}/msr")).ok()?;
+        let mut buf = [0u8; 8];
+
+        f.seek(SeekFrom::Start(_msr as u64)).ok()?;
+        f.read_exact(&mut buf).ok()?;
+
+        Some(u64::from_le_bytes(buf))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    None
+}
+
+/// Microcode revision of the CPU from `/sys/devices/system/cpu/cpu<cpu>/microcode/version`
+/// or the "microcode" field of `/proc/cpuinfo`, `None` if it is not available
+#[cfg(feature = "std")]
+pub fn microcode_revision(_cpu: usize) -> Option<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        let parse = |s: &str| -> Option<u32> {
+            let s = s.trim();
+
+            u32::from_str_radix(s.strip_prefix("0x").unwrap_or(s), 16).ok()
+        };
+
+        if let Ok(version) = std::fs::read_to_string(format!("/sys/devices/system/cpu/cpu{_cpu}/microcode/version")) {
+            return parse(&version);
+        }
+
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+
+        /* the blocks of each CPU are separated by an empty line */
+        cpuinfo.split("\n\n").find_map(|block| {
+            let field = |name: &str| block.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+
+                (key.trim() == name).then_some(value)
+            });
+
+            if field("processor")?.trim().parse::<usize>().ok()? != _cpu { return None }
+
+            parse(field("microcode")?)
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    None
+}
+
+/// Topology of the CPU seen by the OS, which may differ from the topology IDs of CPUID in a VM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OsCpuTopology {
+    pub package_id: u32,
+    /// `None` without the die level (before Linux 5.2)
+    pub die_id: Option<u32>,
+    pub core_id: u32,
+    /// NUMA node, `None` without NUMA
+    pub node_id: Option<u32>,
+}
+
+/// `OsCpuTopology` of the CPU from `/sys/devices/system/cpu/cpu<cpu>/topology`
+/// and the `node<N>` link of the CPU, `None` if it is not available
+#[cfg(feature = "std")]
+pub fn os_cpu_topology(_cpu: usize) -> Option<OsCpuTopology> {
+    #[cfg(target_os = "linux")]
+    {
+        let dir = format!("/sys/devices/system/cpu/cpu{_cpu}");
+        let read = |name: &str| -> Option<u32> {
+            std::fs::read_to_string(format!("{dir}/topology/{name}")).ok()?.trim().parse().ok()
+        };
+        let node_id = std::fs::read_dir(&dir).ok()?.find_map(|entry| {
+            entry.ok()?.file_name().to_str()?.strip_prefix("node")?.parse().ok()
+        });
+
+        Some(OsCpuTopology {
+            package_id: read("physical_package_id")?,
+            die_id: read("die_id"),
+            core_id: read("core_id")?,
+            node_id,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    None
+}
+
+/// Whether the kernel enabled 5-level paging (LA57), from the virtual address size
+/// of the "address sizes" field of `/proc/cpuinfo` (like "46 bits physical, 57 bits virtual"),
+/// `None` if it is not available
+#[cfg(feature = "std")]
+pub fn kernel_la57() -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+        let sizes = cpuinfo.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+
+            (key.trim() == "address sizes").then_some(value)
+        })?;
+        let virtual_ = sizes.split(',').find_map(|size| size.trim().strip_suffix(" bits virtual"))?;
+
+        Some(virtual_.trim().parse::<u32>().ok()? == 57)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    None
+}
+
+/// Container runtime of the process, from `/.dockerenv`, `/run/.containerenv` (Podman)
+/// or the cgroup of the init process, `None` if it is not detected
+#[cfg(feature = "std")]
+pub fn container_runtime() -> Option<&'static str> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::path::Path;
+
+        if Path::new("/.dockerenv").exists() { return Some("Docker") }
+        if Path::new("/run/.containerenv").exists() { return Some("Podman") }
+
+        let cgroup = std::fs::read_to_string("/proc/1/cgroup").ok()?;
+
+        [("kubepods", "Kubernetes"), ("docker", "Docker"), ("libpod", "Podman"), ("lxc", "LXC")]
+            .iter()
+            .find(|(key, _)| cgroup.contains(key))
+            .map(|(_, name)| *name)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    None
+}
+
+/// Online CPUs from `/sys/devices/system/cpu/online` (like "0-3,5"), `None` if it is not available
+#[cfg(all(feature = "std", target_os = "linux"))]
+fn online_cpu_list() -> Option<Vec<usize>> {
+    let online = std::fs::read_to_string("/sys/devices/system/cpu/online").ok()?;
+    let mut cpus: Vec<usize> = Vec::new();
+
+    for range in online.trim().split(',') {
+        match range.split_once('-') {
+            Some((start, end)) => cpus.extend(start.parse::<usize>().ok()?..=end.parse::<usize>().ok()?),
+            None => cpus.push(range.parse::<usize>().ok()?),
+        }
+    }
+
+    Some(cpus)
+}
+
+/// Number of the CPUs configured in the system, including the offline CPUs
+/// and the CPUs outside the allowed cpuset of the process
+#[cfg(feature = "std")]
+pub fn configured_cpu_count() -> Option<usize> {
+    #[cfg(unix)]
+    unsafe {
+        let count = libc::sysconf(libc::_SC_NPROCESSORS_CONF);
+
+        if count < 1 { None } else { Some(count as usize) }
+    }
+
+    #[cfg(not(unix))]
+    None
+}
+
 /// Get list of available CPUs
 #[cfg(feature = "std")]
 pub fn cpu_set_list() -> Result<Vec<usize>, i32> {
+    /* empty on the other platforms (wasm32) */
+    #[cfg_attr(not(any(unix, windows)), allow(unused_mut))]
     let mut cpus: Vec<usize> = Vec::with_capacity(256);
 
     #[cfg(unix)]
@@ -55,7 +251,7 @@ pub fn cpu_set_list() -> Result<Vec<usize>, i32> {
 
         let status = sched_getaffinity(0, core::mem::size_of::<cpu_set_t>(), &mut set);
         if status == -1 {
-            eprintln!("sched_getaffinity failed");
+            log::warn!("sched_getaffinity failed");
             return Err(status);
         }
 
@@ -66,6 +262,12 @@ pub fn cpu_set_list() -> Result<Vec<usize>, i32> {
         }
     }
 
+    /* skip the offline CPUs, if the kernel still has them in the affinity mask */
+    #[cfg(target_os = "linux")]
+    if let Some(online) = online_cpu_list() {
+        cpus.retain(|cpu| online.contains(cpu));
+    }
+
     #[cfg(windows)]
     unsafe {
         use windows::Win32::System::Threading::{
@@ -81,14 +283,15 @@ pub fn cpu_set_list() -> Result<Vec<usize>, i32> {
     Ok(cpus)
 }
 
+#[cfg(target_arch = "x86_64")]
 pub fn get_total_logical_processor() -> Option<u32> {
     if let Some(topo_leaf) = TopoId::get_topology_leaf() {
-        let thread_count = (cpuid!(topo_leaf, 0x1).ebx >> 16) & 0xFF;
+        let thread_count = cpuid!(topo_leaf, 0x1).ebx.bits(16..24);
 
         return Some(thread_count);
     } else {
         let leaf_01h = cpuid!(0x1, 0x0);
-        let proc_count = ((leaf_01h.ebx >> 16) & 0xFF) + 1;
+        let proc_count = leaf_01h.ebx.bits(16..24) + 1;
 
         if proc_count == 0 { return None; }
 
@@ -96,12 +299,13 @@ pub fn get_total_logical_processor() -> Option<u32> {
     }
 }
 
+#[cfg(target_arch = "x86_64")]
 pub fn get_threads_per_core() -> Option<u32> {
     /* Extended Topology Enumeration */
     if let Some(topo_leaf) = TopoId::get_topology_leaf() {
         /* SMT Level */
         let cpuid = cpuid!(topo_leaf, 0x0);
-        let level = (cpuid.ecx >> 8) & 0xFF;
+        let level = cpuid.ecx.bits(8..16);
 
         if level == (TopoLevelType::SMT as u32) {
             return Some(cpuid.ebx & 0xFFFF);
@@ -111,10 +315,10 @@ pub fn get_threads_per_core() -> Option<u32> {
     /*
         AMD TopologyExtensions flag: CPUID[Leaf=0x8000_0001, SubLeaf=0x0].ECX[22]
     */
-    let check_topoext = ((cpuid!(0x8000_0001, 0x0).ecx >> 22) & 0b1) != 0;
+    let check_topoext = cpuid!(0x8000_0001, 0x0).ecx.bit(22);
     if check_topoext {
         let cpuid = cpuid!(0x8000_001E, 0x0).ebx;
-        let per_core = (cpuid >> 8) & 0xFF;
+        let per_core = cpuid.bits(8..16);
 
         return Some(per_core);
     }
@@ -1,4 +1,4 @@
-use crate::{cpuid, CpuidResult};
+use crate::{CpuidResult, CpuidSource};
 
 /* ref: https://github.com/llvm/llvm-project/blob/main/clang/lib/Headers/cpuid.h */
 /* ref: https://github.com/gcc-mirror/gcc/blob/master/gcc/config/i386/cpuid.h */
@@ -48,9 +48,50 @@ impl Vendor {
         edx: Self::SHANGHAI_EDX,
     };
 
+    const HYGON_EBX: u32 = 0x6F67_7948;
+    const HYGON_ECX: u32 = 0x656E_6975;
+    const HYGON_EDX: u32 = 0x6E65_476E;
+    const REG_HYGON: Self = Self {
+        ebx: Self::HYGON_EBX,
+        ecx: Self::HYGON_ECX,
+        edx: Self::HYGON_EDX,
+    };
+
+    #[cfg(target_arch = "x86_64")]
     pub fn get() -> Self {
         Self::from(&cpuid!(0x0, 0x0))
     }
+
+    /// The vendor ID string of `CPUID.(EAX=00h)` (EBX, EDX, ECX), padded with NUL
+    pub fn from_bytes(bytes: &[u8; 12]) -> Self {
+        let reg = |i: usize| u32::from_le_bytes([bytes[i], bytes[i+1], bytes[i+2], bytes[i+3]]);
+
+        Self {
+            ebx: reg(0),
+            edx: reg(4),
+            ecx: reg(8),
+        }
+    }
+
+    /// The signature of `CPUID.(EAX=4000_0000h)`, in the order of EBX, ECX, EDX unlike `CPUID.(EAX=00h)`
+    pub fn from_hypervisor_leaf(cpuid: &CpuidResult) -> Self {
+        Self {
+            ebx: cpuid.ebx,
+            edx: cpuid.ecx,
+            ecx: cpuid.edx,
+        }
+    }
+
+    /// Raw 12-byte vendor ID string (EBX, EDX, ECX)
+    pub fn to_bytes(&self) -> [u8; 12] {
+        let mut bytes = [0u8; 12];
+
+        for (i, reg) in [self.ebx, self.edx, self.ecx].iter().enumerate() {
+            bytes[(i*4)..(i*4+4)].copy_from_slice(&reg.to_le_bytes());
+        }
+
+        bytes
+    }
 }
 
 /*
@@ -68,7 +109,7 @@ impl fmt::Display for Vendor {
             total[(i*4)..(i*4+4)].copy_from_slice(&ProcName::check_reg(*reg))
         }
 
-        write!(f, "{}", String::from_utf8(total.to_vec()).unwrap())
+        write!(f, "{}", String::from_utf8_lossy(&total))
     }
 }
 */
@@ -83,25 +124,88 @@ impl From<&CpuidResult> for Vendor {
     }
 }
 
-/// List of x86_64 CPU vendors
+/// List of x86_64 CPU vendors, and the hypervisors and the binary translators by the signature
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum CpuVendor {
     AuthenticAMD,
     GenuineIntel,
     CentaurHauls,
+    /// Zhaoxin
     Shanghai,
+    HygonGenuine,
+    Via,
+    /// "GenuineTMx86", "TransmetaCPU"
+    Transmeta,
+    Cyrix,
+    /// National Semiconductor (Geode)
+    Nsc,
+    Vortex,
+    Umc,
+    NexGen,
+    Rise,
+    Sis,
+    Rdc,
+    /* hypervisors */
+    Kvm,
+    HyperV,
+    VMware,
+    Xen,
+    QemuTcg,
+    VirtualBox,
+    Parallels,
+    Acrn,
+    Bhyve,
+    Qnx,
+    /* binary translators */
+    /// Rosetta 2 of Apple
+    VirtualApple,
+    /// x86 emulation of Windows on Arm
+    MicrosoftXta,
     Unknown(Vendor),
 }
 
+/* (vendor ID string or signature of the hypervisor, vendor) */
+const VENDOR_ID: &[(&[u8; 12], CpuVendor)] = &[
+    (b"AuthenticAMD", CpuVendor::AuthenticAMD),
+    (b"AMDisbetter!", CpuVendor::AuthenticAMD),
+    (b"GenuineIntel", CpuVendor::GenuineIntel),
+    (b"GenuineIotel", CpuVendor::GenuineIntel),
+    (b"CentaurHauls", CpuVendor::CentaurHauls),
+    (b"  Shanghai  ", CpuVendor::Shanghai),
+    (b"HygonGenuine", CpuVendor::HygonGenuine),
+    (b"VIA VIA VIA ", CpuVendor::Via),
+    (b"GenuineTMx86", CpuVendor::Transmeta),
+    (b"TransmetaCPU", CpuVendor::Transmeta),
+    (b"CyrixInstead", CpuVendor::Cyrix),
+    (b"Geode by NSC", CpuVendor::Nsc),
+    (b"Vortex86 SoC", CpuVendor::Vortex),
+    (b"UMC UMC UMC ", CpuVendor::Umc),
+    (b"NexGenDriven", CpuVendor::NexGen),
+    (b"RiseRiseRise", CpuVendor::Rise),
+    (b"SiS SiS SiS ", CpuVendor::Sis),
+    (b"Genuine  RDC", CpuVendor::Rdc),
+    (b"KVMKVMKVM\0\0\0", CpuVendor::Kvm),
+    (b"Microsoft Hv", CpuVendor::HyperV),
+    (b"VMwareVMware", CpuVendor::VMware),
+    (b"XenVMMXenVMM", CpuVendor::Xen),
+    (b"TCGTCGTCGTCG", CpuVendor::QemuTcg),
+    (b"VBoxVBoxVBox", CpuVendor::VirtualBox),
+    (b" lrpepyh  vr", CpuVendor::Parallels),
+    (b"ACRNACRNACRN", CpuVendor::Acrn),
+    (b"bhyve bhyve ", CpuVendor::Bhyve),
+    (b" QNXQVMBSQG ", CpuVendor::Qnx),
+    (b"VirtualApple", CpuVendor::VirtualApple),
+    (b"MicrosoftXTA", CpuVendor::MicrosoftXta),
+];
+
 impl From<&Vendor> for CpuVendor {
     fn from(vendor: &Vendor) -> Self {
-        match vendor.ebx {
-            Vendor::AMD_EBX => Self::AuthenticAMD,
-            Vendor::INTEL_EBX => Self::GenuineIntel,
-            Vendor::CENTAUR_EBX => Self::CentaurHauls,
-            Vendor::SHANGHAI_EBX => Self::Shanghai,
-            _ => Self::Unknown(vendor.clone()),
-        }
+        let bytes = vendor.to_bytes();
+
+        VENDOR_ID
+            .iter()
+            .find(|(id, _)| **id == bytes)
+            .map_or(Self::Unknown(*vendor), |(_, cpu_vendor)| *cpu_vendor)
     }
 }
 
@@ -112,9 +216,120 @@ impl From<&CpuidResult> for CpuVendor {
 }
 
 impl CpuVendor {
+    #[cfg(target_arch = "x86_64")]
     pub fn get() -> Self {
-        Self::from(&cpuid!(0x0, 0x0))
+        Self::from_source(&crate::NativeCpuid)
+    }
+
+    pub fn from_source<S: CpuidSource + ?Sized>(src: &S) -> Self {
+        Self::from(&src.cpuid(0x0, 0x0))
     }
+
+    /// Vendor of the raw 12-byte vendor ID string
+    pub fn from_bytes(bytes: &[u8; 12]) -> Self {
+        Self::from(&Vendor::from_bytes(bytes))
+    }
+
+    /// Vendor of the ID string shorter than 12 bytes padded with NUL (e.g. "KVMKVMKVM"),
+    /// `None` for the longer string
+    pub fn from_id_str(id: &str) -> Option<Self> {
+        let mut bytes = [0u8; 12];
+
+        bytes.get_mut(..id.len())?.copy_from_slice(id.as_bytes());
+
+        Some(Self::from_bytes(&bytes))
+    }
+
+    /// Hypervisor of the signature of `CPUID.(EAX=4000_0000h)`
+    pub fn from_hypervisor_leaf(cpuid: &CpuidResult) -> Self {
+        Self::from(&Vendor::from_hypervisor_leaf(cpuid))
+    }
+
+    /// The (first) vendor ID string of the vendor, the raw registers for `Unknown`
+    pub fn vendor_id(&self) -> [u8; 12] {
+        match self {
+            Self::Unknown(vendor) => vendor.to_bytes(),
+            _ => VENDOR_ID
+                .iter()
+                .find(|(_, cpu_vendor)| cpu_vendor == self)
+                .map_or([0u8; 12], |(id, _)| **id),
+        }
+    }
+
+    pub fn is_hypervisor(&self) -> bool {
+        matches!(self,
+            Self::Kvm |
+            Self::HyperV |
+            Self::VMware |
+            Self::Xen |
+            Self::QemuTcg |
+            Self::VirtualBox |
+            Self::Parallels |
+            Self::Acrn |
+            Self::Bhyve |
+            Self::Qnx
+        )
+    }
+
+    /// Display name, like "AMD", "Hyper-V"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AuthenticAMD => "AMD",
+            Self::GenuineIntel => "Intel",
+            Self::CentaurHauls => "Centaur",
+            Self::Shanghai => "Zhaoxin",
+            Self::HygonGenuine => "Hygon",
+            Self::Via => "VIA",
+            Self::Transmeta => "Transmeta",
+            Self::Cyrix => "Cyrix",
+            Self::Nsc => "NSC",
+            Self::Vortex => "Vortex86",
+            Self::Umc => "UMC",
+            Self::NexGen => "NexGen",
+            Self::Rise => "Rise",
+            Self::Sis => "SiS",
+            Self::Rdc => "RDC",
+            Self::Kvm => "KVM",
+            Self::HyperV => "Hyper-V",
+            Self::VMware => "VMware",
+            Self::Xen => "Xen",
+            Self::QemuTcg => "QEMU TCG",
+            Self::VirtualBox => "VirtualBox",
+            Self::Parallels => "Parallels",
+            Self::Acrn => "ACRN",
+            Self::Bhyve => "bhyve",
+            Self::Qnx => "QNX",
+            Self::VirtualApple => "Rosetta 2",
+            Self::MicrosoftXta => "Microsoft XTA",
+            Self::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+#[test]
+fn test_cpu_vendor() {
+    /* CPUID.(EAX=00h) of Ryzen 5 5600G */
+    let cpuid = CpuidResult { eax: 0x10, ebx: 0x6874_7541, ecx: 0x444D_4163, edx: 0x6974_6E65 };
+    let vendor = CpuVendor::from(&cpuid);
+
+    assert_eq!(vendor, CpuVendor::AuthenticAMD);
+    assert_eq!(&vendor.vendor_id(), b"AuthenticAMD");
+    assert_eq!(&Vendor::from(&cpuid).to_bytes(), b"AuthenticAMD");
+    assert_eq!(CpuVendor::from_bytes(b"GenuineTMx86"), CpuVendor::Transmeta);
+    assert_eq!(CpuVendor::from_bytes(b"TransmetaCPU"), CpuVendor::Transmeta);
+
+    /* CPUID.(EAX=4000_0000h) of KVM */
+    let hv = CpuVendor::from_hypervisor_leaf(&CpuidResult { eax: 0x4000_0001, ebx: 0x4B4D_564B, ecx: 0x564B_4D56, edx: 0x0000_004D });
+
+    assert_eq!(hv, CpuVendor::Kvm);
+    assert!(hv.is_hypervisor());
+    assert_eq!(CpuVendor::from_id_str("KVMKVMKVM"), Some(CpuVendor::Kvm));
+    assert_eq!(CpuVendor::from_id_str("Unknown vendor ID"), None);
+
+    let unknown = CpuVendor::from_bytes(b"ABCDEFGHIJKL");
+
+    assert!(matches!(unknown, CpuVendor::Unknown(_)));
+    assert_eq!(&unknown.vendor_id(), b"ABCDEFGHIJKL");
 }
 
 #[cfg(feature = "std")]
@@ -0,0 +1,123 @@
+use crate::{CpuidResult, Reg};
+
+/// XSAVE-supported features and XSAVE area sizes available from
+/// `CPUID.(EAX=0Dh, ECX=0)` and `CPUID.(EAX=0Dh, ECX=1)`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XSaveInfo {
+    /// The user state components that can be set in XCR0
+    pub xcr0_supported: u64,
+    /// The supervisor state components that can be set in IA32_XSS
+    pub xss_supported: u64,
+    /// The size of the XSAVE area for the components enabled in XCR0
+    pub size_xcr0_enabled: u32,
+    /// The maximum size of the XSAVE area for all the components supported in XCR0
+    pub size_xcr0_max: u32,
+    /// The size of the XSAVES area (compacted format) for the components enabled in XCR0 | IA32_XSS
+    pub size_xcr0_xss_enabled: u32,
+    pub xsaveopt: bool,
+    pub xsavec: bool,
+    pub xgetbv_ecx1: bool,
+    pub xsaves: bool,
+    pub xfd: bool,
+}
+
+impl XSaveInfo {
+    /// `[00_0D_x0, 00_0D_x1]`
+    pub fn from_cpuid_array(cpuid_array: [CpuidResult; 2]) -> Self {
+        let [x0, x1] = cpuid_array;
+
+        Self {
+            xcr0_supported: ((x0.edx as u64) << 32) | (x0.eax as u64),
+            xss_supported: ((x1.edx as u64) << 32) | (x1.ecx as u64),
+            size_xcr0_enabled: x0.ebx,
+            size_xcr0_max: x0.ecx,
+            size_xcr0_xss_enabled: x1.ebx,
+            xsaveopt: x1.eax.bit(0),
+            xsavec: x1.eax.bit(1),
+            xgetbv_ecx1: x1.eax.bit(2),
+            xsaves: x1.eax.bit(3),
+            xfd: x1.eax.bit(4),
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn get() -> Self {
+        Self::from_cpuid_array([cpuid!(0xD, 0x0), cpuid!(0xD, 0x1)])
+    }
+}
+
+/// Read the extended control register with XGETBV, `None` if OSXSAVE is not set.
+/// XCR0 (`index` = 0) is the user state components enabled by the OS,
+/// XINUSE (`index` = 1, `XSaveInfo.xgetbv_ecx1`) is the components not in the initial configuration.
+/// IA32_XSS is an MSR, cannot be read from the user mode.
+#[cfg(target_arch = "x86_64")]
+pub fn xgetbv(index: u32) -> Option<u64> {
+    #[target_feature(enable = "xsave")]
+    unsafe fn _xgetbv(index: u32) -> u64 {
+        core::arch::x86_64::_xgetbv(index)
+    }
+
+    /* OSXSAVE: CPUID.(EAX=01h):ECX[27] */
+    let osxsave = cpuid!(0x1, 0x0).ecx.bit(27);
+
+    if !osxsave { return None }
+
+    Some(unsafe { _xgetbv(index) })
+}
+
+/// The state component available from `CPUID.(EAX=0Dh, ECX=n)`, n > 1
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XStateComponent {
+    pub index: u32,
+    /// The size (in bytes) of the save area
+    pub size: u32,
+    /// The offset (in bytes) from the beginning of the standard format of the XSAVE area,
+    /// 0 for the supervisor state component
+    pub offset: u32,
+    /// The component is a supervisor state (IA32_XSS), or a user state (XCR0)
+    pub supervisor: bool,
+    /// The component is aligned to the next 64-byte boundary in the compacted format
+    pub align_64: bool,
+    /// XFD faulting is supported for the component
+    pub xfd: bool,
+}
+
+impl XStateComponent {
+    pub fn from_cpuid(index: u32, cpuid: &CpuidResult) -> Self {
+        Self {
+            index,
+            size: cpuid.eax,
+            offset: cpuid.ebx,
+            supervisor: (cpuid.ecx & 0b1) == 0b1,
+            align_64: cpuid.ecx.bit(1),
+            xfd: cpuid.ecx.bit(2),
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn get(index: u32) -> Self {
+        Self::from_cpuid(index, &cpuid!(0xD, index))
+    }
+}
+
+#[test]
+fn test_xsave_info() {
+    /* AMD Ryzen 5 5600G */
+    let x0 = CpuidResult { eax: 0x0000_0207, ebx: 0x0000_0988, ecx: 0x0000_0988, edx: 0x0 };
+    let x1 = CpuidResult { eax: 0x0000_000F, ebx: 0x0000_0348, ecx: 0x0000_1800, edx: 0x0 };
+    let x9 = CpuidResult { eax: 0x0000_0008, ebx: 0x0000_0980, ecx: 0x0, edx: 0x0 };
+    let xc = CpuidResult { eax: 0x0000_0018, ebx: 0x0, ecx: 0x0000_0001, edx: 0x0 };
+
+    let info = XSaveInfo::from_cpuid_array([x0, x1]);
+
+    assert_eq!(info.xcr0_supported, 0x207);
+    assert_eq!(info.xss_supported, 0x1800);
+    assert_eq!(info.size_xcr0_max, 2440);
+    assert!(info.xsaves);
+
+    let pkru = XStateComponent::from_cpuid(0x9, &x9);
+    assert_eq!((pkru.size, pkru.offset, pkru.supervisor), (8, 2432, false));
+
+    let cet_s = XStateComponent::from_cpuid(0xC, &xc);
+    assert_eq!((cet_s.size, cet_s.supervisor), (24, true));
+}
@@ -1,13 +1,21 @@
-use std::io;
-use crate::TOTAL_WIDTH;
+use std::io::{self, Write};
 use crate::load_aida64_log;
-use crate::{cpuid, CpuidDump, dump_all_threads, leaf_pool, CpuVendor, RawCpuid};
+use crate::load_raw;
+use crate::load_dir::MatrixFormat;
+use crate::scan;
+use crate::json;
+use crate::exit_code;
+use crate::config::Config;
+use crate::logger::ColorChoice;
+use crate::output::Output;
+use crate::{cpuid, CpuidDump, MAX_DEFAULT_JOBS, dump_all_threads, disp_all_threads, cpuid_dump_all_threads, leaf_pool, sub_leaf_pool, retain_diff, skipped_cpus_msg, CpuVendor, RawCpuid, RawCpuidFmt, Radix, ThreadLeafPool};
+use std::convert::TryFrom;
+use libcpuid_dump::{FamModStep, FormatConfig, MicroArchLevel};
 
 const LEAF_HEAD: &str = "       [Leaf.Sub]";
 const LEAF_LINE: &str = unsafe { std::str::from_utf8_unchecked(&[b'='; LEAF_HEAD.len()]) };
-const LINE: &str = unsafe { std::str::from_utf8_unchecked(&[b'='; TOTAL_WIDTH]) };
 
-fn hex_head() -> String {
+fn hex_head(cfg: &FormatConfig) -> String {
     const EAX: &str = "  [EAX]   ";
     const EBX: &str = "  [EBX]   ";
     const ECX: &str = "  [ECX]   ";
@@ -15,8 +23,8 @@ fn hex_head() -> String {
 
     format!("\
         {LEAF_HEAD}  {EAX} {EBX} {ECX} {EDX}\n\
-        {LINE}\
-    \n")
+        {}\
+    \n", cfg.line())
 }
 
 fn bin_head() -> String {
@@ -33,21 +41,40 @@ fn bin_head() -> String {
     \n")
 }
 
-pub fn dump_write(pool: &[u8]) -> io::Result<()> {
-    use std::io::{Write, stdout};
-    let mut out = stdout().lock();
+fn default_stem() -> String {
+    let proc_name = libcpuid_dump::ProcName::sanitized(&libcpuid_dump::ProcName::get_trim_name());
+    /* Family, Model, Stepping */
+    let fms = cpuid!(0x1, 0x0).eax;
 
-    out.write_all(pool)?;
-    Ok(())
+    format!("{proc_name}_{fms:08X}")
 }
 
 fn default_name() -> String {
-    let proc_name = libcpuid_dump::ProcName::get_trim_name().replace(' ', "_");
-    /* Family, Model, Stepping */
-    let fms = cpuid!(0x1, 0x0).eax;
-
     /* like "AMD_Ryzen_5_5600G_with_Radeon_Graphics_00A50F00.txt" */
-    format!("{proc_name}_{fms:08X}.txt")
+    format!("{}.txt", default_stem())
+}
+
+/* "[vendor:]F:M:S", the vendor is AMD for Family 10h or later, Zhaoxin for Family 7, Intel for the others */
+fn parse_codename_for(s: &str) -> Result<(CpuVendor, FamModStep), String> {
+    let (vendor, fms) = match s.split_once(':') {
+        Some((vendor, fms)) if s.matches(':').count() == 3 => (Some(vendor.to_ascii_lowercase()), fms),
+        _ => (None, s),
+    };
+    let fms = fms.parse::<FamModStep>().map_err(|e| e.to_string())?;
+
+    let vendor = match vendor.as_deref() {
+        Some("amd") => CpuVendor::AuthenticAMD,
+        Some("intel") => CpuVendor::GenuineIntel,
+        Some("hygon") => CpuVendor::HygonGenuine,
+        Some("zhaoxin") => CpuVendor::Shanghai,
+        Some("centaur") => CpuVendor::CentaurHauls,
+        Some(v) => return Err(format!("unknown vendor \"{v}\", expected amd, intel, hygon, zhaoxin or centaur")),
+        None if 0x10 <= fms.family() => CpuVendor::AuthenticAMD,
+        None if fms.family() == 0x7 => CpuVendor::Shanghai,
+        None => CpuVendor::GenuineIntel,
+    };
+
+    Ok((vendor, fms))
 }
 
 fn help_msg() {
@@ -56,15 +83,66 @@ fn help_msg() {
         "https://github.com/Umio-Yasuno/cpuid_dump_rs\n",
         "\n",
         "USAGE:\n",
-        "    cargo run -- [options ..] or <cpuid_dump> [options ..]\n",
+        "    cargo run -- [subcommand] [options ..] or <cpuid_dump> [subcommand] [options ..]\n",
+        "    Values can be given as \"--opt <value>\" or \"--opt=<value>\",\n",
+        "    \"--\" terminates the options.\n",
+        "\n",
+        "SUBCOMMANDS:\n",
+        "    dump\n",
+        "        Display result of the current CPU, the same as without the subcommand.\n",
+        "    parse <path/filename>\n",
+        "        Display the saved dump, the same as \"--load\". \"-\" reads the dump from stdin,\n",
+        "        e.g. ssh remote cpuid_dump -r | cpuid_dump parse -\n",
+        "    diff <name>\n",
+        "        Display the leaves that differ from the reference dump, the same as \"--diff-reference\".\n",
+        "        \"-\" compares with the dump (\"-r\") from stdin.\n",
+        "    parse-dir <dir>\n",
+        "        Load every dump file in the directory in parallel, and display a line per file\n",
+        "        (model name, Family/Model/Stepping, threads, x86-64 level), the same as \"--parse-dir\".\n",
+        "        With \"--out-dir <dir>\", also write \"<file>.txt\" for each file in the format.\n",
+        "        <dir> can also be a file listing the dump files, one per line.\n",
+        "        With \"--matrix <csv|md>\", display the feature x host matrix instead, with the features\n",
+        "        and the x86-64 level common to all hosts (the safe baseline of the cluster).\n",
+        "    check <u8>\n",
+        "        Check the x86-64 micro-architecture level, the same as \"--require-level\".\n",
+        "    topo\n",
+        "        Display the topology of each thread, one line per thread.\n",
+        "    summary\n",
+        "        Display a condensed one-page overview of the CPU, the same as \"-summary\".\n",
+        "    record <path/filename>\n",
+        "        Record a session file of all threads for \"replay\", the same as \"--record\".\n",
+        "        \"-\" writes the session to stdout.\n",
+        "    replay <path/filename>\n",
+        "        Display the recorded session in the format of the options (e.g. \"-bin\", \"-no-diff\")\n",
+        "        as \"-a\" on the recorded host, the same as \"--replay\". \"-\" reads the session from stdin.\n",
+        "    The options choosing the display (e.g. \"--save\", \"--leaf\", \"-summary\")\n",
+        "    cannot be combined with the subcommands other than \"dump\".\n",
         "\n",
         "FLAGS:\n",
         "    -a, -all\n",
-        "        Display result for all threads.\n",
+        "        Display result for all threads, ordered by package/core/SMT.\n",
+        "        The threads identical to the first thread are rolled up to one line,\n",
+        "        the other threads display only the decoded fields changed from the first thread.\n",
+        "        The leaves are enumerated on each thread, a thread reporting the different\n",
+        "        maximum leaves from the first thread is flagged.\n",
+        "        The CPUs offline or outside the allowed cpuset are skipped, the count is noted.\n",
+        "        The progress (threads completed / total) is displayed on stderr if it is a terminal.\n",
         "    -r, -raw\n",
-        "        Display raw/hex result.\n",
+        "        Display raw/hex result, one \"<leaf> <sub_leaf>: <eax> <ebx> <ecx> <edx>\" line per result.\n",
+        "        The topology line (with the microcode revision on Linux) and the ASCII of the string leaves\n",
+        "        (vendor, processor name) are comments starting with '#', can be loaded with \"--load\".\n",
         "    -bin\n",
-        "        Display binary result.\n",
+        "        Display binary result, with the ASCII of the string leaves.\n",
+        "    -fields\n",
+        "        With \"-bin\", mark the known fields (e.g. Family/Model/Stepping of leaf 0x1 EAX)\n",
+        "        under the bits, and list the fields of each register.\n",
+        "    -interleave\n",
+        "        Display the raw/hex line of each result followed by its decoded block as '#' comments,\n",
+        "        can be loaded with \"--load\".\n",
+        "    -kv\n",
+        "        Display the known fields of the registers and the feature flags set\n",
+        "        as \"<leaf>.<sub_leaf>.<register>.<field>=<value>\" lines, the flags are \"=1\" and the fields\n",
+        "        are in decimal, for diffing across the machines and the versions.\n",
         "    -c, -compat\n",
         "        Display the same format as `cpuid -r` (cpuid by Todd Allen)\n",
         "    -full\n",
@@ -73,173 +151,856 @@ fn help_msg() {
         "        Display result even if E[ABCD]X are zero.\n",
         "    -no-diff\n",
         "        Do not omit diff when all threads execution\n",
+        "    -no-header\n",
+        "        Display only the data rows, without the topology line, the column headers,\n",
+        "        the ruler lines, the leaf titles and the notes of \"-a\".\n",
+        "    -print0\n",
+        "        Terminate each leaf record (or each flag of \"-kernel-flags\") with NUL instead of newline,\n",
+        "        for `xargs -0` and `read -d ''`. Implies \"-no-header\".\n",
+        "    -summary\n",
+        "        Display a condensed one-page overview of the CPU.\n",
+        "    -kernel-flags\n",
+        "        Display the flags line in the same format as Linux `/proc/cpuinfo`.\n",
+        "    -security\n",
+        "        Display speculative execution mitigation features.\n",
+        "    -topology\n",
+        "        Display the tree of Package, Node (AMD), Core and CPU of all threads,\n",
+        "        with the core types and the caches attached to the level sharing them,\n",
+        "        and the cores per L2 of each core type on the hybrid processors.\n",
+        "    -ccx\n",
+        "        Display the logical CPUs of each CCD/CCX and the L3 size of the CCX (AMD Zen).\n",
+        "    -apic-id\n",
+        "        Display the x2APIC ID of each thread decomposed into the SMT/Core/Die/Package fields,\n",
+        "        with the shift values of Leaf 0xB/0x1F or 0x8000_0026 (AMD).\n",
+        "    -boost\n",
+        "        Display whether the hardware boost (Turbo Boost, CPB) and\n",
+        "        the hardware-controlled P-states (HWP, HwPstate) are available.\n",
+        "    -virt\n",
+        "        Display whether VMX or SVM is available, the SVM features for the nested paging (NPT, AVIC)\n",
+        "        and the nested virtualization.\n",
+        "    -confidential\n",
+        "        Display the confidential computing technologies of Intel and AMD in one place:\n",
+        "        SGX, TME, TDX guest, SME, SEV, SEV-ES and SEV-SNP.\n",
+        "    -errata\n",
+        "        Display the known errata and quirks of the Family/Model/Stepping,\n",
+        "        e.g. the features removed by the microcode update.\n",
+        "    -xsave\n",
+        "        Display the XSAVE area layout: offset, size, alignment of each state component,\n",
+        "        and the state components enabled in XCR0 by the OS.\n",
+        "    -tlb\n",
+        "        Display the TLBs (level, type, page sizes, entries, associativity) in the same table for all vendors.\n",
+        "    -json\n",
+        "        Display result in JSON, combined with \"-a\" for all threads.\n",
+        "    -emit-schema\n",
+        "        Display the JSON Schema of \"-json\" output.\n",
+        "    -V, --version\n",
+        "        Display the version. With \"-json\", the build metadata: version, git commit, target,\n",
+        "        cargo features and the revision of the feature bit registry.\n",
+        "    -v, -vv\n",
+        "        Display the debug (\"-v\") or trace (\"-vv\") messages on stderr:\n",
+        "        the CPUs skipped, the unknown leaves and the fallbacks of the parsers.\n",
+        "    -q, -quiet\n",
+        "        Do not display the diagnostic messages (warnings, \"Output to ..\") and the progress on stderr.\n",
+        "    -bit-range\n",
+        "        Display the source bits of the decoded numeric fields, e.g. \"EBX[31:22]+1 = 8\".\n",
+        "    -scan\n",
+        "        Probe the Standard/Hypervisor/Extended/Centaur ranges beyond the reported maxima,\n",
+        "        and display the leaves returning non-default data, each range as soon as it is probed.\n",
         "\n",
         "OPTIONS:\n",
         "    --l <u32>, --leaf <u32>\n",
         "        Display result only for the specified value, the value is Leaf/InputEAX <u32>.\n",
+        "        All valid sub-leaves of the leaf are displayed without \"--sub_leaf\".\n",
         "        e.g. --leaf 1, --leaf 0x8000_0008,\n",
         "    --sub_leaf <u32>, --subleaf <u32>\n",
         "        Display result only for the specified value, the value is Sub_Leaf/InputECX <u32>.\n",
+        "        A range or \"all\" (the valid sub-leaves of the leaf) are also accepted,\n",
+        "        e.g. --leaf 0xD --subleaf 0..=18, --leaf 0x4 --subleaf all,\n",
+        "    --exec <u32> <u32>, --exec=<u32>,<u32>\n",
+        "        Execute CPUID with the Leaf/InputEAX and Sub_Leaf/InputECX as is,\n",
+        "        even if out of the reported range. Can be repeated, e.g. --exec 0x7 0x1 --exec 0x4000_0000 0,\n",
+        "    --radix <dec|hex|bin>\n",
+        "        Display the decoded numeric fields (cache ways, sizes, counts) in the radix.\n",
+        "        Default is \"dec\".\n",
+        "    --width <columns>\n",
+        "        Wrap the decoded fields at the width of the line. Default is 100.\n",
+        "    --jobs <N>\n",
+        "        Run at most N threads at once for the all-thread dumps (\"-a\", \"-topology\", ..).\n",
+        "        Default is the number of the available CPUs, up to 16.\n",
         "    --s <path/filename>, --save <path/filename>\n",
         "        Save dump result to text file.\n",
         "        If there is no path/filename argument, will be used \"./<processor_name>\".\n",
+        "        With \"-a\" and a directory, save one file per thread and an index file.\n",
+        "        \"<format>=<path>\" saves in the format, can be repeated to save several formats\n",
+        "        of the same sampling, <format> is raw, bin, parse, compat, debug, interleave or json.\n",
+        "        e.g. --save raw=dump.txt --save json=dump.json,\n",
+        "    --save-split <dir>\n",
+        "        Save one file per leaf/sub-leaf (e.g. \"0x00000007_x0.txt\") in the format,\n",
+        "        and \"manifest.txt\" listing the files, to track each leaf across BIOS updates.\n",
+        "    --save-append <path/filename>\n",
+        "        Append the dump result to the file after a \"# Run: <timestamp>\" line instead of truncating it,\n",
+        "        \"--load\" displays each run of the file.\n",
+        "    --o <target>, --output <target>\n",
+        "        Write the output to the target instead of stdout:\n",
+        "        <path/filename>, file://<path>, tcp://<host>:<port>, unix://<path> (Unix domain socket).\n",
+        "        e.g. --output tcp://collector:9000,\n",
+        "    --load <path/filename>\n",
+        "        Display the dump saved with \"-r\" instead of the current CPU.\n",
+        "        The CPUID table of the CPU-Z text report and the HWiNFO report/debug dump\n",
+        "        are also accepted.\n",
+        "        The report options (e.g. \"-summary\", \"-topology\", \"-json\") are applied to the loaded dump.\n",
+        "    --record <path/filename>\n",
+        "        Write the session file: the metadata (version, time, OS, CPUs, the topology of the OS)\n",
+        "        and the raw dump of all threads.\n",
+        "        The session file can also be loaded with \"--load\".\n",
+        "        The session is always in the raw format with the headers, \"-no-header\" is ignored,\n",
+        "        the other output formats (e.g. \"-json\", \"-bin\", \"-print0\") are rejected.\n",
+        "    --replay <path/filename>\n",
+        "        Display the session file of \"--record\" instead of the current CPU.\n",
+        "        The report options are applied to the recorded threads, as with \"--load\".\n",
         "    --aida64 <path/filename>\n",
+        "    --config <path/filename>\n",
+        "        Read the default options from the config file,\n",
+        "        instead of \"~/.config/cpuid_dump/config.toml\".\n",
+        "    --no-config\n",
+        "        Do not read the config file.\n",
+        "    --format <raw|bin|parse|compat|debug|interleave|kv>\n",
+        "        Display result in the format, the same as \"-r\", \"-bin\", .., \"-kv\". Default is \"parse\".\n",
+        "    --color <auto|always|never>\n",
+        "        Color the \"error\"/\"warning\" prefixes on stderr.\n",
+        "        Default is \"auto\": on a terminal and without the NO_COLOR environment variable.\n",
+        "    --no-all, --no-full, --no-json, --no-quiet, --skip-zero, --diff, --header\n",
+        "        Negate \"all\", \"full\", \"json\", \"quiet\", \"disp_zero\", \"no_diff\", \"no_header\" of the config file.\n",
+        "    --describe <feature>\n",
+        "        Display the description, Leaf/Bit and vendor applicability of the feature.\n",
+        "        The name in this tool, the flag name of Linux and the aliases are accepted,\n",
+        "        ignoring the case and '_', '-', '.', the close names are suggested for a typo.\n",
+        "        e.g. --describe sha_ni, --describe AVX512F,\n",
+        "    --codename-for <[vendor:]F:M:S>\n",
+        "        Display the codename, microarchitecture and process node of the Family/Model/Stepping (hex).\n",
+        "        The vendor (amd, intel, hygon, zhaoxin, centaur) is AMD for Family 10h or later by default.\n",
+        "        e.g. --codename-for 19:61:2, --codename-for intel:6:97:2,\n",
+        "    --require-level <u8>\n",
+        "        Exit with non-zero status and list the missing features\n",
+        "        if the CPU does not meet the x86-64 micro-architecture level.\n",
+        "        The features of each level are listed with \"+\" (supported) or \"-\" (missing).\n",
+        "        With \"--load\", check the loaded dump.\n",
+        "        e.g. --require-level 3, --require-level x86-64-v3\n",
+        "    --check-file <path/filename>\n",
+        "        Check the required/forbidden features and the minimum x86-64 level of the policy file (TOML),\n",
+        "        exit with non-zero status if any rule fails. With \"--load\", check the loaded dump.\n",
+        "        e.g. required = [\"AVX2\", \"sha_ni\"], forbidden = [\"RTM\"], min_level = 3\n",
+        "    --strict\n",
+        "        Exit with non-zero status and list the registers\n",
+        "        if the bits documented as reserved are set, e.g. by the hypervisor.\n",
+        "    --reference <name>\n",
+        "        Display the built-in reference dump instead of the current CPU.\n",
+        "        Can be combined with \"-summary\", \"-kernel-flags\", \"-security\", \"-boost\", \"-virt\", \"-confidential\", \"-errata\", \"-xsave\", \"-tlb\".\n",
+        "        Use \"--reference list\" to list the available dumps.\n",
+        "    --diff-reference <name>\n",
+        "        Display the leaves that differ between the current CPU and the reference dump.\n",
+        "    --compare-model <model>\n",
+        "        Display the missing/extra features and leaves, and the differing values\n",
+        "        between the current CPU and the reference dump, e.g. --compare-model Ryzen_5_5600G\n",
+        "\n",
+        "EXIT STATUS:\n",
+        "    0  Success\n",
+        "    2  Invalid option/argument or config file\n",
+        "    3  Unsupported platform or build\n",
+        "    4  Check failed (e.g. \"--require-level\", \"--check-file\", \"--strict\")\n",
+        "    5  I/O error\n",
     );
 
     println!("{MSG}")
 }
 
+/* Options that take a value, for "--opt=value" */
+const TAKES_VALUE: &[&str] = &[
+    "s", "save", "save-split", "save-append", "aida64", "load", "record", "replay", "parse-dir", "out-dir", "matrix", "require-level", "reference", "diff-reference",
+    "compare-model", "describe", "codename-for", "l", "leaf", "subleaf", "sub_leaf", "sub-leaf", "config", "radix", "width", "jobs", "exec", "o", "output",
+    "format", "color",
+];
+
+/* the output formats other than the raw format of the session file of "--record" */
+const RECORD_FMT_OPTS: &[&str] = &[
+    "bin", "fields", "c", "compat", "debug", "interleave", "kv", "format", "json", "print0",
+];
+
+/* the options choosing what to display, only one of them per subcommand */
+const MODE_OPTS: &[&str] = &[
+    "s", "save", "save-split", "save-append", "aida64", "load", "record", "replay", "parse-dir", "require-level", "reference", "diff-reference",
+    "compare-model", "describe", "codename-for", "l", "leaf", "exec", "summary", "kernel-flags", "security", "topology", "ccx", "apic-id", "boost", "virt", "confidential", "errata", "xsave", "tlb", "scan", "emit-schema", "V", "version",
+];
+
+/* the options of MODE_OPTS applied to the dump of "--load"/"--replay" */
+const SOURCE_MODE_OPTS: &[&str] = &[
+    "require-level", "diff-reference", "compare-model", "json", "summary", "kernel-flags", "security", "topology", "ccx", "apic-id", "boost", "virt", "confidential", "errata", "xsave", "tlb",
+];
+
+/// "<cpuid_dump> <subcommand> [options ..]", the bare options are the same as "dump"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Subcommand {
+    #[default]
+    Dump,
+    /// "--load <path>"
+    Parse,
+    /// "--parse-dir <dir>"
+    ParseDir,
+    /// "--diff-reference <name>"
+    Diff,
+    /// "--require-level <level>"
+    Check,
+    /// The topology of each thread
+    Topo,
+    /// "-summary"
+    Summary,
+    /// "--record <path>"
+    Record,
+    /// "--replay <path>"
+    Replay,
+}
+
+impl Subcommand {
+    fn from_name(name: &str) -> Option<Self> {
+        let sub = match name {
+            "dump" => Self::Dump,
+            "parse" => Self::Parse,
+            "parse-dir" => Self::ParseDir,
+            "diff" => Self::Diff,
+            "check" => Self::Check,
+            "topo" => Self::Topo,
+            "summary" => Self::Summary,
+            "record" => Self::Record,
+            "replay" => Self::Replay,
+            _ => return None,
+        };
+
+        Some(sub)
+    }
+
+    /// The option of the subcommand and the name of the positional argument
+    fn opt(&self) -> Option<(&'static str, Option<&'static str>)> {
+        match self {
+            Self::Dump | Self::Topo => None,
+            Self::Parse => Some(("--load", Some("path"))),
+            Self::ParseDir => Some(("--parse-dir", Some("dir"))),
+            Self::Diff => Some(("--diff-reference", Some("name"))),
+            Self::Check => Some(("--require-level", Some("level"))),
+            Self::Summary => Some(("--summary", None)),
+            Self::Record => Some(("--record", Some("path"))),
+            Self::Replay => Some(("--replay", Some("path"))),
+        }
+    }
+
+    /// Split the subcommand from the command line, and replace it with the option of the subcommand
+    fn split(cli_args: &[String]) -> Result<(Self, Vec<String>), String> {
+        let Some(sub) = cli_args.first().and_then(|name| Self::from_name(name)) else {
+            return Ok((Self::Dump, cli_args.to_vec()));
+        };
+        let name = &cli_args[0];
+        let mut rest = &cli_args[1..];
+        let mut args = Vec::with_capacity(cli_args.len() + 1);
+
+        if let Some((opt, arg)) = sub.opt() {
+            args.push(opt.to_string());
+
+            if let Some(arg) = arg {
+                match rest.split_first() {
+                    Some((v, r)) if !v.starts_with('-') || v == "-" => {
+                        args.push(v.clone());
+                        rest = r;
+                    },
+                    _ => return Err(format!("missing argument <{arg}> to \"{name}\"")),
+                }
+            }
+        }
+
+        if let Some(opt) = rest.iter().take_while(|arg| *arg != "--").find(|arg| {
+            let arg = arg.trim_start_matches('-');
+            let arg = arg.split_once('=').map_or(arg, |(arg, _)| arg);
+
+            let source = matches!(sub, Self::Parse | Self::Replay) && SOURCE_MODE_OPTS.contains(&arg);
+
+            sub != Self::Dump && MODE_OPTS.contains(&arg) && !source
+        }) {
+            return Err(format!("\"{opt}\" cannot be used with the \"{name}\" subcommand"));
+        }
+
+        args.extend_from_slice(rest);
+
+        Ok((sub, args))
+    }
+}
+
+/// Sub-leaves of "--leaf", selected by "--sub_leaf"
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SubLeafSel {
+    One(u32),
+    /// Inclusive
+    Range(u32, u32),
+    /// Enumerated by `sub_leaf_pool`
+    All,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum DumpFormat {
     Raw,
     Binary,
+    /// `Binary` with the markers and the legend of the known fields, "-bin -fields"
+    BinaryFields,
     Parse,
     CompatCpuid,
     Debug,
+    /// The line of `Raw` followed by the block of `Parse` commented out with '#', can be loaded with "--load"
+    Interleaved,
+    /// `<leaf>.<sub_leaf>.<register>.<field>=<value>` lines of the decoded fields
+    KeyValue,
+}
+
+/* "--format <name>", `format` of the config file */
+impl std::str::FromStr for DumpFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(Self::Raw),
+            "bin" => Ok(Self::Binary),
+            "parse" => Ok(Self::Parse),
+            "compat" => Ok(Self::CompatCpuid),
+            "debug" => Ok(Self::Debug),
+            "interleave" => Ok(Self::Interleaved),
+            "kv" => Ok(Self::KeyValue),
+            _ => Err(format!(
+                "invalid format \"{s}\", expected \"raw\", \"bin\", \"parse\", \"compat\", \"debug\", \"interleave\" or \"kv\""
+            )),
+        }
+    }
 }
 
 impl DumpFormat {
-    pub fn head_fmt(&self) -> String {
+    /// The notes and the topology line are comments starting with '#'
+    pub fn comment_head(&self) -> bool {
+        matches!(self, Self::Raw | Self::Interleaved | Self::KeyValue)
+    }
+
+    pub fn head_fmt(&self, cfg: &FormatConfig) -> String {
         match self {
-            Self::Binary => bin_head(),
+            Self::Binary |
+            Self::BinaryFields => bin_head(),
+            Self::Raw |
+            Self::Interleaved |
+            Self::KeyValue |
             Self::Debug |
             Self::CompatCpuid => "".to_string(),
-            _ => hex_head(),
+            _ => hex_head(cfg),
         }
     }
 
-    pub fn rawcpuid_fmt_func(&self) -> fn(&RawCpuid, &CpuVendor) -> String {
+    pub fn rawcpuid_fmt_func(&self) -> fn(&RawCpuid, &CpuVendor, &FormatConfig, &mut String) {
         match self {
-            Self::Raw => RawCpuid::raw_fmt,
-            Self::Binary => RawCpuid::bin_fmt,
-            Self::Parse => RawCpuid::parse_fmt,
-            Self::CompatCpuid => RawCpuid::compat_fmt,
-            Self::Debug => RawCpuid::debug_fmt,
+            Self::Raw => RawCpuid::write_raw_fmt,
+            Self::Binary => RawCpuid::write_bin_fmt,
+            Self::BinaryFields => RawCpuid::write_bin_fields_fmt,
+            Self::Parse => RawCpuid::write_parse_fmt,
+            Self::CompatCpuid => RawCpuid::write_compat_fmt,
+            Self::Debug => RawCpuid::write_debug_fmt,
+            Self::Interleaved => RawCpuid::write_interleaved_fmt,
+            Self::KeyValue => RawCpuid::write_kv_fmt,
         }
     }
 }
 
+/// Format of the tagged "--save <format>=<path>"
+#[derive(Debug, Copy, Clone)]
+pub enum SaveFormat {
+    Dump(DumpFormat),
+    Json,
+}
+
+impl SaveFormat {
+    /// "raw=dump.txt" to (Raw, "dump.txt"), `None` if not prefixed with a format name
+    fn split_tagged(v: &str) -> Option<(Self, &str)> {
+        let (tag, path) = v.split_once('=')?;
+        let fmt = match tag {
+            "raw" => Self::Dump(DumpFormat::Raw),
+            "bin" => Self::Dump(DumpFormat::Binary),
+            "parse" => Self::Dump(DumpFormat::Parse),
+            "compat" => Self::Dump(DumpFormat::CompatCpuid),
+            "debug" => Self::Dump(DumpFormat::Debug),
+            "interleave" => Self::Dump(DumpFormat::Interleaved),
+            "json" => Self::Json,
+            _ => return None,
+        };
+
+        Some((fmt, path))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MainOpt {
+    pub subcommand: Subcommand,
     pub fmt: DumpFormat,
     pub dump_all: bool,
     pub save_path: Option<String>,
-    pub leaf: Option<(u32, u32)>,
+    pub saves: Vec<(SaveFormat, String)>,
+    pub save_split: Option<String>,
+    pub save_append: Option<String>,
+    pub output: Output,
+    pub leaf: Option<(u32, SubLeafSel)>,
+    pub exec: Vec<(u32, u32)>,
     pub skip_zero: bool,
     pub diff: bool,
+    pub no_header: bool,
+    pub print0: bool,
     pub load_aida64: Option<String>,
+    pub load: Option<String>,
+    pub record: Option<String>,
+    pub replay: Option<String>,
+    pub parse_dir: Option<String>,
+    pub out_dir: Option<String>,
+    pub matrix: Option<MatrixFormat>,
+    pub require_level: Option<MicroArchLevel>,
+    pub strict: bool,
+    pub check_file: Option<String>,
+    pub security: bool,
+    pub topology: bool,
+    pub ccx: bool,
+    pub apic_id: bool,
+    pub boost: bool,
+    pub virt: bool,
+    pub confidential: bool,
+    pub errata: bool,
+    pub summary: bool,
+    pub kernel_flags: bool,
+    pub reference: Option<String>,
+    pub diff_reference: Option<String>,
+    pub compare_model: Option<String>,
+    pub scan: bool,
+    pub xsave: bool,
+    pub tlb: bool,
+    pub json: bool,
+    pub emit_schema: bool,
+    pub version: bool,
+    pub describe: Option<String>,
+    pub codename_for: Option<(CpuVendor, FamModStep)>,
+    pub save_dir: Option<String>,
+    pub leaves: Option<Vec<u32>>,
+    pub quiet: bool,
+    /// "-v": 1, "-vv": 2
+    pub verbose: u8,
+    pub radix: Radix,
+    pub bit_range: bool,
+    pub bin_fields: bool,
+    pub format_config: FormatConfig,
+    /// "--jobs", 0 for the default
+    pub jobs: usize,
+    pub color: ColorChoice,
 }
 
 impl Default for MainOpt {
     fn default() -> Self {
         Self {
+            subcommand: Subcommand::Dump,
             fmt: DumpFormat::Parse,
             dump_all: false,
             save_path: None,
+            saves: Vec::new(),
+            save_split: None,
+            save_append: None,
+            output: Output::Stdout,
             leaf: None,
+            exec: Vec::new(),
             skip_zero: true,
             diff: true,
+            no_header: false,
+            print0: false,
             load_aida64: None,
+            load: None,
+            record: None,
+            replay: None,
+            parse_dir: None,
+            out_dir: None,
+            matrix: None,
+            require_level: None,
+            strict: false,
+            check_file: None,
+            security: false,
+            topology: false,
+            ccx: false,
+            apic_id: false,
+            boost: false,
+            virt: false,
+            confidential: false,
+            errata: false,
+            summary: false,
+            kernel_flags: false,
+            reference: None,
+            diff_reference: None,
+            compare_model: None,
+            scan: false,
+            xsave: false,
+            tlb: false,
+            json: false,
+            emit_schema: false,
+            version: false,
+            describe: None,
+            codename_for: None,
+            save_dir: None,
+            leaves: None,
+            quiet: false,
+            verbose: 0,
+            radix: Radix::Dec,
+            bit_range: false,
+            bin_fields: false,
+            format_config: FormatConfig::default(),
+            jobs: 0,
+            color: ColorChoice::Auto,
         }
     }
 }
 
 impl MainOpt {
-    fn parse_value(raw_value: &str) -> u32 {
+    fn parse_value(name: &str, raw_value: &str) -> Result<u32, String> {
         /* for like "0x8000_0000" */
-        let raw_value = raw_value.replace('_', "");
+        let value = raw_value.replace('_', "");
 
-        if let Some(stripped) = raw_value.strip_prefix("0x") {
-            u32::from_str_radix(stripped, 16).unwrap()
+        let v = if let Some(stripped) = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+            u32::from_str_radix(stripped, 16)
         } else {
-            raw_value.parse::<u32>().unwrap()
-        }
+            value.parse::<u32>()
+        };
+
+        v.map_err(|e| format!("invalid value \"{raw_value}\" for \"--{name}\": {e}"))
     }
 
     pub fn main_parse() -> Self {
-        let mut opt = MainOpt::default();
-        let mut skip = false;
+        let args: Vec<String> = std::env::args().skip(1).collect();
+
+        match Self::load_config(&args).and_then(|config| Self::parse_args(config, &args)) {
+            Ok(opt) => opt,
+            Err(msg) => {
+                log::error!("{msg}");
+                log::info!("For more information, try \"--help\".");
+                std::process::exit(exit_code::CLI_ERROR);
+            },
+        }
+    }
+
+    /* "--no-config", "--config <path>" are checked before parsing the other options */
+    pub(crate) fn load_config(args: &[String]) -> Result<Config, String> {
+        /* "parse <path>" to "--load <path>", so that <path> is skipped as the value */
+        let (_, args) = Subcommand::split(args)?;
+        let mut path: Option<std::path::PathBuf> = None;
+        let mut idx = 0;
+
+        while let Some(raw_arg) = args.get(idx) {
+            idx += 1;
+
+            /* "--" and the unexpected argument, the latter is reported by `parse_args` */
+            if raw_arg == "--" || !raw_arg.starts_with('-') { break }
 
-        let args: Vec<String> = std::env::args().collect();
+            let (arg, inline_value) = match raw_arg.trim_start_matches('-').split_once('=') {
+                Some((arg, v)) => (arg, Some(v)),
+                None => (raw_arg.trim_start_matches('-'), None),
+            };
 
-        for (idx, arg) in args.iter().enumerate() {
-            if skip {
-                skip = false;
-                continue;
+            match (arg, inline_value) {
+                ("config", Some(v)) => path = Some(v.into()),
+                ("config", None) => {
+                    path = args.get(idx).filter(|v| *v != "--").map(|v| v.into());
+
+                    if path.is_none() {
+                        return Err("missing argument <path> to \"--config\"".to_string());
+                    }
+
+                    idx += 1;
+                },
+                ("no-config", _) => return Ok(Config::default()),
+                /* skip the values of the other options, as `parse_args` */
+                (_, Some(_)) => {},
+                /* the value of "--save" is optional */
+                ("s" | "save", None) if args.get(idx).is_some_and(|v| !v.starts_with('-')) => idx += 1,
+                ("s" | "save", None) => {},
+                ("exec", None) => idx += 2,
+                _ if TAKES_VALUE.contains(&arg) => idx += 1,
+                _ => {},
             }
+        }
+
+        match path {
+            /* the specified config file must exist */
+            Some(path) => Config::load(&path)?
+                .ok_or_else(|| format!("{}: No such file", path.display())),
+            None => match Config::path() {
+                Some(path) => Ok(Config::load(&path)?.unwrap_or_default()),
+                None => Ok(Config::default()),
+            },
+        }
+    }
+
+    /// The options of the config file as the defaults
+    fn with_config(mut self, config: Config) -> Self {
+        if let Some(fmt) = config.fmt {
+            self.set_fmt(fmt);
+        }
+
+        if config.full == Some(true) {
+            self.skip_zero = false;
+            self.diff = false;
+        }
+
+        if let Some(b) = config.disp_zero { self.skip_zero &= !b }
+        if let Some(b) = config.no_diff { self.diff &= !b }
+        if let Some(b) = config.all { self.dump_all = b }
+        if let Some(b) = config.json { self.json = b }
+        if let Some(b) = config.no_header { self.no_header = b }
+        if let Some(b) = config.quiet { self.quiet = b }
+        if let Some(color) = config.color { self.color = color }
+
+        self.save_dir = config.save_dir;
+        self.leaves = config.leaves;
 
-            if !arg.starts_with('-') {
-                // eprintln!("Unknown option: {}", args[i]);
-                continue;
+        self
+    }
+
+    /* "-c" also dumps all threads and the zero results as `cpuid -r` */
+    fn set_fmt(&mut self, fmt: DumpFormat) {
+        if let DumpFormat::CompatCpuid = fmt {
+            self.dump_all = true;
+            self.skip_zero = false;
+            self.diff = false;
+        }
+
+        self.fmt = fmt;
+    }
+
+    /// Options are accepted with any number of leading '-', and values as "--opt value" or "--opt=value".
+    /// "--" terminates the options, the rest is used as the save path for "--save".
+    /// The options of the config file are the defaults, the command line overrides them,
+    /// "--no-all", "--skip-zero", .. negate the bool options.
+    /// The subcommand is replaced with its option, e.g. "parse <path>" with "--load <path>".
+    pub fn parse_args(config: Config, cli_args: &[String]) -> Result<Self, String> {
+        let (subcommand, cli_args) = Subcommand::split(cli_args)?;
+        let mut opt = MainOpt { subcommand, ..Default::default() }.with_config(config);
+        let args = cli_args;
+        let mut sub_leaf: Option<SubLeafSel> = None;
+        let mut save = false;
+        /* the output format option on the command line, for "--record" */
+        let mut fmt_flag: Option<String> = None;
+        /* "-json" on the command line, not the default of the config file */
+        let mut json_flag = false;
+        let mut idx = 0;
+
+        while let Some(raw_arg) = args.get(idx) {
+            idx += 1;
+
+            if raw_arg == "--" {
+                break;
             }
 
-            let arg = arg.trim_start_matches('-');
+            if !raw_arg.starts_with('-') || raw_arg == "-" {
+                return Err(format!("unexpected argument \"{raw_arg}\""));
+            }
+
+            let (arg, inline_value) = match raw_arg.trim_start_matches('-').split_once('=') {
+                Some((arg, v)) => (arg, Some(v.to_string())),
+                None => (raw_arg.trim_start_matches('-'), None),
+            };
+
+            /* required value: "--opt=value" or the next argument */
+            let mut value = |v_name: &str| -> Result<String, String> {
+                if let Some(v) = &inline_value {
+                    return Ok(v.clone());
+                }
+
+                match args.get(idx) {
+                    Some(v) if v != "--" => {
+                        idx += 1;
+                        Ok(v.clone())
+                    },
+                    _ => Err(format!("missing argument <{v_name}> to \"--{arg}\"")),
+                }
+            };
 
             match arg {
                 "a" | "all" => {
                     opt.dump_all = true;
                 },
+                "no-all" => {
+                    opt.dump_all = false;
+                },
                 "r" | "raw" => {
                     opt.fmt = DumpFormat::Raw;
                     // opt.skip_zero = false;
                 },
                 "s" | "save" => {
-                    use std::path::Path;
-                    let mut path = default_name();
-
-                    if let Some(v) = args.get(idx+1) {
-                        if v.starts_with('-') { 
-                            opt.save_path = Some(path);
-                            continue;
-                        }
-
-                        path = if Path::new(v).is_dir() {
-                            format!("{v}{path}")
-                        } else {
-                            v.to_string()
-                        };
-                    }
+                    /* the value is optional */
+                    let v = match inline_value.clone() {
+                        Some(v) => Some(v),
+                        None => match args.get(idx) {
+                            Some(v) if !v.starts_with('-') => {
+                                idx += 1;
+                                Some(v.clone())
+                            },
+                            _ => None,
+                        },
+                    };
 
-                    opt.save_path = Some(path);
+                    /* "--save <format>=<path>", repeatable */
+                    match v.as_deref().and_then(SaveFormat::split_tagged) {
+                        Some((_, "")) => return Err(format!("missing path in \"{}\"", v.unwrap_or_default())),
+                        Some((fmt, path)) => opt.saves.push((fmt, path.to_string())),
+                        None => {
+                            save = true;
+                            opt.save_path = v;
+                        },
+                    }
+                },
+                "save-split" => {
+                    opt.save_split = Some(value("dir")?);
+                },
+                "save-append" => {
+                    opt.save_append = Some(value("path")?);
+                },
+                "o" | "output" => {
+                    opt.output = value("target")?.parse()?;
                 },
                 "aida64" => {
-                    opt.load_aida64 = args.get(idx+1).cloned();
+                    opt.load_aida64 = Some(value("path")?);
+                },
+                "load" => {
+                    opt.load = Some(value("path")?);
+                },
+                "record" => {
+                    opt.record = Some(value("path")?);
+                },
+                "replay" => {
+                    opt.replay = Some(value("path")?);
+                },
+                "parse-dir" => {
+                    opt.parse_dir = Some(value("dir")?);
+                },
+                "out-dir" => {
+                    opt.out_dir = Some(value("dir")?);
+                },
+                "matrix" => {
+                    opt.matrix = Some(value("csv|md")?.parse()?);
+                },
+                "require-level" => {
+                    let v = value("u8")?;
+                    let level = {
+                        let v = v.trim_start_matches("x86-64-").trim_start_matches('v');
 
-                    if opt.load_aida64.is_none() {
-                        std::process::exit(1);
-                    }
+                        v.parse::<u8>().ok().and_then(|level| MicroArchLevel::try_from(level).ok())
+                    };
+
+                    let Some(level) = level else {
+                        return Err(format!("invalid value \"{v}\" for \"--require-level\", expected 1-4 or x86-64-v1..v4"));
+                    };
+
+                    opt.require_level = Some(level);
+                },
+                "strict" => {
+                    opt.strict = true;
+                },
+                "check-file" => {
+                    opt.check_file = Some(value("path/filename")?);
+                },
+                "reference" => {
+                    opt.reference = Some(value("name")?);
+                },
+                "diff-reference" => {
+                    opt.diff_reference = Some(value("name")?);
+                },
+                "compare-model" => {
+                    opt.compare_model = Some(value("model")?);
+                },
+                "describe" => {
+                    opt.describe = Some(value("feature")?);
+                },
+                "codename-for" => {
+                    let v = value("F:M:S")?;
+
+                    opt.codename_for = Some(parse_codename_for(&v).map_err(|e| {
+                        format!("invalid value \"{v}\" for \"--codename-for\": {e}")
+                    })?);
+                },
+                "config" => {
+                    /* loaded in `load_config` */
+                    value("path")?;
                 },
-                "leaf" => {
+                "no-config" => {},
+                "l" | "leaf" => {
                     opt.skip_zero = false;
                     opt.diff = false;
 
-                    if let Some(v) = args.get(idx+1) {
-                        let leaf = Self::parse_value(v);
-                        opt.leaf = Some((leaf, 0x0));
-                    } else {
-                        eprintln!("missing argument <u32> to \"--leaf\"");
+                    let leaf = Self::parse_value(arg, &value("u32")?)?;
+                    /* the valid sub-leaves of the leaf, unless "--sub_leaf" */
+                    opt.leaf = Some((leaf, SubLeafSel::All));
+                },
+                "exec" => {
+                    opt.skip_zero = false;
+                    opt.diff = false;
+
+                    /* "--exec=<leaf>,<sub_leaf>" or "--exec <leaf> <sub_leaf>" */
+                    let (leaf, sub_leaf) = match &inline_value {
+                        Some(v) => v.split_once(',')
+                            .map(|(leaf, sub_leaf)| (leaf.to_string(), sub_leaf.to_string()))
+                            .ok_or_else(|| format!("invalid value \"{v}\" for \"--exec\", expected <leaf>,<sub_leaf>"))?,
+                        None => (value("leaf")?, value("sub_leaf")?),
                     };
+
+                    opt.exec.push((Self::parse_value(arg, &leaf)?, Self::parse_value(arg, &sub_leaf)?));
                 },
                 "subleaf" | "sub_leaf" | "sub-leaf" => {
-                    if let (Some((leaf, _)), Some(sub_leaf)) = (opt.leaf, args.get(idx+1)) {
-                        let sub_leaf = Self::parse_value(sub_leaf);
-                        opt.leaf = Some((leaf, sub_leaf));
-                    } else {
-                        eprintln!("missing argument \"--sub_leaf <u32>\"");
+                    sub_leaf = Some(Self::parse_sub_leaf(arg, &value("u32|range|all")?)?);
+                },
+                "radix" => {
+                    opt.radix = value("dec|hex|bin")?.parse()?;
+                },
+                "width" => {
+                    let v = value("columns")?;
+                    let width = v.parse::<usize>().map_err(|e| format!("invalid value \"{v}\" for \"--width\": {e}"))?;
+
+                    opt.format_config = FormatConfig::new(width)
+                        .ok_or_else(|| format!("width must be {} or more", FormatConfig::MIN_TOTAL_WIDTH))?;
+                },
+                "jobs" => {
+                    let v = value("N")?;
+
+                    opt.jobs = match v.parse::<usize>() {
+                        Ok(0) => return Err(format!("invalid value \"{v}\" for \"--jobs\": must be at least 1")),
+                        Ok(jobs) => jobs,
+                        Err(e) => return Err(format!("invalid value \"{v}\" for \"--jobs\": {e}")),
                     };
-                }
+                },
+                "fields" => {
+                    opt.bin_fields = true;
+                },
+                "bit-range" => {
+                    opt.bit_range = true;
+                },
                 "bin" => {
                     opt.fmt = DumpFormat::Binary;
                 },
                 "c" | "compat" => {
-                    opt.dump_all = true;
-                    opt.fmt = DumpFormat::CompatCpuid;
-                    opt.skip_zero = false;
-                    opt.diff = false;
+                    opt.set_fmt(DumpFormat::CompatCpuid);
+                },
+                "format" => {
+                    opt.set_fmt(value("name")?.parse()?);
+                },
+                "color" => {
+                    opt.color = value("auto|always|never")?.parse()?;
                 },
                 "debug" => {
                     opt.fmt = DumpFormat::Debug
                 },
+                "interleave" => {
+                    opt.fmt = DumpFormat::Interleaved;
+                },
+                "kv" => {
+                    opt.fmt = DumpFormat::KeyValue;
+                },
                 "h" | "help" => {
                     help_msg();
                     std::process::exit(0);
@@ -247,85 +1008,900 @@ impl MainOpt {
                 "disp-zero" => {
                     opt.skip_zero = false;
                 },
+                "skip-zero" => {
+                    opt.skip_zero = true;
+                },
                 "no-diff" => {
                     opt.diff = false;
                 },
+                "diff" => {
+                    opt.diff = true;
+                },
+                "no-header" => {
+                    opt.no_header = true;
+                },
+                "header" => {
+                    opt.no_header = false;
+                },
+                "print0" => {
+                    /* the header lines are not records */
+                    opt.print0 = true;
+                    opt.no_header = true;
+                },
+                "summary" => {
+                    opt.summary = true;
+                },
+                "kernel-flags" => {
+                    opt.kernel_flags = true;
+                },
+                "security" => {
+                    opt.security = true;
+                },
+                "topology" => {
+                    opt.topology = true;
+                },
+                "ccx" => {
+                    opt.ccx = true;
+                },
+                "apic-id" => {
+                    opt.apic_id = true;
+                },
+                "boost" => {
+                    opt.boost = true;
+                },
+                "virt" => {
+                    opt.virt = true;
+                },
+                "confidential" => {
+                    opt.confidential = true;
+                },
+                "errata" => {
+                    opt.errata = true;
+                },
+                "scan" => {
+                    opt.scan = true;
+                },
+                "xsave" => {
+                    opt.xsave = true;
+                },
+                "tlb" => {
+                    opt.tlb = true;
+                },
+                "json" => {
+                    opt.json = true;
+                    json_flag = true;
+                },
+                "no-json" => {
+                    opt.json = false;
+                    json_flag = false;
+                },
+                "emit-schema" => {
+                    opt.emit_schema = true;
+                },
+                "V" | "version" => {
+                    opt.version = true;
+                },
                 "full" => {
                     opt.skip_zero = false;
                     opt.diff = false;
                 },
+                "no-full" => {
+                    opt.skip_zero = true;
+                    opt.diff = true;
+                },
+                "q" | "quiet" => {
+                    opt.quiet = true;
+                },
+                "no-quiet" => {
+                    opt.quiet = false;
+                },
+                "v" | "verbose" => {
+                    opt.verbose = opt.verbose.saturating_add(1);
+                },
+                "vv" => {
+                    opt.verbose = opt.verbose.saturating_add(2);
+                },
                 _ => {
-                    eprintln!("Unknown option: {}", arg);
-                    help_msg();
-                    std::process::exit(1);
+                    return Err(format!("unknown option \"{raw_arg}\""));
                 },
             }
+
+            if RECORD_FMT_OPTS.contains(&arg) && !(arg == "format" && matches!(opt.fmt, DumpFormat::Raw)) {
+                fmt_flag = Some(raw_arg.clone());
+            }
+
+            if inline_value.is_some() && !TAKES_VALUE.contains(&arg) {
+                return Err(format!("\"--{arg}\" does not take a value"));
+            }
+        }
+
+        /* the session file is the raw dump of all threads with the topology lines, split into the threads at them */
+        if opt.record.is_some() {
+            if let Some(flag) = fmt_flag {
+                return Err(format!("\"{flag}\" cannot be used with \"--record\", the session is recorded in the raw format"));
+            }
+
+            opt.fmt = DumpFormat::Raw;
+            opt.json = false;
+            opt.no_header = false;
+            opt.print0 = false;
+        }
+
+        if opt.bin_fields {
+            if !matches!(opt.fmt, DumpFormat::Binary) {
+                return Err("\"-fields\" requires \"-bin\"".to_string());
+            }
+
+            opt.fmt = DumpFormat::BinaryFields;
+        }
+
+        /* after "--" */
+        if let Some(v) = args.get(idx) {
+            if !save || opt.save_path.is_some() || args.len() > idx + 1 {
+                return Err(format!("unexpected argument \"{v}\""));
+            }
+
+            opt.save_path = Some(v.clone());
+        }
+
+        if save && opt.save_path.is_none() {
+            /* "save_dir" of the config file */
+            opt.save_path = Some(opt.save_dir.clone().unwrap_or_else(default_name));
+        }
+
+        /* the untagged "--save" in the same sampling pass as the tagged ones */
+        if !opt.saves.is_empty() {
+            if let Some(path) = opt.save_path.take() {
+                opt.saves.push((SaveFormat::Dump(opt.fmt), Self::save_path(&path)));
+            }
+        }
+
+        /* "-a --save <dir>" saves one file per thread in <dir> */
+        if let Some(path) = &opt.save_path {
+            if !(opt.dump_all && std::path::Path::new(path).is_dir()) {
+                opt.save_path = Some(Self::save_path(path));
+            }
+        }
+
+        /* the modes on the current CPU, not on the dump file */
+        if let Some(source) = [("--load", opt.load.is_some()), ("--replay", opt.replay.is_some())].iter().find(|(_, set)| *set) {
+            let live_only = [
+                ("--replay", opt.load.is_some() && opt.replay.is_some()),
+                ("--exec", !opt.exec.is_empty()),
+                ("--leaf", opt.leaf.is_some()),
+                ("--save", !opt.saves.is_empty() || opt.save_path.is_some()),
+                ("--save-split", opt.save_split.is_some()),
+                ("--save-append", opt.save_append.is_some()),
+                ("--reference", opt.reference.is_some()),
+                ("-scan", opt.scan),
+                ("--record", opt.record.is_some()),
+                ("--parse-dir", opt.parse_dir.is_some()),
+                ("--aida64", opt.load_aida64.is_some()),
+            ];
+
+            if let Some((name, _)) = live_only.iter().find(|(_, set)| *set) {
+                return Err(format!("\"{name}\" cannot be used with \"{}\"", source.0));
+            }
+        }
+
+        /* `run_with` runs only one mode, on the current CPU or the dump of "--load"/"--replay" */
+        let modes = [
+            ("--version", opt.version),
+            ("--describe", opt.describe.is_some()),
+            ("--codename-for", opt.codename_for.is_some()),
+            ("topo", opt.subcommand == Subcommand::Topo),
+            ("--exec", !opt.exec.is_empty()),
+            ("--leaf", opt.leaf.is_some()),
+            ("--save", !opt.saves.is_empty() || opt.save_path.is_some()),
+            ("--save-split", opt.save_split.is_some()),
+            ("--save-append", opt.save_append.is_some()),
+            ("--require-level", opt.require_level.is_some()),
+            ("--check-file", opt.check_file.is_some()),
+            ("--strict", opt.strict),
+            ("--reference", opt.reference.is_some()),
+            ("--diff-reference", opt.diff_reference.is_some()),
+            ("--compare-model", opt.compare_model.is_some()),
+            ("-scan", opt.scan),
+            ("-kernel-flags", opt.kernel_flags),
+            ("-summary", opt.summary),
+            ("-xsave", opt.xsave),
+            ("-tlb", opt.tlb),
+            ("-security", opt.security),
+            ("-topology", opt.topology),
+            ("-ccx", opt.ccx),
+            ("-apic-id", opt.apic_id),
+            ("-boost", opt.boost),
+            ("-virt", opt.virt),
+            ("-confidential", opt.confidential),
+            ("-errata", opt.errata),
+            ("-emit-schema", opt.emit_schema),
+            /* "--version -json" is the version in JSON */
+            ("-json", json_flag && !opt.version),
+            ("--record", opt.record.is_some()),
+            ("--parse-dir", opt.parse_dir.is_some()),
+            ("--aida64", opt.load_aida64.is_some()),
+        ];
+        let mut set = modes.iter().filter(|(_, set)| *set).map(|(name, _)| name);
+
+        if let (Some(first), Some(second)) = (set.next(), set.next()) {
+            return Err(format!("\"{second}\" cannot be used with \"{first}\""));
+        }
+
+        match (opt.leaf, sub_leaf) {
+            (Some((leaf, _)), Some(sub_leaf)) => opt.leaf = Some((leaf, sub_leaf)),
+            (None, Some(_)) => return Err("\"--sub_leaf\" requires \"--leaf <u32>\"".to_string()),
+            _ => {},
+        }
+
+        Ok(opt)
+    }
+
+    /* "<u32>", "<u32>..=<u32>", "<u32>..<u32>" or "all" */
+    fn parse_sub_leaf(name: &str, raw_value: &str) -> Result<SubLeafSel, String> {
+        if raw_value == "all" {
+            return Ok(SubLeafSel::All);
+        }
+
+        let Some((start, end)) = raw_value.split_once("..") else {
+            return Self::parse_value(name, raw_value).map(SubLeafSel::One);
+        };
+
+        let start = Self::parse_value(name, start)?;
+        let end = match end.strip_prefix('=') {
+            Some(end) => Some(Self::parse_value(name, end)?),
+            None => Self::parse_value(name, end)?.checked_sub(1),
+        };
+
+        match end {
+            Some(end) if start <= end => Ok(SubLeafSel::Range(start, end)),
+            _ => Err(format!("empty range \"{raw_value}\" for \"--{name}\"")),
+        }
+    }
+
+    fn save_path(v: &str) -> String {
+        use std::path::Path;
+
+        if Path::new(v).is_dir() {
+            let sep = if v.ends_with(std::path::MAIN_SEPARATOR) { "" } else { std::path::MAIN_SEPARATOR_STR };
+
+            format!("{v}{sep}{}", default_name())
+        } else {
+            v.to_string()
+        }
+    }
+
+    /// `leaf_pool()`, or only the leaves listed in the config file
+    fn leaf_pool(&self) -> Vec<(u32, u32)> {
+        self.thread_leaf_pool().build()
+    }
+
+    /// `cpuid_dump_all_threads`, with the warning of the skipped CPUs
+    fn cpuid_dump_all_threads(&self) -> Vec<CpuidDump> {
+        let cpuid_dumps = cpuid_dump_all_threads(self.thread_leaf_pool(), self.skip_zero, self.jobs());
+
+        if let Some(msg) = skipped_cpus_msg(cpuid_dumps.len()) {
+            self.diag(&format!("[Warning: {msg}]\n"));
+        }
+
+        cpuid_dumps
+    }
+
+    /// "--jobs", or the available parallelism up to `MAX_DEFAULT_JOBS`
+    pub fn jobs(&self) -> usize {
+        match self.jobs {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()).min(MAX_DEFAULT_JOBS),
+            jobs => jobs,
         }
+    }
+
+    /// `leaf_pool()` built on each thread of "-a"
+    fn thread_leaf_pool(&self) -> ThreadLeafPool {
+        ThreadLeafPool::Auto(self.leaves.clone())
+    }
+
+    pub fn dump_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        if self.dump_all {
+            return dump_all_threads(self.thread_leaf_pool(), self, true, out);
+        }
+
+        let cpuid_dump = CpuidDump::new(&self.leaf_pool(), self.skip_zero);
+        let s = cpuid_dump.live_head(self.fmt) + &cpuid_dump.top_disp(self.fmt, &self.format_config);
+
+        out.write_all(s.as_bytes())
+    }
+
+    /// Diagnostic messages go to stderr, so that stdout has only the dump data
+    fn diag(&self, msg: &str) {
+        if !msg.is_empty() {
+            log::info!("{msg}");
+        }
+    }
+
+    pub fn summary_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let cpuid_dump = self.source_dump()?;
+
+        let mut summary = cpuid_dump.summary();
+
+        /* The OS and the MSR are of the current CPU, not of the loaded dump */
+        let live = self.load.is_none() && self.replay.is_none();
+
+        /* The CPUs visible to the OS, to check whether SMT is disabled */
+        let os_cpus = live.then(|| libcpuid_dump::util::cpu_set_list().ok().map(|cpus| cpus.len())).flatten();
+        summary.push_str(&crate::report::summary_line("SMT status", &cpuid_dump.smt_status(os_cpus)));
+
+        /* Whether the kernel uses 5-level paging, only on Linux */
+        if let Some(enabled) = live.then(libcpuid_dump::util::kernel_la57).flatten() {
+            let la57 = if enabled { "Enabled" } else { "Disabled" };
+
+            summary.push_str(&crate::report::summary_line("LA57 in kernel", la57));
+        }
+
+        /* The number of MKTME keys is available only from IA32_TME_CAPABILITY (MSR 0x981) */
+        if live && cpuid_dump.mem_encrypt_tme().pconfig_mktme {
+            use libcpuid_dump::{util, MemEncryptTme};
+
+            let keys = match util::read_msr(0, 0x981) {
+                Some(cap) => {
+                    let (max_keys, keyid_bits) = MemEncryptTme::mktme_keys_from_tme_capability(cap);
+                    format!("{max_keys} keys, {keyid_bits} KeyID bits")
+                },
+                None => "- (IA32_TME_CAPABILITY is not readable)".to_string(),
+            };
+
+            summary.push_str(&crate::report::summary_line("MKTME", &keys));
+        }
+
+        out.write_all(summary.as_bytes())
+    }
+
+    /// "topo" subcommand, the topology line of each thread
+    pub fn topo_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let s: String = self.source_dumps()?
+            .iter()
+            .map(|cpuid_dump| cpuid_dump.topo_info_head().trim_start().to_string())
+            .collect();
+
+        out.write_all(s.as_bytes())
+    }
+
+    pub fn topology_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        out.write_all(crate::report::topology_tree(&self.source_dumps()?).as_bytes())
+    }
+
+    pub fn ccx_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        out.write_all(crate::report::ccx_report(&self.source_dumps()?).as_bytes())
+    }
+
+    pub fn apic_id_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        out.write_all(crate::report::apic_id_report(&self.source_dumps()?).as_bytes())
+    }
+
+    pub fn kernel_flags_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let cpuid_dump = self.source_dump()?;
+
+        if self.print0 {
+            let flags: String = cpuid_dump.kernel_flags().iter().map(|flag| format!("{flag}\0")).collect();
+
+            return out.write_all(flags.as_bytes());
+        }
+
+        out.write_all(cpuid_dump.kernel_flags_line().as_bytes())
+    }
 
-        opt
+    pub fn security_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let cpuid_dump = self.source_dump()?;
+
+        out.write_all(cpuid_dump.security_report().as_bytes())
+    }
+
+    pub fn boost_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let cpuid_dump = self.source_dump()?;
+
+        out.write_all(cpuid_dump.boost_report().as_bytes())
+    }
+
+    pub fn virt_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let cpuid_dump = self.source_dump()?;
+
+        out.write_all(cpuid_dump.virt_report().as_bytes())
+    }
+
+    pub fn confidential_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let cpuid_dump = self.source_dump()?;
+
+        out.write_all(cpuid_dump.confidential_report().as_bytes())
+    }
+
+    pub fn errata_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let cpuid_dump = self.source_dump()?;
+
+        out.write_all(cpuid_dump.errata_report().as_bytes())
+    }
+
+    pub fn xsave_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let cpuid_dump = self.source_dump()?;
+
+        out.write_all(cpuid_dump.xsave_layout().as_bytes())?;
+        out.write_all(cpuid_dump.xcr0_report().as_bytes())
     }
 
-    pub fn dump_pool(&self) -> Vec<u8> {
-        let leaf_pool = leaf_pool();
+    pub fn tlb_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let cpuid_dump = self.source_dump()?;
+
+        out.write_all(cpuid_dump.tlb_report().as_bytes())
+    }
+
+    pub fn json_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        let cpuid_dumps = match self.file_dumps()? {
+            Some(mut dumps) => {
+                if !self.dump_all {
+                    dumps.truncate(1);
+                }
+
+                if self.skip_zero {
+                    for cpuid_dump in &mut dumps {
+                        cpuid_dump.rawcpuid_pool.retain(|rawcpuid| !rawcpuid.check_result_zero());
+                    }
+                }
+
+                dumps
+            },
+            None if self.dump_all => self.cpuid_dump_all_threads(),
+            None => vec![CpuidDump::new(&self.leaf_pool(), self.skip_zero)],
+        };
 
+        /* the vendor of the dump, not of the current CPU for "--load"/"--replay" */
+        out.write_all(json::json_fmt(&cpuid_dumps[0].cpu_vendor, &cpuid_dumps).as_bytes())
+    }
+
+    pub fn scan_pool(&self, out: &mut dyn Write) -> io::Result<()> {
+        scan::scan_leaf(&CpuVendor::get(), &self.format_config, out)
+    }
+
+    pub fn only_leaf(&self, out: &mut dyn Write, leaf: u32, sub_leaf: SubLeafSel) -> io::Result<()> {
+        let sub_leaves = match sub_leaf {
+            SubLeafSel::One(sub_leaf) => vec![sub_leaf],
+            SubLeafSel::Range(start, end) => (start..=end).collect(),
+            SubLeafSel::All => sub_leaf_pool(leaf),
+        };
+        let leaf_pool: Vec<(u32, u32)> = sub_leaves.into_iter().map(|sub_leaf| (leaf, sub_leaf)).collect();
+
+        self.custom_pool(out, &leaf_pool)
+    }
+
+    /// Dump the given pairs as is, without the filter of `leaf_pool()`
+    pub fn custom_pool(&self, out: &mut dyn Write, leaf_pool: &[(u32, u32)]) -> io::Result<()> {
         if self.dump_all {
-            return dump_all_threads(&leaf_pool, self.skip_zero, self.fmt, self.diff).into_bytes();
+            return dump_all_threads(ThreadLeafPool::Fixed(leaf_pool.into()), self, false, out);
+        }
+
+        let cpuid_dump = CpuidDump::new(leaf_pool, self.skip_zero);
+
+        out.write_all(cpuid_dump.top_disp(self.fmt, &self.format_config).as_bytes())
+    }
+
+    pub fn describe(&self, out: &mut dyn Write, ftr: &str) -> io::Result<bool> {
+        match crate::report::describe(ftr) {
+            Some(s) => {
+                out.write_all(s.as_bytes())?;
+                Ok(true)
+            },
+            None => {
+                log::error!("unknown feature: {ftr}{}", crate::report::did_you_mean(ftr));
+                Ok(false)
+            },
+        }
+    }
+
+    pub fn codename_for(&self, out: &mut dyn Write, vendor: &CpuVendor, fms: &FamModStep) -> io::Result<()> {
+        out.write_all(crate::report::codename_for(fms, vendor).as_bytes())
+    }
+
+    /// The threads of "--load" (the last run of "--save-append") or "--replay",
+    /// `None` for the current CPU
+    fn file_dumps(&self) -> io::Result<Option<Vec<CpuidDump>>> {
+        let (path, dumps) = match (&self.load, &self.replay) {
+            (Some(path), _) => {
+                let dump = load_raw::read_dump(path)?;
+                let run = load_raw::split_runs(&dump).last().map_or("", |(_, run)| *run);
+
+                (path, load_raw::parse_raw(run))
+            },
+            (None, Some(path)) => (path, crate::session::parse_session(&load_raw::read_dump(path)?)),
+            (None, None) => return Ok(None),
+        };
+
+        match dumps {
+            Ok(dumps) if !dumps.is_empty() => Ok(Some(dumps)),
+            Ok(_) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("{path}: no CPUID dump found"))),
+            Err(msg) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {msg}"))),
+        }
+    }
+
+    /// The first thread of "--load"/"--replay", or the current CPU with the leaves of the config file
+    fn source_dump(&self) -> io::Result<CpuidDump> {
+        self.source_dump_of(&self.leaf_pool())
+    }
+
+    /// For the checks and the comparisons, which need all leaves
+    fn source_dump_of(&self, leaf_pool: &[(u32, u32)]) -> io::Result<CpuidDump> {
+        match self.file_dumps()? {
+            Some(dumps) => Ok(dumps.into_iter().next().unwrap()),
+            None => Ok(CpuidDump::new(leaf_pool, false)),
+        }
+    }
+
+    /// All threads of "--load"/"--replay", or of the current CPU
+    fn source_dumps(&self) -> io::Result<Vec<CpuidDump>> {
+        match self.file_dumps()? {
+            Some(dumps) => Ok(dumps),
+            None => Ok(self.cpuid_dump_all_threads()),
+        }
+    }
+
+    /// `source_dump` for the checks, `Ok(None)` if the dump is invalid
+    fn check_target(&self) -> io::Result<Option<CpuidDump>> {
+        match self.source_dump_of(&leaf_pool()) {
+            Ok(cpuid_dump) => Ok(Some(cpuid_dump)),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                log::error!("{e}");
+                Ok(None)
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// `Ok(None)` if the dump of "--load"/"--replay" is invalid
+    pub fn require_level(&self, out: &mut dyn Write, level: MicroArchLevel) -> io::Result<Option<bool>> {
+        let Some(cpuid_dump) = self.check_target()? else { return Ok(None) };
+        let (meets, report) = cpuid_dump.level_report(level);
+
+        out.write_all(report.as_bytes())?;
+
+        Ok(Some(meets))
+    }
+
+    /// "--check-file", `Ok(None)` if the policy file or the dump is invalid, `Ok(Some(false))` if any rule fails
+    pub fn check_file(&self, out: &mut dyn Write, path: &str) -> io::Result<Option<bool>> {
+        let policy = match crate::policy::Policy::load(path) {
+            Ok(policy) => policy,
+            Err(msg) => {
+                log::error!("{msg}");
+                return Ok(None);
+            },
+        };
+        let Some(cpuid_dump) = self.check_target()? else { return Ok(None) };
+
+        let results = policy.check(&cpuid_dump);
+        let mut failed = 0;
+
+        for (rule, result) in &results {
+            match result {
+                Ok(true) => writeln!(out, "PASS  {rule}")?,
+                Ok(false) => writeln!(out, "FAIL  {rule}")?,
+                Err(msg) => writeln!(out, "FAIL  {rule} ({msg})")?,
+            }
+
+            if !matches!(result, Ok(true)) {
+                failed += 1;
+            }
+        }
+
+        writeln!(out, "{} passed, {failed} failed", results.len() - failed)?;
+
+        Ok(Some(failed == 0))
+    }
+
+    /// "--strict", `false` if the reserved bits are set
+    pub fn strict(&self, out: &mut dyn Write) -> io::Result<bool> {
+        let cpuid_dump = self.source_dump_of(&leaf_pool())?;
+        let violations = cpuid_dump.reserved_bit_violations();
+
+        if violations.is_empty() {
+            writeln!(out, "No reserved bits are set")?;
+            return Ok(true);
         }
 
-        let cpuid_dump = CpuidDump::new(&leaf_pool, self.skip_zero);
+        writeln!(out, "Reserved bits are set:")?;
 
-        cpuid_dump.top_disp(self.fmt).into_bytes()
+        for v in violations {
+            writeln!(out, "    {v}")?;
+        }
+
+        Ok(false)
     }
 
-    pub fn only_leaf(&self, leaf: u32, sub_leaf: u32) -> io::Result<()> {
-        let tmp = if self.dump_all {
-            dump_all_threads(&[(leaf, sub_leaf)], self.skip_zero, self.fmt, self.diff)
+    pub fn save_file(&self, save_path: &str) -> io::Result<()> {
+        if std::path::Path::new(save_path).is_dir() {
+            return self.save_per_thread(save_path);
+        }
+
+        let mut f = Output::File(save_path.to_string()).open()?;
+
+        self.dump_pool(&mut f)?;
+        f.flush()?;
+        self.diag(&format!("Output to \"{save_path}\"\n"));
+
+        Ok(())
+    }
+
+    /// "--save-append", the dump after the run separator at the end of the file
+    pub fn save_append(&self, save_path: &str) -> io::Result<()> {
+        let mut f = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(save_path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{save_path}: {e}")))?;
+        let mut s = Vec::new();
+
+        writeln!(s, "{}", load_raw::run_head(std::time::SystemTime::now()))?;
+        self.dump_pool(&mut s)?;
+
+        /* one write for the whole run */
+        f.write_all(&s)?;
+        f.flush()?;
+        self.diag(&format!("Append to \"{save_path}\"\n"));
+
+        Ok(())
+    }
+
+    /// Sample the CPUID once, and write the results in each format of "--save <format>=<path>"
+    pub fn save_formats(&self, saves: &[(SaveFormat, String)]) -> io::Result<()> {
+        let cpuid_dumps = if self.dump_all {
+            self.cpuid_dump_all_threads()
         } else {
-            let cpuid_dump = CpuidDump::new(&[(leaf, sub_leaf)], self.skip_zero);
-            cpuid_dump.top_disp(self.fmt)
+            vec![CpuidDump::new(&self.leaf_pool(), self.skip_zero)]
         };
 
-        dump_write(&tmp.into_bytes())?;
+        for (fmt, path) in saves {
+            let s = match fmt {
+                SaveFormat::Json => json::json_fmt(&CpuVendor::get(), &cpuid_dumps),
+                SaveFormat::Dump(dump_fmt) if self.dump_all => {
+                    let skipped = skipped_cpus_msg(cpuid_dumps.len());
+
+                    cpuid_dumps[0].live_head(*dump_fmt) + &disp_all_threads(&cpuid_dumps, *dump_fmt, &self.format_config, self.diff, skipped)
+                },
+                SaveFormat::Dump(dump_fmt) => cpuid_dumps[0].live_head(*dump_fmt) + &cpuid_dumps[0].top_disp(*dump_fmt, &self.format_config),
+            };
+
+            let mut f = Output::File(path.to_string()).open()?;
+
+            f.write_all(s.as_bytes())?;
+            f.flush()?;
+            self.diag(&format!("Output to \"{path}\"\n"));
+        }
 
         Ok(())
     }
 
-    pub fn save_file(&self, save_path: &str) -> io::Result<()> {
-        use std::fs::File;
-        use std::io::Write;
+    /// Write "<name>_cpuNNN.txt" for each thread, and "<name>_index.txt" listing the files with the topology
+    fn save_per_thread(&self, dir: &str) -> io::Result<()> {
+        use std::path::Path;
+
+        let stem = default_stem();
+        let cpuid_dumps = self.cpuid_dump_all_threads();
+        let mut index = format!(
+            "# {}\n# file thread_id pkg_id core_id smt_id x2apic_id\n",
+            libcpuid_dump::ProcName::get_trim_name(),
+        );
+
+        for cpuid_dump in &cpuid_dumps {
+            let thread_id = cpuid_dump.thread_id.unwrap_or(0);
+            let name = format!("{stem}_cpu{thread_id:03}.txt");
+
+            std::fs::write(Path::new(dir).join(&name), cpuid_dump.top_disp(self.fmt, &self.format_config))?;
+
+            let topo = match &cpuid_dump.topo_id {
+                Some(t) => format!("{} {} {} {}", t.pkg_id, t.core_id, t.smt_id, t.x2apic_id),
+                None => "- - - -".to_string(),
+            };
+
+            index.push_str(&format!("{name} {thread_id} {topo}\n"));
+        }
+
+        let index_path = Path::new(dir).join(format!("{stem}_index.txt"));
+        std::fs::write(&index_path, index)?;
+
+        self.diag(&format!(
+            "Output to \"{dir}\" (threads: {}, index: \"{}\")\n",
+            cpuid_dumps.len(),
+            index_path.display(),
+        ));
+
+        Ok(())
+    }
+
+    /// Write "0xLLLLLLLL_xS.txt" for each leaf/sub-leaf of the current thread, and "manifest.txt" listing the files
+    pub fn save_split(&self, dir: &str) -> io::Result<()> {
+        use std::path::Path;
 
-        let pool = self.dump_pool();
+        std::fs::create_dir_all(dir)?;
 
-        let mut f = File::create(save_path)?;
+        let cpuid_dump = CpuidDump::new(&self.leaf_pool(), self.skip_zero);
+        let split = cpuid_dump.split_disp(self.fmt, &self.format_config);
+        let mut manifest = format!(
+            "# {}\n# file leaf sub_leaf\n",
+            libcpuid_dump::ProcName::get_trim_name(),
+        );
 
-        f.write_all(&pool)?;
-        println!("Output to \"{save_path}\"");
+        for (rawcpuid, s) in &split {
+            let name = format!("0x{:08X}_x{:X}.txt", rawcpuid.leaf, rawcpuid.sub_leaf);
+
+            std::fs::write(Path::new(dir).join(&name), s)?;
+            manifest.push_str(&format!("{name} {:#X} {:#X}\n", rawcpuid.leaf, rawcpuid.sub_leaf));
+        }
+
+        let manifest_path = Path::new(dir).join("manifest.txt");
+        std::fs::write(&manifest_path, manifest)?;
+
+        self.diag(&format!(
+            "Output to \"{dir}\" (leaves: {}, manifest: \"{}\")\n",
+            split.len(),
+            manifest_path.display(),
+        ));
+
+        Ok(())
+    }
+
+    /// Display the dump saved with "-r"
+    pub fn load(&self, out: &mut dyn Write, path: &str) -> io::Result<()> {
+        let dump = load_raw::read_dump(path)?;
+        let s = load_raw::disp_raw(&dump, self.fmt, &self.format_config)
+            .map_err(|msg| io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {msg}")))?;
+
+        out.write_all(s.as_bytes())
+    }
+
+    /// "record <path>", the session file of all threads, "-" for stdout
+    pub fn record(&self, out: &mut dyn Write, path: &str) -> io::Result<()> {
+        let session = crate::session::record_fmt(&self.cpuid_dump_all_threads());
+
+        if path == "-" {
+            return out.write_all(session.as_bytes());
+        }
+
+        let mut f = Output::File(path.to_string()).open()?;
+
+        f.write_all(session.as_bytes())?;
+        f.flush()?;
+        self.diag(&format!("Output to \"{path}\"\n"));
 
         Ok(())
     }
 
-    pub fn load_aida64(&self, path: &String) -> io::Result<()> {
-        let log = std::fs::read_to_string(path)?;
+    /// "replay <path>", the session file in the format of the options
+    pub fn replay(&self, out: &mut dyn Write, path: &str) -> io::Result<()> {
+        let session = load_raw::read_dump(path)?;
+        let s = crate::session::replay_fmt(&session, self.fmt, &self.format_config, self.diff)
+            .map_err(|msg| io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {msg}")))?;
+
+        out.write_all(s.as_bytes())
+    }
+
+    /// "parse-dir <dir>", the summary line of each dump file
+    pub fn parse_dir(&self, out: &mut dyn Write, dir: &str) -> io::Result<()> {
+        let report = crate::load_dir::parse_dir(dir, self.fmt, &self.format_config, self.out_dir.as_deref(), self.matrix)?;
+
+        if let Some(out_dir) = &self.out_dir {
+            self.diag(&format!("Output to \"{out_dir}\"\n"));
+        }
+
+        out.write_all(report.as_bytes())
+    }
+
+    pub fn load_aida64(&self, out: &mut dyn Write, path: &str) -> io::Result<()> {
+        let log = load_raw::read_dump(path)?;
         let mut vec_cpuid_dump = load_aida64_log::parse_aida64(&log);
         let mut cpuid_dump_iter = vec_cpuid_dump.iter_mut();
-        let first = cpuid_dump_iter.next().unwrap();
+        let Some(first) = cpuid_dump_iter.next() else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{path}: no CPUID dump found")));
+        };
 
-        let s = first.top_disp(self.fmt);
+        let s = first.top_disp(self.fmt, &self.format_config);
         let ss: String = cpuid_dump_iter.map(|cpuid_dump| {
             if self.diff {
-                let mut first_rawcpuid_pool = first.rawcpuid_pool.iter();
-
-                cpuid_dump.rawcpuid_pool.retain(|sub| {
-                    let Some(first) = first_rawcpuid_pool.next() else { return false };
-                    first != sub
-                });
+                retain_diff(&mut cpuid_dump.rawcpuid_pool, &first.rawcpuid_pool);
             }
 
-            cpuid_dump.disp(self.fmt)
+            cpuid_dump.disp(self.fmt, &self.format_config)
         }).collect();
 
-        dump_write(&format!("{s}{ss}").into_bytes())?;
+        out.write_all(s.as_bytes())?;
+        out.write_all(ss.as_bytes())
+    }
 
-        Ok(())
+    /// Returns the exit status
+    #[cfg(feature = "reference")]
+    pub fn reference(&self, out: &mut dyn Write, name: &str, diff: bool) -> io::Result<i32> {
+        use crate::reference;
+
+        /* "diff -": the dump from stdin instead of the embedded one */
+        let ref_dump = if diff && name == "-" {
+            let dump = load_raw::read_dump(name)?;
+
+            load_raw::parse_raw(&dump)
+                .and_then(|dumps| dumps.into_iter().next().ok_or_else(|| "no CPUID dump found".to_string()))
+                .map_err(|msg| io::Error::new(io::ErrorKind::InvalidData, format!("stdin: {msg}")))
+                .map(Some)?
+        } else {
+            reference::get_reference(name)
+        };
+
+        let Some(ref_dump) = ref_dump else {
+            if name == "list" {
+                out.write_all(reference::reference_list_msg().as_bytes())?;
+                return Ok(exit_code::SUCCESS);
+            }
+
+            log::error!("unknown reference: {name}");
+            log::info!("Available reference dumps:\n{}", reference::reference_list_msg());
+
+            return Ok(exit_code::CLI_ERROR);
+        };
+
+        let tmp = if diff {
+            let mut cpuid_dump = self.source_dump_of(&leaf_pool())?;
+
+            if self.skip_zero {
+                cpuid_dump.rawcpuid_pool.retain(|rawcpuid| !rawcpuid.check_result_zero());
+            }
+
+            cpuid_dump.diff_reference(&ref_dump, name)
+        } else if self.kernel_flags {
+            ref_dump.kernel_flags_line()
+        } else if self.summary {
+            ref_dump.summary()
+        } else if self.security {
+            ref_dump.security_report()
+        } else if self.boost {
+            ref_dump.boost_report()
+        } else if self.virt {
+            ref_dump.virt_report()
+        } else if self.confidential {
+            ref_dump.confidential_report()
+        } else if self.errata {
+            ref_dump.errata_report()
+        } else if self.xsave {
+            ref_dump.xsave_layout()
+        } else if self.tlb {
+            ref_dump.tlb_report()
+        } else {
+            ref_dump.top_disp(self.fmt, &self.format_config)
+        };
+
+        out.write_all(tmp.as_bytes())?;
+
+        Ok(exit_code::SUCCESS)
+    }
+
+    /// Returns the exit status
+    #[cfg(feature = "reference")]
+    pub fn compare_model(&self, out: &mut dyn Write, model: &str) -> io::Result<i32> {
+        use crate::reference;
+
+        let Some((name, ref_dump)) = reference::find_reference(model) else {
+            log::error!("unknown model: {model}");
+            log::info!("Available reference dumps:\n{}", reference::reference_list_msg());
+
+            return Ok(exit_code::CLI_ERROR);
+        };
+        let mut cpuid_dump = self.source_dump_of(&leaf_pool())?;
+        cpuid_dump.rawcpuid_pool.retain(|rawcpuid| !rawcpuid.check_result_zero());
+
+        out.write_all(cpuid_dump.compare_model(&ref_dump, name).as_bytes())?;
+
+        Ok(exit_code::SUCCESS)
+    }
+
+    #[cfg(not(feature = "reference"))]
+    pub fn compare_model(&self, _out: &mut dyn Write, _model: &str) -> io::Result<i32> {
+        log::error!("cpuid_dump was built without the \"reference\" feature");
+        Ok(exit_code::UNSUPPORTED)
+    }
+
+    #[cfg(not(feature = "reference"))]
+    pub fn reference(&self, _out: &mut dyn Write, _name: &str, _diff: bool) -> io::Result<i32> {
+        log::error!("cpuid_dump was built without the \"reference\" feature");
+        Ok(exit_code::UNSUPPORTED)
     }
 }
@@ -0,0 +1,227 @@
+use std::path::PathBuf;
+use crate::DumpFormat;
+use crate::logger::ColorChoice;
+
+/// Default options read from `$XDG_CONFIG_HOME/cpuid_dump/config.toml`
+/// (or `~/.config/cpuid_dump/config.toml`), overridden by the command line options.
+///
+/// ```toml
+/// # raw, bin, parse, compat, debug, interleave, kv
+/// format = "raw"
+/// all = true
+/// full = true
+/// json = false
+/// # auto, always, never
+/// color = "never"
+/// # used by "--save" without <path/filename>
+/// save_dir = "/var/log/cpuid"
+/// # dump only these leaves (with their sub-leaves)
+/// leaves = [0x0, 0x1, 0x7, 0x8000_0001]
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub fmt: Option<DumpFormat>,
+    pub all: Option<bool>,
+    pub full: Option<bool>,
+    pub json: Option<bool>,
+    pub disp_zero: Option<bool>,
+    pub no_diff: Option<bool>,
+    pub no_header: Option<bool>,
+    pub quiet: Option<bool>,
+    pub color: Option<ColorChoice>,
+    pub save_dir: Option<String>,
+    pub leaves: Option<Vec<u32>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Str(String),
+    Bool(bool),
+    Int(u32),
+    Array(Vec<Value>),
+}
+
+/* A subset of TOML: `key = value` lines of string, bool, integer, and single-line array */
+pub(crate) fn parse_value(s: &str) -> Option<Value> {
+    let s = s.trim();
+
+    if let Some(s) = s.strip_prefix('[') {
+        let s = s.strip_suffix(']')?.trim();
+
+        if s.is_empty() {
+            return Some(Value::Array(Vec::new()));
+        }
+
+        let array: Option<Vec<Value>> = split_array(s)
+            .into_iter()
+            .filter(|v| !v.trim().is_empty())
+            .map(parse_value)
+            .collect();
+
+        return array.map(Value::Array);
+    }
+
+    if let Some(s) = s.strip_prefix('"') {
+        return Some(Value::Str(s.strip_suffix('"')?.to_string()));
+    }
+
+    match s {
+        "true" => return Some(Value::Bool(true)),
+        "false" => return Some(Value::Bool(false)),
+        _ => {},
+    }
+
+    let s = s.replace('_', "");
+
+    let int = match s.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => s.parse::<u32>().ok()?,
+    };
+
+    Some(Value::Int(int))
+}
+
+/* split at the commas, except in the strings */
+fn split_array(s: &str) -> Vec<&str> {
+    let mut in_str = false;
+    let mut start = 0;
+    let mut items = Vec::new();
+
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_str = !in_str,
+            ',' if !in_str => {
+                items.push(&s[start..i]);
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+
+    items.push(&s[start..]);
+
+    items
+}
+
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let mut in_str = false;
+
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_str = !in_str,
+            '#' if !in_str => return &line[..i],
+            _ => {},
+        }
+    }
+
+    line
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+
+        Some(config_home.join("cpuid_dump").join("config.toml"))
+    }
+
+    /// `Ok(None)` if the config file does not exist
+    pub fn load(path: &std::path::Path) -> Result<Option<Self>, String> {
+        let s = match std::fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+        };
+
+        Self::parse(&s)
+            .map(Some)
+            .map_err(|msg| format!("{}: {msg}", path.display()))
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut config = Self::default();
+
+        for (i, line) in s.lines().enumerate() {
+            let line = strip_comment(line).trim();
+
+            if line.is_empty() { continue }
+
+            let err = |msg: &str| format!("line {}: {msg}", i+1);
+
+            let (key, value) = line.split_once('=').ok_or_else(|| err("expected `key = value`"))?;
+            let key = key.trim();
+            let value = parse_value(value).ok_or_else(|| err("invalid value"))?;
+
+            match (key, value) {
+                ("format", Value::Str(fmt)) => {
+                    config.fmt = Some(fmt.parse().map_err(|e: String| err(&e))?);
+                },
+                ("color", Value::Str(when)) => {
+                    config.color = Some(when.parse().map_err(|e: String| err(&e))?);
+                },
+                ("all", Value::Bool(b)) => config.all = Some(b),
+                ("full", Value::Bool(b)) => config.full = Some(b),
+                ("json", Value::Bool(b)) => config.json = Some(b),
+                ("disp_zero", Value::Bool(b)) => config.disp_zero = Some(b),
+                ("no_diff", Value::Bool(b)) => config.no_diff = Some(b),
+                ("no_header", Value::Bool(b)) => config.no_header = Some(b),
+                ("quiet", Value::Bool(b)) => config.quiet = Some(b),
+                ("save_dir", Value::Str(dir)) => {
+                    config.save_dir = Some(dir);
+                },
+                ("leaves", Value::Array(array)) => {
+                    let leaves: Option<Vec<u32>> = array
+                        .into_iter()
+                        .map(|v| if let Value::Int(leaf) = v { Some(leaf) } else { None })
+                        .collect();
+
+                    config.leaves = Some(leaves.ok_or_else(|| err("leaves must be an array of integers"))?);
+                },
+                ("format" | "color" | "all" | "full" | "json" | "disp_zero" | "no_diff" | "no_header" | "quiet" | "save_dir" | "leaves", _) => {
+                    return Err(err(&format!("invalid type of value for `{key}`")));
+                },
+                _ => return Err(err(&format!("unknown key `{key}`"))),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+#[test]
+fn test_config() {
+    let config = Config::parse("format = \"raw\" # comment\nall = true\ncolor = \"never\"\nleaves = [0x1, 7]").unwrap();
+
+    assert!(matches!(config.fmt, Some(DumpFormat::Raw)));
+    assert_eq!(config.all, Some(true));
+    assert_eq!(config.color, Some(ColorChoice::Never));
+    assert_eq!(config.leaves, Some(vec![0x1, 0x7]));
+
+    assert_eq!(
+        parse_value(r#"["a,b", "c"]"#),
+        Some(Value::Array(vec![Value::Str("a,b".to_string()), Value::Str("c".to_string())])),
+    );
+    assert!(Config::parse("format = \"xml\"").is_err());
+    assert!(Config::parse("all = 1").is_err());
+
+    /* the defaults of the options, overridden by the command line */
+    let args = |args: &[&str]| crate::MainOpt::parse_args(config.clone(), &args.iter().map(|s| s.to_string()).collect::<Vec<_>>()).unwrap();
+    let opt = args(&[]);
+
+    assert!(opt.dump_all && matches!(opt.fmt, DumpFormat::Raw));
+    assert_eq!(opt.leaves, Some(vec![0x1, 0x7]));
+
+    let opt = args(&["--no-all", "--format", "parse", "--color=always"]);
+
+    assert!(!opt.dump_all && matches!(opt.fmt, DumpFormat::Parse));
+    assert_eq!(opt.color, ColorChoice::Always);
+
+    /* "config" as the value of the other option, not "--config" */
+    let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    assert!(crate::MainOpt::load_config(&args(&["--describe", "config"])).is_ok());
+    assert!(crate::MainOpt::load_config(&args(&["parse", "config"])).is_ok());
+    assert!(crate::MainOpt::load_config(&args(&["--config"])).is_err());
+}
@@ -0,0 +1,60 @@
+use crate::{cpuid, CpuidResult, CpuVendor};
+use libcpuid_dump::{AmdCoreType, AmdExtTopo, AmdNativeModelId, HybridCoreType, HybridInfo, IntelNativeModelId};
+
+/// Core type and native model id of the hybrid architecture,
+/// from `CPUID.(EAX=1Ah)` for Intel or `CPUID.(EAX=8000_0026h, ECX=0)` for AMD
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreTypeInfo {
+    Intel(HybridCoreType, IntelNativeModelId),
+    Amd(AmdCoreType, AmdNativeModelId),
+}
+
+impl CoreTypeInfo {
+    pub fn from_cpuid(vendor: &CpuVendor, cpuid: &CpuidResult) -> Option<Self> {
+        match vendor {
+            CpuVendor::GenuineIntel => {
+                let core_type = HybridInfo::get_core_type(cpuid)?;
+                let native_model_id = HybridInfo::get_native_model_id(cpuid);
+
+                Some(Self::Intel(core_type, native_model_id))
+            },
+            CpuVendor::AuthenticAMD => {
+                let ext_topo = AmdExtTopo::from(cpuid);
+
+                Some(Self::Amd(ext_topo.core_type?, ext_topo.native_model_id?))
+            },
+            _ => None,
+        }
+    }
+
+    pub fn get(vendor: &CpuVendor) -> Option<Self> {
+        let leaf = Self::leaf(vendor)?;
+        let max_leaf = cpuid!(leaf & 0x8000_0000, 0x0).eax;
+
+        if max_leaf < leaf {
+            return None;
+        }
+
+        Self::from_cpuid(vendor, &cpuid!(leaf, 0x0))
+    }
+
+    /// The leaf which this information is available from
+    pub fn leaf(vendor: &CpuVendor) -> Option<u32> {
+        match vendor {
+            CpuVendor::GenuineIntel => Some(0x1A),
+            CpuVendor::AuthenticAMD => Some(0x8000_0026),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for CoreTypeInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Intel(core_type, native_model_id) =>
+                write!(f, "Type: {core_type}, Model: {native_model_id}"),
+            Self::Amd(core_type, native_model_id) =>
+                write!(f, "Type: {core_type}, Model: {native_model_id}"),
+        }
+    }
+}
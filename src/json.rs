@@ -0,0 +1,113 @@
+use crate::{CpuidDump, RawCpuid, RawCpuidFmt, CpuVendor};
+use libcpuid_dump::TopoId;
+
+/// JSON Schema for the `-json` output, bump `SCHEMA_VERSION` on incompatible changes
+pub const SCHEMA: &str = include_str!("../schema/cpuid_dump.schema.json");
+const SCHEMA_VERSION: u32 = 1;
+
+fn json_str(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+
+    escaped.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+
+    escaped
+}
+
+fn json_option<T: std::fmt::Display>(v: &Option<T>) -> String {
+    match v {
+        Some(v) => v.to_string(),
+        None => "null".to_string(),
+    }
+}
+
+fn leaf_json(rawcpuid: &RawCpuid, vendor: &CpuVendor) -> String {
+    let features: Vec<String> = rawcpuid.features(vendor).iter().map(|f| json_str(f)).collect();
+
+    format!(
+        "{{\"leaf\":{},\"sub_leaf\":{},\"eax\":{},\"ebx\":{},\"ecx\":{},\"edx\":{},\"features\":[{}]}}",
+        rawcpuid.leaf,
+        rawcpuid.sub_leaf,
+        rawcpuid.result.eax,
+        rawcpuid.result.ebx,
+        rawcpuid.result.ecx,
+        rawcpuid.result.edx,
+        features.join(","),
+    )
+}
+
+impl CpuidDump {
+    fn json_fmt(&self) -> String {
+        let topology = match &self.topo_id {
+            Some(TopoId { pkg_id, core_id, smt_id, x2apic_id, node_id }) => format!(
+                "{{\"pkg_id\":{pkg_id},\"core_id\":{core_id},\"smt_id\":{smt_id},\"x2apic_id\":{x2apic_id},\"node_id\":{}}}",
+                json_option(node_id),
+            ),
+            None => "null".to_string(),
+        };
+        let leaves: Vec<String> = self.rawcpuid_pool
+            .iter()
+            .map(|rawcpuid| leaf_json(rawcpuid, &self.cpu_vendor))
+            .collect();
+
+        format!(
+            "{{\"thread_id\":{},\"topology\":{topology},\"microcode\":{},\"leaves\":[{}]}}",
+            json_option(&self.thread_id),
+            json_option(&self.microcode),
+            leaves.join(","),
+        )
+    }
+}
+
+pub(crate) fn json_fmt(vendor: &CpuVendor, cpuid_dumps: &[CpuidDump]) -> String {
+    let threads: Vec<String> = cpuid_dumps.iter().map(|cpuid_dump| cpuid_dump.json_fmt()).collect();
+
+    format!(
+        "{{\"schema_version\":{SCHEMA_VERSION},\"version\":{},\"vendor\":{},\"threads\":[{}]}}\n",
+        json_str(env!("CARGO_PKG_VERSION")),
+        json_str(&vendor.to_string()),
+        threads.join(","),
+    )
+}
+
+/// "--version -json", the build metadata to trace the dump to the decoder
+pub(crate) fn version_json() -> String {
+    let features: &[&str] = &[
+        #[cfg(feature = "reference")]
+        "reference",
+    ];
+    let features: Vec<String> = features.iter().map(|f| json_str(f)).collect();
+
+    format!(
+        "{{\"name\":{},\"version\":{},\"git_commit\":{},\"target\":{},\"features\":[{}],\
+        \"feature_registry\":{{\"revision\":{},\"entries\":{},\"digest\":\"{:08x}\"}},\"schema_version\":{SCHEMA_VERSION}}}\n",
+        json_str(env!("CARGO_PKG_NAME")),
+        json_str(env!("CARGO_PKG_VERSION")),
+        json_str(env!("CPUID_DUMP_GIT_COMMIT")),
+        json_str(env!("CPUID_DUMP_TARGET")),
+        features.join(","),
+        crate::FTR_REVISION,
+        crate::FTR_BITS.len(),
+        crate::ftr_digest(),
+    )
+}
+
+#[test]
+fn test_version_json() {
+    let s = version_json();
+
+    assert!(s.starts_with("{\"name\":\"cpuid_dump_rs\",\"version\":"), "{}", s);
+    assert!(s.contains(&format!("\"revision\":{}", crate::FTR_REVISION)), "{}", s);
+    assert!(s.contains(&format!("\"digest\":\"{:08x}\"", crate::ftr_digest())), "{}", s);
+    assert_eq!(s.matches('{').count(), s.matches('}').count());
+}
@@ -0,0 +1,133 @@
+use crate::RawCpuid;
+use std::sync::Arc;
+use libcpuid_dump::{leaf, CpuidSource, NativeCpuid};
+
+/// Sub-leaves of the leaf to dump, enumerated by the sub-leaf count or the valid flag of the leaf
+pub fn sub_leaf_pool(leaf: u32) -> Vec<u32> {
+    sub_leaf_pool_from(&NativeCpuid, leaf)
+}
+
+pub fn sub_leaf_pool_from(src: &dyn CpuidSource, leaf: u32) -> Vec<u32> {
+    match leaf {
+        /* Cache Properties, Intel */
+        leaf::CACHE_PROPERTIES => (0x0..=0x4).collect(),
+        leaf::STRUCTURED_EXT_FEATURES => {
+            /* CPUID[Leaf=0x7, SubLeaf=0x0].EAX, StructExtFeatIdMax */
+            let max_sub_leaf = RawCpuid::exe_from(src, leaf::STRUCTURED_EXT_FEATURES, 0x0).result.eax;
+
+            (0x0..=max_sub_leaf).collect()
+        },
+        /*  Extended Topology Enumeration, Intel, AMD Zen 2 <=
+            SMT_LEVEL = 0,
+            CORE_LEVEL = 1,
+        */
+        leaf::EXT_TOPOLOGY => (0x0..=0x1).collect(),
+        /* 0xD: Processor Extended State Enumeration, up to APX (0x13) */
+        leaf::XSTATE => (0x0..=0x13).collect(),
+        /* 0x12: SGX Capability Enumeration, Intel
+            SubLeaf 0x2..: EPC sections, until the invalid section */
+        leaf::SGX => (0x0..=0x11)
+            .take_while(|&sub_leaf| sub_leaf < 0x2 || (RawCpuid::exe_from(src, leaf, sub_leaf).result.eax & 0xF) != 0x0)
+            .collect(),
+        /* 0x18: Deterministic Address Translation Parameters, Intel */
+        leaf::TLB_PARAMETERS => {
+            let max_sub_leaf = RawCpuid::exe_from(src, leaf::TLB_PARAMETERS, 0x0).result.eax;
+
+            (0x0..max_sub_leaf).collect()
+        },
+        /* 0x1B: PCONFIG Information, Intel
+            until the invalid sub-leaf */
+        leaf::PCONFIG => {
+            let mut sub_leaves = Vec::new();
+
+            for sub_leaf in 0x0..0x10 {
+                sub_leaves.push(sub_leaf);
+
+                if (RawCpuid::exe_from(src, leaf, sub_leaf).result.eax & 0xFFF) == 0x0 {
+                    break;
+                }
+            }
+
+            sub_leaves
+        },
+        /* 0x1F: V2 Extended Topology Enumeration Leaf, Intel */
+        leaf::EXT_TOPOLOGY_V2 => (0x0..=0x4).collect(),
+        /* Cache Properties, AMD, same format as Intel Leaf 0x4 */
+        leaf::AMD_CACHE_PROPERTIES => (0x0..=0x4).collect(),
+        /* AMD Platform QoS Enforcement for Memory Bandwidth */
+        leaf::AMD_PQOS_EXT => (0x0..=0x1).collect(),
+        /* AMD Extended CPU Topology */
+        leaf::AMD_EXT_TOPOLOGY => (0x0..=0x4).collect(),
+        _ => vec![0x0],
+    }
+}
+
+pub fn leaf_pool() -> Vec<(u32, u32)> {
+    leaf_pool_from(&NativeCpuid)
+}
+
+pub fn leaf_pool_from(src: &dyn CpuidSource) -> Vec<(u32, u32)> {
+    let mut leaf_pool: Vec<(u32, u32)> = Vec::with_capacity(64);
+
+    /* LFuncStd: largest standard function */
+    let max_std_leaf = RawCpuid::exe_from(src, leaf::VENDOR_ID, 0x0).result.eax;
+    /* LFuncExt: largest extended function */
+    let max_ext_leaf = RawCpuid::exe_from(src, leaf::EXT_MAX, 0x0).result.eax;
+
+    /* Base, Ext */
+    for leaf in (leaf::VENDOR_ID..=max_std_leaf).chain(leaf::EXT_MAX..=max_ext_leaf) {
+        for sub_leaf in sub_leaf_pool_from(src, leaf) {
+            leaf_pool.push((leaf, sub_leaf))
+        }
+    }
+
+    leaf_pool
+}
+
+/// Leaf pool of "-a", built on each pinned thread from the maxima of the thread,
+/// some hybrid/buggy systems report the different maxima per core
+#[derive(Debug, Clone)]
+pub enum ThreadLeafPool {
+    /// `leaf_pool()` of the thread, only the listed leaves if `Some`
+    Auto(Option<Vec<u32>>),
+    /// The given pairs as is
+    Fixed(Arc<[(u32, u32)]>),
+}
+
+impl ThreadLeafPool {
+    pub fn build(&self) -> Vec<(u32, u32)> {
+        match self {
+            Self::Auto(leaves) => {
+                let mut leaf_pool = leaf_pool();
+
+                if let Some(leaves) = leaves {
+                    leaf_pool.retain(|(leaf, _)| leaves.contains(leaf));
+                }
+
+                leaf_pool
+            },
+            Self::Fixed(leaf_pool) => leaf_pool.to_vec(),
+        }
+    }
+}
+
+#[test]
+fn test_leaf_pool_from_source() {
+    use libcpuid_dump::CpuidResult;
+
+    let dump = include_str!("../tests/corpus/zen3_ryzen5_5600g.txt");
+    let (_, pool) = crate::split_raw_dump(dump).unwrap().remove(0);
+    let src: Vec<(u32, u32, CpuidResult)> = pool
+        .iter()
+        .map(|rawcpuid| (rawcpuid.leaf, rawcpuid.sub_leaf, rawcpuid.result))
+        .collect();
+
+    let leaf_pool = leaf_pool_from(&src);
+
+    /* Zen 3: LFuncStd 0x10, LFuncExt 0x8000_0023 */
+    assert_eq!(leaf_pool.first(), Some(&(0x0, 0x0)));
+    assert_eq!(leaf_pool.last(), Some(&(0x8000_0023, 0x0)));
+    /* StructExtFeatIdMax = 0 */
+    assert_eq!(sub_leaf_pool_from(&src, 0x7), vec![0x0]);
+    assert!(pool.iter().all(|rawcpuid| leaf_pool.contains(&(rawcpuid.leaf, rawcpuid.sub_leaf))));
+}
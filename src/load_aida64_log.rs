@@ -1,33 +1,50 @@
-use crate::{CpuidDump, CpuidResult, CpuVendor, RawCpuid};
+use crate::{CpuidDump, CpuidResult, CpuVendor, RawCpuid, CoreTypeInfo};
 use libcpuid_dump::{TopoLevelType, TopoId};
 
-pub(crate) fn parse_aida64(log: &String) -> Vec<CpuidDump> {
+pub(crate) fn parse_aida64(log: &str) -> Vec<CpuidDump> {
     let mut rawcpuid_pool: Vec<RawCpuid> = Vec::new();
     let mut pre_leaf = u32::MAX;
     let mut sub_leaf = 0u32;
     let mut cpu_vendor: Option<CpuVendor> = None;
     let [mut smt_cpuid, mut core_cpuid]: [Option<CpuidResult>; 2] = [None, None];
+    let [mut amd_80_08, mut amd_80_1e]: [Option<CpuidResult>; 2] = [None, None];
     let mut vec_cpuid_dump = Vec::new();
 
     for line in log.lines().skip(1) {
         if line.starts_with("Group:") || line.starts_with("------[ CPUID Registers") {
             let Some(cpu_vendor) = cpu_vendor else { continue };
-            let topo_id = if let [Some(smt_cpuid), Some(core_cpuid)] = [smt_cpuid, core_cpuid] {
-                Some(TopoId::get_topo_info_with_smt_core_cpuid(&smt_cpuid, &core_cpuid))
-            } else {
-                None
+            let topo_id = match [smt_cpuid, core_cpuid, amd_80_08, amd_80_1e] {
+                [Some(smt_cpuid), Some(core_cpuid), _, _] =>
+                    Some(TopoId::get_topo_info_with_smt_core_cpuid(&smt_cpuid, &core_cpuid)),
+                [_, _, Some(amd_80_08), Some(amd_80_1e)] =>
+                    Some(TopoId::get_topo_info_with_amd_cpuid(&amd_80_08, &amd_80_1e)),
+                _ => CpuidDump::legacy_topo_id(&cpu_vendor, |leaf| rawcpuid_pool
+                    .iter()
+                    .find(|rawcpuid| rawcpuid.leaf == leaf && rawcpuid.sub_leaf == 0x0)
+                    .map(|rawcpuid| rawcpuid.result)
+                ),
             };
 
+            let core_type_info = CoreTypeInfo::leaf(&cpu_vendor).and_then(|leaf| {
+                let rawcpuid = rawcpuid_pool.iter().find(|rawcpuid| rawcpuid.leaf == leaf)?;
+
+                CoreTypeInfo::from_cpuid(&cpu_vendor, &rawcpuid.result)
+            });
+
             vec_cpuid_dump.push(CpuidDump {
                 cpu_vendor,
                 rawcpuid_pool: rawcpuid_pool.clone(),
                 topo_id,
                 thread_id: None,
+                core_type_info,
+                microcode: None,
             });
 
             rawcpuid_pool.clear();
             smt_cpuid = None;
             core_cpuid = None;
+            amd_80_08 = None;
+            amd_80_1e = None;
         }
         if line == "------[ All CPUs ]------" {
             break;
@@ -62,6 +79,14 @@ pub(crate) fn parse_aida64(log: &String) -> Vec<CpuidDump> {
             }
         }
 
+        if let Some(CpuVendor::AuthenticAMD) = cpu_vendor {
+            match leaf {
+                0x8000_0008 => amd_80_08 = Some(result),
+                0x8000_001E => amd_80_1e = Some(result),
+                _ => {},
+            }
+        }
+
         let rawcpuid = RawCpuid {
             leaf,
             sub_leaf,
@@ -0,0 +1,268 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::{CpuidDump, DumpFormat};
+use crate::load_raw;
+use libcpuid_dump::{FamModStep, FormatConfig, MicroArchLevel};
+
+/// (Dump file, the parsed threads or the error message)
+type LoadResult = (PathBuf, Result<Vec<CpuidDump>, String>);
+
+/// Files of the directory sorted by the name, without the sub-directories and the hidden files,
+/// or the paths listed in the file (one per line, relative to the list, '#' for comments)
+fn dump_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    if dir.is_file() {
+        let base = dir.parent().unwrap_or(Path::new(""));
+        let list = std::fs::read_to_string(dir)?;
+
+        return Ok(list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| base.join(line))
+            .collect());
+    }
+
+    let mut paths = Vec::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+
+        if !hidden && entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+
+    paths.sort();
+
+    Ok(paths)
+}
+
+fn load_file(path: &Path) -> Result<Vec<CpuidDump>, String> {
+    let dump = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let dumps = load_raw::parse_raw(&dump)?;
+
+    if dumps.is_empty() {
+        return Err("no CPUID dump found".to_string());
+    }
+
+    Ok(dumps)
+}
+
+/// Load and parse every dump file of the directory (or the list) on the threads of `available_parallelism`,
+/// in the order of the file names
+pub(crate) fn load_dir(dir: &Path) -> io::Result<Vec<LoadResult>> {
+    let paths = dump_files(dir)?;
+    let jobs = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = paths.len().div_ceil(jobs).max(1);
+
+    let results = std::thread::scope(|s| {
+        let handles: Vec<_> = paths.chunks(chunk_size).map(|paths| {
+            s.spawn(move || {
+                paths.iter().map(|path| (path.clone(), load_file(path))).collect::<Vec<LoadResult>>()
+            })
+        }).collect();
+
+        handles.into_iter().filter_map(|h| h.join().ok()).flatten().collect()
+    });
+
+    Ok(results)
+}
+
+/* "<file>: <model name>, <Family/Model/Stepping>, <threads>, <x86-64 level>" */
+fn report_line(name: &str, dumps: &[CpuidDump]) -> String {
+    let first = &dumps[0];
+    let leaf_01h = first.get_cpuid(0x1, 0x0);
+    let FamModStep { syn_fam, syn_mod, step, .. } = FamModStep::from(&leaf_01h);
+    let level = MicroArchLevel::from_cpuid_array([
+        leaf_01h,
+        first.get_cpuid(0x7, 0x0),
+        first.get_cpuid(0x8000_0001, 0x0),
+    ]);
+    let model = match first.summary_name() {
+        model if model.is_empty() => first.cpu_vendor.to_string(),
+        model => model,
+    };
+    let threads = dumps.len();
+
+    format!(
+        "{name}: {model}, {syn_fam:#X}/{syn_mod:#X}/{step:#X}, {threads} thread{}, {level}\n",
+        if threads == 1 { "" } else { "s" },
+    )
+}
+
+/// Output format of "--matrix"
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MatrixFormat {
+    Csv,
+    Markdown,
+}
+
+impl std::str::FromStr for MatrixFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "md" | "markdown" => Ok(Self::Markdown),
+            _ => Err(format!("invalid matrix format \"{s}\", expected \"csv\" or \"md\"")),
+        }
+    }
+}
+
+/// Feature (the flag names of Linux) x host matrix of the loaded dumps, the host is the file stem.
+/// The features of all hosts (the safe common baseline) are in the "all" column (CSV) or in bold (Markdown),
+/// followed by the x86-64 level of all hosts. The files failed to load are skipped.
+pub(crate) fn fleet_matrix(results: &[LoadResult], matrix_fmt: MatrixFormat) -> String {
+    let hosts: Vec<(String, &CpuidDump)> = results
+        .iter()
+        .filter_map(|(path, result)| {
+            let host = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+
+            result.as_ref().ok().map(|dumps| (host, &dumps[0]))
+        })
+        .collect();
+    let host_flags: Vec<Vec<&str>> = hosts.iter().map(|(_, dump)| dump.kernel_flags()).collect();
+
+    /* in the order of the first appearance */
+    let mut features: Vec<&str> = Vec::new();
+    for flag in host_flags.iter().flatten() {
+        if !features.contains(flag) {
+            features.push(flag);
+        }
+    }
+
+    let common_level = hosts
+        .iter()
+        .map(|(_, dump)| MicroArchLevel::from_cpuid_array([
+            dump.get_cpuid(0x1, 0x0),
+            dump.get_cpuid(0x7, 0x0),
+            dump.get_cpuid(0x8000_0001, 0x0),
+        ]))
+        .min()
+        .map_or("-".to_string(), |level| level.to_string());
+
+    let host_names: Vec<&str> = hosts.iter().map(|(host, _)| host.as_str()).collect();
+    let mut s = match matrix_fmt {
+        MatrixFormat::Csv => format!("feature,{},all\n", host_names.join(",")),
+        MatrixFormat::Markdown => format!(
+            "| feature | {} |\n|---|{}\n",
+            host_names.join(" | "),
+            "---|".repeat(hosts.len()),
+        ),
+    };
+
+    for ftr in &features {
+        let present: Vec<bool> = host_flags.iter().map(|flags| flags.contains(ftr)).collect();
+        let all = present.iter().all(|p| *p);
+
+        let line = match matrix_fmt {
+            MatrixFormat::Csv => {
+                let cells: Vec<&str> = present.iter().map(|p| if *p { "1" } else { "0" }).collect();
+
+                format!("{ftr},{},{}\n", cells.join(","), all as u8)
+            },
+            MatrixFormat::Markdown => {
+                let cells: Vec<&str> = present.iter().map(|p| if *p { "x" } else { " " }).collect();
+                let ftr = if all { format!("**{ftr}**") } else { ftr.to_string() };
+
+                format!("| {ftr} | {} |\n", cells.join(" | "))
+            },
+        };
+
+        s.push_str(&line);
+    }
+
+    let common = features.iter().filter(|ftr| host_flags.iter().all(|flags| flags.contains(ftr))).count();
+
+    match matrix_fmt {
+        MatrixFormat::Csv => s.push_str(&format!("# common baseline: {common} features, {common_level}\n")),
+        MatrixFormat::Markdown => s.push_str(&format!("\nCommon baseline: {common} features, {common_level}\n")),
+    }
+
+    s
+}
+
+/// "parse-dir <dir>": a line per dump file and the count of the errors,
+/// and with `out_dir` the output of each file ("<file stem>.txt") in the format
+pub(crate) fn parse_dir(
+    dir: &str,
+    dump_fmt: DumpFormat,
+    cfg: &FormatConfig,
+    out_dir: Option<&str>,
+    matrix: Option<MatrixFormat>,
+) -> io::Result<String> {
+    let results = load_dir(Path::new(dir))?;
+
+    if let Some(matrix_fmt) = matrix {
+        return Ok(fleet_matrix(&results, matrix_fmt));
+    }
+
+    let mut report = String::new();
+    let mut errors = 0;
+
+    if let Some(out_dir) = out_dir {
+        std::fs::create_dir_all(out_dir)?;
+    }
+
+    for (path, result) in &results {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+        let dumps = match result {
+            Ok(dumps) => dumps,
+            Err(msg) => {
+                errors += 1;
+                report.push_str(&format!("{name}: error: {msg}\n"));
+                continue;
+            },
+        };
+
+        report.push_str(&report_line(&name, dumps));
+
+        if let Some(out_dir) = out_dir {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+            std::fs::write(Path::new(out_dir).join(format!("{stem}.txt")), load_raw::disp_dumps(dumps, dump_fmt, cfg))?;
+        }
+    }
+
+    report.push_str(&format!("{} files, {errors} errors\n", results.len()));
+
+    Ok(report)
+}
+
+#[test]
+fn test_load_dir() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let results = load_dir(&dir).unwrap();
+    let names: Vec<_> = results.iter().map(|(path, _)| path.file_name().unwrap().to_owned()).collect();
+    let mut sorted = names.clone();
+    sorted.sort();
+
+    assert_eq!(names, sorted);
+
+    let (_, zen3) = results
+        .iter()
+        .find(|(path, _)| path.ends_with("zen3_ryzen5_5600g.txt"))
+        .unwrap();
+    let line = report_line("zen3_ryzen5_5600g.txt", zen3.as_ref().unwrap());
+
+    assert!(line.starts_with("zen3_ryzen5_5600g.txt: AMD Ryzen 5 5600G"), "{}", line);
+    assert!(line.contains("0x19/0x50/0x0"), "{}", line);
+}
+
+#[test]
+fn test_fleet_matrix() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let results: Vec<LoadResult> = ["zen3_ryzen5_5600g.txt", "zenplus_ryzen5_2600.txt"]
+        .iter()
+        .map(|name| (dir.join(name), load_file(&dir.join(name))))
+        .collect();
+    let csv = fleet_matrix(&results, MatrixFormat::Csv);
+
+    assert!(csv.starts_with("feature,zen3_ryzen5_5600g,zenplus_ryzen5_2600,all\n"), "{}", csv);
+    /* Zen+ lacks VAES */
+    assert!(csv.contains("\nvaes,1,0,0\n"), "{}", csv);
+    assert!(csv.contains("\navx2,1,1,1\n"), "{}", csv);
+    assert!(csv.ends_with("x86-64-v3\n"), "{}", csv);
+}
@@ -0,0 +1,208 @@
+use crate::{split_raw_dump, CpuidDump, DumpFormat};
+use libcpuid_dump::FormatConfig;
+use crate::load_report::{parse_report, ReportKind};
+
+/// Parse the output of `-r` (`-raw`), one `CpuidDump` per "# [..]" topology line.
+/// Other lines starting with '#' and empty lines are ignored, any other line is an error.
+/// The CPUID table of the CPU-Z/HWiNFO reports is also accepted.
+pub(crate) fn parse_raw(dump: &str) -> Result<Vec<CpuidDump>, String> {
+    let pools = match ReportKind::detect(dump) {
+        Some(kind) => {
+            log::debug!("{kind:?} report detected, parsing the CPUID table");
+            parse_report(dump)?
+        },
+        None => split_raw_dump(dump)?,
+    };
+    let microcodes: Vec<Option<u32>> = dump
+        .lines()
+        .filter_map(|line| line.strip_prefix("# ["))
+        .map(head_microcode)
+        .collect();
+    /* the results before the first topology line are the first pool */
+    let offset = pools.len().saturating_sub(microcodes.len());
+
+    pools.into_iter().enumerate().map(|(i, (thread_id, pool))| {
+        let mut cpuid_dump = CpuidDump::from_rawcpuid_pool(pool)
+            .ok_or_else(|| "the result of leaf 0x0 is missing".to_string())?;
+        cpuid_dump.thread_id = thread_id;
+        cpuid_dump.microcode = i.checked_sub(offset).and_then(|i| microcodes.get(i).copied().flatten());
+
+        Ok(cpuid_dump)
+    }).collect()
+}
+
+/* "Microcode: 0x.." field of the topology line */
+fn head_microcode(head: &str) -> Option<u32> {
+    let rev = head
+        .split(", ")
+        .find_map(|s| s.strip_prefix("Microcode: "))?
+        .trim_end_matches(']');
+
+    u32::from_str_radix(rev.strip_prefix("0x").unwrap_or(rev), 16).ok()
+}
+
+/// Read the dump text from the stream, e.g. the pipe of `ssh remote cpuid_dump -r`
+pub(crate) fn read_dump_from<R: std::io::Read>(mut reader: R) -> std::io::Result<String> {
+    let mut dump = String::new();
+    reader.read_to_string(&mut dump)?;
+
+    Ok(dump)
+}
+
+/// Read the dump text from the file, or from stdin if `path` is "-"
+pub(crate) fn read_dump(path: &str) -> std::io::Result<String> {
+    if path == "-" {
+        read_dump_from(std::io::stdin().lock())
+    } else {
+        std::fs::read_to_string(path)
+    }
+}
+
+/// Separator line of the runs appended with "--save-append"
+pub(crate) const RUN_HEAD: &str = "# Run: ";
+
+/// "# Run: YYYY-MM-DDThh:mm:ssZ" of the time, in UTC
+pub(crate) fn run_head(time: std::time::SystemTime) -> String {
+    format!("{RUN_HEAD}{}", utc_timestamp(time))
+}
+
+/// "YYYY-MM-DDThh:mm:ssZ" (RFC 3339) of the time
+pub(crate) fn utc_timestamp(time: std::time::SystemTime) -> String {
+    let secs = time.duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, rem) = (secs / 86400, secs % 86400);
+
+    /* civil date from the days since 1970-01-01 */
+    let z = days + 719468;
+    let (era, doe) = (z / 146097, z % 146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600, rem / 60 % 60, rem % 60,
+    )
+}
+
+/// Split the dump file of "--save-append" at the "# Run: <timestamp>" lines, into (timestamp, dump) of each run.
+/// The lines before the first separator are a run without the timestamp, if they are not blank.
+pub(crate) fn split_runs(dump: &str) -> Vec<(Option<&str>, &str)> {
+    let mut runs = Vec::new();
+    let mut time = None;
+    let mut start = 0;
+    let mut pos = 0;
+
+    for line in dump.split_inclusive('\n') {
+        if let Some(t) = line.strip_prefix(RUN_HEAD) {
+            let body = &dump[start..pos];
+
+            if time.is_some() || !body.trim().is_empty() {
+                runs.push((time, body));
+            }
+
+            time = Some(t.trim_end());
+            start = pos + line.len();
+        }
+
+        pos += line.len();
+    }
+
+    let body = &dump[start..];
+
+    if time.is_some() || !body.trim().is_empty() {
+        runs.push((time, body));
+    }
+
+    runs
+}
+
+/// Display of "--load", the header only for the first thread
+/// The runs of "--save-append" are displayed each after its "# Run: <timestamp>" line
+pub(crate) fn disp_raw(dump: &str, dump_fmt: DumpFormat, cfg: &FormatConfig) -> Result<String, String> {
+    let runs = split_runs(dump);
+
+    if runs.iter().all(|(time, _)| time.is_none()) {
+        return Ok(disp_dumps(&parse_raw(dump)?, dump_fmt, cfg));
+    }
+
+    runs.iter().map(|(time, run)| {
+        let head = time.map(|t| format!("{RUN_HEAD}{t}\n")).unwrap_or_default();
+
+        Ok(head + &disp_dumps(&parse_raw(run)?, dump_fmt, cfg))
+    }).collect()
+}
+
+/// The header only for the first thread
+pub(crate) fn disp_dumps(cpuid_dumps: &[CpuidDump], dump_fmt: DumpFormat, cfg: &FormatConfig) -> String {
+    cpuid_dumps.iter().enumerate().map(|(i, cpuid_dump)| {
+        if i == 0 { cpuid_dump.top_disp(dump_fmt, cfg) } else { cpuid_dump.disp(dump_fmt, cfg) }
+    }).collect()
+}
+
+/* `tests/corpus/<name>.txt` (`-r`) against `<name>.expected` (`--load <name>.txt -parse`),
+   `CPUID_DUMP_BLESS=1 cargo test` to rewrite the expected outputs after an intended decoder change */
+#[test]
+fn test_corpus() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let bless = std::env::var_os("CPUID_DUMP_BLESS").is_some();
+    let mut paths: Vec<_> = std::fs::read_dir(&dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| matches!(path.extension(), Some(ext) if ext == "txt"))
+        .collect();
+    paths.sort();
+
+    assert!(!paths.is_empty());
+
+    for path in paths {
+        let dump = std::fs::read_to_string(&path).unwrap();
+        let disp = disp_raw(&dump, DumpFormat::Parse, &FormatConfig::default())
+            .unwrap_or_else(|msg| panic!("{}: {msg}", path.display()));
+        let expected_path = path.with_extension("expected");
+
+        if bless {
+            std::fs::write(&expected_path, &disp).unwrap();
+            continue;
+        }
+
+        let expected = std::fs::read_to_string(&expected_path)
+            .unwrap_or_else(|e| panic!("{}: {e}", expected_path.display()));
+
+        assert!(disp == expected, "{}: the output differs from {}", path.display(), expected_path.display());
+    }
+}
+
+#[test]
+fn test_read_dump_from() {
+    let dump = "# [Thread: 000]\n\
+        0x00000000 0x00000000: 0x00000010 0x68747541 0x444D4163 0x69746E65\n";
+    let dump = read_dump_from(dump.as_bytes()).unwrap();
+    let cpuid_dumps = parse_raw(&dump).unwrap();
+
+    assert_eq!(cpuid_dumps.len(), 1);
+    assert_eq!(cpuid_dumps[0].get_cpuid(0x0, 0x0).eax, 0x10);
+}
+
+#[test]
+fn test_split_runs() {
+    let leaf0 = "0x00000000 0x00000000: 0x00000010 0x68747541 0x444D4163 0x69746E65\n";
+    let t0 = std::time::UNIX_EPOCH + std::time::Duration::from_secs(951_827_696);
+    let t1 = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_790_000_000);
+
+    assert_eq!(run_head(t0), "# Run: 2000-02-29T12:34:56Z");
+    assert_eq!(run_head(t1), "# Run: 2026-09-21T14:13:20Z");
+
+    let dump = format!("{}\n{leaf0}{}\n{}", run_head(t0), run_head(t1), leaf0.replace("0x00000010", "0x00000011"));
+    let runs = split_runs(&dump);
+
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0], (Some("2000-02-29T12:34:56Z"), leaf0));
+    assert_eq!(runs[1].0, Some("2026-09-21T14:13:20Z"));
+    assert_eq!(parse_raw(runs[1].1).unwrap()[0].get_cpuid(0x0, 0x0).eax, 0x11);
+
+    /* a plain dump is one run without the timestamp */
+    assert_eq!(split_runs(leaf0), vec![(None, leaf0)]);
+    assert!(disp_raw(&dump, DumpFormat::Raw, &FormatConfig::default()).unwrap().starts_with("# Run: 2000-02-29T12:34:56Z\n"));
+}
@@ -0,0 +1,115 @@
+use crate::{dedup_identical, RawCpuid, CpuidResult, ThreadPool};
+
+/// Text reports of the other tools, with the CPUID table of each thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReportKind {
+    /// "CPU-Z TXT Report", "Thread dumps" section
+    CpuZ,
+    /// HWiNFO report and debug dump
+    HwInfo,
+}
+
+impl ReportKind {
+    /// From the first non-empty line of the report
+    pub(crate) fn detect(report: &str) -> Option<Self> {
+        let head = report.lines().map(str::trim).find(|line| !line.is_empty())?;
+        let head = head.trim_start_matches('\u{FEFF}');
+
+        if head.contains("CPU-Z") {
+            Some(Self::CpuZ)
+        } else if head.contains("HWiNFO") {
+            Some(Self::HwInfo)
+        } else {
+            None
+        }
+    }
+}
+
+/// Extract the CPUID table of each thread from the CPU-Z/HWiNFO report.
+/// The threads are delimited by "CPU Thread <n>" (CPU-Z) or "CPU #<n>" (HWiNFO) lines,
+/// the rows are "CPUID <leaf> [<sub_leaf>] <eax> <ebx> <ecx> <edx>" in hex,
+/// separated by spaces, tabs, '-', ':' or "EAX=" like labels.
+/// Without the sub-leaf, the repeated rows of the same leaf are numbered from 0 like the AIDA64 log.
+pub(crate) fn parse_report(report: &str) -> Result<Vec<ThreadPool>, String> {
+    let Some(kind) = ReportKind::detect(report) else {
+        return Err("not a CPU-Z/HWiNFO report".to_string());
+    };
+    let mut pools: Vec<ThreadPool> = Vec::new();
+    let mut pre_leaf = u32::MAX;
+    let mut sub_leaf = 0u32;
+
+    for line in report.lines() {
+        let line = line.trim();
+
+        if let Some(thread_id) = thread_head(kind, line) {
+            pools.push((thread_id, Vec::new()));
+            pre_leaf = u32::MAX;
+            continue;
+        }
+
+        let Some(row) = line.strip_prefix("CPUID") else { continue };
+        let Some((leaf, explicit_sub_leaf, result)) = parse_row(row) else { continue };
+
+        sub_leaf = match explicit_sub_leaf {
+            Some(sub_leaf) => sub_leaf,
+            None if pre_leaf == leaf => sub_leaf + 1,
+            None => 0,
+        };
+        pre_leaf = leaf;
+
+        let rawcpuid = RawCpuid { leaf, sub_leaf, result };
+
+        match pools.last_mut() {
+            Some((_, pool)) => pool.push(rawcpuid),
+            None => pools.push((None, vec![rawcpuid])),
+        }
+    }
+
+    /* the reports may list the same table twice */
+    for (_, pool) in pools.iter_mut() {
+        dedup_identical(pool);
+    }
+
+    pools.retain(|(_, pool)| !pool.is_empty());
+
+    if pools.is_empty() {
+        return Err(format!("no CPUID table found in the {kind:?} report"));
+    }
+
+    Ok(pools)
+}
+
+/* "CPU Thread 0" (CPU-Z), "CPU #0" or "CPU#0: ..." (HWiNFO) */
+fn thread_head(kind: ReportKind, line: &str) -> Option<Option<usize>> {
+    let id = match kind {
+        ReportKind::CpuZ => line.strip_prefix("CPU Thread ")?,
+        ReportKind::HwInfo => line.strip_prefix("CPU #").or_else(|| line.strip_prefix("CPU#"))?,
+    };
+    let id: String = id.chars().take_while(char::is_ascii_digit).collect();
+
+    if id.is_empty() { return None }
+
+    Some(id.parse::<usize>().ok())
+}
+
+/* the hex values of the row: leaf, [sub_leaf], eax, ebx, ecx, edx */
+fn parse_row(row: &str) -> Option<(u32, Option<u32>, CpuidResult)> {
+    let values: Vec<u32> = row
+        .split(|c: char| c.is_ascii_whitespace() || matches!(c, '-' | ':' | '=' | ',' | '.' | '[' | ']' | '(' | ')'))
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| {
+            let s = s.strip_prefix("0x").unwrap_or(s);
+            let s = s.strip_suffix('h').unwrap_or(s);
+
+            u32::from_str_radix(s, 16).ok()
+        })
+        .collect();
+
+    let (leaf, sub_leaf, regs) = match values.as_slice() {
+        [leaf, regs @ ..] if regs.len() == 4 => (*leaf, None, regs),
+        [leaf, sub_leaf, regs @ ..] if regs.len() == 4 => (*leaf, Some(*sub_leaf), regs),
+        _ => return None,
+    };
+
+    Some((leaf, sub_leaf, CpuidResult { eax: regs[0], ebx: regs[1], ecx: regs[2], edx: regs[3] }))
+}
@@ -0,0 +1,98 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// "--color", `color` of the config file: the prefixes of the errors and the warnings in red/yellow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// On a terminal, without `NO_COLOR`
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(format!("invalid color \"{s}\", expected \"auto\", \"always\" or \"never\"")),
+        }
+    }
+}
+
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// The messages on stderr, so that stdout has only the dump data.
+/// The info messages ("Output to ..") have no prefix, the others "error: ", "warning: ", ..
+struct StderrLogger;
+
+static LOGGER: StderrLogger = StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) { return }
+
+        let (prefix, sgr) = match record.level() {
+            Level::Error => ("error: ", "1;31"),
+            Level::Warn => ("warning: ", "1;33"),
+            Level::Info => ("", ""),
+            Level::Debug => ("debug: ", ""),
+            Level::Trace => ("trace: ", ""),
+        };
+        let msg = record.args().to_string();
+        let nl = if msg.ends_with('\n') { "" } else { "\n" };
+
+        if COLOR.load(Ordering::Relaxed) && !sgr.is_empty() {
+            eprint!("\x1b[{sgr}m{prefix}\x1b[0m{msg}{nl}");
+        } else {
+            eprint!("{prefix}{msg}{nl}");
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the logger, the level is `Info` until the options are parsed
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
+
+pub fn set_color(choice: ColorChoice) {
+    let color = match choice {
+        ColorChoice::Auto => std::io::stderr().is_terminal()
+            && !matches!(std::env::var_os("NO_COLOR"), Some(v) if !v.is_empty()),
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    };
+
+    COLOR.store(color, Ordering::Relaxed);
+}
+
+/// "-q": errors only, "-v": + debug, "-vv": + trace
+pub fn level_filter(quiet: bool, verbose: u8) -> LevelFilter {
+    match (quiet, verbose) {
+        (true, _) => LevelFilter::Error,
+        (_, 0) => LevelFilter::Info,
+        (_, 1) => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+#[test]
+fn test_level_filter() {
+    assert_eq!(level_filter(false, 0), LevelFilter::Info);
+    assert_eq!(level_filter(false, 1), LevelFilter::Debug);
+    assert_eq!(level_filter(false, 3), LevelFilter::Trace);
+    assert_eq!(level_filter(true, 2), LevelFilter::Error);
+}
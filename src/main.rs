@@ -4,127 +4,90 @@
 use core::arch::x86_64::CpuidResult;
 
 use libcpuid_dump::TopoId;
-use libcpuid_dump::{cpuid, CpuVendor};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use libcpuid_dump::{cpuid, CpuVendor, FormatConfig};
+
+/// Exit status of `cpuid_dump`, also listed in the help message
+pub mod exit_code {
+    pub const SUCCESS: i32 = 0;
+    /// Invalid option/argument, config file
+    pub const CLI_ERROR: i32 = 2;
+    /// The platform or the build does not support the operation
+    pub const UNSUPPORTED: i32 = 3;
+    /// The check (e.g. "--require-level") failed
+    pub const CHECK_FAILED: i32 = 4;
+    pub const IO_ERROR: i32 = 5;
+}
 
-pub const INPUT_WIDTH: usize = "  0x00000000 0x0:  ".len();
-pub const OUTPUT_WIDTH: usize = "0x00000000 ".len() * 4;
-pub const TOTAL_WIDTH: usize = 100;
-pub const PARSE_WIDTH: usize = TOTAL_WIDTH - INPUT_WIDTH - OUTPUT_WIDTH - 1; // " ".len()
 // pub const VERSION_HEAD: &str = concat!("CPUID Dump ", env!("CARGO_PKG_VERSION"), "\n");
 
 mod raw_cpuid;
 pub use raw_cpuid::*;
 
+mod leaf_pool;
+use leaf_pool::{leaf_pool, sub_leaf_pool, ThreadLeafPool};
+
 mod parse;
 pub use parse::*;
 
+mod report;
+
+mod core_type_info;
+use core_type_info::*;
+
 mod args;
 use args::*;
 
 mod load_aida64_log;
-
-/// Main flow:
-///    pub struct RawCpuid {
-///        pub leaf: u32,
-///        pub sub_leaf: u32,
-///        // https://doc.rust-lang.org/core/arch/x86_64/struct.CpuidResult.html
-///        pub result: CpuidResult {
-///            pub eax: u32,
-///            pub ebx: u32,
-///            pub ecx: u32,
-///            pub edx: u32,
-///        },
-///    } 
-///    // src/main.rs
-///    MainOpt::parse() -> MainOpt
-///            |
-///    opt.rawcpuid_pool(&leaf_pool()) -> Vec<RawCpuid>
-///            |
-///    // src/raw_cpuid.rs
-///    let parsed_pool: Vec<u8>;
-///    cpuid_parse: {
-///        for raw_cpuid in cpuid_pool {
-///            // src/raw_cpuid.rs, src/parse/*
-///            let cpuid_parsed: String = raw_cpuid.parse();
-///            parsed_pool.extend(cpuid_parsed.into_bytes());
-///        }
-///    }
-///            |
-///    // src/main.rs
-///    dump_write(&parsed_pool) // print, write stdout
-///    
-
-fn leaf_pool() -> Vec<(u32, u32)> {
-    let mut leaf_pool: Vec<(u32, u32)> = Vec::with_capacity(64);
-
-    /* LFuncStd: largest standard function */
-    let max_std_leaf = RawCpuid::exe(0x0, 0x0).result.eax;
-    /* LFuncExt: largest extended function */
-    let max_ext_leaf = RawCpuid::exe(0x8000_0000, 0x0).result.eax;
-
-    /* Base */
-    for leaf in 0x0..=max_std_leaf {
-        match leaf {
-            /* Cache Properties, Intel */
-            0x4 => for sub_leaf in 0x0..=0x4 {
-                leaf_pool.push((leaf, sub_leaf))
-            },
-            0x7 => {
-                /* CPUID[Leaf=0x7, SubLeaf=0x0].EAX, StructExtFeatIdMax */
-                let max_sub_leaf = RawCpuid::exe(0x7, 0x0).result.eax;
-
-                for sub_leaf in 0x0..=max_sub_leaf {
-                    leaf_pool.push((leaf, sub_leaf))
-                }
-            },
-            /*  Extended Topology Enumeration, Intel, AMD Zen 2 <=
-                SMT_LEVEL = 0,
-                CORE_LEVEL = 1,
-            */
-            0xB => for sub_leaf in 0x0..=0x1 {
-                leaf_pool.push((leaf, sub_leaf))
-            },
-            /* 0xD: Processor Extended State Enumeration */
-            0xD => for sub_leaf in 0x0..0xF {
-                leaf_pool.push((leaf, sub_leaf))
-            },
-            /* 0x18: Deterministic Address Translation Parameters, Intel */
-            0x18 => {
-                let max_sub_leaf = RawCpuid::exe(0x18, 0x0).result.eax;
-
-                for sub_leaf in 0x0..max_sub_leaf {
-                    leaf_pool.push((leaf, sub_leaf))
-                }
-            },
-            /* 0x1F: V2 Extended Topology Enumeration Leaf, Intel */
-            0x1F => for sub_leaf in 0x0..=0x4 {
-                leaf_pool.push((0x1F, sub_leaf))
-            },
-            _ => leaf_pool.push((leaf, 0x0)),
-        }
-    }
-
-    /* Ext */
-    for leaf in 0x8000_0000..=max_ext_leaf {
-        match leaf {
-            /* Cache Properties, AMD, same format as Intel Leaf 0x4 */
-            0x8000_001D => for sub_leaf in 0x0..=0x4 {
-                leaf_pool.push((leaf, sub_leaf))
-            },
-            /* AMD Platform QoS Enforcement for Memory Bandwidth */
-            0x8000_0020 => for sub_leaf in 0x0..=0x1 {
-                leaf_pool.push((leaf, sub_leaf))
-            },
-            /* AMD Extended CPU Topology */
-            0x8000_0026 => for sub_leaf in 0x0..=0x4 {
-                leaf_pool.push((leaf, sub_leaf))
-            },
-            _ => leaf_pool.push((leaf, 0x0)),
-        }
-    }
-
-    leaf_pool
-}
+mod load_report;
+
+mod load_raw;
+mod load_dir;
+mod session;
+
+mod scan;
+
+mod json;
+
+mod config;
+mod policy;
+
+mod output;
+mod logger;
+mod progress;
+
+#[cfg(feature = "reference")]
+mod reference;
+
+// Main flow:
+//    pub struct RawCpuid {
+//        pub leaf: u32,
+//        pub sub_leaf: u32,
+//        // https://doc.rust-lang.org/core/arch/x86_64/struct.CpuidResult.html
+//        pub result: CpuidResult {
+//            pub eax: u32,
+//            pub ebx: u32,
+//            pub ecx: u32,
+//            pub edx: u32,
+//        },
+//    } 
+//    // src/main.rs
+//    MainOpt::parse() -> MainOpt
+//            |
+//    opt.rawcpuid_pool(&leaf_pool()) -> Vec<RawCpuid>
+//            |
+//    // src/raw_cpuid.rs
+//    let parsed_pool: Vec<u8>;
+//    cpuid_parse: {
+//        for raw_cpuid in cpuid_pool {
+//            // src/raw_cpuid.rs, src/parse/*
+//            let cpuid_parsed: String = raw_cpuid.parse();
+//            parsed_pool.extend(cpuid_parsed.into_bytes());
+//        }
+//    }
+//            |
+//    // src/main.rs
+//    out.write_all(&parsed_pool) // stdout, file, socket (src/output.rs)
 
 #[derive(Debug, Clone)]
 struct CpuidDump {
@@ -132,168 +95,902 @@ struct CpuidDump {
     pub rawcpuid_pool: Vec<RawCpuid>,
     pub topo_id: Option<TopoId>,
     pub thread_id: Option<usize>,
+    pub core_type_info: Option<CoreTypeInfo>,
+    /// Microcode revision, `None` for the dumps without it
+    pub microcode: Option<u32>,
 }
 
 impl CpuidDump {
     fn new(leaf_pool: &[(u32, u32)], skip_zero: bool) -> Self {
         let cpu_vendor = CpuVendor::get();
 
-        let rawcpuid_pool = leaf_pool.iter().filter_map(|(leaf, sub_leaf)| {
-            let rawcpuid = RawCpuid::exe(*leaf, *sub_leaf);
-            
-            if skip_zero && rawcpuid.check_result_zero() {
-                None
-            } else {
-                Some(rawcpuid)
-            }
-        }).collect();
+        let mut rawcpuid_pool: Vec<RawCpuid> = leaf_pool
+            .iter()
+            .map(|(leaf, sub_leaf)| RawCpuid::exe(*leaf, *sub_leaf))
+            .collect();
+
+        if skip_zero {
+            retain_nonzero(&mut rawcpuid_pool);
+        }
+
         let topo_id = TopoId::get_topo_info();
+        let core_type_info = CoreTypeInfo::get(&cpu_vendor);
+        /* the current thread is not pinned, the revision of CPU 0 */
+        let microcode = libcpuid_dump::util::microcode_revision(0);
 
         Self {
             cpu_vendor,
             rawcpuid_pool,
             topo_id,
             thread_id: None,
+            core_type_info,
+            microcode,
         }
     }
 
     fn new_with_thread_id(leaf_pool: &[(u32, u32)], skip_zero: bool, thread_id: usize) -> Self {
         let mut tmp = Self::new(leaf_pool, skip_zero);
         tmp.thread_id = Some(thread_id);
+        tmp.microcode = libcpuid_dump::util::microcode_revision(thread_id);
 
         tmp
     }
 
-    fn top_disp(&self, dump_fmt: DumpFormat) -> String {
-        [
-            self.topo_info_head(),
-            dump_fmt.head_fmt(),
-            self.select_pool(dump_fmt),
-        ].concat()
+    /// `TopoId` of the dumped results without the topology leaves, from leaf 0x1 and
+    /// leaf 0x4 (Intel) or 0x8000_0008 (AMD), `None` if the leaves are not in the dump
+    fn legacy_topo_id<F: Fn(u32) -> Option<CpuidResult>>(cpu_vendor: &CpuVendor, find: F) -> Option<TopoId> {
+        let zero = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+        let cpuid_01 = find(0x1)?;
+        let [cpuid_04, cpuid_80_08] = match cpu_vendor {
+            CpuVendor::AuthenticAMD |
+            CpuVendor::HygonGenuine => [zero, find(0x8000_0008)?],
+            _ => [find(0x4)?, zero],
+        };
+
+        Some(TopoId::get_topo_info_with_legacy_cpuid(&cpuid_01, &cpuid_04, &cpuid_80_08))
+    }
+
+    /// Build from the dumped results (e.g. file), `None` if leaf 0x0 is missing
+    fn from_rawcpuid_pool(rawcpuid_pool: Vec<RawCpuid>) -> Option<Self> {
+        let find = |leaf: u32, sub_leaf: u32| -> Option<CpuidResult> {
+            rawcpuid_pool
+                .iter()
+                .find(|rawcpuid| rawcpuid.leaf == leaf && rawcpuid.sub_leaf == sub_leaf)
+                .map(|rawcpuid| rawcpuid.result)
+        };
+
+        let cpu_vendor = CpuVendor::from(&find(0x0, 0x0)?);
+        let topo_id = match [find(0xB, 0x0), find(0xB, 0x1), find(0x8000_0008, 0x0), find(0x8000_001E, 0x0)] {
+            [Some(smt_cpuid), Some(core_cpuid), _, _] =>
+                Some(TopoId::get_topo_info_with_smt_core_cpuid(&smt_cpuid, &core_cpuid)),
+            [_, _, Some(amd_80_08), Some(amd_80_1e)] =>
+                Some(TopoId::get_topo_info_with_amd_cpuid(&amd_80_08, &amd_80_1e)),
+            _ => Self::legacy_topo_id(&cpu_vendor, |leaf| find(leaf, 0x0)),
+        };
+        let core_type_info = CoreTypeInfo::leaf(&cpu_vendor)
+            .and_then(|leaf| CoreTypeInfo::from_cpuid(&cpu_vendor, &find(leaf, 0x0)?));
+
+        Some(Self {
+            cpu_vendor,
+            rawcpuid_pool,
+            topo_id,
+            thread_id: None,
+            core_type_info,
+            microcode: None,
+        })
+    }
+
+    /* The raw format has no decorations, the topology line is a comment with the microcode revision */
+    fn head(&self, dump_fmt: DumpFormat) -> String {
+        if !header() { return String::new() }
+
+        let head = self.topo_info_head();
+
+        if !dump_fmt.comment_head() { return head }
+
+        let head = head.trim().trim_start_matches('[').trim_end_matches(']');
+        let microcode = self.microcode.map(|rev| format!("Microcode: {rev:#X}"));
+        let fields: Vec<&str> = [Some(head), microcode.as_deref()]
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        if fields.is_empty() {
+            String::new()
+        } else {
+            format!("# [{}]\n", fields.join(", "))
+        }
+    }
+
+    /// The lines of the dump header only for the current CPU, "[Environment: ..]" and "[Warning: ..]"
+    fn live_head(&self, dump_fmt: DumpFormat) -> String {
+        if !header() { return String::new() }
+
+        let lines = [
+            self.environment_head(),
+            self.brand_check_head(),
+            self.leaf_limit_head(),
+            self.thread_count_head(libcpuid_dump::util::configured_cpu_count()),
+        ].concat();
+
+        if !dump_fmt.comment_head() { return lines }
+
+        /* "# [..]" is the topology line of the raw format */
+        lines.lines().map(|line| format!("# {}\n", line.trim_start_matches('[').trim_end_matches(']'))).collect()
+    }
+
+    fn top_disp(&self, dump_fmt: DumpFormat, cfg: &FormatConfig) -> String {
+        let mut buf = String::with_capacity(self.disp_capacity(cfg));
+        self.write_top_disp(&mut buf, dump_fmt, cfg);
+
+        buf
+    }
+
+    fn disp(&self, dump_fmt: DumpFormat, cfg: &FormatConfig) -> String {
+        let mut buf = String::with_capacity(self.disp_capacity(cfg));
+        self.write_disp(&mut buf, dump_fmt, cfg);
+
+        buf
+    }
+
+    /* a line per result, and some lines for the parsed result */
+    /// Package, core, SMT order of "-a", the thread ID for the dumps without the topology
+    fn topo_order(&self) -> (Option<(u32, u32, u32)>, Option<usize>) {
+        let topo = self.topo_id.map(|TopoId { pkg_id, core_id, smt_id, .. }| (pkg_id, core_id, smt_id));
+
+        (topo, self.thread_id)
+    }
+
+    /// LFuncStd (Leaf 0x0), LFuncExt (Leaf 0x8000_0000), `None` if the leaf is not in the results
+    fn leaf_maxima(&self) -> [Option<u32>; 2] {
+        [0x0, 0x8000_0000].map(|leaf| {
+            self.rawcpuid_pool
+                .iter()
+                .find(|rawcpuid| rawcpuid.leaf == leaf && rawcpuid.sub_leaf == 0x0)
+                .map(|rawcpuid| rawcpuid.result.eax)
+        })
+    }
+
+    /// Flag the thread reporting the different maxima from `self` (the first thread)
+    fn write_maxima_diff(&self, buf: &mut String, maxima: [Option<u32>; 2], dump_fmt: DumpFormat) {
+        if !header() { return }
+        let Some(first_id) = self.thread_id else { return };
+        let diff: Vec<String> = ["Std", "Ext"].iter()
+            .zip(self.leaf_maxima().iter().zip(maxima.iter()))
+            .filter(|(_, (first, sub))| first != sub)
+            .map(|(name, (first, sub))| {
+                let max = |v: &Option<u32>| v.map_or("-".to_string(), |v| format!("{v:#X}"));
+                format!("{name} {} → {}", max(first), max(sub))
+            })
+            .collect();
+
+        if diff.is_empty() { return }
+
+        let msg = format!("Max leaf differs from CPU {first_id}: {}", diff.join(", "));
+
+        match dump_fmt {
+            f if f.comment_head() => buf.push_str(&format!("# {msg}\n")),
+            _ => buf.push_str(&format!("[{msg}]\n")),
+        }
+    }
+
+    /// Roll-up of the threads omitted by diff, like "CPUs 1-31: identical to CPU 0"
+    fn write_identical(&self, buf: &mut String, thread_ids: &[usize], dump_fmt: DumpFormat) {
+        if !header() { return }
+        let (Some(first_id), Some(last_id)) = (self.thread_id, thread_ids.last()) else { return };
+        let cpus = if thread_ids.len() == 1 {
+            format!("CPU {last_id}")
+        } else {
+            format!("CPUs {}", cpu_ranges(thread_ids))
+        };
+
+        match dump_fmt {
+            f if f.comment_head() => buf.push_str(&format!("# {cpus}: identical to CPU {first_id}\n")),
+            _ => buf.push_str(&format!("\n[{cpus}: identical to CPU {first_id}]\n")),
+        }
+    }
+
+    fn disp_capacity(&self, cfg: &FormatConfig) -> usize {
+        (self.rawcpuid_pool.len() + 4) * cfg.total_width * 2
+    }
+
+    fn write_top_disp(&self, buf: &mut String, dump_fmt: DumpFormat, cfg: &FormatConfig) {
+        buf.push_str(&self.head(dump_fmt));
+
+        if header() {
+            buf.push_str(&dump_fmt.head_fmt(cfg));
+        }
+
+        self.write_pool(buf, dump_fmt, cfg);
     }
 
-    fn disp(&self, dump_fmt: DumpFormat) -> String {
-        [
-            self.topo_info_head(),
-            // dump_fmt.head_fmt(),
-            self.select_pool(dump_fmt),
-        ].concat()
+    fn write_disp(&self, buf: &mut String, dump_fmt: DumpFormat, cfg: &FormatConfig) {
+        buf.push_str(&self.head(dump_fmt));
+        // dump_fmt.head_fmt(),
+        self.write_pool(buf, dump_fmt, cfg);
+    }
+
+    /// "-parse" of the thread omitted by diff, with only the decoded fields changed from `base`
+    fn write_diff_pool(&self, buf: &mut String, base: &Self, cfg: &FormatConfig) {
+        let mut title = None;
+
+        for rawcpuid in &self.rawcpuid_pool {
+            if header() {
+                rawcpuid.write_leaf_title(&self.cpu_vendor, &mut title, buf);
+            }
+            let base_rawcpuid = base.rawcpuid_pool
+                .iter()
+                .find(|b| b.leaf == rawcpuid.leaf && b.sub_leaf == rawcpuid.sub_leaf);
+
+            match base_rawcpuid {
+                Some(base_rawcpuid) => rawcpuid.write_parse_diff_fmt(base_rawcpuid, &self.cpu_vendor, cfg, buf),
+                None => rawcpuid.write_parse_fmt(&self.cpu_vendor, cfg, buf),
+            }
+        }
+    }
+
+    /// Format the results into the buffer, without collecting a `String` per result.
+    /// The decoders of "-parse" (`src/parse`) also write into the buffer.
+    fn write_pool(&self, buf: &mut String, dump_fmt: DumpFormat, cfg: &FormatConfig) {
+        let mut title = None;
+
+        for rawcpuid in &self.rawcpuid_pool {
+            self.write_rawcpuid(rawcpuid, buf, dump_fmt, cfg, &mut title);
+
+            /* "-print0": a NUL-terminated unit per leaf record */
+            if print0() {
+                if buf.ends_with('\n') { buf.pop(); }
+                buf.push('\0');
+            }
+        }
     }
 
-    fn select_pool(&self, dump_fmt: DumpFormat) -> String {
-        let fmt_func = dump_fmt.rawcpuid_fmt_func();
+    fn write_rawcpuid(
+        &self,
+        rawcpuid: &RawCpuid,
+        buf: &mut String,
+        dump_fmt: DumpFormat,
+        cfg: &FormatConfig,
+        title: &mut Option<&'static str>,
+    ) {
+        if let DumpFormat::Interleaved = dump_fmt {
+            let mut parse = String::new();
+            self.write_rawcpuid(rawcpuid, &mut parse, DumpFormat::Parse, cfg, title);
+
+            rawcpuid.write_raw_fmt(&self.cpu_vendor, cfg, buf);
+            RawCpuid::push_commented(buf, &parse);
+
+            return;
+        }
+
+        if let (DumpFormat::Parse, true) = (dump_fmt, header()) {
+            rawcpuid.write_leaf_title(&self.cpu_vendor, title, buf);
+        }
 
+        match dump_fmt {
+            DumpFormat::Parse => {
+                let ctx = ParseContext {
+                    microcode: self.microcode,
+                    addr_size: (rawcpuid.leaf == 0x8000_0008 && rawcpuid.sub_leaf == 0x0)
+                        .then(|| self.addr_size()),
+                };
+
+                rawcpuid.write_parse_ctx_fmt(&self.cpu_vendor, cfg, &ctx, buf)
+            },
+            _ => dump_fmt.rawcpuid_fmt_func()(rawcpuid, &self.cpu_vendor, cfg, buf),
+        }
+    }
+
+    /// One formatted result per leaf/sub-leaf, for "--save-split"
+    fn split_disp(&self, dump_fmt: DumpFormat, cfg: &FormatConfig) -> Vec<(&RawCpuid, String)> {
         self.rawcpuid_pool
             .iter()
-            .map(|rawcpuid| fmt_func(rawcpuid, &self.cpu_vendor))
+            .map(|rawcpuid| {
+                let mut buf = String::new();
+                self.write_rawcpuid(rawcpuid, &mut buf, dump_fmt, cfg, &mut None);
+
+                (rawcpuid, buf)
+            })
             .collect()
     }
 
+    fn node_id_head(node_id: &Option<u32>) -> String {
+        match node_id {
+            Some(node_id) => format!("Node: {node_id:03}, "),
+            None => String::new(),
+        }
+    }
+
+    fn core_type_head(&self) -> String {
+        match self.core_type_info {
+            Some(info) => format!(", {info}"),
+            None => String::new(),
+        }
+    }
+
     fn topo_info_head(&self) -> String {
         match (&self.topo_id, &self.thread_id) {
             (Some(topo), Some(thread_id)) => {
-                let TopoId { pkg_id, core_id, smt_id, x2apic_id } = topo;
+                let TopoId { pkg_id, core_id, smt_id, x2apic_id, node_id } = topo;
+                let node = Self::node_id_head(node_id);
+                let core_type = self.core_type_head();
 
                 format!("\n[\
                     Pkg: {pkg_id:03}, \
+                    {node}\
                     Core: {core_id:03}, \
                     SMT: {smt_id:03}, \
                     x2APIC: {x2apic_id:03}, \
                     Thread: {thread_id:03}\
+                    {core_type}\
                 ]\n")
             },
             (Some(topo), None) => {
-                let TopoId { pkg_id, core_id, smt_id, x2apic_id } = topo;
+                let TopoId { pkg_id, core_id, smt_id, x2apic_id, node_id } = topo;
+                let node = Self::node_id_head(node_id);
 
                 format!("\n[\
                     Pkg: {pkg_id:03}, \
+                    {node}\
                     Core: {core_id:03}, \
                     SMT: {smt_id:03}, \
                     x2APIC: {x2apic_id:03}\
                 ]\n")
             },
-            (_, Some(thread_id)) => format!("[Thread: {thread_id:03}]\n"),
+            (_, Some(thread_id)) => format!("[Thread: {thread_id:03}{}]\n", self.core_type_head()),
             (_, _) => String::new(),
         }
     }
 }
 
+/* "-no-header": only the data rows, set once from the options before dumping */
+static NO_HEADER: AtomicBool = AtomicBool::new(false);
+
+fn set_no_header(no_header: bool) {
+    NO_HEADER.store(no_header, Ordering::Relaxed);
+}
+
+/// The topology header, the column headers, the ruler lines, the titles and the notes of the dump
+fn header() -> bool {
+    !NO_HEADER.load(Ordering::Relaxed)
+}
+
+/* "-print0": NUL-terminated records, set once from the options before dumping */
+static PRINT0: AtomicBool = AtomicBool::new(false);
+
+fn set_print0(print0: bool) {
+    PRINT0.store(print0, Ordering::Relaxed);
+}
+
+fn print0() -> bool {
+    PRINT0.load(Ordering::Relaxed)
+}
+
+/// Default of "--jobs", not to spawn hundreds of threads at once on the large servers
+const MAX_DEFAULT_JOBS: usize = 16;
+
+/// Run `f` for each CPU on at most `jobs` threads, each thread takes the next CPU of the list,
+/// the results are in no particular order
+fn map_cpus<T: Send, F: Fn(usize) -> Option<T> + Sync>(cpus: &[usize], jobs: usize, f: F) -> Vec<T> {
+    use std::thread;
+
+    let next = AtomicUsize::new(0);
+    let progress = progress::Progress::new("threads", cpus.len());
+    let workers = jobs.min(cpus.len());
+
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..workers).map(|_| s.spawn(|| {
+            let mut results = Vec::new();
+
+            while let Some(cpu) = cpus.get(next.fetch_add(1, Ordering::Relaxed)) {
+                results.extend(f(*cpu));
+                progress.inc();
+            }
+
+            results
+        })).collect();
+
+        handles.into_iter().filter_map(|h| h.join().ok()).flatten().collect()
+    })
+}
+
+fn cpu_list() -> Vec<usize> {
+    libcpuid_dump::util::cpu_set_list().unwrap_or_else(|_| {
+        log::error!("failed to get the list of available CPUs");
+        std::process::exit(exit_code::UNSUPPORTED);
+    })
+}
+
+/// Pin the current thread to the CPU, "-v" shows the CPUs failed to pin
+fn pin_thread(cpu: usize) -> Option<()> {
+    libcpuid_dump::util::pin_thread(cpu)
+        .map_err(|e| log::debug!("CPU {cpu}: failed to pin the thread ({e}), skipped"))
+        .ok()
+}
+
+/// "2 of 8 CPUs skipped", the CPUs offline, outside the allowed cpuset, or failed to pin the thread
+fn skipped_cpus_msg(dumped: usize) -> Option<String> {
+    skipped_cpus_msg_of(dumped, libcpuid_dump::util::configured_cpu_count())
+}
+
+/// `skipped_cpus_msg` with the CPU count configured in the OS of the dump, e.g. the recorded session
+fn skipped_cpus_msg_of(dumped: usize, configured: Option<usize>) -> Option<String> {
+    let configured = configured.unwrap_or(dumped).max(dumped);
+    let skipped = configured - dumped;
+
+    if skipped == 0 { return None }
+
+    Some(format!("{skipped} of {configured} CPUs skipped (offline, outside the allowed cpuset or failed to pin)"))
+}
+
+/// "0-3, 5, 8-9"
+fn cpu_ranges(ids: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+    for &id in ids {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == id => *end = id,
+            _ => ranges.push((id, id)),
+        }
+    }
+
+    ranges.iter().map(|&(start, end)| {
+        if start == end { start.to_string() } else { format!("{start}-{end}") }
+    }).collect::<Vec<String>>().join(", ")
+}
+
+/// Run `f` for each CPU on at most `jobs` threads as `map_cpus`, and pass the results to `emit`
+/// in the order of the list, each as soon as it and the preceding ones are done
+fn for_each_cpu_in_order<T, F, E>(cpus: &[usize], jobs: usize, progress: &progress::Progress, f: F, mut emit: E) -> std::io::Result<()>
+where
+    T: Send,
+    F: Fn(usize) -> Option<T> + Sync,
+    E: FnMut(T) -> std::io::Result<()>,
+{
+    use std::collections::BTreeMap;
+    use std::sync::mpsc;
+    use std::thread;
+
+    let next = AtomicUsize::new(0);
+    let workers = jobs.min(cpus.len());
+    let (tx, rx) = mpsc::channel::<(usize, Option<T>)>();
+
+    thread::scope(|s| {
+        for _ in 0..workers {
+            let (tx, next, f) = (tx.clone(), &next, &f);
+
+            s.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(cpu) = cpus.get(i) else { break };
+                let result = f(*cpu);
+
+                progress.inc();
+
+                /* the receiver is dropped if `emit` fails */
+                if tx.send((i, result)).is_err() { break }
+            });
+        }
+
+        drop(tx);
+
+        /* the results finished before the preceding ones */
+        let mut pending: BTreeMap<usize, Option<T>> = BTreeMap::new();
+        let mut pos = 0;
+
+        for (i, result) in rx {
+            pending.insert(i, result);
+
+            while let Some(result) = pending.remove(&pos) {
+                pos += 1;
+
+                if let Some(result) = result {
+                    progress.clear();
+                    emit(result)?;
+                }
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Dump all threads of "-a" to `out`: the first thread at once, then the others in the order of the topology,
+/// each as soon as it and the preceding ones are done.
+/// The format, "-skip-zero", "-diff" and "--jobs" are of `opt`.
 fn dump_all_threads(
-    leaf_pool: &[(u32, u32)],
-    skip_zero: bool,
-    dump_fmt: DumpFormat,
-    diff: bool,
-) -> String {
+    leaf_pool: ThreadLeafPool,
+    opt: &MainOpt,
+    live_head: bool,
+    out: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
     use std::thread;
-    use std::sync::Arc;
-    use libcpuid_dump::util;
 
-    let leaf_pool = Arc::from(leaf_pool);
-    let cpu_list = util::cpu_set_list().unwrap();
-    let mut handles: Vec<thread::JoinHandle<_>> = Vec::with_capacity(cpu_list.len());
+    let MainOpt { skip_zero, fmt: dump_fmt, format_config: ref cfg, diff, .. } = *opt;
 
-    let first = {
+    let cpu_list = cpu_list();
+    let progress = progress::Progress::new("threads", cpu_list.len());
+
+    /* the first CPU the thread can be pinned to, and the topology of the other CPUs to write them in order */
+    let (first, head, mut rest) = {
         /* To confine the effects of pin_thread */
         thread::scope(|s| s.spawn(|| {
-            let cpu = cpu_list[0];
-            util::pin_thread(cpu).unwrap();
+            let mut first: Option<(CpuidDump, String)> = None;
+            let mut rest = Vec::new();
 
-            Arc::new(CpuidDump::new_with_thread_id(&leaf_pool, skip_zero, cpu))
+            for &cpu in &cpu_list {
+                if pin_thread(cpu).is_none() {
+                    progress.inc();
+                    continue;
+                }
+
+                if first.is_none() {
+                    let cpuid_dump = CpuidDump::new_with_thread_id(&leaf_pool.build(), skip_zero, cpu);
+                    let head = if live_head { cpuid_dump.live_head(dump_fmt) } else { String::new() };
+
+                    progress.inc();
+                    first = Some((cpuid_dump, head));
+                } else {
+                    let topo = TopoId::get_topo_info().map(|TopoId { pkg_id, core_id, smt_id, .. }| (pkg_id, core_id, smt_id));
+
+                    rest.push((topo, cpu));
+                }
+            }
+
+            first.map(|(first, head)| (first, head, rest))
         }).join().unwrap())
+    }.unwrap_or_else(|| {
+        log::error!("failed to pin the thread to any of the available CPUs");
+        std::process::exit(exit_code::UNSUPPORTED);
+    });
+
+    /* the same order as `CpuidDump::topo_order` */
+    rest.sort_unstable();
+    let rest: Vec<usize> = rest.into_iter().map(|(_, cpu)| cpu).collect();
+
+    let mut buf = head;
+    let mut writer = AllThreadsWriter::begin(&first, dump_fmt, cfg, diff, skipped_cpus_msg(rest.len() + 1), &mut buf);
+
+    let mut write_buf = |buf: &str| -> std::io::Result<()> {
+        if buf.is_empty() { return Ok(()) }
+
+        out.write_all(buf.as_bytes())?;
+        out.flush()
     };
 
-    for cpu in &cpu_list[1..] {
-        let cpu = *cpu;
-        let leaf_pool = Arc::clone(&leaf_pool);
-        let first = Arc::clone(&first);
+    progress.clear();
+    write_buf(&buf)?;
 
-        handles.push(thread::spawn(move || {
-            util::pin_thread(cpu).unwrap();
+    for_each_cpu_in_order(&rest, opt.jobs(), &progress, |cpu| {
+        pin_thread(cpu)?;
 
-            let cpuid_dump = {
-                let mut sub = CpuidDump::new_with_thread_id(&leaf_pool, skip_zero, cpu);
+        /* the leaf pool of the thread, to sample the leaves hidden on the other cores */
+        let mut sub = CpuidDump::new_with_thread_id(&leaf_pool.build(), skip_zero, cpu);
+        let maxima = sub.leaf_maxima();
 
-                if diff {
-                    let mut first_rawcpuid_pool = first.rawcpuid_pool.iter();
+        if diff {
+            retain_diff(&mut sub.rawcpuid_pool, &first.rawcpuid_pool);
+        }
 
-                    sub.rawcpuid_pool.retain(|sub| {
-                        let Some(first) = first_rawcpuid_pool.next() else { return false };
-                        first != sub
-                    });
-                }
+        Some((sub, maxima))
+    }, |(sub, maxima)| {
+        let mut buf = String::with_capacity(sub.disp_capacity(cfg));
 
-                sub
-            };
+        writer.write(&mut buf, &sub, maxima);
+        write_buf(&buf)
+    })?;
 
-            cpuid_dump
-        }));
+    let mut buf = String::new();
+    writer.finish(&mut buf);
+
+    write_buf(&buf)
+}
+
+/// Display the threads sampled by `cpuid_dump_all_threads`, ordered by topology,
+/// with the note of the skipped CPUs (`skipped_cpus_msg`)
+fn disp_all_threads(cpuid_dumps: &[CpuidDump], dump_fmt: DumpFormat, cfg: &FormatConfig, diff: bool, skipped: Option<String>) -> String {
+    let Some((first, rest)) = cpuid_dumps.split_first() else { return String::new() };
+    let mut buf = String::with_capacity(first.disp_capacity(cfg) * cpuid_dumps.len());
+    let mut writer = AllThreadsWriter::begin(first, dump_fmt, cfg, diff, skipped, &mut buf);
+
+    for cpuid_dump in rest {
+        let mut sub = cpuid_dump.clone();
+        let maxima = sub.leaf_maxima();
+
+        if diff {
+            retain_diff(&mut sub.rawcpuid_pool, &first.rawcpuid_pool);
+        }
+
+        writer.write(&mut buf, &sub, maxima);
     }
 
-    let s = first.top_disp(dump_fmt);
-    let ss: String = handles.into_iter().filter_map(|h| {
-        let cpuid_dump = h.join().ok()?;
-        Some(cpuid_dump.disp(dump_fmt))
-    }).collect();
+    writer.finish(&mut buf);
 
-    format!("{s}{ss}")
+    buf
 }
 
-fn main() {
-    let opt = MainOpt::main_parse();
+/// Writer of the threads after the first one, rolling up the threads identical to the first one
+struct AllThreadsWriter<'a> {
+    first: &'a CpuidDump,
+    dump_fmt: DumpFormat,
+    cfg: &'a FormatConfig,
+    diff: bool,
+    identical: Vec<usize>,
+}
+
+impl<'a> AllThreadsWriter<'a> {
+    /// Write the note of the skipped CPUs and the first thread
+    fn begin(first: &'a CpuidDump, dump_fmt: DumpFormat, cfg: &'a FormatConfig, diff: bool, skipped: Option<String>, buf: &mut String) -> Self {
+        if let (Some(msg), true) = (skipped, header()) {
+            match dump_fmt {
+                f if f.comment_head() => buf.push_str(&format!("# {msg}\n")),
+                _ => buf.push_str(&format!("[{msg}]\n")),
+            }
+        }
+
+        first.write_top_disp(buf, dump_fmt, cfg);
+
+        Self { first, dump_fmt, cfg, diff, identical: Vec::new() }
+    }
+
+    /// The thread omitted by diff is written in the roll-up before the next differing thread
+    fn write(&mut self, buf: &mut String, cpuid_dump: &CpuidDump, maxima: [Option<u32>; 2]) {
+        let Self { first, dump_fmt, cfg, diff, .. } = *self;
+
+        if diff && cpuid_dump.rawcpuid_pool.is_empty() {
+            self.identical.extend(cpuid_dump.thread_id);
+            return;
+        }
+
+        first.write_identical(buf, &self.identical, dump_fmt);
+        self.identical.clear();
+
+        buf.push_str(&cpuid_dump.head(dump_fmt));
+        first.write_maxima_diff(buf, maxima, dump_fmt);
+
+        match dump_fmt {
+            DumpFormat::Parse if diff => cpuid_dump.write_diff_pool(buf, first, cfg),
+            _ => cpuid_dump.write_pool(buf, dump_fmt, cfg),
+        }
+    }
+
+    fn finish(self, buf: &mut String) {
+        self.first.write_identical(buf, &self.identical, self.dump_fmt);
+    }
+}
+
+/// Pin each thread to each CPU, and collect the results without omitting diff
+fn cpuid_dump_all_threads(leaf_pool: ThreadLeafPool, skip_zero: bool, jobs: usize) -> Vec<CpuidDump> {
+    let mut cpuid_dumps: Vec<CpuidDump> = map_cpus(&cpu_list(), jobs, |cpu| {
+        pin_thread(cpu)?;
+
+        Some(CpuidDump::new_with_thread_id(&leaf_pool.build(), skip_zero, cpu))
+    });
+    cpuid_dumps.sort_by_key(CpuidDump::topo_order);
+
+    cpuid_dumps
+}
+
+fn run(opt: &MainOpt) -> std::io::Result<i32> {
+    use std::io::Write;
 
+    set_num_fmt(opt.radix, opt.bit_range);
+    set_no_header(opt.no_header);
+    set_print0(opt.print0);
+
+    let mut out = opt.output.open_lazy();
+    let code = run_with(opt, &mut out)?;
+    out.flush()?;
+
+    Ok(code)
+}
+
+fn run_with(opt: &MainOpt, out: &mut dyn std::io::Write) -> std::io::Result<i32> {
     match opt {
+        MainOpt { version: true, json: true, .. } => {
+            out.write_all(json::version_json().as_bytes())?
+        },
+        MainOpt { version: true, .. } => {
+            writeln!(out, "CPUID Dump {} ({})", env!("CARGO_PKG_VERSION"), env!("CPUID_DUMP_GIT_COMMIT"))?
+        },
+        MainOpt { describe: Some(ref ftr), .. } => {
+            if !opt.describe(out, ftr)? {
+                return Ok(exit_code::CLI_ERROR);
+            }
+        },
+        MainOpt { codename_for: Some((ref vendor, ref fms)), .. } => {
+            opt.codename_for(out, vendor, fms)?
+        },
+        MainOpt { subcommand: Subcommand::Topo, .. } => {
+            opt.topo_pool(out)?
+        },
+        MainOpt { ref exec, .. } if !exec.is_empty() => {
+            opt.custom_pool(out, exec)?
+        },
         MainOpt { leaf: Some(leaf), .. } => {
-            opt.only_leaf(leaf.0, leaf.1).expect("faild only_leaf")
+            opt.only_leaf(out, leaf.0, leaf.1)?
+        },
+        MainOpt { ref saves, .. } if !saves.is_empty() => {
+            opt.save_formats(saves)?
         },
         MainOpt { save_path: Some(ref path), .. } => {
-            opt.save_file(path).expect("faild save_file")
+            opt.save_file(path)?
+        },
+        MainOpt { save_split: Some(ref dir), .. } => {
+            opt.save_split(dir)?
+        },
+        MainOpt { save_append: Some(ref path), .. } => {
+            opt.save_append(path)?
+        },
+        MainOpt { require_level: Some(level), .. } => {
+            match opt.require_level(out, *level)? {
+                Some(true) => {},
+                Some(false) => return Ok(exit_code::CHECK_FAILED),
+                None => return Ok(exit_code::CLI_ERROR),
+            }
+        },
+        MainOpt { check_file: Some(ref path), .. } => {
+            match opt.check_file(out, path)? {
+                Some(true) => {},
+                Some(false) => return Ok(exit_code::CHECK_FAILED),
+                None => return Ok(exit_code::CLI_ERROR),
+            }
+        },
+        MainOpt { strict: true, .. } => {
+            if !opt.strict(out)? {
+                return Ok(exit_code::CHECK_FAILED);
+            }
+        },
+        MainOpt { reference: Some(ref name), .. } => {
+            return opt.reference(out, name, false);
+        },
+        MainOpt { diff_reference: Some(ref name), .. } => {
+            return opt.reference(out, name, true);
+        },
+        MainOpt { compare_model: Some(ref model), .. } => {
+            return opt.compare_model(out, model);
+        },
+        MainOpt { scan: true, .. } => {
+            opt.scan_pool(out)?
+        },
+        MainOpt { kernel_flags: true, .. } => {
+            opt.kernel_flags_pool(out)?
+        },
+        MainOpt { summary: true, .. } => {
+            opt.summary_pool(out)?
+        },
+        MainOpt { xsave: true, .. } => {
+            opt.xsave_pool(out)?
+        },
+        MainOpt { tlb: true, .. } => {
+            opt.tlb_pool(out)?
+        },
+        MainOpt { security: true, .. } => {
+            opt.security_pool(out)?
+        },
+        MainOpt { topology: true, .. } => {
+            opt.topology_pool(out)?
+        },
+        MainOpt { ccx: true, .. } => {
+            opt.ccx_pool(out)?
+        },
+        MainOpt { apic_id: true, .. } => {
+            opt.apic_id_pool(out)?
+        },
+        MainOpt { boost: true, .. } => {
+            opt.boost_pool(out)?
+        },
+        MainOpt { virt: true, .. } => {
+            opt.virt_pool(out)?
+        },
+        MainOpt { confidential: true, .. } => {
+            opt.confidential_pool(out)?
+        },
+        MainOpt { errata: true, .. } => {
+            opt.errata_pool(out)?
+        },
+        MainOpt { emit_schema: true, .. } => {
+            out.write_all(json::SCHEMA.as_bytes())?
+        },
+        MainOpt { json: true, .. } => {
+            opt.json_pool(out)?
+        },
+        MainOpt { load: Some(ref path), .. } => {
+            opt.load(out, path)?
+        },
+        MainOpt { record: Some(ref path), .. } => {
+            opt.record(out, path)?
+        },
+        MainOpt { replay: Some(ref path), .. } => {
+            opt.replay(out, path)?
+        },
+        MainOpt { parse_dir: Some(ref dir), .. } => {
+            opt.parse_dir(out, dir)?
         },
         MainOpt { load_aida64: Some(ref path), .. } => {
-            opt.load_aida64(path).expect("faild load_aida64")
+            opt.load_aida64(out, path)?
         },
         _ => {
-            dump_write(&opt.dump_pool()).expect("faild dump_write")
+            opt.dump_pool(out)?
         },
     }
 
+    Ok(exit_code::SUCCESS)
+}
+
+fn main() {
+    logger::init();
+
+    let opt = MainOpt::main_parse();
+    log::set_max_level(logger::level_filter(opt.quiet, opt.verbose));
+    logger::set_color(opt.color);
+
+    let code = run(&opt).unwrap_or_else(|e| {
+        log::error!("{e}");
+        exit_code::IO_ERROR
+    });
+
+    std::process::exit(code);
+}
+
+#[test]
+fn test_cpu_ranges() {
+    assert_eq!(cpu_ranges(&[1]), "1");
+    assert_eq!(cpu_ranges(&[1, 2, 3, 5, 8, 9]), "1-3, 5, 8-9");
+}
+
+#[test]
+fn test_map_cpus() {
+    let mut even = map_cpus(&(0..40).collect::<Vec<usize>>(), 8, |cpu| (cpu % 2 == 0).then_some(cpu));
+    even.sort_unstable();
+
+    assert_eq!(even, (0..40).step_by(2).collect::<Vec<usize>>());
+    assert!(map_cpus(&[], 8, Some).is_empty());
+}
+
+#[test]
+fn test_for_each_cpu_in_order() {
+    let cpus: Vec<usize> = (0..40).collect();
+    let progress = progress::Progress::new("threads", cpus.len());
+    let mut emitted = Vec::new();
+
+    for_each_cpu_in_order(&cpus, 8, &progress, |cpu| (cpu % 2 == 0).then_some(cpu), |cpu| {
+        emitted.push(cpu);
+        Ok(())
+    }).unwrap();
+
+    assert_eq!(emitted, (0..40).step_by(2).collect::<Vec<usize>>());
+}
+
+#[test]
+fn test_file_source() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/corpus/zen3_ryzen5_5600g.txt");
+    let args = |args: &[&str]| MainOpt::parse_args(config::Config::default(), &args.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+
+    /* the mode flags are applied to the loaded dump, not to the current CPU */
+    for cli in [&["--load", path, "-summary"][..], &["parse", path, "-summary"]] {
+        let mut out = Vec::new();
+
+        run_with(&args(cli).unwrap(), &mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("AMD Ryzen 5 5600G"));
+    }
+
+    /* "-json" of the loaded dump */
+    let mut out = Vec::new();
+
+    run_with(&args(&["parse", path, "-json"]).unwrap(), &mut out).unwrap();
+    let json = String::from_utf8(out).unwrap();
+    assert!(json.contains("\"vendor\":\"AuthenticAMD\""));
+    assert!(json.contains("\"leaf\":1,\"sub_leaf\":0,\"eax\":10817280,"));
+
+    assert!(args(&["--load", path, "--scan"]).is_err());
+    assert!(args(&["parse", path, "--leaf", "0x1"]).is_err());
+    assert!(args(&["--replay", path, "--load", path]).is_err());
+
+    /* one mode at once */
+    assert_eq!(args(&["-summary", "-topology"]).unwrap_err(), "\"-topology\" cannot be used with \"-summary\"");
+    assert!(args(&["topo", "-summary"]).is_err());
+    assert!(args(&["--version", "-json"]).is_ok());
+    assert!(args(&["-summary", "-json"]).is_err());
 }
@@ -0,0 +1,132 @@
+use std::io::{self, BufWriter, Write};
+
+/// Output target of the dump, "--output <target>"
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Output {
+    #[default]
+    Stdout,
+    File(String),
+    /// "host:port"
+    Tcp(String),
+    /// Path of the socket
+    Unix(String),
+}
+
+impl std::str::FromStr for Output {
+    type Err = String;
+
+    /// "-", "tcp://<host>:<port>", "unix://<path>", "file://<path>" or "<path>"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            return Ok(Self::Stdout);
+        }
+
+        if let Some(addr) = s.strip_prefix("tcp://") {
+            let valid = matches!(
+                addr.rsplit_once(':'),
+                Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok()
+            );
+
+            if !valid {
+                return Err(format!("invalid address \"{s}\", expected tcp://<host>:<port>"));
+            }
+
+            return Ok(Self::Tcp(addr.to_string()));
+        }
+
+        let (output, path) = match s.split_once("://") {
+            Some(("unix", path)) => (Self::Unix(path.to_string()), path),
+            Some(("file", path)) => (Self::File(path.to_string()), path),
+            Some((scheme, _)) => return Err(format!("unsupported output \"{scheme}://\"")),
+            None => (Self::File(s.to_string()), s),
+        };
+
+        if path.is_empty() {
+            return Err(format!("missing path in \"{s}\""));
+        }
+
+        Ok(output)
+    }
+}
+
+impl std::fmt::Display for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Stdout => write!(f, "stdout"),
+            Self::File(path) => write!(f, "{path}"),
+            Self::Tcp(addr) => write!(f, "tcp://{addr}"),
+            Self::Unix(path) => write!(f, "unix://{path}"),
+        }
+    }
+}
+
+impl Output {
+    /// Open the sink, connect to the collector for the sockets
+    pub fn open(&self) -> io::Result<Box<dyn Write>> {
+        let with_target = |e: io::Error| io::Error::new(e.kind(), format!("{self}: {e}"));
+
+        let sink: Box<dyn Write> = match self {
+            Self::Stdout => Box::new(io::stdout().lock()),
+            Self::File(path) => Box::new(std::fs::File::create(path).map_err(with_target)?),
+            Self::Tcp(addr) => Box::new(std::net::TcpStream::connect(addr).map_err(with_target)?),
+            #[cfg(unix)]
+            Self::Unix(path) => Box::new(std::os::unix::net::UnixStream::connect(path).map_err(with_target)?),
+            #[cfg(not(unix))]
+            Self::Unix(_) => return Err(with_target(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Unix domain socket is not supported on this platform",
+            ))),
+        };
+
+        Ok(Box::new(BufWriter::new(sink)))
+    }
+
+    /// `open` on the first write, not to truncate the file (or connect to the collector)
+    /// when the run fails before writing anything
+    pub fn open_lazy(&self) -> LazyOutput {
+        LazyOutput { output: self.clone(), sink: None }
+    }
+}
+
+pub struct LazyOutput {
+    output: Output,
+    sink: Option<Box<dyn Write>>,
+}
+
+impl LazyOutput {
+    fn sink(&mut self) -> io::Result<&mut Box<dyn Write>> {
+        if self.sink.is_none() {
+            self.sink = Some(self.output.open()?);
+        }
+
+        Ok(self.sink.as_mut().unwrap())
+    }
+}
+
+impl Write for LazyOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sink()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.sink {
+            Some(sink) => sink.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_lazy_output() {
+    let path = std::env::temp_dir().join(format!("cpuid_dump_lazy_output_{}.txt", std::process::id()));
+    let mut out = Output::File(path.to_string_lossy().to_string()).open_lazy();
+
+    out.flush().unwrap();
+    assert!(!path.exists());
+
+    out.write_all(b"0x00000000").unwrap();
+    out.flush().unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "0x00000000");
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -1,559 +1,247 @@
+use crate::CpuVendor;
+pub use libcpuid_dump::{amd_only_leaf, reg_ftr, Register, FtrBit, FTR_BITS, FTR_REVISION, ftr_digest, FTR_ALIASES, normalize_ftr_name, ftr_aliases, find_ftr};
+use Register::*;
+
 pub(crate) const fn ftr_00_01_edx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "FPU";
-    ftr[1] = "VME";
-    ftr[2] = "DE"; // DebugExt
-    ftr[3] = "PSE";
-    ftr[4] = "TSC";
-    ftr[5] = "MSR";
-    ftr[6] = "PAE";
-    ftr[7] = "MCE";
-    ftr[8] = "CX8";
-    ftr[9] = "APIC";
-    ftr[10] = "";
-    ftr[11] = "SEP";
-    ftr[12] = "MTRR";
-    ftr[13] = "PGE";
-    ftr[14] = "MCA";
-    ftr[15] = "CMOV";
-    ftr[16] = "PAT";
-    ftr[17] = "PSE36";
-    ftr[18] = "PSN";
-    ftr[19] = "CLFLUSH";
-    ftr[20] = "";
-    ftr[21] = "DS";
-    ftr[22] = "ACPI";
-    ftr[23] = "MMX";
-    ftr[24] = "FXSR";
-    ftr[25] = "SSE";
-    ftr[26] = "SSE2";
-    ftr[27] = "SS"; // Self Snoop
-    ftr[28] = "HTT";
-    ftr[29] = "TM"; // Thermal Monitor
-    ftr[30] = "";
-    ftr[31] = "PBE"; // Pending Break Enable
-
-    ftr
+    reg_ftr(0x1, 0x0, EDX)
 }
 
 pub(crate) const fn ftr_00_01_ecx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "SSE3";
-    ftr[1] = "PCLMULQDQ";
-    ftr[2] = "DTES64"; // 64-bit DS Area
-    ftr[3] = "MONITOR"; // MONITOR/MWAIT
-    ftr[4] = "DS-CPL"; // CPL Qualified Debug Store
-    ftr[5] = "VMX"; // Virtual Machine Extensions
-    ftr[6] = "SMX"; // Safer Mode Extensions
-    ftr[7] = "EST"; // Enhanced Intel SpeedStep Technology
-    ftr[8] = "TM2"; // Thermal Monito 2
-    ftr[9] = "SSSE3";
-    ftr[10] = "CNXT-ID"; // L1 Context ID
-    ftr[11] = "SDBG"; // Silicon Debug
-    ftr[12] = "FMA";
-    ftr[13] = "CX16"; // CMPXCHG16B
-    ftr[14] = "xTPR Update Control";
-    ftr[15] = "PDCM"; // Perfmon and Debug Capability
-    ftr[16] = "";
-    ftr[17] = "PCID";
-    ftr[18] = "DCA";
-    ftr[19] = "SSE4.1";
-    ftr[20] = "SSE4.2";
-    ftr[21] = "x2APIC";
-    ftr[22] = "MOVBE";
-    ftr[23] = "POPCNT";
-    ftr[24] = "TSC-Deadline";
-    ftr[25] = "AES"; // AESNI
-    ftr[26] = "XSAVE"; // XSAVE/XRSTOR
-    ftr[27] = "OSXSAVE"; // XSETBV/XGETBV
-    ftr[28] = "AVX";
-    ftr[29] = "F16C";
-    ftr[30] = "RDRAND";
-    ftr[31] = "";
-
-    ftr
+    reg_ftr(0x1, 0x0, ECX)
 }
 
 pub(crate) const fn ftr_00_06_eax_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "DiditalTempSensor";
-    ftr[1] = "TurboBoost";
-    ftr[2] = "ARAT"; // APIC-Timer-always-running, always running APIC timer
-    ftr[3] = "";
-    ftr[4] = "PLN"; // Power Limit Management
-    ftr[5] = "ECMD"; // Clock modulation duty cycle extension
-    ftr[6] = "PTM"; // Package Thermal Management
-    ftr[7] = "HWP";
-    ftr[8] = "HWP_Notification";
-    ftr[9] = "HWP_Activity_Window";
-    ftr[10] = "HWP_Energy_Performance_Preference";
-    ftr[11] = "HWP_Package_Level_Request";
-    ftr[12] = "";
-    ftr[13] = "HDC";
-    ftr[14] = "TurboBoostMax";
-    ftr[15] = "HWP_Capabilities";
-    ftr[16] = "HWP_PECI";
-    ftr[17] = "Flexible_HWP";
-    ftr[18] = "FastAccessMode";
-    ftr[19] = "HFI"; // Hardware Feedback Interface
-    ftr[20] = ""; // Ignoring Idle Logical Processor HWP req
-    ftr[21] = "";
-    ftr[22] = "";
-    ftr[23] = "EHFI";
-
-    ftr
+    reg_ftr(0x6, 0x0, EAX)
 }
 
 pub(crate) const fn ftr_00_07_ebx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "FSGSBASE";
-    ftr[1] = "TSC_Adjust";
-    ftr[2] = "SGX";
-    ftr[3] = "BMI1";
-    ftr[4] = "HLE";
-    ftr[5] = "AVX2";
-    ftr[6] = "FDP_EXCPTN_ONLY";
-    ftr[7] = "SMEP";
-    ftr[8] = "BMI2";
-    ftr[9] = "ERMS"; // Enhanced REP MOVSB/STOSB
-    ftr[10] = "INVPCID";
-    ftr[11] = "RTM";
-    ftr[12] = "PQM"; // AMD: Platform QoS Monitoring, Intel: RDT-M (Resource Director Technology - Monitoring)
-    ftr[13] = "FPU_CS_DS"; // Deprecates FPU CS and FPU DS
-    ftr[14] = "MemoryProtectionExtensions";
-    ftr[15] = "PQE"; // AMD: PQE (Cache Allocation Technology, Platform QoS Allocation?), Intel: RTD-A (Allocation),
-    ftr[16] = "AVX512F";
-    ftr[17] = "AVX512DQ";
-    ftr[18] = "RDSEED";
-    ftr[19] = "ADX";
-    ftr[20] = "SMAP";
-    ftr[21] = "AVX512IFMA";
-    // ftr[22] = "";
-    ftr[23] = "CLFLUSHOPT";
-    ftr[24] = "CLWB";
-    ftr[25] = "ProcessorTrace";
-    ftr[26] = "AVX512PF";
-    ftr[27] = "AVX512ER";
-    ftr[28] = "AVX512CD";
-    ftr[29] = "SHA";
-    ftr[30] = "AVX512BW";
-    ftr[31] = "AVX512VL";
-                 
-    ftr
+    reg_ftr(0x7, 0x0, EBX)
 }
 
 pub(crate) const fn ftr_00_07_ecx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "PREFETCHWT1"; // Intel Xeon Phi only
-    ftr[1] = "AVX512_VBMI";
-    ftr[2] = "UMIP";
-    ftr[3] = "PKU";
-    ftr[4] = "OSPKE";
-    ftr[5] = "WAITPKG";
-    ftr[6] = "AVX512_VBMI2";
-    ftr[7] = "CET_SS";
-    ftr[8] = "GFNI";
-    ftr[9] = "VAES";
-    ftr[10] = "VPCLMULQDQ";
-    ftr[11] = "AVX512_VNNI";
-    ftr[12] = "AVX512_BITALG";
-    ftr[13] = "TME_EN";
-    ftr[14] = "AVX512_VPOPCNTDQ";
-    // ftr[15] = "";
-    ftr[16] = "LA57"; // 57-bit linear addresses
-    /*  The value of MAWAU used by the BNDLDX and BNDSTX instructions in 64-bit mode.
-    ftr[17] = "";
-    ftr[18] = "";
-    ftr[19] = "";
-    ftr[20] = "";
-    ftr[21] = "";
-    */
-    ftr[22] = "RDPID";
-    ftr[23] = "KL"; // Key Locker
-    ftr[24] = "BUS_LOCK_DETECT";
-    ftr[25] = "CLDEMOTE";
-    // ftr[26] = "";
-    ftr[27] = "MOVDIRI";
-    ftr[28] = "MOVDIRI64B";
-    ftr[29] = "ENQCMD"; // Enqueue Stores
-    ftr[30] = "SGX_LC"; // SGX Launch Configuration
-    ftr[31] = "PKS"; // protection keys for supervisor-mode pages
-
-    ftr
+    reg_ftr(0x7, 0x0, ECX)
 }
 
 pub(crate) const fn ftr_00_07_edx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[2] = "AVX512_4VNNIW"; // Intel Xeon Phi only
-    ftr[3] = "AVX512_4FMAPS"; // Intel Xeon Phi only
-    ftr[4] = "FSRM"; // Fast Short REP MOV
-    ftr[5] = "UINTR"; // the processor supports user interrupts
-    // ftr[6] = "";
-    // ftr[7] = "";
-    ftr[8] = "AVX512_VP2INTERSECT";
-    ftr[9] = "SRBDS_CTRL";
-    ftr[10] = "MD_CLEAR";
-    ftr[11] = "RTM_ALWAYS_ABORT";
-    // ftr[12] = "";
-    ftr[13] = "RTM_FORCE_ABORT";
-    ftr[14] = "SERIALIZE";
-    ftr[15] = "Hybrid";
-    ftr[16] = "TSXLDTRK"; // Intel TSX suspend load address tracking
-    // ftr[17] = "";
-    ftr[18] = "PCONFIG";
-    ftr[19] = "ArchitecturalLBR";
-    ftr[20] = "CET_IBT";
-    // ftr[21] = "";
-    ftr[22] = "AMX-BF16";
-    ftr[23] = "AVX512_FP16";
-    ftr[24] = "AMX-TILE";
-    ftr[25] = "AMX-INT8";
-    ftr[26] = "IBRS";
-    ftr[27] = "STIBP";
-    ftr[28] = "L1D_FLUSH";
-    // ftr[29] = "IA32_ARCH_CAPABILITIES";
-    // ftr[30] = "IA32_CORE_CAPABILITIES";
-    ftr[31] = "SSBD";
-
-    ftr
+    reg_ftr(0x7, 0x0, EDX)
 }
 
 pub(crate) const fn ftr_00_07_eax_x1() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[3] = "RAO-INT";
-    ftr[4] = "AVX-VNNI";
-    ftr[5] = "AVX512_BF16";
-    ftr[6] = "LASS"; // Linear Address Space Separation.
-    ftr[7] = "CMPCCXADD";
-    ftr[8] = "ArchPerfmonExt";
-    ftr[10] = "FZRM"; // fast zero-length MOVSB
-    ftr[11] = "FSRS"; // fast short STOSB
-    ftr[12] = "FSRC"; // fast short CMPSB, SCASB
-    /* https://lore.kernel.org/lkml/20221006154041.13001-2-xin3.li@intel.com/ */
-    ftr[18] = "LKGS"; // Load "kernel" (userspace) gs
-    ftr[19] = "WRMSRNS"; // Non-Serializing WRMSR
-    ftr[21] = "AMX-FP16";
-    ftr[22] = "HRESET";
-    ftr[23] = "AVX-IFMA";
-    ftr[26] = "LAM"; // Linear Address Masking
-    ftr[27] = "MSRLIST"; // [RD,WR]MSRLIST
-
-    ftr
+    reg_ftr(0x7, 0x1, EAX)
 }
 
 pub(crate) const fn ftr_00_07_edx_x1() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[4] = "AVX-VNNI-INT8";
-    ftr[5] = "AVX-NE-CONVERT";
-    ftr[14] = "PREFETCHITI";
-    ftr[18] = "CET_SSS"; // CET Supervisor Shadow Stack
-
-    ftr
+    reg_ftr(0x7, 0x1, EDX)
 }
 
 pub(crate) const fn ftr_00_07_edx_x2() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "PSFD"; // Fast Store Forwarding Predictor without disabling Speculative Store Bypass
-    ftr[1] = "IPRED_CTRL";
-    ftr[2] = "RRSBA_CTRL";
-    ftr[3] = "DDPD_U"; // Data Dependent Prefetche
-    ftr[4] = "BHI_CTRL";
-    ftr[5] = "MCDT_NO"; // MXCSR Configuration Dependent Timing
-
-    ftr
+    reg_ftr(0x7, 0x2, EDX)
 }
 
 /* Ref: https://github.com/torvalds/linux/blob/master/arch/x86/kernel/fpu/xstate.c */
 pub(crate) const fn xfeature_mask_00_0d_eax_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "X87";
-    ftr[1] = "SSE";
-    ftr[2] = "AVX256";
-    ftr[3] = "MPX bounds";
-    ftr[4] = "MPX CSR";
-    ftr[5] = "AVX512 opmask"; // KREGS
-    ftr[6] = "AVX512 Hi256";
-    ftr[7] = "AVX512 ZMM_Hi256";
-    ftr[8] = "Processor Trace"; // unused
-    ftr[9] = "Protection Key User";
-    ftr[10] = "PASID";
-    ftr[11] = "";
-    ftr[12] = "";
-    ftr[13] = "";
-    ftr[14] = "";
-    ftr[15] = "";
-    ftr[16] = "";
-    ftr[17] = "AMX Tile config";
-    ftr[18] = "AMX Tile data";
-
-    ftr
+    reg_ftr(0xD, 0x0, EAX)
 }
 
 pub(crate) const fn xsave_00_0d_eax_x1() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "XSAVEOPT";
-    ftr[1] = "XSAVEC";
-    ftr[2] = "XGETBV";
-    ftr[3] = "XSAVES";
-    ftr[4] = "XFD";
-
-    ftr
+    reg_ftr(0xD, 0x1, EAX)
 }
 
 pub(crate) const fn xsave_00_0d_ecx_x1() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[11] = "CET User";
-    ftr[12] = "CET SuperVisor";
-
-    ftr
+    reg_ftr(0xD, 0x1, ECX)
 }
 
 pub(crate) const fn ftr_80_01_ecx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "LAHF/SAHF";
-    ftr[1] = "CmpLegacy";
-    ftr[2] = "SVM";
-    ftr[3] = "Ext_APIC_Space";
-    ftr[4] = "Alt_MOV_CR8";
-    ftr[5] = "ABM";
-    ftr[6] = "SSE4A";
-    ftr[7] = "MisAlignSSE";
-    ftr[8] = "3DNowPrefetch";
-    ftr[9] = "OSVW"; // OS visible workaround
-    ftr[10] = "IBS"; // Instruction based sampling
-    ftr[11] = "XOP"; // Extended operation
-    ftr[12] = "SKINIT"; // SKINIT and STGI
-    ftr[13] = "WDT"; // Watchdog timer
-    ftr[14] = "";
-    ftr[15] = "LWP"; // Lightweight profiling
-    ftr[16] = "FMA4";
-    ftr[17] = "TCE"; // Translation Cache Extension
-    ftr[18] = "";
-    ftr[19] = "";
-    ftr[20] = "";
-    ftr[21] = "TBM"; // Trailing bit manipulation
-    ftr[22] = "TopologyExtensions"; // CPUID Fn8000_001D_EAX_x[N:0]-CPUID Fn8000_001E_EDX
-    ftr[23] = "PerfCtrExtCore"; // Processor performance counter extensions
-    ftr[24] = "PerfCtrExtNB"; // NB performance counter extensions
-    ftr[25] = "";
-    ftr[26] = "DataBkptExt"; // Data access breakpoint extension
-    ftr[27] = "PerfTSC"; // Performance time-stamp counter
-    ftr[28] = "PerfCtrExtLLC"; // L3 performance counter extension
-    ftr[29] = "MONITORX"; // MWAITX/MONITORX
-    ftr[30] = "AddrMaskExt";
-
-    ftr
+    reg_ftr(0x8000_0001, 0x0, ECX)
 }
 
 pub(crate) const fn ftr_80_01_edx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[11] = "SYSCALL/SYSRET";
-    ftr[20] = "NXbit";
-    ftr[26] = "Page1GB";
-    ftr[27] = "RDTSCP";
-    ftr[29] = "LongMode";
-    ftr[30] = "3DNow!Ext";
-    ftr[31] = "3DNow!";
-
-    ftr
+    reg_ftr(0x8000_0001, 0x0, EDX)
 }
 
 pub(crate) const fn ftr_amd_80_07_edx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "TS"; // Temperature Sensor
-    ftr[1] = "";
-    ftr[2] = "";
-    ftr[3] = "TTP"; // THERMTRIP
-    ftr[4] = "TM"; // Hardware thermal control
-    ftr[5] = "";
-    ftr[6] = "100MHzSteps";
-    ftr[7] = "HwPstate";
-    ftr[8] = "TscInvariant";
-    ftr[9] = "CPB"; // Core Performance Boost
-    ftr[10] = "EffFreqRO"; // read-only effective frequency interface
-    ftr[11] = "ProcFeedbackInterface";
-    ftr[12] = "ProcPowerReporting";
-    ftr[13] = "ConnectedStandby";
-    ftr[14] = "RAPL";
-
-    ftr
+    reg_ftr(0x8000_0007, 0x0, EDX)
 }
 
 pub(crate) const fn ftr_80_08_ebx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "CLZERO";
-    ftr[1] = "InstRetCntMsr";
-    ftr[2] = "RstrFpErrPtrs";
-    ftr[3] = "INVLPGB"; // INVLPGB and TLBSYNC instruction
-    ftr[4] = "RDPRU";
-    ftr[5] = "";
-    ftr[6] = "";
-    ftr[7] = "";
-    ftr[8] = "MCOMMIT";
-    ftr[9] = "WBNOINVD";
-    ftr[10] = "";
-    ftr[11] = "";
-    ftr[12] = "IBPB";
-    ftr[13] = "INT_WBINVD";
-    ftr[14] = "IBRS";
-    ftr[15] = "STIBP";
-    ftr[16] = "IBRS_Always_On";
-    ftr[17] = "STIBP_Always_On";
-    ftr[18] = "IBRS_Preferred";
-    ftr[19] = "IBRS_Same_Mode";
-    ftr[20] = "EFER_LMSLE_Unsupported";
-    ftr[21] = "INVLPGB_Nested_Pages";
-    ftr[22] = "";
-    ftr[23] = "";
-    ftr[24] = "SSBD"; // Speculative Store Bypass Disable
-    ftr[25] = "SSBD_Virt_Spec_Ctrl";
-    ftr[26] = "SSBD_Not_Required";
-    ftr[27] = "";
-    ftr[28] = "PSFD"; // Predictive Store Forward Disable
-
-    ftr
+    reg_ftr(0x8000_0008, 0x0, EBX)
 }
 
 pub const fn ftr_amd_80_0a_edx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "NestedPaging";
-    ftr[1] = "LbrVirt";
-    ftr[2] = "SVM_Lock";
-    ftr[3] = "NRIP_Save";
-    ftr[4] = "TSC_Rate_MSR";
-    ftr[5] = "VMCB_Clean";
-    ftr[6] = "FlushByASID";
-    ftr[7] = "DecodeAssists";
-    ftr[8] = "";
-    ftr[9] = "";
-    ftr[10] = "PauseFilter";
-    ftr[11] = "";
-    ftr[12] = "PauseFilterThreshold";
-    ftr[13] = "AVIC"; // AMD virtual interrupt controller
-    ftr[14] = "";
-    ftr[15] = "V_VMSAVE_VMLOAD";
-    ftr[16] = "vGIF"; // Virtualized GIF
-    ftr[17] = "GMET"; // Guest Mode Execute Trap
-    ftr[18] = "x2AVIC";
-    ftr[19] = "SupervisorShadowStack";
-    ftr[20] = "GuestSpecCtrl";
-    ftr[21] = "ROGPT"; // Read-Only Guest Page Table
-    ftr[22] = "";
-    ftr[23] = "HOST_MCE_OVERRIDE";
-    ftr[25] = "VNMI"; // NMI Virtualization
-    ftr[26] = "IbsVirt";
-
-    ftr
+    reg_ftr(0x8000_000A, 0x0, EDX)
 }
 
 pub(crate) const fn ftr_amd_80_1a_eax_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "FP128";
-    ftr[1] = "MOVU";
-    ftr[2] = "FP256";
-
-    ftr
+    reg_ftr(0x8000_001A, 0x0, EAX)
 }
 
 pub const fn ftr_amd_80_1b_eax_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "IBSFFV"; // IBS feature flags valid
-    ftr[1] = "FetchSam"; // IBS fetch sampling
-    ftr[2] = "OpSam"; // IBS execution sampling
-    ftr[3] = "RdWrOpCnt"; // Read write of op counter
-    ftr[4] = "OpCnt"; // Op counting mode
-    ftr[5] = "BrnTrgt"; // Branch target address reporting
-    ftr[6] = "OpCntExt"; // IbsOpCurCnt and IbsOpMaxCnt extend by 7 bits
-    ftr[7] = "RipInvalidChk"; // Invalid RIP indication
-    ftr[8] = "OpBrnFuse"; // Fused branch micro-op indication
-    ftr[11] = "IbsL3MissFiltering";
-
-    ftr
+    reg_ftr(0x8000_001B, 0x0, EAX)
 }
 
 pub const fn ftr_amd_80_1f_eax_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "SME"; // Secure Memory Encryption
-    ftr[1] = "SEV"; // Secure Encrypted Virtualization
-    ftr[2] = "PageFlushMSR";
-    ftr[3] = "SEV-ES"; // SEV Encrypted State
-    ftr[4] = "SEV-SNP"; // SEV Secure Nested Paging
-    ftr[5] = "VMPL"; // VM Permission Levels
-    ftr[6] = "";
-    ftr[7] = "";
-    ftr[8] = "SecureTSC";
-    ftr[9] = "";
-    ftr[10] = "HwEnfCacheCoh"; // Hardware cache coherency across encryption domains enforced
-    ftr[11] = "64BitHost";
-    ftr[12] = "RestrictedInjection";
-    ftr[13] = "AlternateInjection";
-    ftr[14] = "DebugSwap";
-    ftr[15] = "PreventHostIBS";
-    ftr[16] = "VTE"; // Virtual Transparent Encryption
-    ftr[17] = "VmgexitParameter";
-    ftr[18] = "VirtualTomMsr";
-    ftr[19] = "IbsVirtGuestCtl";
-    /* .. */
-    ftr[24] = "VMSARegProt"; // VMSA Register Protection
-    ftr[25] = "SmtProtection";
-    /* .. */
-    ftr[28] = "SvsmCommPageMSR"; // SVSM Communication Page MSR
-    ftr[29] = "NestedVirtSnpMsr";
-
-    ftr
+    reg_ftr(0x8000_001F, 0x0, EAX)
 }
 
-/*
-pub(crate) const fn ftr_amd_80_20_ebx_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[1] = "MBE"; // Memory bandwidth enforcement
-
-    return ftr;
+pub(crate) const fn ftr_amd_80_21_eax_x0() -> [&'static str; 32] {
+    reg_ftr(0x8000_0021, 0x0, EAX)
 }
-*/
 
-pub(crate) const fn ftr_amd_80_21_eax_x0() -> [&'static str; 32] {
-    let mut ftr = [""; 32];
-
-    ftr[0] = "NoNestedDataBp"; // Processor ignores nested data breakpoints
-    ftr[1] = "FsGsKernelGsBaseNonSerializing";
-    ftr[2] = "LFenceAlwaysSerializing";
-    ftr[3] = "SmmPgCfgLock";
-    // ftr[4] = "";
-    // ftr[5] = "";
-    ftr[6] = "NullSelectClearsBase";
-    ftr[7] = "UpperAddressIgnore";
-    ftr[8] = "AutomaticIBRS";
-    ftr[9] = "NoSmmCtlMSR";
-    ftr[10] = "FSRS"; // Fast Short Rep Stosb
-    ftr[11] = "FSRC"; // Fast Short Repe Cmpsb
-    // ftr[12] = "";
-    ftr[13] = "PrefetchCtlMsr";
-    /* */
-    ftr[17] = "CpuidUserDis"; // GpOnUserCpuid
-    ftr[18] = "EPSF"; // Enhanced Predictive Store Forwarding
-    ftr[22] = "Workload_Class";
-
-    ftr
+pub(crate) const fn ftr_amd_80_20_ebx_x0() -> [&'static str; 32] {
+    reg_ftr(0x8000_0020, 0x0, EBX)
+}
+
+/// Feature tables for (leaf, sub_leaf): `(Register index of [EAX, EBX, ECX, EDX], table)`,
+/// without the bits only defined by AMD for the other vendors
+pub(crate) fn ftr_table(leaf: u32, sub_leaf: u32, vendor: &CpuVendor) -> Vec<(usize, [&'static str; 32])> {
+    /* Hygon Dhyana is derived from AMD Zen */
+    let amd = matches!(vendor, CpuVendor::AuthenticAMD | CpuVendor::HygonGenuine);
+
+    if amd_only_leaf(leaf) && !amd {
+        return Vec::new();
+    }
+
+    let mut tables: Vec<(usize, [&'static str; 32])> = Vec::new();
+
+    for f in FTR_BITS.iter().filter(|f| (f.leaf, f.sub_leaf) == (leaf, sub_leaf)) {
+        let idx = f.reg.index();
+        let pos = match tables.iter().position(|(i, _)| *i == idx) {
+            Some(pos) => pos,
+            None => { tables.push((idx, [""; 32])); tables.len() - 1 },
+        };
+
+        if amd || f.vendor != "AMD" {
+            tables[pos].1[f.bit as usize] = f.name;
+        }
+    }
+
+    tables
+}
+
+/// `reg_ftr` of the vendor, see `ftr_table`
+pub(crate) fn vendor_reg_ftr(leaf: u32, sub_leaf: u32, reg: Register, vendor: &CpuVendor) -> [&'static str; 32] {
+    ftr_table(leaf, sub_leaf, vendor)
+        .into_iter()
+        .find(|(idx, _)| *idx == reg.index())
+        .map_or([""; 32], |(_, table)| table)
+}
+
+/// Bits documented as reserved in `CPUID.(EAX=leaf, ECX=sub_leaf):reg`, expected to be zero.
+/// Checked by "--strict" to spot the leaky emulation of the hypervisors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReservedBits {
+    pub leaf: u32,
+    pub sub_leaf: u32,
+    pub reg: Register,
+    pub mask: u32,
+    /// "Intel, AMD", "Intel" or "AMD"
+    pub vendor: &'static str,
+}
+
+const fn rsvd(leaf: u32, sub_leaf: u32, reg: Register, mask: u32) -> ReservedBits {
+    ReservedBits { leaf, sub_leaf, reg, mask, vendor: "Intel, AMD" }
+}
+
+impl ReservedBits {
+    const fn only(self, vendor: &'static str) -> Self {
+        Self { vendor, ..self }
+    }
+
+    /// The vendor-specific masks are not applied to the other vendors
+    pub fn applies_to(&self, vendor: &CpuVendor) -> bool {
+        match vendor {
+            CpuVendor::GenuineIntel => self.vendor.contains("Intel"),
+            CpuVendor::AuthenticAMD |
+            CpuVendor::HygonGenuine => self.vendor.contains("AMD"),
+            _ => self.vendor == "Intel, AMD",
+        }
+    }
+}
+
+pub const RESERVED_BITS: &[ReservedBits] = &[
+    rsvd(0x1, 0x0, ECX, 1 << 16),
+    rsvd(0x1, 0x0, EDX, (1 << 30) | (1 << 20) | (1 << 10)),
+    rsvd(0x6, 0x0, EAX, (1 << 12) | (1 << 3)).only("Intel"),
+    /* Extended Topology Enumeration: EAX[31:5], EBX[31:16], ECX[31:16] */
+    rsvd(0xB, 0x0, EAX, 0xFFFF_FFE0),
+    rsvd(0xB, 0x0, EBX, 0xFFFF_0000),
+    rsvd(0xB, 0x0, ECX, 0xFFFF_0000),
+    rsvd(0xB, 0x1, EAX, 0xFFFF_FFE0),
+    rsvd(0xB, 0x1, EBX, 0xFFFF_0000),
+    rsvd(0xB, 0x1, ECX, 0xFFFF_0000),
+    /* Deterministic Cache Parameters: EAX[13:10], EDX[31:3] */
+    rsvd(0x4, 0x0, EAX, 0x3C00).only("Intel"),
+    rsvd(0x4, 0x0, EDX, 0xFFFF_FFF8).only("Intel"),
+    rsvd(0x4, 0x1, EAX, 0x3C00).only("Intel"),
+    rsvd(0x4, 0x1, EDX, 0xFFFF_FFF8).only("Intel"),
+    rsvd(0x4, 0x2, EAX, 0x3C00).only("Intel"),
+    rsvd(0x4, 0x2, EDX, 0xFFFF_FFF8).only("Intel"),
+    rsvd(0x4, 0x3, EAX, 0x3C00).only("Intel"),
+    rsvd(0x4, 0x3, EDX, 0xFFFF_FFF8).only("Intel"),
+    /* Processor Frequency: [15:0] of EAX, EBX, ECX */
+    rsvd(0x16, 0x0, EAX, 0xFFFF_0000).only("Intel"),
+    rsvd(0x16, 0x0, EBX, 0xFFFF_0000).only("Intel"),
+    rsvd(0x16, 0x0, ECX, 0xFFFF_0000).only("Intel"),
+    rsvd(0x16, 0x0, EDX, 0xFFFF_FFFF).only("Intel"),
+    /* Hybrid Information: EAX only */
+    rsvd(0x1A, 0x0, EBX, 0xFFFF_FFFF).only("Intel"),
+    rsvd(0x1A, 0x0, ECX, 0xFFFF_FFFF).only("Intel"),
+    rsvd(0x1A, 0x0, EDX, 0xFFFF_FFFF).only("Intel"),
+    /* Intel has no vendor ID in the extended leaf */
+    rsvd(0x8000_0000, 0x0, EBX, 0xFFFF_FFFF).only("Intel"),
+    rsvd(0x8000_0000, 0x0, ECX, 0xFFFF_FFFF).only("Intel"),
+    rsvd(0x8000_0000, 0x0, EDX, 0xFFFF_FFFF).only("Intel"),
+    /* Intel: LAHF/SAHF, LZCNT, PREFETCHW; SYSCALL, XD, Page1GB, RDTSCP, Intel 64 */
+    rsvd(0x8000_0001, 0x0, ECX, 0xFFFF_FEDE).only("Intel"),
+    rsvd(0x8000_0001, 0x0, EDX, 0xD3EF_F7FF).only("Intel"),
+    rsvd(0x8000_0008, 0x0, EAX, 0xFF00_0000),
+    rsvd(0x8000_0008, 0x0, ECX, 0xFFFC_0F00).only("AMD"),
+    /* Processor Topology: EBX[31:16], ECX[31:11], EDX */
+    rsvd(0x8000_001E, 0x0, EBX, 0xFFFF_0000).only("AMD"),
+    rsvd(0x8000_001E, 0x0, ECX, 0xFFFF_F800).only("AMD"),
+    rsvd(0x8000_001E, 0x0, EDX, 0xFFFF_FFFF).only("AMD"),
+];
+
+/// Reserved bits of the vendor for (leaf, sub_leaf)
+pub fn reserved_bits<'a>(leaf: u32, sub_leaf: u32, vendor: &'a CpuVendor) -> impl Iterator<Item = &'static ReservedBits> + 'a {
+    RESERVED_BITS
+        .iter()
+        .filter(move |r| (r.leaf, r.sub_leaf) == (leaf, sub_leaf) && r.applies_to(vendor))
+}
+
+#[test]
+fn test_ftr_bits() {
+    for (i, f) in FTR_BITS.iter().enumerate() {
+        assert!(f.bit < 32 && !f.name.is_empty(), "{:?}", f);
+        /* "--describe" of every flag */
+        assert!(!f.desc.is_empty(), "no description: {:?}", f);
+
+        let dup = FTR_BITS[..i].iter().any(|g| (g.leaf, g.sub_leaf, g.reg, g.bit) == (f.leaf, f.sub_leaf, f.reg, f.bit));
+        assert!(!dup, "duplicate bit: {:?}", f);
+    }
+
+    assert_eq!(ftr_00_01_edx_x0()[25], "SSE");
+    assert_eq!(find_ftr("sse").count(), 2);
+
+    for (name, _) in FTR_ALIASES {
+        assert!(find_ftr(name).next().is_some(), "alias of unknown feature: {}", name);
+    }
+    assert!(ftr_table(0x8000_001F, 0x0, &CpuVendor::GenuineIntel).is_empty());
+    assert_eq!(ftr_table(0x8000_001F, 0x0, &CpuVendor::HygonGenuine), ftr_table(0x8000_001F, 0x0, &CpuVendor::AuthenticAMD));
+    assert!(!ftr_table(0x8000_001F, 0x0, &CpuVendor::HygonGenuine).is_empty());
+    /* Intel only defines the Invariant TSC in 0x8000_0007 */
+    let apmi = vendor_reg_ftr(0x8000_0007, 0x0, Register::EDX, &CpuVendor::GenuineIntel);
+    assert_eq!(apmi.iter().filter(|name| !name.is_empty()).collect::<Vec<_>>(), [&"TscInvariant"]);
+    assert_eq!(vendor_reg_ftr(0x8000_0007, 0x0, Register::EDX, &CpuVendor::AuthenticAMD), ftr_amd_80_07_edx_x0());
+    assert_eq!(find_ftr("DiditalTempSensor").count(), 0);
+    assert!(ftr_aliases("DigitalTempSensor").contains(&"DiditalTempSensor"));
+
+    /* the reserved bits common to the vendors have no feature */
+    for r in RESERVED_BITS.iter().filter(|r| r.vendor == "Intel, AMD") {
+        let named = reg_ftr(r.leaf, r.sub_leaf, r.reg)
+            .iter()
+            .enumerate()
+            .any(|(bit, name)| !name.is_empty() && ((r.mask >> bit) & 0b1) == 0b1);
+        assert!(!named, "{:?}", r);
+    }
 }
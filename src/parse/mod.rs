@@ -3,7 +3,9 @@ use crate::CpuidResult;
 mod const_feature_str;
 pub use const_feature_str::*;
 
-#[macro_use]
+mod reg_fields;
+pub use reg_fields::*;
+
 mod parse_util;
 pub use parse_util::*;
 
@@ -15,3 +17,6 @@ pub use parse_amd::*;
 
 mod parse_intel;
 pub use parse_intel::*;
+
+mod vendor_parser;
+pub use vendor_parser::*;
@@ -1,96 +1,91 @@
+use std::fmt::Write;
 use super::*;
-use libcpuid_dump::{TlbType, TlbInfo, Tlb};
+use libcpuid_dump::{Reg, TlbType, TlbEntries, Tlb, AmdBwEnforcement, FormatConfig};
 
-trait PrintTlb {
-    fn print_tlb(&self) -> String;
+trait WriteTlb {
+    fn write_tlb(&self, buf: &mut String);
 }
 
-impl PrintTlb for Tlb {
-    fn print_tlb(&self) -> String {
+impl WriteTlb for Tlb {
+    fn write_tlb(&self, buf: &mut String) {
         const PAD: &str = unsafe { std::str::from_utf8_unchecked(&[b' '; 7]) };
 
-        format!("\
+        write!(buf, "\
             {LN_PAD}[{}TLB 4K: {}\
             {LN_PAD}{PAD} 2M: {}\
             {LN_PAD}{PAD} 4M: {}]\
-        ", self.type_, self.page_4k, self.page_2m, self.page_4m)
+        ", self.type_, self.page_4k, self.page_2m, self.page_4m).unwrap();
     }
 }
 
+/// The decoders write the decoded fields into `buf`, the same as `ParseGeneric`
 pub trait ParseAMD {
-    fn pkgtype_amd_80_01h(&self) -> String;
-    fn l1_amd_80_05h(&self) -> String;
-    fn l2_amd_80_06h(&self) -> String;
-    fn apmi_amd_80_07h(&self) -> String;
-    fn size_id_amd_80_08h(&self) -> String;
-    fn svm_rev_amd_80_0ah_eax_ebx(&self) -> String;
-    fn svm_ftr_amd_80_0ah_edx(&self) -> String;
-    fn l1l2tlb_1g_amd_80_19h(&self) -> String;
-    fn fpu_width_amd_80_1ah(&self) -> String;
-    fn ibs_amd_80_1bh(&self) -> String;
-    fn cpu_topo_amd_80_1eh(&self) -> String;
-    fn encrypt_ftr_amd_80_1fh(&self) -> String;
-    fn reduction_phys_addr_amd_80_1fh(&self) -> String;
-    fn ext_amd_80_21h(&self) -> String;
-    fn amd_ext_topo_80_26h(&self) -> String;
+    fn pkgtype_amd_80_01h(&self, buf: &mut String);
+    fn l1_amd_80_05h(&self, buf: &mut String);
+    fn l2_amd_80_06h(&self, buf: &mut String);
+    fn apmi_amd_80_07h(&self, cfg: &FormatConfig, buf: &mut String);
+    fn size_id_amd_80_08h(&self, buf: &mut String);
+    fn svm_rev_amd_80_0ah_eax_ebx(&self, buf: &mut String);
+    fn svm_ftr_amd_80_0ah_edx(&self, cfg: &FormatConfig, buf: &mut String);
+    fn l1l2tlb_1g_amd_80_19h(&self, buf: &mut String);
+    fn fpu_width_amd_80_1ah(&self, cfg: &FormatConfig, buf: &mut String);
+    fn ibs_amd_80_1bh(&self, cfg: &FormatConfig, buf: &mut String);
+    fn cpu_topo_amd_80_1eh(&self, buf: &mut String);
+    fn encrypt_ftr_amd_80_1fh(&self, cfg: &FormatConfig, buf: &mut String);
+    fn reduction_phys_addr_amd_80_1fh(&self, buf: &mut String);
+    fn pqos_amd_80_20h(&self, sub_leaf: u32, cfg: &FormatConfig, buf: &mut String);
+    fn ext_amd_80_21h(&self, cfg: &FormatConfig, buf: &mut String);
+    fn amd_ext_topo_80_26h(&self, buf: &mut String);
 }
 
 impl ParseAMD for CpuidResult {
-    fn pkgtype_amd_80_01h(&self) -> String {
+    fn pkgtype_amd_80_01h(&self, buf: &mut String) {
         let pkg_type = libcpuid_dump::AmdPkgType::from(self);
 
-        format!("[PkgType: {pkg_type}]")
+        write!(buf, "[PkgType: {pkg_type}]").unwrap();
     }
 
-    fn l1_amd_80_05h(&self) -> String {
-        let l1d_size = self.ecx >> 24; // KiB
-        let l1i_size = self.edx >> 24; // KiB
+    fn l1_amd_80_05h(&self, buf: &mut String) {
+        let l1d_size = field(self.ecx.bits(24..32), "ECX[31:24]"); // KiB
+        let l1i_size = field(self.edx.bits(24..32), "EDX[31:24]"); // KiB
 
-        let l1itlb = Tlb::reg(
+        write!(buf, "[L1D {l1d_size}K/L1I {l1i_size}K]").unwrap();
+
+        Tlb::reg(
             TlbType::L1i,
             (self.ebx & 0xFFFF) as u16,
             (self.eax & 0xFFFF) as u16
-        ).print_tlb();
-        let l1dtlb = Tlb::reg(
+        ).write_tlb(buf);
+        Tlb::reg(
             TlbType::L1d,
-            (self.ebx >> 16) as u16,
-            (self.eax >> 16) as u16
-        ).print_tlb();
-
-        format!("\
-            [L1D {l1d_size}K/L1I {l1i_size}K]\
-            {l1itlb}\
-            {l1dtlb}\
-        ")
+            self.ebx.bits(16..32) as u16,
+            self.eax.bits(16..32) as u16
+        ).write_tlb(buf);
     }
 
-    fn l2_amd_80_06h(&self) -> String {
-        let l2_size = self.ecx >> 16; // KiB
-        let l3_size = (self.edx >> 18) / 2; // 512 KiB
+    fn l2_amd_80_06h(&self, buf: &mut String) {
+        let l2_size = field(self.ecx.bits(16..32), "ECX[31:16]"); // KiB
+        let l3_size = field(self.edx.bits(18..32) / 2, "EDX[31:18]/2"); // 512 KiB
 
-        let l2itlb = Tlb::reg(
+        write!(buf, "[L2 {l2_size}K/L3 {l3_size}M]").unwrap();
+
+        Tlb::reg(
             TlbType::L2i,
             (self.ebx & 0xFFFF) as u16,
             (self.eax & 0xFFFF) as u16
-        ).print_tlb();
-        let l2dtlb = Tlb::reg(
+        ).write_tlb(buf);
+        Tlb::reg(
             TlbType::L2d,
-            (self.ebx >> 16) as u16,
-            (self.eax >> 16) as u16
-        ).print_tlb();
-
-        format!("\
-            [L2 {l2_size}K/L3 {l3_size}M]\
-            {l2itlb}\
-            {l2dtlb}\
-        ")
+            self.ebx.bits(16..32) as u16,
+            self.eax.bits(16..32) as u16
+        ).write_tlb(buf);
     }
 
-    fn apmi_amd_80_07h(&self) -> String {
-        align_mold_ftr(&str_detect_ftr(self.edx, &ftr_amd_80_07_edx_x0()))
+    fn apmi_amd_80_07h(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &str_detect_ftr(self.edx, &ftr_amd_80_07_edx_x0()), cfg)
     }
 
-    fn size_id_amd_80_08h(&self) -> String {
+    fn size_id_amd_80_08h(&self, buf: &mut String) {
         use libcpuid_dump::AmdSizeId;
 
         let AmdSizeId {
@@ -100,41 +95,46 @@ impl ParseAMD for CpuidResult {
             rdpru_max_input,
             invlpgb_max_page,
         } = AmdSizeId::from(self);
+        let perf_tsc_size = field(perf_tsc_size, "40+ECX[17:16]*8");
+        let apic_id_size = field(apic_id_size, "ECX[15:12]");
+        let num_thread = field(num_thread, "ECX[7:0]+1");
+        let rdpru_max_input = field(rdpru_max_input, "EDX[31:16]");
+        let invlpgb_max_page = field(invlpgb_max_page, "EDX[15:0]");
 
-        format!("\
+        write!(buf, "\
             [Num Threads: {num_thread}] [APIC ID: {apic_id_size}-bits]\
             {LN_PAD}[Perf TSC size: {perf_tsc_size}-bits]\
             {LN_PAD}[RDPRU max input: {rdpru_max_input}]\
             {LN_PAD}[INVLPGB max page: {invlpgb_max_page}]\
-        ")
+        ").unwrap();
     }
 
-    fn svm_rev_amd_80_0ah_eax_ebx(&self) -> String {
+    fn svm_rev_amd_80_0ah_eax_ebx(&self, buf: &mut String) {
         let rev = self.eax & 0xFF;
         let nasid = self.ebx;
 
-        format!("[SVM Rev: {rev:#X}] [NASID: {nasid:#X}]")
+        write!(buf, "[SVM Rev: {rev:#X}] [NASID: {nasid:#X}]").unwrap();
     }
 
-    fn svm_ftr_amd_80_0ah_edx(&self) -> String {
-        align_mold_ftr(&str_detect_ftr(self.edx, &ftr_amd_80_0a_edx_x0()))
+    fn svm_ftr_amd_80_0ah_edx(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &str_detect_ftr(self.edx, &ftr_amd_80_0a_edx_x0()), cfg)
     }
 
-    fn l1l2tlb_1g_amd_80_19h(&self) -> String {
+    fn l1l2tlb_1g_amd_80_19h(&self, buf: &mut String) {
         let [eax, ebx] = [self.eax, self.ebx];
 
         /* Inst TLB number of entries for 1-GB pages, size: Bit00-11, assoc: Bit12-15 */
         /* Data TLB number of entries for 1-GB pages, size: Bit16-27, assoc: Bit28-31 */
         let [l1dtlb, l1itlb, l2dtlb, l2itlb] = [
-            (eax >> 16),
-            (eax & 0xFFFF),
-            (ebx >> 16),
-            (ebx & 0xFFFF),
+            eax.bits(16..32),
+            eax.bits(0..16),
+            ebx.bits(16..32),
+            ebx.bits(0..16),
         ].map(|reg|
-            TlbInfo::from_reg_l2(reg as u16)
+            TlbEntries::from_reg_l2(reg as u16)
         );
 
-        format!("\
+        write!(buf, "\
             [L1iTLB 1G: {l1itlb}]\
             {LN_PAD}\
             [L1dTLB 1G: {l1dtlb}]\
@@ -142,18 +142,18 @@ impl ParseAMD for CpuidResult {
             [L2iTLB 1G: {l2itlb}]\
             {LN_PAD}\
             [L2dTLB 1G: {l2dtlb}]\
-        ")
+        ").unwrap();
     }
 
-    fn fpu_width_amd_80_1ah(&self) -> String {
-        align_mold_ftr(&str_detect_ftr(self.eax, &ftr_amd_80_1a_eax_x0()))
+    fn fpu_width_amd_80_1ah(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &str_detect_ftr(self.eax, &ftr_amd_80_1a_eax_x0()), cfg)
     }
 
-    fn ibs_amd_80_1bh(&self) -> String {
-        align_mold_ftr(&str_detect_ftr(self.eax, &ftr_amd_80_1b_eax_x0()))
+    fn ibs_amd_80_1bh(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &str_detect_ftr(self.eax, &ftr_amd_80_1b_eax_x0()), cfg)
     }
 
-    fn cpu_topo_amd_80_1eh(&self) -> String {
+    fn cpu_topo_amd_80_1eh(&self, buf: &mut String) {
         use libcpuid_dump::AmdProcTopo;
         let AmdProcTopo {
             ext_apic_id: _,
@@ -162,68 +162,77 @@ impl ParseAMD for CpuidResult {
             nodes_per_processor: _,
             node_id,
         } = AmdProcTopo::from(self);
+        let node_id = field(node_id, "ECX[7:0]");
+        let core_id = field(core_id, "EBX[7:0]");
+        let threads_per_core = field(threads_per_core, "EBX[15:8]+1");
 
-        format!("\
+        write!(buf, "\
             [NodeId: {node_id}, CoreId: {core_id}]\
             {LN_PAD}\
             [threads per core: {threads_per_core}]\
-        ")
+        ").unwrap();
     }
 
-    fn encrypt_ftr_amd_80_1fh(&self) -> String {
-        align_mold_ftr(&str_detect_ftr(self.eax, &ftr_amd_80_1f_eax_x0()))
+    fn encrypt_ftr_amd_80_1fh(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &str_detect_ftr(self.eax, &ftr_amd_80_1f_eax_x0()), cfg)
     }
 
-    fn reduction_phys_addr_amd_80_1fh(&self) -> String {
+    fn reduction_phys_addr_amd_80_1fh(&self, buf: &mut String) {
         // Reduction of physical address space in bits when 
         // memory encryption is enabled (0 indicates no reduction).
         // [Reserved]: Bit16-31
         // VmplSupported: Bit12-15
         // MemEncryptPhysAddWidth: Bit6-11
         // CBit: Bit00-05
-        let reduction_size = (self.ebx >> 6) & 0x3F;
+        let reduction_size = self.ebx.bits(6..12);
 
         if 0 < reduction_size {
-            format!("{LN_PAD}[MemEncryptPhysAddWidth: {reduction_size}-bits]")
-        } else {
-            "".to_string()
+            let reduction_size = field(reduction_size, "EBX[11:6]");
+
+            write!(buf, "{LN_PAD}[MemEncryptPhysAddWidth: {reduction_size}-bits]").unwrap();
         }
     }
 
-    fn ext_amd_80_21h(&self) -> String {
-        let ftr = align_mold_ftr(&str_detect_ftr(self.eax, &ftr_amd_80_21_eax_x0()));
+    fn pqos_amd_80_20h(&self, sub_leaf: u32, cfg: &FormatConfig, buf: &mut String) {
+        let name = match sub_leaf {
+            0x0 => return write_align_mold_ftr(buf, &str_detect_ftr(self.ebx, &ftr_amd_80_20_ebx_x0()), cfg),
+            0x1 => "L3MBE",
+            0x2 => "L3SMBE",
+            _ => return,
+        };
+        let bw = AmdBwEnforcement::from(self);
+
+        write!(
+            buf,
+            "[{name}: BwLen: {} bits, COS: {}]",
+            field(bw.bw_len, "EAX"),
+            field(bw.cos_count, "EDX+1"),
+        ).unwrap();
+    }
+
+    fn ext_amd_80_21h(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &str_detect_ftr(self.eax, &ftr_amd_80_21_eax_x0()), cfg);
+
         let ucode_patch_size = self.ebx & 0xFFF;
 
         if 0 < ucode_patch_size {
-            format!("\
-                {ftr}\
-                {LN_PAD}[uCodePatchSize: {ucode_patch_size} Bytes]\
-            ")
-        } else {
-            ftr
+            let ucode_patch_size = field(ucode_patch_size, "EBX[11:0]");
+
+            write!(buf, "{LN_PAD}[uCodePatchSize: {ucode_patch_size} Bytes]").unwrap();
         }
     }
 
-    fn amd_ext_topo_80_26h(&self) -> String {
+    fn amd_ext_topo_80_26h(&self, buf: &mut String) {
         let ext_topo = libcpuid_dump::AmdExtTopo::from(self);
 
-        let core_type = match ext_topo.core_type {
-            Some(core_type) => format!("{LN_PAD}[CoreType: {core_type}]"),
-            None => "".to_string(),
-        };
+        write!(buf, "[LevelType: {}, NumProc: {}]", ext_topo.level_type, ext_topo.num_proc).unwrap();
 
-        let nid = match ext_topo.native_model_id {
-            Some(nid) => format!("{LN_PAD}[Model: {nid}]"),
-            None => "".to_string(),
-        };
+        if let Some(nid) = ext_topo.native_model_id {
+            write!(buf, "{LN_PAD}[Model: {nid}]").unwrap();
+        }
 
-        format!("\
-            [LevelType: {}, NumProc: {}]\
-            {nid}\
-            {core_type}\
-        ",
-            ext_topo.level_type,
-            ext_topo.num_proc,
-        )
+        if let Some(core_type) = ext_topo.core_type {
+            write!(buf, "{LN_PAD}[CoreType: {core_type}]").unwrap();
+        }
     }
 }
@@ -1,25 +1,29 @@
-use crate::{CpuidResult, CpuVendor, TOTAL_WIDTH};
+use std::fmt::Write;
+use crate::{CpuidResult, CpuVendor};
+use libcpuid_dump::{AddressSize, FormatConfig};
 use super::*;
 
+/// The decoders write the decoded fields into `buf`, after the registers of the line
 pub trait ParseGeneric {
-    fn info_00_01h(&self, vendor: &CpuVendor) -> String;
-    fn monitor_mwait_00_05h(&self) -> String;
-    fn feature_00_01h(&self) -> String;
-    fn thermal_power_00_06h(&self) -> String;
-    fn feature_00_07h_x0(&self) -> String;
-    fn feature_00_07h_x1(&self) -> String;
-    fn feature_00_07h_x2(&self) -> String;
-    fn topo_ext_00_0bh(&self) -> String;
-    fn xstate_00_0dh(&self, sub_leaf: u32) -> String;
-    fn feature_80_01h(&self) -> String;
-    fn addr_size_80_08h(&self) -> String;
-    fn ftr_ext_id_80_08h_ebx(&self) -> String;
-    fn cpu_name(&self) -> String;
-    fn cache_prop(&self) -> String;
+    fn info_00_01h(&self, vendor: &CpuVendor, microcode: Option<u32>, buf: &mut String);
+    fn monitor_mwait_00_05h(&self, buf: &mut String);
+    fn feature_00_01h(&self, cfg: &FormatConfig, buf: &mut String);
+    fn thermal_power_00_06h(&self, cfg: &FormatConfig, buf: &mut String);
+    fn feature_00_07h_x0(&self, cfg: &FormatConfig, buf: &mut String);
+    fn feature_00_07h_x1(&self, cfg: &FormatConfig, buf: &mut String);
+    fn feature_00_07h_x2(&self, cfg: &FormatConfig, buf: &mut String);
+    fn topo_ext_00_0bh(&self, buf: &mut String);
+    fn xstate_00_0dh(&self, sub_leaf: u32, cfg: &FormatConfig, buf: &mut String);
+    fn feature_80_01h(&self, cfg: &FormatConfig, buf: &mut String);
+    fn apmi_80_07h(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String);
+    fn addr_size_80_08h(&self, addr_size: Option<&AddressSize>, buf: &mut String);
+    fn ftr_ext_id_80_08h_ebx(&self, cfg: &FormatConfig, buf: &mut String);
+    fn cpu_name(&self, buf: &mut String);
+    fn cache_prop(&self, cfg: &FormatConfig, buf: &mut String);
 }
 
 impl ParseGeneric for CpuidResult {
-    fn info_00_01h(&self, vendor: &CpuVendor) -> String {
+    fn info_00_01h(&self, vendor: &CpuVendor, microcode: Option<u32>, buf: &mut String) {
         use libcpuid_dump::{FamModStep, Info01h};
 
         let fms = FamModStep::from(self);
@@ -31,38 +35,48 @@ impl ParseGeneric for CpuidResult {
         } =  Info01h::from(self);
 
         let proc_info = libcpuid_dump::ProcInfo::from_fms(&fms, vendor);
-        let codename = match proc_info.codename {
-            libcpuid_dump::CpuCodename::Unknown(_, _, _) => "".to_string(),
+        let FamModStep { syn_fam, syn_mod, step, raw_eax: _ } = fms;
+
+        write!(buf, "[F: {syn_fam:#X}, M: {syn_mod:#X}, S: {step:#X}]").unwrap();
+
+        if let Some(microcode) = microcode {
+            write!(buf, "{LN_PAD}[Microcode: {microcode:#X}]").unwrap();
+        }
+
+        match proc_info.codename {
+            libcpuid_dump::CpuCodename::Unknown(_, _, _) => {},
             _ => {
-                let step_info = match proc_info.step_info {
-                    libcpuid_dump::CpuStepping::Unknown(_) => "".to_string(),
-                    _ => format!(" ({})", proc_info.step_info),
-                };
+                write!(buf, "{LN_PAD}[Codename: {}", proc_info.codename).unwrap();
 
-                format!("{LN_PAD}[Codename: {}{}]", proc_info.codename, step_info)
+                match proc_info.step_info {
+                    libcpuid_dump::CpuStepping::Unknown(_) => {},
+                    _ => write!(buf, " ({})", proc_info.step_info).unwrap(),
+                }
+
+                buf.push(']');
             },
-        };
-        let node = match proc_info.node {
-            Some(size) => format!("{LN_PAD}[ProcessNode: {size}]"),
-            None => "".to_string(),
-        };
-        let archname = match proc_info.archname {
-            libcpuid_dump::CpuMicroArch::Unknown => "".to_string(),
-            _ => format!("{LN_PAD}[Arch: {}]", proc_info.archname),
-        };
-        let FamModStep { syn_fam, syn_mod, step, raw_eax: _ } = fms;
+        }
+
+        if let Some(size) = proc_info.node {
+            write!(buf, "{LN_PAD}[ProcessNode: {size}]").unwrap();
+        }
+
+        match proc_info.archname {
+            libcpuid_dump::CpuMicroArch::Unknown => {},
+            _ => write!(buf, "{LN_PAD}[Arch: {}]", proc_info.archname).unwrap(),
+        }
 
-        format!("\
-            [F: {syn_fam:#X}, M: {syn_mod:#X}, S: {step:#X}]\
-            {codename}\
-            {node}\
-            {archname}\
+        let local_apic_id = field(local_apic_id, "EBX[31:24]");
+        let max_apic_id = field(max_apic_id, "EBX[23:16]");
+        let clflush_size = field(clflush_size, "EBX[15:8]*8");
+
+        write!(buf, "\
             {LN_PAD}[APIC ID: {local_apic_id:>3}, Max: {max_apic_id:>3}]\
             {LN_PAD}[CLFlush: {clflush_size:3}B]\
-        ")
+        ").unwrap();
     }
 
-    fn monitor_mwait_00_05h(&self) -> String {
+    fn monitor_mwait_00_05h(&self, buf: &mut String) {
         use libcpuid_dump::MonitorMwait;
 
         let MonitorMwait {
@@ -73,154 +87,182 @@ impl ParseGeneric for CpuidResult {
             mwait_sub_states,
         } = MonitorMwait::from(self);
 
-        let mut ftr = String::with_capacity(TOTAL_WIDTH);
+        let [min, max] = [field(min, "EAX[15:0]"), field(max, "EBX[15:0]")];
 
-        if emx { ftr.push_str("[EMX] ") }
-        if ibe { ftr.push_str("[IBE] ") }
+        write!(buf, "[MonitorLineSize: Min {min}, Max {max}]{LN_PAD}").unwrap();
 
-        let mut sub_state = String::with_capacity(TOTAL_WIDTH * 8);
+        if emx { buf.push_str("[EMX] ") }
+        if ibe { buf.push_str("[IBE] ") }
 
         for (i, val) in mwait_sub_states.iter().enumerate() {
             if *val != 0 {
-                sub_state.push_str(
-                    &format!("{LN_PAD}[C{i} sub-state using MWAIT: {val}]")
-                )
+                let val = field(*val, &format!("EDX[{}:{}]", i*4+3, i*4));
+
+                write!(buf, "{LN_PAD}[C{i} sub-state using MWAIT: {val}]").unwrap();
             }
         }
-
-        format!("\
-            [MonitorLineSize: Min {min}, Max {max}]\
-            {LN_PAD}{ftr}{sub_state}\
-        ")
     }
 
-    fn feature_00_01h(&self) -> String {
-        align_mold_ftr(&[
+    fn feature_00_01h(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &[
             str_detect_ftr(self.edx, &ftr_00_01_edx_x0()),
             str_detect_ftr(self.ecx, &ftr_00_01_ecx_x0()),
-        ].concat())
+        ].concat(), cfg)
     }
 
-    fn thermal_power_00_06h(&self) -> String {
-        align_mold_ftr(&str_detect_ftr(self.eax, &ftr_00_06_eax_x0()))
+    fn thermal_power_00_06h(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &str_detect_ftr(self.eax, &ftr_00_06_eax_x0()), cfg)
     }
 
-    fn feature_00_07h_x0(&self) -> String {
-        align_mold_ftr(&[
+    fn feature_00_07h_x0(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &[
             str_detect_ftr(self.ebx, &ftr_00_07_ebx_x0()),
             str_detect_ftr(self.ecx, &ftr_00_07_ecx_x0()),
             str_detect_ftr(self.edx, &ftr_00_07_edx_x0()),
-        ].concat())
+        ].concat(), cfg)
     }
 
-    fn feature_00_07h_x1(&self) -> String {
-        align_mold_ftr(&[
+    fn feature_00_07h_x1(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &[
             str_detect_ftr(self.eax, &ftr_00_07_eax_x1()),
             str_detect_ftr(self.edx, &ftr_00_07_edx_x1()),
-        ].concat())
+        ].concat(), cfg)
     }
 
-    fn feature_00_07h_x2(&self) -> String {
-        align_mold_ftr(&str_detect_ftr(self.edx, &ftr_00_07_edx_x2()))
+    fn feature_00_07h_x2(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &str_detect_ftr(self.edx, &ftr_00_07_edx_x2()), cfg)
     }
 
-    fn topo_ext_00_0bh(&self) -> String {
+    fn topo_ext_00_0bh(&self, buf: &mut String) {
         let topo = libcpuid_dump::IntelExtTopo::from(self);
 
-        format!("[LevelType: {}, num: {}]", topo.level_type, topo.num_proc)
+        write!(buf, "[LevelType: {}, num: {}]", topo.level_type, field(topo.num_proc, "EBX[15:0]")).unwrap();
     }
 
-    fn xstate_00_0dh(&self, sub_leaf: u32) -> String {
-        let size = |eax: u32, txt: &str| -> String {
-            /* 00_0D_X{SUB}:EAX is the state size, EAX = 0 indicates not supported it */
-            if eax != 0x0 {
-                format!("[{txt:<16} save size: {eax:>4}B]")
-            } else {
-                "".to_string()
-            }
-        };
-
+    fn xstate_00_0dh(&self, sub_leaf: u32, cfg: &FormatConfig, buf: &mut String) {
         let eax = self.eax;
-
-        match sub_leaf {
+        let txt = match sub_leaf {
             0x0 => {
-                [
-                    format!("[-XFEATURE Mask-]{LN_PAD}"),
-                    align_mold_ftr(&str_detect_ftr(eax, &xfeature_mask_00_0d_eax_x0())),
-                ]
-                .concat()
+                write!(buf, "[-XFEATURE Mask-]{LN_PAD}").unwrap();
+                return write_align_mold_ftr(buf, &str_detect_ftr(eax, &xfeature_mask_00_0d_eax_x0()), cfg);
             },
             0x1 => {
-                align_mold_ftr(&[
+                return write_align_mold_ftr(buf, &[
                     str_detect_ftr(self.eax, &xsave_00_0d_eax_x1()),
                     str_detect_ftr(self.ecx, &xsave_00_0d_ecx_x1()),
-                ].concat())
+                ].concat(), cfg);
             },
-            0x2 => size(eax, "YMMHI"),
-            0x3 | 0x4 => size(eax, "MPX"),
-            0x5 => size(eax, "KREGS"),
-            0x6 => size(eax, "ZMMHI"),
-            0x7 => size(eax, "HIZMM"),
-            0x8 => size(eax, "IA32_XSS"),
-            0x9 => size(eax, "Protection Key"),
-            0xB => size(eax, "CET User"),
-            0xC => size(eax, "CET SuperVisor"),
-            _ => size(eax, "Unknown"),
+            0x2 => "YMMHI",
+            0x3 | 0x4 => "MPX",
+            0x5 => "KREGS",
+            0x6 => "ZMMHI",
+            0x7 => "HIZMM",
+            0x8 => "IA32_XSS",
+            0x9 => "Protection Key",
+            0xB => "CET User",
+            0xC => "CET SuperVisor",
+            0x11 => "AMX TILECFG",
+            0x12 => "AMX TILEDATA",
+            0x13 => "APX",
+            _ => "Unknown",
+        };
+
+        /* 00_0D_X{SUB}:EAX is the state size, EAX = 0 indicates not supported it */
+        if eax != 0x0 {
+            write!(buf, "[{txt:<16} save size: {:>4}B]", field(eax, "EAX")).unwrap();
         }
     }
 
-    fn feature_80_01h(&self) -> String {
+    fn feature_80_01h(&self, cfg: &FormatConfig, buf: &mut String) {
         /* 0x8000_0001_E{CD}X_x0 */
         let buff = [
             str_detect_ftr(self.ecx, &ftr_80_01_ecx_x0()),
             str_detect_ftr(self.edx, &ftr_80_01_edx_x0()),
         ].concat();
 
-        align_mold_ftr(&buff)
+        write_align_mold_ftr(buf, &buff, cfg)
     }
 
-    fn addr_size_80_08h(&self) -> String {
+    fn apmi_80_07h(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String) {
+        /* Intel only defines EDX[8] (Invariant TSC) */
+        write_align_mold_ftr(buf, &str_detect_ftr(self.edx, &vendor_reg_ftr(0x8000_0007, 0x0, Register::EDX, vendor)), cfg)
+    }
+
+    fn addr_size_80_08h(&self, addr_size: Option<&AddressSize>, buf: &mut String) {
         const LEN: usize = "[Address size:".len();
         const PAD: &str = unsafe { std::str::from_utf8_unchecked(&[b' '; LEN]) };
 
-        let addr_size = libcpuid_dump::AddressSize::from(self);
-        let phy = addr_size.physical;
-        let virt = addr_size.virtual_;
+        /* LAM/UAI are of the other leaves, only with the `AddressSize` of the caller */
+        let upper = addr_size.map(|addr_size| (addr_size.lam, addr_size.uai));
+        let addr_size = addr_size.cloned().unwrap_or_else(|| AddressSize::from(self));
+        let phy = field(addr_size.physical, "EAX[7:0]");
+        let virt = field(addr_size.virtual_, "EAX[15:8]");
 
-        format!("\
+        write!(buf, "\
             [Address size: {phy:2}-bits physical\
-            {LN_PAD}{PAD} {virt:2}-bits virtual]\
-        ")
+            {LN_PAD}{PAD} {virt:2}-bits virtual\
+        ").unwrap();
+
+        /* GuestPhysAddrSize (AMD), 0: the same as the physical address size */
+        if addr_size.guest_physical != 0 {
+            let guest = field(addr_size.guest_physical, "EAX[23:16]");
+
+            write!(buf, "{LN_PAD}{PAD} {guest:2}-bits guest physical").unwrap();
+        }
+
+        buf.push(']');
+
+        if let Some((lam, uai)) = upper {
+            let upper = match (lam, uai) {
+                (true, true) => "LAM, UAI",
+                (true, false) => "LAM",
+                (false, true) => "UAI",
+                (false, false) => "-",
+            };
+
+            write!(buf, "{LN_PAD}[Upper address ignore: {upper}]").unwrap();
+        }
     }
 
-    fn ftr_ext_id_80_08h_ebx(&self) -> String {
-        align_mold_ftr(&str_detect_ftr(self.ebx, &ftr_80_08_ebx_x0()))
+    fn ftr_ext_id_80_08h_ebx(&self, cfg: &FormatConfig, buf: &mut String) {
+        write_align_mold_ftr(buf, &str_detect_ftr(self.ebx, &ftr_80_08_ebx_x0()), cfg)
     }
 
-    fn cpu_name(&self) -> String {
-        let name = libcpuid_dump::ProcName::dec_cpuid(self).to_vec();
+    fn cpu_name(&self, buf: &mut String) {
+        let name = libcpuid_dump::ProcName::dec_cpuid(self);
 
-        String::from_utf8(name).unwrap()
+        buf.push_str(&String::from_utf8_lossy(&name));
     }
 
-    fn cache_prop(&self) -> String {
-        let cache = match libcpuid_dump::CacheProp::option_from_cpuid(self) {
-            Some(prop) => prop,
-            None => return "".to_string(),
-        };
-        
-        let inclusive = if cache.inclusive {
-            "[Inclusive]"
-        } else {
-            ""
-        }.to_string();
-
-        format!("[L{}{},{:>3}_way,{:>4}_{}] {inclusive}",
+    fn cache_prop(&self, cfg: &FormatConfig, buf: &mut String) {
+        let Some(cache) = libcpuid_dump::CacheProp::option_from_cpuid(self) else { return };
+
+        write!(buf, "[L{}{},{:>3}_way,{:>4}_{}] ",
             cache.level,
             &cache.cache_type.to_string()[..1],
-            cache.way,
+            field(cache.way, "EBX[31:22]+1"),
             cache.size_in_the_unit(),
             &cache.size_unit.to_string()[..1],
-        )
+        ).unwrap();
+
+        let share = format!("SharedIDs: {}", field(cache.share_thread, "EAX[25:14]+1"));
+        /* EAX[31:26] is reserved in the AMD leaf (0x8000_001D) */
+        let cores = format!("CoreIDs: {}", field(cache.core_ids, "EAX[31:26]+1"));
+        let mut props = vec![share.as_str()];
+
+        if 1 < cache.core_ids { props.push(&cores); }
+
+        for (flag, name) in [
+            (cache.inclusive, "Inclusive"),
+            (cache.self_init, "SelfInit"),
+            (cache.fully_assoc, "FullyAssoc"),
+            (cache.wbinvd_no_lower, "WBINVD:NoLower"),
+            (cache.complex_index, "ComplexIndex"),
+        ] {
+            if flag { props.push(name); }
+        }
+
+        buf.push_str(LN_PAD);
+        write_align_mold_ftr(buf, &props, cfg);
     }
 }
@@ -1,78 +1,78 @@
+use std::fmt::Write;
 use super::*;
-use crate::PARSE_WIDTH;
 
+/// The decoders write the decoded fields into `buf`, the same as `ParseGeneric`
 pub trait ParseIntel {
-    fn clock_speed_intel_00_16h(&self) -> String;
-    fn intel_tlb_param_00_18h(&self) -> String;
-    fn intel_hybrid_1ah(&self) -> String;
-    fn v2_ext_topo_intel_1fh(&self) -> String;
+    fn sgx_epc_intel_00_12h(&self, sub_leaf: u32, buf: &mut String);
+    fn clock_speed_intel_00_16h(&self, buf: &mut String);
+    fn intel_tlb_param_00_18h(&self, buf: &mut String);
+    fn intel_hybrid_1ah(&self, buf: &mut String);
+    fn v2_ext_topo_intel_1fh(&self, buf: &mut String);
 }
 
 impl ParseIntel for CpuidResult {
-    fn clock_speed_intel_00_16h(&self) -> String {
-        format!(
+    fn sgx_epc_intel_00_12h(&self, sub_leaf: u32, buf: &mut String) {
+        if sub_leaf < 0x2 {
+            return;
+        }
+
+        let Some(epc) = libcpuid_dump::SgxEpcSection::option_from_cpuid(self) else {
+            return;
+        };
+
+        write!(buf, "[EPC: {:#X}, {} MiB]", epc.base, epc.size_in_mib()).unwrap();
+    }
+
+    fn clock_speed_intel_00_16h(&self, buf: &mut String) {
+        write!(
+            buf,
             "[Base {}, Max {}, Bus {} MHz]",
-            self.eax & 0xFFFF,
-            self.ebx & 0xFFFF,
-            self.ecx & 0xFFFF
-        )
+            field(self.eax & 0xFFFF, "EAX[15:0]"),
+            field(self.ebx & 0xFFFF, "EBX[15:0]"),
+            field(self.ecx & 0xFFFF, "ECX[15:0]"),
+        ).unwrap();
     }
 
-    fn intel_tlb_param_00_18h(&self) -> String {
+    fn intel_tlb_param_00_18h(&self, buf: &mut String) {
         let tlb_param = libcpuid_dump::IntelTlbParam::from(self);
 
         if let libcpuid_dump::IntelTlbType::Null = tlb_param.cache_type {
-            return "".to_string();
+            return;
         }
 
-        let mut support_page = String::with_capacity(PARSE_WIDTH);
-        {
-            if tlb_param.support_4k { support_page.push_str("[4K]") }
-            if tlb_param.support_2m { support_page.push_str("[2M]") }
-            if tlb_param.support_4m { support_page.push_str("[4M]") }
-            if tlb_param.support_1g { support_page.push_str("[1GB]") }
-        }
+        write!(buf, "[Type: {}] ", tlb_param.cache_type).unwrap();
 
-        let fully_assoc = if tlb_param.fully_assoc {
-            "{LN_PAD}[Fully Assoc]"
-        } else {
-            ""
-        }.to_string();
+        if tlb_param.support_4k { buf.push_str("[4K]") }
+        if tlb_param.support_2m { buf.push_str("[2M]") }
+        if tlb_param.support_4m { buf.push_str("[4M]") }
+        if tlb_param.support_1g { buf.push_str("[1GB]") }
 
-        let partitioning = if tlb_param.partitioning == 0 {
-            format!("{LN_PAD}[Soft partitioning]")
-        } else {
-            "".to_string()
-        };
+        if tlb_param.fully_assoc {
+            write!(buf, "{LN_PAD}[Fully Assoc]").unwrap();
+        }
+
+        if tlb_param.partitioning == 0 {
+            write!(buf, "{LN_PAD}[Soft partitioning]").unwrap();
+        }
 
-        format!("\
-            [Type: {cache_type}] {support_page}\
-            {fully_assoc}\
-            {partitioning}\
-            {LN_PAD}[way: {way:>3}, set: {set:>3}]\
-        ",
-            cache_type = tlb_param.cache_type,
-            way = tlb_param.way,
-            set = tlb_param.set,
-        )
+        write!(buf, "{LN_PAD}[way: {way:>3}, set: {set:>3}]",
+            way = field(tlb_param.way, "EBX[31:16]"),
+            set = field(tlb_param.set, "ECX"),
+        ).unwrap();
     }
 
-    fn intel_hybrid_1ah(&self) -> String {
+    fn intel_hybrid_1ah(&self, buf: &mut String) {
         use libcpuid_dump::HybridInfo;
 
-        let core_type = match HybridInfo::get_core_type(self) {
-            Some(v) => v,
-            None => return "".to_string(),
-        };
-
+        let Some(core_type) = HybridInfo::get_core_type(self) else { return };
         let native_model_id = HybridInfo::get_native_model_id(self);
 
-        format!("[Type: {core_type}, Model: {native_model_id}]")
+        write!(buf, "[Type: {core_type}, Model: {native_model_id}]").unwrap();
     }
 
-    fn v2_ext_topo_intel_1fh(&self) -> String {
+    fn v2_ext_topo_intel_1fh(&self, buf: &mut String) {
         let topo = libcpuid_dump::IntelExtTopo::from(self);
 
-        format!("[LevelType: {}, num: {}]", topo.level_type, topo.num_proc)
+        write!(buf, "[LevelType: {}, num: {}]", topo.level_type, topo.num_proc).unwrap();
     }
 }
@@ -1,6 +1,11 @@
-use crate::{INPUT_WIDTH, OUTPUT_WIDTH, PARSE_WIDTH};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU8, Ordering};
+use crate::CpuidResult;
+use libcpuid_dump::{FormatConfig, Reg};
 
-const PAD_WIDTH: usize = INPUT_WIDTH + OUTPUT_WIDTH + 1;
+pub const TOTAL_WIDTH: usize = FormatConfig::DEFAULT_TOTAL_WIDTH;
+
+const PAD_WIDTH: usize = FormatConfig::PAD_WIDTH;
 // pub const PAD: &str = unsafe { std::str::from_utf8_unchecked(&[b' '; PAD_WIDTH]) };
 
 const fn ln_pad() -> [u8; PAD_WIDTH+1] {
@@ -12,54 +17,321 @@ const fn ln_pad() -> [u8; PAD_WIDTH+1] {
 
 pub const LN_PAD: &str = unsafe { std::str::from_utf8_unchecked(&ln_pad()) };
 
-#[macro_export]
-macro_rules! lnpad {
-    () => {
-        LN_PAD.to_string()
+/// Radix of the decoded numeric fields (cache ways, sizes, counts)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Radix {
+    #[default]
+    Dec,
+    Hex,
+    Bin,
+}
+
+impl std::str::FromStr for Radix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dec" => Ok(Self::Dec),
+            "hex" => Ok(Self::Hex),
+            "bin" => Ok(Self::Bin),
+            _ => Err(format!("invalid radix \"{s}\", expected \"dec\", \"hex\" or \"bin\"")),
+        }
+    }
+}
+
+/* The parse functions are called per result, set once from the options before dumping */
+static NUM_FMT: AtomicU8 = AtomicU8::new(0);
+const BIT_RANGE: u8 = 0b100;
+
+pub fn set_num_fmt(radix: Radix, bit_range: bool) {
+    let v = radix as u8 | if bit_range { BIT_RANGE } else { 0 };
+
+    NUM_FMT.store(v, Ordering::Relaxed);
+}
+
+fn num_fmt() -> (Radix, bool) {
+    let v = NUM_FMT.load(Ordering::Relaxed);
+    let radix = match v & 0b11 {
+        1 => Radix::Hex,
+        2 => Radix::Bin,
+        _ => Radix::Dec,
     };
+
+    (radix, (v & BIT_RANGE) != 0)
+}
+
+/// Decoded numeric value in the radix of "--radix"
+pub(crate) fn num<T: Into<u64>>(val: T) -> String {
+    let val: u64 = val.into();
+
+    match num_fmt().0 {
+        Radix::Dec => val.to_string(),
+        Radix::Hex => format!("{val:#X}"),
+        Radix::Bin => format!("{val:#b}"),
+    }
+}
+
+/// Decoded numeric value, prefixed with its source bits (e.g. `EBX[31:22]+1 = 8`) by "-bit-range"
+pub(crate) fn field<T: Into<u64>>(val: T, src: &str) -> String {
+    let val = num(val);
+
+    if num_fmt().1 {
+        format!("{src} = {val}")
+    } else {
+        val
+    }
 }
 
 fn u32_to_bool_array(reg: u32) -> [bool; 32] {
     let mut flags = [false; 32];
 
     for (pos, flag) in flags.iter_mut().enumerate() {
-        *flag = ((reg >> pos) & 0b1) != 0;
+        *flag = reg.bit(pos as u32);
     }
 
     flags
 }
 
-pub(crate) fn str_detect_ftr(reg: u32, ftr_str: &[&str]) -> Vec<String> {
+pub(crate) fn str_detect_ftr<'a>(reg: u32, ftr_str: &[&'a str]) -> Vec<&'a str> {
     let flags = u32_to_bool_array(reg);
-    let mut buff: Vec<String> = Vec::with_capacity(32);
+    let mut buff: Vec<&str> = Vec::with_capacity(32);
 
     for (flag, ftr) in flags.iter().zip(ftr_str.iter()) {
         if *flag && !ftr.is_empty() {
-            buff.push(ftr.to_string());
+            buff.push(ftr);
         }
     }
 
     buff
 }
 
-pub(crate) fn align_mold_ftr(ftrs: &[String]) -> String {
-    let mut rest: usize = PARSE_WIDTH;
-    let mut mold = String::with_capacity(ftrs.len() * 48);
+/// Write the features as `[FTR] `, wrapped at `FormatConfig::parse_width`
+pub(crate) fn write_align_mold_ftr(mold: &mut String, ftrs: &[&str], cfg: &FormatConfig) {
+    write_align_mold_ftr_width(mold, ftrs, cfg.parse_width())
+}
 
+/// Write the features as `[FTR] ` from the start of the line, wrapped at `width` (including the trailing space).
+/// A feature is never split, the feature longer than `width` is put alone on the line.
+pub(crate) fn write_align_mold_ftr_width(mold: &mut String, ftrs: &[&str], width: usize) {
     const DECO_LEN: usize = "[] ".len();
-    
+    let mut used: usize = 0;
+
     for f in ftrs {
         let len = f.len() + DECO_LEN;
 
-        if len <= rest {
-            rest -= len;
-        } else {
-            mold += LN_PAD;
-            rest = PARSE_WIDTH.saturating_sub(len);
+        if used != 0 && width < used + len {
+            mold.push_str(LN_PAD);
+            used = 0;
+        }
+
+        for s in [ "[", f, "] " ] {
+            mold.push_str(s);
+        }
+
+        used += len;
+    }
+}
+
+/// Hints for the result of the leaf without the decoder, like `[looks like ASCII: "AuthenticAMD"]`,
+/// the printable string of the registers, the small integers (counts) and the address widths (32-64 bits)
+pub(crate) fn heuristic_hints(cpuid: &CpuidResult) -> String {
+    let mut hints = String::new();
+    write_heuristic_hints(cpuid, &mut hints);
+
+    hints
+}
+
+pub(crate) fn write_heuristic_hints(cpuid: &CpuidResult, buf: &mut String) {
+    let CpuidResult { eax, ebx, ecx, edx } = *cpuid;
+    let regs = [("EAX", eax), ("EBX", ebx), ("ECX", ecx), ("EDX", edx)];
+
+    /* the register order of the vendor ID, then of the processor name */
+    let ascii = [vec![ebx, edx, ecx], vec![eax, ebx, ecx, edx]].iter().find_map(|order| {
+        let mut bytes: Vec<u8> = order.iter().flat_map(|reg| reg.to_le_bytes()).collect();
+
+        while bytes.last() == Some(&0) {
+            bytes.pop();
+        }
+
+        let printable = bytes.iter().all(|b| b.is_ascii_graphic() || *b == b' ');
+
+        (4 <= bytes.len() && printable).then(|| String::from_utf8_lossy(&bytes).into_owned())
+    });
+
+    if let Some(ascii) = ascii {
+        return write!(buf, "[looks like ASCII: \"{ascii}\"]").unwrap();
+    }
+
+    let width = |b: u32| (32..=64).contains(&b);
+    let addr_widths: Vec<String> = regs
+        .iter()
+        .filter(|(_, reg)| 0xFF < *reg && *reg <= 0xFFFF && width(reg & 0xFF) && width(reg >> 8))
+        .map(|(name, reg)| format!("{name} {}/{}", reg & 0xFF, reg >> 8))
+        .collect();
+    let counts: Vec<String> = regs
+        .iter()
+        .filter(|(_, reg)| 0x0 < *reg && *reg <= 0xFF)
+        .map(|(name, reg)| format!("{name}={reg}"))
+        .collect();
+
+    if !addr_widths.is_empty() {
+        write!(buf, "[looks like address widths: {}]", addr_widths.join(", ")).unwrap();
+    }
+
+    if !counts.is_empty() {
+        if !addr_widths.is_empty() { buf.push_str(LN_PAD) }
+        write!(buf, "[looks like counts: {}]", counts.join(", ")).unwrap();
+    }
+}
+
+#[test]
+fn test_heuristic_hints() {
+    let cpuid = CpuidResult { eax: 0x10, ebx: 0x68747541, ecx: 0x444D4163, edx: 0x69746E65 };
+    assert_eq!(heuristic_hints(&cpuid), "[looks like ASCII: \"AuthenticAMD\"]");
+
+    let cpuid = CpuidResult { eax: 0x3930, ebx: 0x8, ecx: 0x0, edx: 0x12345678 };
+    assert_eq!(heuristic_hints(&cpuid), format!("[looks like address widths: EAX 48/57]{LN_PAD}[looks like counts: EBX=8]"));
+
+    assert_eq!(heuristic_hints(&CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 }), "");
+}
+
+/// Decoded fields of the parse output, `(Key, Some(value))` for "[Key: value, ..]", `(FLAG, None)` for "[FLAG]"
+pub(crate) fn decoded_fields(parsed: &str) -> Vec<(&str, Option<&str>)> {
+    /* the outermost "[...]", the bit ranges of "-bit-range" (e.g. "[APIC ID: EBX[31:24] = 1]") are in the item */
+    let mut items: Vec<&str> = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, c) in parsed.char_indices() {
+        match c {
+            '[' => {
+                if depth == 0 { start = i + 1 }
+                depth += 1;
+            },
+            ']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 { items.push(&parsed[start..i]) }
+            },
+            _ => {},
         }
+    }
+
+    items
+        .into_iter()
+        .flat_map(|item| item.split(", "))
+        .map(|field| match field.split_once(": ") {
+            Some((key, value)) => (key.trim(), Some(value.trim())),
+            None => (field.trim(), None),
+        })
+        .collect()
+}
 
-        [ "[", f, "] " ].map(|s| mold.push_str(s));
+/// Name of the feature for the key of "-kv", the characters other than ASCII alphanumerics to '_',
+/// e.g. "SSE4.2" to "SSE4_2", "LAHF/SAHF" to "LAHF_SAHF"
+pub(crate) fn kv_name(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            key.push(c);
+        } else if !key.ends_with('_') {
+            key.push('_');
+        }
     }
 
-    mold
+    key.trim_matches('_').to_string()
+}
+
+/// Decoded fields changed from `base` to `sub`, the values like "APIC ID: 0 → 12",
+/// and the flags set/cleared like "+AVX512F", "-SMT"
+pub(crate) fn diff_fields(base: &str, sub: &str) -> (Vec<String>, Vec<String>) {
+    let mut base = decoded_fields(base);
+    let mut values: Vec<String> = Vec::new();
+    let mut flags: Vec<String> = Vec::new();
+
+    for (key, value) in decoded_fields(sub) {
+        /* the first unpaired field of the same key in `base` */
+        match base.iter().position(|(k, v)| *k == key && v.is_some() == value.is_some()) {
+            Some(pos) => {
+                let (_, base_value) = base.remove(pos);
+
+                if let (Some(base_value), Some(value)) = (base_value, value) {
+                    if base_value != value {
+                        values.push(format!("{key}: {base_value} → {value}"));
+                    }
+                }
+            },
+            None if value.is_none() => flags.push(format!("+{key}")),
+            None => values.push(format!("{key}: - → {}", value.unwrap_or_default())),
+        }
+    }
+
+    for (key, value) in base {
+        match value {
+            Some(value) => values.push(format!("{key}: {value} → -")),
+            None => flags.push(format!("-{key}")),
+        }
+    }
+
+    (values, flags)
+}
+
+#[test]
+fn test_align_mold_ftr() {
+    /* the feature names of the tables, and a feature longer than any width */
+    let long = "X".repeat(200);
+    let mut ftrs: Vec<&str> = [
+        crate::ftr_00_01_ecx_x0(),
+        crate::ftr_00_01_edx_x0(),
+        crate::ftr_00_07_ebx_x0(),
+        crate::ftr_00_07_ecx_x0(),
+        crate::ftr_00_07_edx_x0(),
+    ].iter().flatten().copied().filter(|ftr| !ftr.is_empty()).collect();
+    ftrs.insert(ftrs.len() / 2, &long);
+
+    for width in [0, 1, 16, 24, FormatConfig::DEFAULT.parse_width(), 80, 200] {
+        for n in 0..=ftrs.len() {
+            let mut mold = String::new();
+            write_align_mold_ftr_width(&mut mold, &ftrs[..n], width);
+            let lines: Vec<&str> = mold.split(LN_PAD).collect();
+
+            /* no empty line, and every feature is on a line */
+            assert!(n == 0 || lines.iter().all(|line| !line.is_empty()), "{:?}", lines);
+            assert_eq!(lines.iter().map(|line| line.matches("] ").count()).sum::<usize>(), n);
+
+            for line in &lines {
+                let single = line.matches("] ").count() == 1;
+
+                assert!(line.len() <= width || single, "width {}: {:?}", width, line);
+            }
+
+            /* the features are not split, and in the order */
+            let joined: Vec<&str> = lines
+                .iter()
+                .flat_map(|line| line.split_terminator("] "))
+                .map(|ftr| ftr.trim_start_matches('['))
+                .collect();
+            assert_eq!(joined, &ftrs[..n]);
+        }
+    }
+
+    let mold = |width: usize| {
+        let mut mold = String::new();
+        write_align_mold_ftr_width(&mut mold, &["SSE", "SSE2"], width);
+
+        mold
+    };
+
+    assert_eq!(mold(13), "[SSE] [SSE2] ");
+    assert_eq!(mold(12), format!("[SSE] {LN_PAD}[SSE2] "));
+}
+
+#[test]
+fn test_kv() {
+    assert_eq!(kv_name("SSE4.2"), "SSE4_2");
+    assert_eq!(kv_name("LAHF/SAHF"), "LAHF_SAHF");
+    assert_eq!(
+        decoded_fields("[APIC ID: EBX[31:24] = 10, Max: EBX[23:16] = 12][SSE2]"),
+        [("APIC ID", Some("EBX[31:24] = 10")), ("Max", Some("EBX[23:16] = 12")), ("SSE2", None)],
+    );
 }
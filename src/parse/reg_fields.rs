@@ -0,0 +1,117 @@
+use crate::CpuVendor;
+use super::Register::{self, *};
+
+/// Bit field of the register, `[hi:lo]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegField {
+    pub hi: u32,
+    pub lo: u32,
+    pub name: &'static str,
+}
+
+impl RegField {
+    /// Value of the field in the register
+    pub fn value(&self, reg: u32) -> u32 {
+        (reg >> self.lo) & (u32::MAX >> (31 - (self.hi - self.lo)))
+    }
+}
+
+const fn f(hi: u32, lo: u32, name: &'static str) -> RegField {
+    RegField { hi, lo, name }
+}
+
+const CACHE_PROP_EAX: &[RegField] = &[
+    f(31, 26, "CoreIDs"), f(25, 14, "SharedIDs"), f(9, 9, "FullyAssoc"),
+    f(8, 8, "SelfInit"), f(7, 5, "Level"), f(4, 0, "Type"),
+];
+const CACHE_PROP_EBX: &[RegField] = &[f(31, 22, "Ways"), f(21, 12, "Partitions"), f(11, 0, "LineSize")];
+const EXT_TOPO_EAX: &[RegField] = &[f(4, 0, "Shift")];
+const EXT_TOPO_EBX: &[RegField] = &[f(15, 0, "NumLP")];
+const EXT_TOPO_ECX: &[RegField] = &[f(15, 8, "LevelType"), f(7, 0, "LevelNum")];
+
+/// The field layouts of the registers for "-bin -fields": (leaf, AMD only, register, fields from the MSB),
+/// the same for all the sub-leaves of the leaf
+const REG_FIELDS: &[(u32, bool, Register, &[RegField])] = &[
+    (0x1, false, EAX, &[
+        f(27, 20, "ExtFamily"), f(19, 16, "ExtModel"), f(13, 12, "Type"),
+        f(11, 8, "Family"), f(7, 4, "Model"), f(3, 0, "Stepping"),
+    ]),
+    (0x1, false, EBX, &[f(31, 24, "APIC_ID"), f(23, 16, "MaxLogicalID"), f(15, 8, "CLFlush"), f(7, 0, "BrandIndex")]),
+    (0x4, false, EAX, CACHE_PROP_EAX),
+    (0x4, false, EBX, CACHE_PROP_EBX),
+    (0xB, false, EAX, EXT_TOPO_EAX),
+    (0xB, false, EBX, EXT_TOPO_EBX),
+    (0xB, false, ECX, EXT_TOPO_ECX),
+    (0x1F, false, EAX, EXT_TOPO_EAX),
+    (0x1F, false, EBX, EXT_TOPO_EBX),
+    (0x1F, false, ECX, EXT_TOPO_ECX),
+    (0x8000_0001, true, EBX, &[f(31, 28, "PkgType")]),
+    (0x8000_0008, false, EAX, &[f(23, 16, "GuestPhysAddr"), f(15, 8, "LinearAddr"), f(7, 0, "PhysAddr")]),
+    (0x8000_0008, true, ECX, &[f(17, 16, "PerfTscSize"), f(15, 12, "ApicIdSize"), f(7, 0, "NC")]),
+    (0x8000_001D, true, EAX, CACHE_PROP_EAX),
+    (0x8000_001D, true, EBX, CACHE_PROP_EBX),
+    (0x8000_001E, true, EBX, &[f(15, 8, "ThreadsPerCore"), f(7, 0, "CoreId")]),
+    (0x8000_001E, true, ECX, &[f(10, 8, "NodesPerProc"), f(7, 0, "NodeId")]),
+    (0x8000_001F, true, EBX, &[f(15, 12, "NumVMPL"), f(11, 6, "PhysAddrReduction"), f(5, 0, "CBit")]),
+];
+
+/// Known fields of [EAX, EBX, ECX, EDX] of the leaf, empty for the registers without the layout
+pub fn reg_fields(leaf: u32, vendor: &CpuVendor) -> [&'static [RegField]; 4] {
+    let amd = matches!(vendor, CpuVendor::AuthenticAMD | CpuVendor::HygonGenuine);
+    let mut fields: [&[RegField]; 4] = [&[]; 4];
+
+    for (_, _, reg, f) in REG_FIELDS.iter().filter(|(l, amd_only, _, _)| *l == leaf && (amd || !amd_only)) {
+        fields[reg.index()] = f;
+    }
+
+    fields
+}
+
+/// The markers under the binary of `write_bin_fmt` ("xxxxxxxx_xxxxxxxx_xxxxxxxx_xxxxxxxx"):
+/// "[---]" for the field, '|' for the 1-bit field, ' ' for the bits out of the fields
+pub fn field_overlay(fields: &[RegField]) -> String {
+    /* bit 31 is the first, '_' every 8 bits */
+    let col = |bit: u32| { let idx = (31 - bit) as usize; idx + idx / 8 };
+    let mut line = [b' '; 35];
+
+    for field in fields {
+        let (start, end) = (col(field.hi), col(field.lo));
+
+        if start == end {
+            line[start] = b'|';
+            continue;
+        }
+
+        line[start] = b'[';
+        line[start+1..end].fill(b'-');
+        line[end] = b']';
+    }
+
+    String::from_utf8_lossy(&line).into_owned()
+}
+
+/// "ExtFamily[27:20] ExtModel[19:16] .. Stepping[3:0]", "SelfInit[8]" for the 1-bit field
+pub fn field_legend(fields: &[RegField]) -> String {
+    fields.iter().map(|field| {
+        if field.hi == field.lo {
+            format!("{}[{}]", field.name, field.hi)
+        } else {
+            format!("{}[{}:{}]", field.name, field.hi, field.lo)
+        }
+    }).collect::<Vec<String>>().join(" ")
+}
+
+#[test]
+fn test_reg_fields() {
+    let [eax, ..] = reg_fields(0x1, &CpuVendor::GenuineIntel);
+
+    /*                          00000000_00001000_00000110_11111000 */
+    assert_eq!(field_overlay(eax), "    [-------][--]   [][--] [--][--]");
+    assert_eq!(field_legend(eax), "ExtFamily[27:20] ExtModel[19:16] Type[13:12] Family[11:8] Model[7:4] Stepping[3:0]");
+    assert_eq!(field_overlay(reg_fields(0x4, &CpuVendor::GenuineIntel)[0]).len(), 35);
+    /* Core i9-12900K: 0x90672 */
+    assert_eq!(eax.iter().map(|field| field.value(0x0009_0672)).collect::<Vec<u32>>(), [0x0, 0x9, 0x0, 0x6, 0x7, 0x2]);
+    /* AMD only */
+    assert!(reg_fields(0x8000_001E, &CpuVendor::GenuineIntel).iter().all(|f| f.is_empty()));
+    assert!(!reg_fields(0x8000_001E, &CpuVendor::AuthenticAMD)[1].is_empty());
+}
@@ -0,0 +1,212 @@
+use std::fmt::Write;
+use crate::{CpuidResult, CpuVendor, RawCpuid, RawCpuidFmt};
+use libcpuid_dump::{leaf, AddressSize, FormatConfig};
+use super::*;
+
+/// Inputs of the decoders from outside of the result, `Default` for the result alone
+#[derive(Debug, Clone, Default)]
+pub struct ParseContext {
+    /// Microcode revision from the OS, next to the Family/Model/Stepping of leaf 0x1
+    pub microcode: Option<u32>,
+    /// Address sizes with the support of ignoring the upper address bits from the other leaves,
+    /// for leaf 0x8000_0008
+    pub addr_size: Option<AddressSize>,
+}
+
+/// Leaf decoders of the vendor, chosen once by `vendor_parser`.
+/// The leaves without the vendor-specific decoder fall back to `parse_generic`.
+/// The decoded fields are written into `buf`, after the registers of the line.
+pub trait VendorParser {
+    /// `false` if the leaf has no vendor-specific decoder, nothing is written then
+    fn parse_vendor_leaf(&self, rawcpuid: &RawCpuid, cfg: &FormatConfig, ctx: &ParseContext, buf: &mut String) -> bool;
+
+    fn parse_leaf(&self, rawcpuid: &RawCpuid, vendor: &CpuVendor, cfg: &FormatConfig, ctx: &ParseContext, buf: &mut String) {
+        if !self.parse_vendor_leaf(rawcpuid, cfg, ctx, buf) {
+            parse_generic(rawcpuid, vendor, cfg, ctx, buf)
+        }
+    }
+}
+
+pub struct IntelParser;
+pub struct AmdParser;
+pub struct HygonParser;
+pub struct ZhaoxinParser;
+pub struct HypervisorParser;
+pub struct GenericParser;
+
+pub fn vendor_parser(vendor: &CpuVendor) -> &'static dyn VendorParser {
+    match vendor {
+        CpuVendor::GenuineIntel => &IntelParser,
+        CpuVendor::AuthenticAMD => &AmdParser,
+        CpuVendor::HygonGenuine => &HygonParser,
+        CpuVendor::CentaurHauls |
+        CpuVendor::Shanghai |
+        CpuVendor::Via => &ZhaoxinParser,
+        _ => &GenericParser,
+    }
+}
+
+/// Decoders of the leaves common to the vendors
+pub fn parse_generic(rawcpuid: &RawCpuid, vendor: &CpuVendor, cfg: &FormatConfig, ctx: &ParseContext, buf: &mut String) {
+    let cpuid = rawcpuid.result;
+
+    match rawcpuid.leaf {
+        leaf::VENDOR_ID => write!(buf, "[{vendor}]").unwrap(),
+        leaf::SIGNATURE_FEATURES => {
+            cpuid.info_00_01h(vendor, ctx.microcode, buf);
+            buf.push_str(LN_PAD);
+            cpuid.feature_00_01h(cfg, buf);
+        },
+        leaf::MONITOR_MWAIT => cpuid.monitor_mwait_00_05h(buf),
+        leaf::THERMAL_POWER => cpuid.thermal_power_00_06h(cfg, buf),
+        leaf::STRUCTURED_EXT_FEATURES => match rawcpuid.sub_leaf {
+            0x0 => cpuid.feature_00_07h_x0(cfg, buf),
+            0x1 => cpuid.feature_00_07h_x1(cfg, buf),
+            0x2 => cpuid.feature_00_07h_x2(cfg, buf),
+            _ => {},
+        },
+        leaf::EXT_TOPOLOGY => cpuid.topo_ext_00_0bh(buf),
+        leaf::XSTATE => cpuid.xstate_00_0dh(rawcpuid.sub_leaf, cfg, buf),
+        leaf::EXT_SIGNATURE_FEATURES => cpuid.feature_80_01h(cfg, buf),
+        leaf::ADV_POWER_MGMT => cpuid.apmi_80_07h(vendor, cfg, buf),
+        leaf::PROC_NAME_0..=leaf::PROC_NAME_2 => {
+            buf.push_str("[\"");
+            cpuid.cpu_name(buf);
+            buf.push_str("\"]");
+        },
+        leaf::ADDR_SIZES => {
+            cpuid.addr_size_80_08h(ctx.addr_size.as_ref(), buf);
+            buf.push_str(LN_PAD);
+            cpuid.ftr_ext_id_80_08h_ebx(cfg, buf);
+        },
+        /* the leaf unknown to this crate */
+        _ if libcpuid_dump::LeafName::from_leaf(rawcpuid.leaf, vendor).is_none() => {
+            log::trace!("leaf {:#X}: unknown leaf, the heuristic hints", rawcpuid.leaf);
+            write_heuristic_hints(&cpuid, buf)
+        },
+        _ => {},
+    }
+}
+
+impl VendorParser for IntelParser {
+    fn parse_vendor_leaf(&self, rawcpuid: &RawCpuid, cfg: &FormatConfig, _ctx: &ParseContext, buf: &mut String) -> bool {
+        let cpuid = rawcpuid.result;
+
+        match rawcpuid.leaf {
+            leaf::CACHE_PROPERTIES => cpuid.cache_prop(cfg, buf),
+            leaf::SGX => cpuid.sgx_epc_intel_00_12h(rawcpuid.sub_leaf, buf),
+            leaf::PROCESSOR_FREQUENCY => cpuid.clock_speed_intel_00_16h(buf),
+            leaf::TLB_PARAMETERS => cpuid.intel_tlb_param_00_18h(buf),
+            leaf::HYBRID_INFO => cpuid.intel_hybrid_1ah(buf),
+            leaf::EXT_TOPOLOGY_V2 => cpuid.v2_ext_topo_intel_1fh(buf),
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl VendorParser for AmdParser {
+    fn parse_vendor_leaf(&self, rawcpuid: &RawCpuid, cfg: &FormatConfig, ctx: &ParseContext, buf: &mut String) -> bool {
+        let cpuid = rawcpuid.result;
+
+        match rawcpuid.leaf {
+            leaf::EXT_SIGNATURE_FEATURES => {
+                cpuid.pkgtype_amd_80_01h(buf);
+                buf.push_str(LN_PAD);
+                cpuid.feature_80_01h(cfg, buf);
+            },
+            leaf::AMD_L1_CACHE_TLB => cpuid.l1_amd_80_05h(buf),
+            leaf::L2_L3_CACHE_TLB => cpuid.l2_amd_80_06h(buf),
+            leaf::ADV_POWER_MGMT => cpuid.apmi_amd_80_07h(cfg, buf),
+            leaf::ADDR_SIZES => {
+                cpuid.addr_size_80_08h(ctx.addr_size.as_ref(), buf);
+                buf.push_str(LN_PAD);
+                cpuid.ftr_ext_id_80_08h_ebx(cfg, buf);
+                buf.push_str(LN_PAD);
+                cpuid.size_id_amd_80_08h(buf);
+            },
+            leaf::AMD_SVM => {
+                cpuid.svm_rev_amd_80_0ah_eax_ebx(buf);
+                buf.push_str(LN_PAD);
+                cpuid.svm_ftr_amd_80_0ah_edx(cfg, buf);
+            },
+            leaf::AMD_TLB_1G => cpuid.l1l2tlb_1g_amd_80_19h(buf),
+            leaf::AMD_PERF_OPT => cpuid.fpu_width_amd_80_1ah(cfg, buf),
+            leaf::AMD_IBS => cpuid.ibs_amd_80_1bh(cfg, buf),
+            leaf::AMD_CACHE_PROPERTIES => cpuid.cache_prop(cfg, buf),
+            leaf::AMD_PROC_TOPOLOGY => cpuid.cpu_topo_amd_80_1eh(buf),
+            leaf::SEV => {
+                cpuid.encrypt_ftr_amd_80_1fh(cfg, buf);
+                cpuid.reduction_phys_addr_amd_80_1fh(buf);
+            },
+            leaf::AMD_PQOS_EXT => cpuid.pqos_amd_80_20h(rawcpuid.sub_leaf, cfg, buf),
+            leaf::AMD_EXT_FEATURES_2 => cpuid.ext_amd_80_21h(cfg, buf),
+            leaf::AMD_EXT_TOPOLOGY => cpuid.amd_ext_topo_80_26h(buf),
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+/* Hygon Dhyana is derived from AMD Zen, without the package type of AMD */
+impl VendorParser for HygonParser {
+    fn parse_vendor_leaf(&self, rawcpuid: &RawCpuid, cfg: &FormatConfig, ctx: &ParseContext, buf: &mut String) -> bool {
+        match rawcpuid.leaf {
+            leaf::EXT_SIGNATURE_FEATURES => false,
+            _ => AmdParser.parse_vendor_leaf(rawcpuid, cfg, ctx, buf),
+        }
+    }
+}
+
+impl VendorParser for ZhaoxinParser {
+    fn parse_vendor_leaf(&self, rawcpuid: &RawCpuid, cfg: &FormatConfig, _ctx: &ParseContext, buf: &mut String) -> bool {
+        let cpuid = rawcpuid.result;
+
+        match rawcpuid.leaf {
+            leaf::CACHE_PROPERTIES => cpuid.cache_prop(cfg, buf),
+            leaf::AMD_L1_CACHE_TLB => cpuid.l1_amd_80_05h(buf),
+            leaf::L2_L3_CACHE_TLB => cpuid.l2_amd_80_06h(buf),
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl HypervisorParser {
+    /// Leaves of the hypervisor, independent of the CPU vendor
+    pub fn is_hypervisor_leaf(leaf: u32) -> bool {
+        (0x4000_0000..=0x4000_00FF).contains(&leaf)
+    }
+}
+
+impl VendorParser for HypervisorParser {
+    fn parse_vendor_leaf(&self, rawcpuid: &RawCpuid, _cfg: &FormatConfig, _ctx: &ParseContext, buf: &mut String) -> bool {
+        let CpuidResult { eax, ebx, .. } = rawcpuid.result;
+
+        match rawcpuid.leaf {
+            leaf::HYPERVISOR_VENDOR => {
+                let sig = rawcpuid.ascii().unwrap_or_default();
+
+                write!(buf, "[Hypervisor: \"{sig}\"]{LN_PAD}[Max: {eax:#X}]").unwrap();
+            },
+            /* VMware, KVM: Generic timing information */
+            leaf::HYPERVISOR_TIMING if eax != 0 => {
+                let [tsc, bus] = [field(eax, "EAX"), field(ebx, "EBX")];
+
+                write!(buf, "[TSC: {tsc} kHz, Bus: {bus} kHz]").unwrap();
+            },
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl VendorParser for GenericParser {
+    fn parse_vendor_leaf(&self, _rawcpuid: &RawCpuid, _cfg: &FormatConfig, _ctx: &ParseContext, _buf: &mut String) -> bool {
+        false
+    }
+}
@@ -0,0 +1,127 @@
+use crate::CpuidDump;
+use crate::config::{parse_value, strip_comment, Value};
+use libcpuid_dump::MicroArchLevel;
+use std::convert::TryFrom;
+
+/// Required/forbidden features and the minimum x86-64 micro-architecture level for "--check-file",
+/// the feature names of this tool and the flag names of Linux are accepted.
+///
+/// ```toml
+/// required = ["AVX2", "BMI2", "sha_ni"]
+/// forbidden = ["RTM"]
+/// # 1-4, x86-64-v1..v4
+/// min_level = 3
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    pub required: Vec<String>,
+    pub forbidden: Vec<String>,
+    pub min_level: Option<MicroArchLevel>,
+}
+
+/// Result of a rule, like ("required AVX2", Ok(true))
+pub type RuleResult = (String, Result<bool, String>);
+
+impl Policy {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+
+        Self::parse(&s).map_err(|msg| format!("{path}: {msg}"))
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut policy = Self::default();
+
+        for (i, line) in s.lines().enumerate() {
+            let line = strip_comment(line).trim();
+
+            if line.is_empty() { continue }
+
+            let err = |msg: &str| format!("line {}: {msg}", i+1);
+
+            let (key, value) = line.split_once('=').ok_or_else(|| err("expected `key = value`"))?;
+            let key = key.trim();
+            let value = parse_value(value).ok_or_else(|| err("invalid value"))?;
+
+            match (key, value) {
+                ("required" | "forbidden", Value::Array(array)) => {
+                    let names: Option<Vec<String>> = array
+                        .into_iter()
+                        .map(|v| if let Value::Str(name) = v { Some(name) } else { None })
+                        .collect();
+                    let names = names.ok_or_else(|| err(&format!("{key} must be an array of strings")))?;
+
+                    if key == "required" {
+                        policy.required.extend(names);
+                    } else {
+                        policy.forbidden.extend(names);
+                    }
+                },
+                ("min_level", value) => {
+                    let level = match value {
+                        Value::Int(level) => Some(level),
+                        Value::Str(s) => s.trim_start_matches("x86-64-").trim_start_matches('v').parse::<u32>().ok(),
+                        _ => None,
+                    };
+                    let level = level
+                        .and_then(|level| u8::try_from(level).ok())
+                        .and_then(|level| MicroArchLevel::try_from(level).ok())
+                        .ok_or_else(|| err("min_level must be 1-4 or \"x86-64-v1\"..\"x86-64-v4\""))?;
+
+                    policy.min_level = Some(level);
+                },
+                ("required" | "forbidden", _) => {
+                    return Err(err(&format!("invalid type of value for `{key}`")));
+                },
+                _ => return Err(err(&format!("unknown key `{key}`"))),
+            }
+        }
+
+        Ok(policy)
+    }
+
+    /// Pass/fail of each rule, `Err` for the unknown feature
+    pub fn check(&self, cpuid_dump: &CpuidDump) -> Vec<RuleResult> {
+        let unknown = |name: &str| format!("unknown feature \"{name}\"{}", crate::report::did_you_mean(name));
+
+        let required = self.required.iter().map(|name| {
+            (format!("required {name}"), cpuid_dump.has_feature(name).ok_or_else(|| unknown(name)))
+        });
+        let forbidden = self.forbidden.iter().map(|name| {
+            (format!("forbidden {name}"), cpuid_dump.has_feature(name).map(|has| !has).ok_or_else(|| unknown(name)))
+        });
+        let level = self.min_level.map(|min_level| {
+            let cpuid_array = [
+                cpuid_dump.get_cpuid(0x1, 0x0),
+                cpuid_dump.get_cpuid(0x7, 0x0),
+                cpuid_dump.get_cpuid(0x8000_0001, 0x0),
+            ];
+            let missing = MicroArchLevel::missing_features_from_cpuid_array(min_level, cpuid_array);
+
+            (format!("min_level {min_level}"), Ok(missing.is_empty()))
+        });
+
+        required.chain(forbidden).chain(level).collect()
+    }
+}
+
+#[test]
+fn test_policy() {
+    let policy = Policy::parse("\
+        required = [\"AVX2\", \"sha_ni\"] # comment\n\
+        forbidden = [\"AVX512F\", \"FOO\"]\n\
+        min_level = \"x86-64-v3\"\n\
+    ").unwrap();
+
+    assert_eq!(policy.min_level, Some(MicroArchLevel::X86_64_V3));
+    assert!(Policy::parse("min_level = 5").is_err());
+
+    /* Ryzen 5 5600G */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+    let results: Vec<Result<bool, String>> = policy.check(cpuid_dump).into_iter().map(|(_, r)| r).collect();
+
+    assert_eq!(results, [Ok(true), Ok(true), Ok(true), Err("unknown feature \"FOO\"".to_string()), Ok(true)]);
+}
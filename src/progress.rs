@@ -0,0 +1,48 @@
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// "[12/384 threads]" on stderr while the long scans ("-a", "-scan") run,
+/// only on a terminal and without "-q", the line is cleared when done
+pub struct Progress {
+    label: &'static str,
+    total: usize,
+    done: AtomicUsize,
+    enabled: bool,
+}
+
+impl Progress {
+    pub fn new(label: &'static str, total: usize) -> Self {
+        let enabled = log::log_enabled!(log::Level::Info) && std::io::stderr().is_terminal();
+
+        Self { label, total, done: AtomicUsize::new(0), enabled }
+    }
+
+    /// One more item completed, redraw the line at every 1% of the total
+    pub fn inc(&self) {
+        if !self.enabled { return }
+
+        /* lock stderr first, so that the counts are drawn in order */
+        let mut stderr = std::io::stderr().lock();
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        let step = (self.total / 100).max(1);
+
+        if done.is_multiple_of(step) || done == self.total {
+            let _ = write!(stderr, "\r[{done}/{} {}]", self.total, self.label);
+        }
+    }
+
+    /// Erase the line before writing the results to the terminal, the next `inc` redraws it
+    pub fn clear(&self) {
+        if self.enabled && self.done.load(Ordering::Relaxed) != 0 {
+            let width = format!("[{0}/{0} {1}]", self.total, self.label).len();
+
+            eprint!("\r{:width$}\r", "");
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
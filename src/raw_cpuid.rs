@@ -1,163 +1,460 @@
-use crate::{CpuidResult, CpuVendor};
+use std::fmt::Write;
+use crate::{CpuidResult, CpuVendor, TOTAL_WIDTH};
+use libcpuid_dump::FormatConfig;
 use super::*;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct RawCpuid {
-    pub leaf: u32,
-    pub sub_leaf: u32,
-    pub result: CpuidResult,
+pub use libcpuid_dump::{RawCpuid, ThreadPool, split_raw_dump, sort_by_leaf, dedup_identical, retain_nonzero};
+
+/// The formats of the result: raw, parsed, binary, compat, debug and key-value
+pub trait RawCpuidFmt: Sized {
+    /// Names of the features supported in the result
+    fn features(&self, vendor: &CpuVendor) -> Vec<String>;
+    /// The decoded fields of `write_parse`, for the callers comparing them (diff, "-kv")
+    fn parse(&self, vendor: &CpuVendor, cfg: &FormatConfig) -> String;
+    /// Write the decoded fields by the parser of the vendor (or of the hypervisor leaves)
+    fn write_parse(&self, vendor: &CpuVendor, cfg: &FormatConfig, ctx: &ParseContext, buf: &mut String);
+    fn result(&self, end_str: &str) -> String;
+    fn write_result(&self, buf: &mut String, end_str: &str);
+    /// The input and the registers of `write_result`, before the decoded fields
+    fn write_result_head(&self, buf: &mut String);
+    /// ASCII of the registers for the leaves carrying a string (vendor, hypervisor, processor name),
+    /// non-printable characters are replaced with '.'
+    fn ascii(&self) -> Option<String>;
+    fn write_ascii_column(&self, buf: &mut String);
+    /// One line per result, `from_raw_line` parses it back:
+    /// `<leaf> <sub_leaf>: <eax> <ebx> <ecx> <edx>`, each "0x" and 8 upper hex digits,
+    /// followed by `  # "<ASCII>"` for the leaves carrying a string
+    fn raw_fmt(&self, vendor: &CpuVendor) -> String;
+    fn write_raw_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String);
+    /// The line of `write_raw_fmt` and the block of `write_parse_fmt` commented out
+    fn write_interleaved_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String);
+    /// Push each line of `s` with the '#' prefix
+    fn push_commented(buf: &mut String, s: &str);
+    /// Parse the line of `raw_fmt`, `None` if it does not strictly match the format
+    fn from_raw_line(line: &str) -> Option<Self>;
+    fn parse_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig) -> String;
+    fn write_parse_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String);
+    /// `write_parse_fmt` with the inputs from outside of the result, e.g. the microcode revision of leaf 0x1,
+    /// the support of ignoring the upper address bits (Intel LAM, AMD UAI) of leaf 0x8000_0008
+    fn write_parse_ctx_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, ctx: &ParseContext, buf: &mut String);
+    /// Title line of the leaf like "  [Structured Extended Feature Flags]",
+    /// once for the results of the same leaf (or the leaves of the same name, e.g. Processor Name String)
+    fn write_leaf_title(&self, vendor: &CpuVendor, prev: &mut Option<&'static str>, buf: &mut String);
+    /// `parse_fmt` with only the decoded fields changed from `base`, e.g. "[APIC ID: 0 → 12]"
+    fn write_parse_diff_fmt(&self, base: &RawCpuid, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String);
+    fn write_bin_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String);
+    /// "-bin -fields": `write_bin_fmt` with the markers of the known fields under each row,
+    /// and the legend of the fields of each register
+    fn write_bin_fields_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String);
+    fn write_bin_rows(&self, buf: &mut String, fields: &[&[RegField]; 4]);
+    fn write_compat_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String);
+    fn write_debug_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String);
+    /// "-kv": a `<leaf>.<sub_leaf>.<register>.<field>=<value>` line per known field of the registers (`reg_fields`),
+    /// and per feature flag set (`ftr_table`) with `=1`.
+    /// The keys are the names of the field layouts and of `FTR_BITS`, not of the text of `parse`.
+    fn write_kv_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String);
 }
 
-impl RawCpuid {
-    pub fn exe(leaf: u32, sub_leaf: u32) -> Self {
-        Self {
-            leaf,
-            sub_leaf,
-            result: cpuid!(leaf, sub_leaf),
+impl RawCpuidFmt for RawCpuid {
+    fn features(&self, vendor: &CpuVendor) -> Vec<String> {
+        let CpuidResult { eax, ebx, ecx, edx } = self.result;
+        let regs = [eax, ebx, ecx, edx];
+
+        ftr_table(self.leaf, self.sub_leaf, vendor)
+            .iter()
+            .flat_map(|(idx, table)| str_detect_ftr(regs[*idx], table))
+            .map(|ftr| ftr.to_string())
+            .collect()
+    }
+
+    fn parse(&self, vendor: &CpuVendor, cfg: &FormatConfig) -> String {
+        let mut buf = String::new();
+        self.write_parse(vendor, cfg, &ParseContext::default(), &mut buf);
+
+        buf
+    }
+
+    fn write_parse(&self, vendor: &CpuVendor, cfg: &FormatConfig, ctx: &ParseContext, buf: &mut String) {
+        let parser = if HypervisorParser::is_hypervisor_leaf(self.leaf) {
+            &HypervisorParser
+        } else {
+            vendor_parser(vendor)
+        };
+
+        parser.parse_leaf(self, vendor, cfg, ctx, buf)
+    }
+
+    fn result(&self, end_str: &str) -> String {
+        let mut buf = String::with_capacity(TOTAL_WIDTH);
+        self.write_result(&mut buf, end_str);
+
+        buf
+    }
+
+    fn write_result(&self, buf: &mut String, end_str: &str) {
+        self.write_result_head(buf);
+        buf.push_str(end_str);
+        buf.push('\n');
+    }
+
+    fn write_result_head(&self, buf: &mut String) {
+        let [leaf, sub_leaf] = [self.leaf, self.sub_leaf];
+        let CpuidResult { eax, ebx, ecx, edx } = self.result;
+
+        write!(buf, "  {leaf:#010X} {sub_leaf:#3X}:  {eax:#010X} {ebx:#010X} {ecx:#010X} {edx:#010X}  ").unwrap();
+    }
+
+    fn ascii(&self) -> Option<String> {
+        let CpuidResult { eax, ebx, ecx, edx } = self.result;
+
+        let regs = match self.leaf {
+            0x0 | 0x8000_0000 => vec![ebx, edx, ecx],
+            0x4000_0000 => vec![ebx, ecx, edx],
+            0x8000_0002..=0x8000_0004 => vec![eax, ebx, ecx, edx],
+            _ => return None,
+        };
+
+        let mut bytes: Vec<u8> = regs.iter().flat_map(|reg| reg.to_le_bytes()).collect();
+
+        while bytes.last() == Some(&0) {
+            bytes.pop();
         }
+
+        if bytes.is_empty() {
+            return None;
+        }
+
+        Some(bytes.iter().map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' }).collect())
     }
 
-    pub fn check_result_zero(&self) -> bool {
-        self.result == CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 }
-    }
-
-    fn parse(&self, vendor: &CpuVendor) -> String {
-        let cpuid = self.result;
-
-        match self.leaf {
-            0x0 => format!("[{vendor}]"),
-            0x1 => [
-                cpuid.info_00_01h(vendor),
-                lnpad!(),
-                cpuid.feature_00_01h(),
-            ].concat(),
-            0x5 => cpuid.monitor_mwait_00_05h(),
-            0x6 => cpuid.thermal_power_00_06h(),
-            0x7 => match self.sub_leaf {
-                0x0 => cpuid.feature_00_07h_x0(),
-                0x1 => cpuid.feature_00_07h_x1(),
-                0x2 => cpuid.feature_00_07h_x2(),
-                _ => "".to_string(),
-            },
-            0xB => cpuid.topo_ext_00_0bh(),
-            0xD => cpuid.xstate_00_0dh(self.sub_leaf),
-            0x8000_0001 => [
-                if let CpuVendor::AuthenticAMD = vendor {
-                    [cpuid.pkgtype_amd_80_01h(), lnpad!()].concat()
-                } else {
-                    "".to_string()
-                },
-                cpuid.feature_80_01h(),
-            ].concat(),
-            0x8000_0002..=0x8000_0004 => format!("[\"{}\"]", cpuid.cpu_name()),
-            0x8000_0008 => [
-                cpuid.addr_size_80_08h(),
-                lnpad!(),
-                cpuid.ftr_ext_id_80_08h_ebx(),
-                if let CpuVendor::AuthenticAMD = vendor {
-                    format!("{LN_PAD}{}", cpuid.size_id_amd_80_08h())
-                } else {
-                    "".to_string()
-                },
-            ].concat(),
-            _ => match vendor {
-                CpuVendor::AuthenticAMD => match self.leaf {
-                    0x8000_0005 => cpuid.l1_amd_80_05h(),
-                    0x8000_0006 => cpuid.l2_amd_80_06h(),
-                    0x8000_0007 => cpuid.apmi_amd_80_07h(),
-                    0x8000_000A => [
-                        cpuid.svm_rev_amd_80_0ah_eax_ebx(),
-                        lnpad!(),
-                        cpuid.svm_ftr_amd_80_0ah_edx()
-                    ].concat(),
-                    0x8000_0019 => cpuid.l1l2tlb_1g_amd_80_19h(),
-                    0x8000_001A => cpuid.fpu_width_amd_80_1ah(),
-                    0x8000_001B => cpuid.ibs_amd_80_1bh(),
-                    0x8000_001D => cpuid.cache_prop(),
-                    0x8000_001E => cpuid.cpu_topo_amd_80_1eh(),
-                    0x8000_001F => [
-                        cpuid.encrypt_ftr_amd_80_1fh(),
-                        cpuid.reduction_phys_addr_amd_80_1fh(),
-                    ].concat(),
-                    0x8000_0021 => cpuid.ext_amd_80_21h(),
-                    0x8000_0026 => cpuid.amd_ext_topo_80_26h(),
-                    _ => "".to_string(),
-                },
-                CpuVendor::GenuineIntel => match self.leaf {
-                    0x4 => cpuid.cache_prop(),
-                    0x16 => cpuid.clock_speed_intel_00_16h(),
-                    0x18 => cpuid.intel_tlb_param_00_18h(),
-                    0x1A => cpuid.intel_hybrid_1ah(),
-                    0x1F => cpuid.v2_ext_topo_intel_1fh(),
-                    _ => "".to_string(),
-                },
-                CpuVendor::CentaurHauls |
-                CpuVendor::Shanghai => match self.leaf {
-                    0x4 => cpuid.cache_prop(),
-                    0x8000_0005 => cpuid.l1_amd_80_05h(),
-                    0x8000_0006 => cpuid.l2_amd_80_06h(),
-                    _ => "".to_string(),
-                },
-                _ => "".to_string(),
-            }
+    fn write_ascii_column(&self, buf: &mut String) {
+        if let Some(ascii) = self.ascii() {
+            write!(buf, "  # \"{ascii}\"").unwrap();
         }
     }
 
-    fn result(&self, end_str: &str) -> String {
-        format!(
-            "  {:#010X} {:#3X}:  {:#010X} {:#010X} {:#010X} {:#010X}  {}\n",
-            self.leaf,
-            self.sub_leaf,
-            self.result.eax,
-            self.result.ebx,
-            self.result.ecx,
-            self.result.edx,
-            end_str,
-        )
+    fn raw_fmt(&self, vendor: &CpuVendor) -> String {
+        let mut buf = String::with_capacity(TOTAL_WIDTH);
+        self.write_raw_fmt(vendor, &FormatConfig::DEFAULT, &mut buf);
+
+        buf
+    }
+
+    fn write_raw_fmt(&self, _: &CpuVendor, _: &FormatConfig, buf: &mut String) {
+        write!(buf, "{self}").unwrap();
+        self.write_ascii_column(buf);
+        buf.push('\n');
+    }
+
+    fn write_interleaved_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String) {
+        let mut parse = String::new();
+        self.write_parse_fmt(vendor, cfg, &mut parse);
+
+        self.write_raw_fmt(vendor, cfg, buf);
+        Self::push_commented(buf, &parse);
+    }
+
+    fn push_commented(buf: &mut String, s: &str) {
+        for line in s.lines() {
+            buf.push('#');
+            buf.push_str(line);
+            buf.push('\n');
+        }
+    }
+
+    fn from_raw_line(line: &str) -> Option<Self> {
+        line.parse().ok()
+    }
+
+    fn parse_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig) -> String {
+        let mut buf = String::with_capacity(cfg.total_width);
+        self.write_parse_fmt(vendor, cfg, &mut buf);
+
+        buf
+    }
+
+    fn write_parse_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String) {
+        self.write_parse_ctx_fmt(vendor, cfg, &ParseContext::default(), buf)
+    }
+
+    fn write_parse_ctx_fmt(&self, vendor: &CpuVendor, cfg: &FormatConfig, ctx: &ParseContext, buf: &mut String) {
+        self.write_result_head(buf);
+        self.write_parse(vendor, cfg, ctx, buf);
+        buf.push('\n');
+    }
+
+    fn write_leaf_title(&self, vendor: &CpuVendor, prev: &mut Option<&'static str>, buf: &mut String) {
+        let name = libcpuid_dump::LeafName::from_leaf(self.leaf, vendor);
+
+        if name.is_some() && name != *prev {
+            writeln!(buf, "  [{}]", name.unwrap_or_default()).unwrap();
+        }
+
+        *prev = name;
+    }
+
+    fn write_parse_diff_fmt(&self, base: &RawCpuid, vendor: &CpuVendor, cfg: &FormatConfig, buf: &mut String) {
+        let (values, flags) = diff_fields(&base.parse(vendor, cfg), &self.parse(vendor, cfg));
+        let flags: Vec<&str> = flags.iter().map(|s| s.as_str()).collect();
+        let mut end_str = values
+            .iter()
+            .map(|s| format!("[{s}]"))
+            .collect::<Vec<String>>()
+            .join(LN_PAD);
+
+        if !flags.is_empty() {
+            if !end_str.is_empty() { end_str.push_str(LN_PAD) }
+            write_align_mold_ftr(&mut end_str, &flags, cfg);
+        }
+
+        self.write_result(buf, &end_str)
     }
 
-    pub fn raw_fmt(&self, _: &CpuVendor) -> String {
-        self.result("")
+    fn write_bin_fmt(&self, _: &CpuVendor, _: &FormatConfig, buf: &mut String) {
+        self.write_bin_rows(buf, &[&[]; 4]);
     }
 
-    pub fn parse_fmt(&self, vendor: &CpuVendor) -> String {
-        self.result(&self.parse(vendor))
+    fn write_bin_fields_fmt(&self, vendor: &CpuVendor, _: &FormatConfig, buf: &mut String) {
+        const PAD: &str = unsafe { std::str::from_utf8_unchecked(&[b' '; 18]) };
+
+        let fields = reg_fields(self.leaf, vendor);
+
+        self.write_bin_rows(buf, &fields);
+
+        for (reg, fields) in ["EAX", "EBX", "ECX", "EDX"].iter().zip(fields) {
+            if fields.is_empty() { continue }
+
+            writeln!(buf, "{PAD} {reg}: {}", field_legend(fields)).unwrap();
+        }
     }
 
-    pub fn bin_fmt(&self, _: &CpuVendor) -> String {
-        let separate = |reg: u32| -> String {
-            let tmp = format!("{reg:032b}");
+    /* the rows of EAX/EBX and ECX/EDX, each followed by the markers of the fields if any */
+    fn write_bin_rows(&self, buf: &mut String, fields: &[&[RegField]; 4]) {
+        const PAD: &str = unsafe { std::str::from_utf8_unchecked(&[b' '; 18]) };
+
+        let separate = |buf: &mut String, reg: u32| {
+            let [b0, b1, b2, b3] = reg.to_le_bytes();
 
-            format!(
-                "{}_{}_{}_{}",
-                &tmp[..8],
-                &tmp[8..16],
-                &tmp[16..24],
-                &tmp[24..32],
-            )
+            write!(buf, "{b3:08b}_{b2:08b}_{b1:08b}_{b0:08b}").unwrap();
+        };
+        let overlay = |buf: &mut String, [a, b]: [usize; 2]| {
+            if fields[a].is_empty() && fields[b].is_empty() { return }
+
+            let line = format!("{PAD} {}  {}", field_overlay(fields[a]), field_overlay(fields[b]));
+            writeln!(buf, "{}", line.trim_end()).unwrap();
         };
 
         let [leaf, sub_leaf] = [self.leaf, self.sub_leaf];
-        let [eax, ebx, ecx, edx] = [
-            self.result.eax,
-            self.result.ebx,
-            self.result.ecx,
-            self.result.edx,
-        ].map(separate);
+        let CpuidResult { eax, ebx, ecx, edx } = self.result;
 
-        const PAD: &str = unsafe { std::str::from_utf8_unchecked(&[b' '; 18]) };
+        write!(buf, "  {leaf:#010X} {sub_leaf:#03X}:  ").unwrap();
+        separate(buf, eax);
+        buf.push_str("  ");
+        separate(buf, ebx);
+        self.write_ascii_column(buf);
+        buf.push_str(" \n");
+        overlay(buf, [0, 1]);
+        write!(buf, "{PAD} ").unwrap();
+        separate(buf, ecx);
+        buf.push_str("  ");
+        separate(buf, edx);
+        buf.push_str(" \n");
+        overlay(buf, [2, 3]);
+    }
+
+    fn write_compat_fmt(&self, _: &CpuVendor, _: &FormatConfig, buf: &mut String) {
+        let [leaf, sub_leaf] = [self.leaf, self.sub_leaf];
+        let CpuidResult { eax, ebx, ecx, edx } = self.result;
 
-        format!("  {leaf:#010X} {sub_leaf:#03X}:  {eax}  {ebx} \n{PAD} {ecx}  {edx} \n")
+        writeln!(buf, "   {leaf:#010x} {sub_leaf:#04x}: eax={eax:#010x} ebx={ebx:#010x} ecx={ecx:#010x} edx={edx:#010x}").unwrap();
     }
 
-    pub fn compat_fmt(&self, _: &CpuVendor) -> String {
+    fn write_debug_fmt(&self, _: &CpuVendor, _: &FormatConfig, buf: &mut String) {
+        writeln!(buf, "{:#X?}", self).unwrap();
+    }
+
+    fn write_kv_fmt(&self, vendor: &CpuVendor, _: &FormatConfig, buf: &mut String) {
+        const REGS: [&str; 4] = ["EAX", "EBX", "ECX", "EDX"];
+
         let [leaf, sub_leaf] = [self.leaf, self.sub_leaf];
         let CpuidResult { eax, ebx, ecx, edx } = self.result;
+        let regs = [eax, ebx, ecx, edx];
+
+        for ((reg, name), fields) in regs.iter().zip(REGS.iter()).zip(reg_fields(leaf, vendor)) {
+            for field in fields {
+                writeln!(buf, "{leaf:#010X}.{sub_leaf:#X}.{name}.{}={}", field.name, field.value(*reg)).unwrap();
+            }
+        }
 
-        format!("   {leaf:#010x} {sub_leaf:#04x}: eax={eax:#010x} ebx={ebx:#010x} ecx={ecx:#010x} edx={edx:#010x}\n")
+        for (idx, table) in ftr_table(leaf, sub_leaf, vendor) {
+            for (bit, ftr) in table.iter().enumerate() {
+                if ftr.is_empty() || (regs[idx] >> bit) & 1 == 0 { continue }
+
+                writeln!(buf, "{leaf:#010X}.{sub_leaf:#X}.{}.{}=1", REGS[idx], kv_name(ftr)).unwrap();
+            }
+        }
     }
+}
+
+/// Keep only the results differing from `first` (the dump of the first thread),
+/// and the leaves not in `first` (the thread reporting the different maxima)
+pub fn retain_diff(pool: &mut Vec<RawCpuid>, first: &[RawCpuid]) {
+    let mut idx = 0;
+
+    pool.retain(|sub| {
+        let same_input = |rawcpuid: &&RawCpuid| rawcpuid.leaf == sub.leaf && rawcpuid.sub_leaf == sub.sub_leaf;
+        /* the same position in the same leaf pool, or search in the different leaf pool */
+        let base = first.get(idx).filter(same_input).or_else(|| first.iter().find(same_input));
+        idx += 1;
+
+        base != Some(sub)
+    });
+}
+
+#[test]
+fn test_raw_round_trip() {
+    /* Ryzen 5 5600G */
+    let vendor = CpuVendor::AuthenticAMD;
+    let pool = [
+        RawCpuid { leaf: 0x0, sub_leaf: 0x0, result: CpuidResult { eax: 0x10, ebx: 0x6874_7541, ecx: 0x444D_4163, edx: 0x6974_6E65 } },
+        RawCpuid { leaf: 0x8000_0002, sub_leaf: 0x0, result: CpuidResult { eax: 0x2044_4D41, ebx: 0x657A_7952, ecx: 0x2035_206E, edx: 0x3030_3635 } },
+        RawCpuid { leaf: 0xD, sub_leaf: 0xB, result: CpuidResult { eax: 0x10, ebx: 0x0, ecx: 0x1, edx: 0x0 } },
+        RawCpuid { leaf: 0x8000_001E, sub_leaf: 0x0, result: CpuidResult { eax: 0xB, ebx: 0x105, ecx: 0x0, edx: 0x0 } },
+        RawCpuid { leaf: u32::MAX, sub_leaf: u32::MAX, result: CpuidResult { eax: u32::MAX, ebx: 0x0, ecx: u32::MAX, edx: 0x0 } },
+    ];
 
-    pub fn debug_fmt(&self, _: &CpuVendor) -> String {
-        format!("{:#X?}\n", self)
+    for rawcpuid in &pool {
+        let line = rawcpuid.raw_fmt(&vendor);
+
+        assert_eq!(RawCpuid::from_raw_line(line.trim_end_matches('\n')), Some(*rawcpuid));
+    }
+
+    assert_eq!(pool[0].ascii().as_deref(), Some("AuthenticAMD"));
+    assert_eq!(pool[1].ascii().as_deref(), Some("AMD Ryzen 5 5600"));
+
+    assert_eq!(RawCpuid::from_raw_line("0x00000000 0x0: 0x00000010 0x68747541 0x444D4163 0x69746E65"), None);
+    assert_eq!(RawCpuid::from_raw_line("0x00000000 0x00000000: 0x00000010 0x68747541 0x444D4163"), None);
+
+    for rawcpuid in &pool {
+        assert_eq!(rawcpuid.to_string().parse::<RawCpuid>(), Ok(*rawcpuid));
+    }
+
+    assert_eq!(pool[0].to_string(), "0x00000000 0x00000000: 0x00000010 0x68747541 0x444D4163 0x69746E65");
+    assert!("0x00000000 0x00000000: 0x00000010 0x68747541 0x444D4163 0x69746e65".parse::<RawCpuid>().is_err());
+}
+
+#[test]
+fn test_kv_fmt() {
+    /* Ryzen 5 5600G */
+    let vendor = CpuVendor::AuthenticAMD;
+    let rawcpuid = RawCpuid {
+        leaf: 0x1,
+        sub_leaf: 0x0,
+        result: CpuidResult { eax: 0x00A50F00, ebx: 0x0A0C0800, ecx: 0x7EF8320B, edx: 0x178BFBFF },
+    };
+    let mut kv = String::new();
+
+    rawcpuid.write_kv_fmt(&vendor, &FormatConfig::default(), &mut kv);
+
+    assert!(kv.contains("0x00000001.0x0.EAX.Family=15\n"), "{}", kv);
+    assert!(kv.contains("0x00000001.0x0.EBX.APIC_ID=10\n"), "{}", kv);
+    assert!(kv.contains("0x00000001.0x0.EBX.MaxLogicalID=12\n"), "{}", kv);
+    assert!(kv.contains("0x00000001.0x0.EDX.SSE2=1\n"), "{}", kv);
+    assert!(kv.contains("0x00000001.0x0.ECX.SSE4_2=1\n"), "{}", kv);
+    assert!(kv.contains("0x00000001.0x0.ECX.AVX=1\n"), "{}", kv);
+    assert!(kv.lines().all(|line| line.split_once('=').is_some_and(|(key, _)| key.split('.').count() == 4)), "{}", kv);
+}
+
+#[test]
+fn test_kv_keys() {
+    /* Core i9-12900K, P-core */
+    let vendor = CpuVendor::GenuineIntel;
+    let rawcpuid = RawCpuid {
+        leaf: 0x1,
+        sub_leaf: 0x0,
+        result: CpuidResult { eax: 0x0009_0672, ebx: 0x0080_0800, ecx: 0x7FFA_FBFF, edx: 0xBFEB_FBFF },
+    };
+    let mut kv = String::new();
+
+    rawcpuid.write_kv_fmt(&vendor, &FormatConfig::default(), &mut kv);
+
+    /* the keys are the names of the field layouts and of the feature table, whatever the labels of "-parse" */
+    let fields = reg_fields(rawcpuid.leaf, &vendor);
+    let ftrs = ftr_table(rawcpuid.leaf, rawcpuid.sub_leaf, &vendor);
+    let known = |name: &str| {
+        fields.iter().flat_map(|fields| fields.iter()).any(|field| field.name == name) ||
+        ftrs.iter().flat_map(|(_, table)| table.iter()).any(|ftr| !ftr.is_empty() && kv_name(ftr) == name)
+    };
+
+    for line in kv.lines() {
+        let (key, _) = line.split_once('=').unwrap();
+        let name = key.rsplit('.').next().unwrap();
+
+        assert!(known(name), "{}", line);
     }
+
+    let parsed = rawcpuid.parse(&vendor, &FormatConfig::default());
+    assert!(parsed.contains("APIC ID: ") && !kv.contains("APIC ID"), "{}", parsed);
+    assert!(kv.contains("0x00000001.0x0.EBX.APIC_ID=0\n"), "{}", kv);
+    /* no repeated keys */
+    let mut keys: Vec<&str> = kv.lines().filter_map(|line| line.split_once('=')).map(|(key, _)| key).collect();
+    let len = keys.len();
+    keys.sort_unstable();
+    keys.dedup();
+    assert_eq!(keys.len(), len);
+}
+
+#[test]
+fn test_parse_microcode() {
+    /* Ryzen 5 5600G */
+    let vendor = CpuVendor::AuthenticAMD;
+    let rawcpuid = RawCpuid {
+        leaf: 0x1,
+        sub_leaf: 0x0,
+        result: CpuidResult { eax: 0x00A50F00, ebx: 0x0A0C0800, ecx: 0x7EF8320B, edx: 0x178BFBFF },
+    };
+    let ctx = ParseContext { microcode: Some(0xA50000C), ..Default::default() };
+    let mut buf = String::new();
+
+    rawcpuid.write_parse_ctx_fmt(&vendor, &FormatConfig::default(), &ctx, &mut buf);
+
+    /* right after the Family/Model/Stepping, before the codename */
+    let fms_microcode = format!("[F: 0x19, M: 0x50, S: 0x0]{LN_PAD}[Microcode: 0xA50000C]{LN_PAD}[Codename:");
+    assert!(buf.contains(&fms_microcode), "{}", buf);
+    assert!(!rawcpuid.parse(&vendor, &FormatConfig::default()).contains("[Microcode:"));
+}
+
+#[test]
+fn test_parse_diff() {
+    /* Core i9-12900K, P-core (Thread 0) and E-core (Thread 16) */
+    let vendor = CpuVendor::GenuineIntel;
+    let base = [
+        RawCpuid { leaf: 0x1, sub_leaf: 0x0, result: CpuidResult { eax: 0x0009_0672, ebx: 0x0080_0800, ecx: 0x7FFA_FBFF, edx: 0xBFEB_FBFF } },
+        RawCpuid { leaf: 0x1A, sub_leaf: 0x0, result: CpuidResult { eax: 0x4000_0001, ebx: 0x0, ecx: 0x0, edx: 0x0 } },
+    ];
+    let sub = [
+        RawCpuid { leaf: 0x1, sub_leaf: 0x0, result: CpuidResult { eax: 0x0009_0672, ebx: 0x2080_0800, ecx: 0x7FFA_FBFF, edx: 0xBFEB_FBFF } },
+        RawCpuid { leaf: 0x1A, sub_leaf: 0x0, result: CpuidResult { eax: 0x2000_0001, ebx: 0x0, ecx: 0x0, edx: 0x0 } },
+    ];
+    let mut buf = String::new();
+
+    for (base, sub) in base.iter().zip(sub.iter()) {
+        sub.write_parse_diff_fmt(base, &vendor, &FormatConfig::default(), &mut buf);
+    }
+    assert!(buf.contains("[APIC ID: 0 → 32]"));
+    assert!(buf.contains("[Type: Core → Atom]"));
+    assert!(buf.contains("[Model: GoldenCove → Gracemont]"));
+    assert!(!buf.contains("[CLFlush"));
+}
+
+#[test]
+fn test_retain_diff() {
+    let rawcpuid = |leaf: u32, eax: u32| RawCpuid { leaf, sub_leaf: 0x0, result: CpuidResult { eax, ebx: 0x0, ecx: 0x0, edx: 0x0 } };
+    let first = [rawcpuid(0x0, 0x1), rawcpuid(0x1, 0x0)];
+
+    /* the thread reporting LFuncStd = 0x2 */
+    let mut pool = vec![rawcpuid(0x0, 0x2), rawcpuid(0x1, 0x0), rawcpuid(0x2, 0x0)];
+    retain_diff(&mut pool, &first);
+
+    assert_eq!(pool, [rawcpuid(0x0, 0x2), rawcpuid(0x2, 0x0)]);
 }
@@ -0,0 +1,153 @@
+use crate::{CpuidDump, CpuidResult, RawCpuid, RawCpuidFmt};
+
+/// Canonical dumps, same format as `cpuid -r` (`-compat`)
+const REFERENCE_LIST: &[(&str, &str)] = &[
+    ("zenplus_ryzen5_2600", include_str!("../reference/zenplus_ryzen5_2600.txt")),
+    ("zen3_ryzen5_5600g", include_str!("../reference/zen3_ryzen5_5600g.txt")),
+];
+
+/// The first line of the reference is used as the description
+pub(crate) fn reference_list_msg() -> String {
+    REFERENCE_LIST.iter().map(|(name, dump)| {
+        let desc = dump.lines().next().unwrap_or("").trim_start_matches('#').trim();
+
+        format!("    {name:<24} {desc}\n")
+    }).collect()
+}
+
+pub(crate) fn get_reference(name: &str) -> Option<CpuidDump> {
+    let (_, dump) = REFERENCE_LIST.iter().find(|(n, _)| *n == name)?;
+
+    parse_compat(dump)
+}
+
+/// Look up by the reference name, or by the model name in the description,
+/// e.g. "Ryzen_5_5600G", "ryzen 5 2600"
+pub(crate) fn find_reference(model: &str) -> Option<(&'static str, CpuidDump)> {
+    let normalize = |s: &str| -> String {
+        s.to_ascii_lowercase().replace(['_', '-'], " ")
+    };
+    let model = normalize(model);
+
+    let (name, dump) = REFERENCE_LIST.iter().find(|(name, dump)| {
+        let desc = dump.lines().next().unwrap_or("");
+
+        normalize(name) == model || normalize(desc).contains(&model)
+    })?;
+
+    Some((name, parse_compat(dump)?))
+}
+
+fn parse_compat(dump: &str) -> Option<CpuidDump> {
+    let rawcpuid_pool: Vec<RawCpuid> = dump
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(RawCpuid::from_compat_line)
+        .collect();
+
+    CpuidDump::from_rawcpuid_pool(rawcpuid_pool)
+}
+
+impl CpuidDump {
+    /// Leaves that differ between `self` and `reference`, or exist in only one of them
+    pub(crate) fn diff_reference(&self, reference: &Self, name: &str) -> String {
+        let mut input: Vec<(u32, u32)> = self.rawcpuid_pool
+            .iter()
+            .chain(reference.rawcpuid_pool.iter())
+            .map(|rawcpuid| (rawcpuid.leaf, rawcpuid.sub_leaf))
+            .collect();
+        input.sort_unstable();
+        input.dedup();
+
+        let find = |dump: &Self, leaf: u32, sub_leaf: u32| -> Option<RawCpuid> {
+            dump.rawcpuid_pool
+                .iter()
+                .find(|rawcpuid| rawcpuid.leaf == leaf && rawcpuid.sub_leaf == sub_leaf)
+                .copied()
+        };
+        let zero = |leaf: u32, sub_leaf: u32| -> RawCpuid {
+            RawCpuid { leaf, sub_leaf, result: CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 } }
+        };
+
+        let diff: String = input.iter().filter_map(|(leaf, sub_leaf)| {
+            let cur = find(self, *leaf, *sub_leaf).unwrap_or_else(|| zero(*leaf, *sub_leaf));
+            let refer = find(reference, *leaf, *sub_leaf).unwrap_or_else(|| zero(*leaf, *sub_leaf));
+
+            if cur == refer { return None }
+
+            Some(format!(
+                "-{}+{}",
+                refer.raw_fmt(&reference.cpu_vendor),
+                cur.raw_fmt(&self.cpu_vendor),
+            ))
+        }).collect();
+
+        format!("--- {name}\n+++ current\n{diff}")
+    }
+
+    /// Categorize the differences from `reference` into "missing", "extra" and "value differs",
+    /// features are compared by the Linux flag names
+    pub(crate) fn compare_model(&self, reference: &Self, name: &str) -> String {
+        let [cur_ftr, ref_ftr] = [self.kernel_flags(), reference.kernel_flags()];
+        let missing_ftr: Vec<String> = ref_ftr
+            .iter()
+            .filter(|f| !cur_ftr.contains(f))
+            .map(|f| f.to_string())
+            .collect();
+        let extra_ftr: Vec<String> = cur_ftr
+            .iter()
+            .filter(|f| !ref_ftr.contains(f))
+            .map(|f| f.to_string())
+            .collect();
+
+        let find = |dump: &Self, rawcpuid: &RawCpuid| -> Option<RawCpuid> {
+            dump.rawcpuid_pool
+                .iter()
+                .find(|r| r.leaf == rawcpuid.leaf && r.sub_leaf == rawcpuid.sub_leaf)
+                .copied()
+        };
+        let leaf_fmt = |rawcpuid: &RawCpuid| -> String {
+            format!("{:#010X} {:#X}", rawcpuid.leaf, rawcpuid.sub_leaf)
+        };
+
+        let missing_leaf: Vec<String> = reference.rawcpuid_pool
+            .iter()
+            .filter(|r| find(self, r).is_none())
+            .map(leaf_fmt)
+            .collect();
+        let extra_leaf: Vec<String> = self.rawcpuid_pool
+            .iter()
+            .filter(|r| find(reference, r).is_none())
+            .map(leaf_fmt)
+            .collect();
+        let differs: String = reference.rawcpuid_pool.iter().filter_map(|refer| {
+            let cur = find(self, refer)?;
+
+            if cur == *refer { return None }
+
+            Some(format!(
+                "-{}+{}",
+                refer.raw_fmt(&reference.cpu_vendor),
+                cur.raw_fmt(&self.cpu_vendor),
+            ))
+        }).collect();
+
+        let list = |v: &[String]| -> String {
+            if v.is_empty() { "    -\n".to_string() } else { format!("    {}\n", v.join(" ")) }
+        };
+
+        [
+            format!("[Compare with: {name}]\n"),
+            "Missing features:\n".to_string(),
+            list(&missing_ftr),
+            "Extra features:\n".to_string(),
+            list(&extra_ftr),
+            "Missing leaves:\n".to_string(),
+            list(&missing_leaf),
+            "Extra leaves:\n".to_string(),
+            list(&extra_leaf),
+            "Value differs:\n".to_string(),
+            differs,
+        ].concat()
+    }
+}
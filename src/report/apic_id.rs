@@ -0,0 +1,69 @@
+use crate::CpuidDump;
+use super::*;
+use libcpuid_dump::{ApicIdField, ApicIdLayout};
+
+/* "pkg [31:6] | core [5:1] | smt [0]", from the MSB */
+fn layout_line(fields: &[ApicIdField]) -> String {
+    fields.iter().rev().map(|f| {
+        let hi = f.lo + f.width - 1;
+
+        if hi == f.lo {
+            format!("{} [{}]", f.name, f.lo)
+        } else {
+            format!("{} [{hi}:{}]", f.name, f.lo)
+        }
+    }).collect::<Vec<String>>().join(" | ")
+}
+
+/* "pkg 0 | core 26 | smt 0" */
+fn decompose_line(fields: &[ApicIdField], x2apic_id: u32) -> String {
+    fields.iter().rev()
+        .map(|f| format!("{} {}", f.name, f.id(x2apic_id)))
+        .collect::<Vec<String>>()
+        .join(" | ")
+}
+
+/// The fields of x2APIC ID for the topology levels and the IDs of each thread,
+/// e.g. "x2APIC 0x34 = pkg 0 | core 26 | smt 0".
+/// The shift values are from `CPUID.(EAX=8000_0026h)`, `CPUID.(EAX=1Fh/0Bh)` or `CPUID.(EAX=8000_001Eh)`.
+pub(crate) fn apic_id_report(cpuid_dumps: &[CpuidDump]) -> String {
+    let Some(first) = cpuid_dumps.first() else { return String::new() };
+    let head = report_head("x2APIC ID", &first.cpu_vendor);
+    let layout = |cpuid_dump: &CpuidDump| ApicIdLayout::from_source(&|leaf, sub_leaf| cpuid_dump.get_cpuid(leaf, sub_leaf));
+
+    let Some(first_layout) = layout(first) else {
+        return [head, "    No topology leaf (CPUID.(EAX=0Bh/1Fh), 8000_001Eh or 8000_0026h)\n".to_string()].concat();
+    };
+    let layout_head = format!(
+        "    Layout (CPUID.(EAX={:X}h)): {}\n",
+        first_layout.leaf,
+        layout_line(first_layout.fields()),
+    );
+
+    let body: String = cpuid_dumps.iter().enumerate().filter_map(|(i, cpuid_dump)| {
+        let x2apic_id = cpuid_dump.topo_id?.x2apic_id;
+        /* the layout of the thread, the same as the first except for the broken dumps */
+        let layout = layout(cpuid_dump).unwrap_or(first_layout);
+        let thread_id = cpuid_dump.thread_id.unwrap_or(i);
+
+        Some(format!(
+            "    CPU {thread_id:<3} x2APIC 0x{x2apic_id:02X} = {}\n",
+            decompose_line(layout.fields(), x2apic_id),
+        ))
+    }).collect();
+
+    [head, layout_head, body].concat()
+}
+
+#[test]
+fn test_apic_id_report() {
+    /* Ryzen 5 5600G, the first thread: Leaf 0xB, SMT shift 1, Core shift 4 */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let report = apic_id_report(&crate::load_raw::parse_raw(&dump).unwrap());
+    let mut lines = report.lines().skip(2);
+
+    assert_eq!(lines.next(), Some("    Layout (CPUID.(EAX=Bh)): pkg [31:4] | core [3:1] | smt [0]"));
+    assert_eq!(lines.next(), Some("    CPU 0   x2APIC 0x00 = pkg 0 | core 0 | smt 0"));
+}
@@ -0,0 +1,60 @@
+use crate::{CpuidDump, CpuVendor};
+use super::*;
+use libcpuid_dump::Reg;
+
+impl CpuidDump {
+    fn is_amd_family(&self) -> bool {
+        matches!(self.cpu_vendor, CpuVendor::AuthenticAMD | CpuVendor::HygonGenuine)
+    }
+
+    /// (Hardware boost, Hardware-controlled P-states),
+    /// Intel: Turbo Boost and HWP (Leaf 0x6), AMD: CPB and HwPstate (Leaf 0x8000_0007)
+    pub(crate) fn boost_capability(&self) -> (bool, bool) {
+        if self.is_amd_family() {
+            let edx = self.get_cpuid(0x8000_0007, 0x0).edx;
+
+            (edx.bit(9), edx.bit(7))
+        } else {
+            let eax = self.get_cpuid(0x6, 0x0).eax;
+
+            (eax.bit(1), eax.bit(7))
+        }
+    }
+
+    pub fn boost_report(&self) -> String {
+        let (boost, hwp) = self.boost_capability();
+
+        let list: Vec<(&str, bool, &str)> = if self.is_amd_family() {
+            let edx = self.get_cpuid(0x8000_0007, 0x0).edx;
+            let ebx = self.get_cpuid(0x8000_0008, 0x0).ebx;
+
+            vec![
+                ("Boost", boost, "80_07_EDX[9] CPB"),
+                ("HW P-states", hwp, "80_07_EDX[7] HwPstate"),
+                ("CPPC", ebx.bit(27), "80_08_EBX[27]"),
+                ("EffFreqRO", edx.bit(10), "80_07_EDX[10]"),
+                ("100MHzSteps", edx.bit(6), "80_07_EDX[6]"),
+            ]
+        } else {
+            let eax = self.get_cpuid(0x6, 0x0).eax;
+
+            vec![
+                ("Boost", boost, "00_06_EAX[1] TurboBoost"),
+                ("HW P-states", hwp, "00_06_EAX[7] HWP"),
+                ("TurboBoostMax", eax.bit(14), "00_06_EAX[14]"),
+                ("HWP_Notification", eax.bit(8), "00_06_EAX[8]"),
+                ("HWP_Activity_Window", eax.bit(9), "00_06_EAX[9]"),
+                ("HWP_EPP", eax.bit(10), "00_06_EAX[10]"),
+                ("HWP_Package_Level", eax.bit(11), "00_06_EAX[11]"),
+                ("HWP_Capabilities", eax.bit(15), "00_06_EAX[15]"),
+            ]
+        };
+
+        let body: String = list
+            .iter()
+            .map(|(name, supported, source)| report_line(name, *supported, source))
+            .collect();
+
+        [report_head("Boost", &self.cpu_vendor), body].concat()
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{CpuidDump, CpuVendor};
+use libcpuid_dump::FamModStep;
+
+/* (Keyword of the brand string, the vendors of the keyword) */
+const BRAND_VENDOR: &[(&str, &[CpuVendor])] = &[
+    ("Intel", &[CpuVendor::GenuineIntel]),
+    ("Xeon", &[CpuVendor::GenuineIntel]),
+    ("Pentium", &[CpuVendor::GenuineIntel]),
+    ("Celeron", &[CpuVendor::GenuineIntel]),
+    ("Hygon", &[CpuVendor::HygonGenuine]),
+    ("AMD", &[CpuVendor::AuthenticAMD]),
+    ("Ryzen", &[CpuVendor::AuthenticAMD]),
+    ("EPYC", &[CpuVendor::AuthenticAMD]),
+    ("Opteron", &[CpuVendor::AuthenticAMD]),
+    ("Athlon", &[CpuVendor::AuthenticAMD]),
+    ("ZHAOXIN", &[CpuVendor::Shanghai, CpuVendor::CentaurHauls]),
+    ("VIA", &[CpuVendor::CentaurHauls]),
+];
+
+/* the brands of AMD Zen (Family 17h-1Ah) */
+const ZEN_BRAND: &[&str] = &["Ryzen", "EPYC", "Threadripper"];
+
+/* the vendors implied by the brand string, the first keyword of `BRAND_VENDOR` in the words,
+   `None` for the generic names of the hypervisors (e.g. "QEMU Virtual CPU") */
+fn brand_vendor(name: &str) -> Option<&'static [CpuVendor]> {
+    let words: Vec<&str> = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    BRAND_VENDOR
+        .iter()
+        .find(|(keyword, _)| words.contains(keyword))
+        .map(|(_, vendors)| *vendors)
+}
+
+impl CpuidDump {
+    /// The mismatches between the brand string and the vendor ID (`CPUID.(EAX=00h)`) or the family
+    /// (`CPUID.(EAX=01h)`), e.g. "Intel Xeon" on the AMD family by the misconfigured hypervisor
+    pub(crate) fn brand_mismatch(&self) -> Vec<String> {
+        let name = self.summary_name();
+        let fms = FamModStep::from(&self.get_cpuid(0x1, 0x0));
+        let mut mismatch = Vec::new();
+
+        let Some(vendors) = brand_vendor(&name) else { return mismatch };
+
+        if !vendors.contains(&self.cpu_vendor) {
+            mismatch.push(format!(
+                "the brand string \"{name}\" implies {}, but the vendor ID is {}",
+                vendors[0],
+                self.cpu_vendor,
+            ));
+        }
+
+        /* Family 17h-1Ah are only of AMD Zen (and Hygon Dhyana) */
+        let zen_family = (0x17..=0x1A).contains(&fms.syn_fam);
+
+        if let Some(brand) = ZEN_BRAND.iter().find(|brand| name.contains(*brand)) {
+            if !zen_family {
+                mismatch.push(format!(
+                    "the brand string \"{name}\" implies AMD Zen ({brand}), but the family is {:#X}",
+                    fms.syn_fam,
+                ));
+            }
+        } else if vendors.contains(&CpuVendor::GenuineIntel) && zen_family {
+            mismatch.push(format!(
+                "the brand string \"{name}\" implies Intel, but the family {:#X} is of AMD Zen",
+                fms.syn_fam,
+            ));
+        }
+
+        mismatch
+    }
+
+    /// Warning lines of `brand_mismatch` for the dump header
+    pub(crate) fn brand_check_head(&self) -> String {
+        self.brand_mismatch().iter().map(|msg| format!("[Warning: {msg}]\n")).collect()
+    }
+}
+
+#[test]
+fn test_brand_mismatch() {
+    /* Ryzen 5 5600G */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let mut cpuid_dump = crate::load_raw::parse_raw(&dump).unwrap().remove(0);
+
+    assert!(cpuid_dump.brand_mismatch().is_empty());
+
+    /* the brand string starting with "Intel(R) Xeon(R)" on the AMD family */
+    for rawcpuid in cpuid_dump.rawcpuid_pool.iter_mut().filter(|rawcpuid| rawcpuid.leaf == 0x8000_0002) {
+        rawcpuid.result = libcpuid_dump::CpuidResult { eax: 0x65746E49, ebx: 0x2952286C, ecx: 0x6F655820, edx: 0x2952286E };
+    }
+
+    let mismatch = cpuid_dump.brand_mismatch();
+
+    assert_eq!(mismatch.len(), 2, "{:?}", mismatch);
+    assert!(mismatch[0].ends_with("implies GenuineIntel, but the vendor ID is AuthenticAMD"), "{}", mismatch[0]);
+    assert!(mismatch[1].ends_with("implies Intel, but the family 0x19 is of AMD Zen"), "{}", mismatch[1]);
+}
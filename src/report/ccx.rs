@@ -0,0 +1,111 @@
+use crate::{cpu_ranges, CpuidDump, CpuVendor};
+use super::*;
+use libcpuid_dump::{AmdExtTopo, AmdTopoLevelType, CacheProp, FamModStep};
+
+/* CCX (Core Complex) of the thread, the threads sharing the L3 */
+struct CcxThread {
+    thread_id: usize,
+    ccd: Option<u32>,
+    ccx: u32,
+    /* like "32 MiB", `None` without the L3 */
+    l3: Option<String>,
+}
+
+impl CpuidDump {
+    /* shift of x2APIC ID to the ID of the level, from `CPUID.(EAX=8000_0026h)` on Zen 4 or later */
+    fn amd_ext_topo_shift(&self, level_type: AmdTopoLevelType) -> Option<u32> {
+        (0x0..=0x4).find_map(|sub_leaf| {
+            let cpuid = self.get_cpuid(0x8000_0026, sub_leaf);
+            let ext_topo = AmdExtTopo::from(&cpuid);
+
+            (ext_topo.level_type == level_type && (cpuid.ecx & 0xFF) == sub_leaf)
+                .then_some(ext_topo.next_level as u32)
+        })
+    }
+
+    fn ccx_thread(&self, i: usize) -> Option<CcxThread> {
+        let topo_id = self.topo_id?;
+        let x2apic_id = topo_id.x2apic_id;
+        let l3 = (0x0..=0x4)
+            .filter_map(|sub_leaf| CacheProp::option_from_cpuid(&self.get_cpuid(0x8000_001D, sub_leaf)))
+            .find(|cache| cache.level == 3);
+
+        let ccx = match (self.amd_ext_topo_shift(AmdTopoLevelType::Complex), &l3) {
+            (Some(shift), _) => x2apic_id >> shift,
+            (None, Some(l3)) => x2apic_id.checked_shr(32 - (l3.share_thread - 1).leading_zeros()).unwrap_or(0),
+            (None, None) => return None,
+        };
+        /* the node is the die (Zeppelin) on Zen/Zen+, the node of NPS on Zen 2 and later */
+        let fms = FamModStep::from(&self.get_cpuid(0x1, 0x0));
+        let ccd = match self.amd_ext_topo_shift(AmdTopoLevelType::Die) {
+            Some(shift) => Some(x2apic_id >> shift),
+            None if fms.syn_fam == 0x17 && fms.syn_mod < 0x30 => topo_id.node_id,
+            None => None,
+        };
+
+        Some(CcxThread {
+            thread_id: self.thread_id.unwrap_or(i),
+            ccd,
+            ccx,
+            l3: l3.map(|l3| format!("{} {}", l3.size_in_the_unit(), l3.size_unit)),
+        })
+    }
+}
+
+/// Logical CPUs of each CCD/CCX and the L3 of the CCX, AMD Zen only.
+/// The CCX is from `CPUID.(EAX=8000_0026h)` (Zen 4 or later) or the threads sharing the L3 (`CPUID.(EAX=8000_001Dh)`),
+/// the CCD is from `CPUID.(EAX=8000_0026h)` or the node ID of `CPUID.(EAX=8000_001Eh)` on Zen/Zen+.
+pub(crate) fn ccx_report(cpuid_dumps: &[CpuidDump]) -> String {
+    let Some(first) = cpuid_dumps.first() else { return String::new() };
+    let head = report_head("CCD/CCX", &first.cpu_vendor);
+
+    if !matches!(first.cpu_vendor, CpuVendor::AuthenticAMD | CpuVendor::HygonGenuine) {
+        return [head, "    Available only on AMD Zen\n".to_string()].concat();
+    }
+
+    let mut threads: Vec<CcxThread> = cpuid_dumps
+        .iter()
+        .enumerate()
+        .filter_map(|(i, cpuid_dump)| cpuid_dump.ccx_thread(i))
+        .collect();
+
+    if threads.is_empty() {
+        return [head, "    No CCX information (CPUID.(EAX=8000_001Dh) or 8000_0026h)\n".to_string()].concat();
+    }
+
+    threads.sort_by_key(|t| (t.ccd, t.ccx, t.thread_id));
+
+    /* (the first thread of the CCX, logical CPUs) */
+    let mut ccx_list: Vec<(CcxThread, Vec<usize>)> = Vec::new();
+
+    for t in threads {
+        match ccx_list.last_mut() {
+            Some((first, ids)) if (first.ccd, first.ccx) == (t.ccd, t.ccx) => ids.push(t.thread_id),
+            _ => {
+                let ids = vec![t.thread_id];
+                ccx_list.push((t, ids));
+            },
+        }
+    }
+
+    let body: String = ccx_list.iter().map(|(CcxThread { ccd, ccx, l3, .. }, ids)| {
+        let ccd = ccd.map_or("-".to_string(), |ccd| ccd.to_string());
+        let l3 = l3.as_deref().unwrap_or("-");
+        let cpus = if ids.len() == 1 { "CPU" } else { "CPUs" };
+
+        format!("    CCD {ccd:<3} CCX {ccx:<3} L3: {l3:<8} {cpus} {}\n", cpu_ranges(ids))
+    }).collect();
+
+    [head, body].concat()
+}
+
+#[test]
+fn test_ccx_report() {
+    /* Ryzen 5 5600G, the first thread */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let report = ccx_report(&crate::load_raw::parse_raw(&dump).unwrap());
+
+    assert_eq!(report.lines().nth(2), Some("    CCD -   CCX 0   L3: 16 MiB   CPU 0"));
+}
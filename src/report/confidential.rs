@@ -0,0 +1,79 @@
+use crate::{CpuidDump, CpuVendor};
+use super::*;
+use libcpuid_dump::{AmdMemEncrypt, Reg};
+
+impl CpuidDump {
+    /// The confidential computing technologies of both vendors in one place:
+    /// SGX (`CPUID.(EAX=07h)`, `CPUID.(EAX=12h)`), TME, TDX guest (`CPUID.(EAX=21h)`)
+    /// and SME/SEV (`CPUID.(EAX=8000_001Fh)`)
+    pub fn confidential_report(&self) -> String {
+        let leaf_07h = self.get_cpuid(0x7, 0x0);
+        let sgx = leaf_07h.ebx.bit(2);
+        let leaf_12h = self.get_cpuid(0x12, 0x0);
+        /* "IntelTDX    " in EBX, EDX, ECX, only reported to the TD guest */
+        let tdx_guest = self.get_cpuid(0x21, 0x0);
+        let tdx_guest = [tdx_guest.ebx, tdx_guest.edx, tdx_guest.ecx] == [0x6574_6E49, 0x5844_546C, 0x2020_2020];
+        let enc = match self.cpu_vendor {
+            CpuVendor::AuthenticAMD |
+            CpuVendor::HygonGenuine => AmdMemEncrypt::from(&self.get_cpuid(0x8000_001F, 0x0)),
+            _ => AmdMemEncrypt::default(),
+        };
+        let hypervisor = self.get_cpuid(0x1, 0x0).ecx.bit(31);
+
+        let list = [
+            ("SGX", sgx, "07_EBX[2]"),
+            ("SGX1", sgx && leaf_12h.eax.bit(0), "12_EAX[0]"),
+            ("SGX2", sgx && leaf_12h.eax.bit(1), "12_EAX[1]"),
+            ("SGX Launch Control", leaf_07h.ecx.bit(30), "07_ECX[30] SGX_LC"),
+            ("TME", leaf_07h.ecx.bit(13), "07_ECX[13] TME_EN"),
+            ("TDX guest (active)", tdx_guest, "21_EBX,EDX,ECX \"IntelTDX    \""),
+            ("SME", enc.sme, "80_1F_EAX[0]"),
+            ("SEV", enc.sev, "80_1F_EAX[1]"),
+            ("SEV-ES", enc.sev_es, "80_1F_EAX[3]"),
+            ("SEV-SNP", enc.sev_snp, "80_1F_EAX[4]"),
+        ];
+        let mut body: String = list
+            .iter()
+            .map(|(name, supported, source)| report_line(name, *supported, source))
+            .collect();
+
+        if let Some((total, sections)) = self.summary_sgx_epc() {
+            body.push_str(&format!("    SGX EPC {total} MiB ({} sections)\n", sections.len()));
+        }
+
+        if let Some(tme) = self.summary_tme() {
+            body.push_str(&format!("    Memory encryption: {tme}\n"));
+        }
+
+        if let Some(sev) = self.summary_sev() {
+            body.push_str(&format!("    SEV: {sev}\n"));
+        }
+
+        /* SYSCFG[MemEncryptionModEn], SEV_STATUS */
+        if enc.sme || enc.sev {
+            body.push_str(if hypervisor {
+                "    SEV is exposed by the hypervisor, the guest is encrypted if SEV_STATUS (MSR 0xC0010131) is set\n"
+            } else {
+                "    SME/SEV are enabled by SYSCFG (MSR 0xC0010010), not enumerated by CPUID\n"
+            });
+        }
+
+        [report_head("Confidential Computing", &self.cpu_vendor), body].concat()
+    }
+}
+
+#[test]
+fn test_confidential_report() {
+    /* Ryzen 5 5600G */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+    let report = cpuid_dump.confidential_report();
+
+    assert!(report.contains("    SGX                      No   (07_EBX[2])\n"), "{}", report);
+    assert!(report.contains("    TDX guest (active)       No "), "{}", report);
+    assert!(report.contains("    SEV-ES                   Yes  (80_1F_EAX[3])\n"), "{}", report);
+    assert!(report.contains("    SEV-SNP                  No   (80_1F_EAX[4])\n"), "{}", report);
+    assert!(report.ends_with("not enumerated by CPUID\n"), "{}", report);
+}
@@ -0,0 +1,127 @@
+use crate::{CpuidDump, FtrBit, FTR_BITS, ftr_aliases, normalize_ftr_name};
+use super::kernel_flags::kernel_flag_name;
+
+/* the name in this crate, the flag name of Linux and the aliases of the feature bit */
+fn ftr_names(f: &FtrBit) -> impl Iterator<Item = &'static str> {
+    std::iter::once(f.name)
+        .chain(kernel_flag_name(f.name))
+        .chain(ftr_aliases(f.name).iter().copied())
+}
+
+/* the feature bits of the name, ignoring the case and the punctuation */
+fn ftr_bits_by_name(query: &str) -> impl Iterator<Item = &'static FtrBit> {
+    let query = normalize_ftr_name(query);
+
+    FTR_BITS.iter().filter(move |f| ftr_names(f).any(|name| normalize_ftr_name(name) == query))
+}
+
+/* Levenshtein distance */
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+
+        for (j, cb) in b.iter().enumerate() {
+            let subst = prev[j] + usize::from(ca != *cb);
+            cur[j + 1] = subst.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+
+        prev = cur;
+    }
+
+    prev[b.len()]
+}
+
+/// The feature names close to the unknown name (typo), up to 3, the closest first
+pub(crate) fn suggest_ftr(query: &str) -> Vec<&'static str> {
+    let query = normalize_ftr_name(query);
+    let max_dist = if query.len() <= 3 { 1 } else { 2 };
+
+    let mut candidates: Vec<(usize, &'static str)> = FTR_BITS
+        .iter()
+        .filter_map(|f| {
+            let dist = ftr_names(f).map(|name| edit_distance(&query, &normalize_ftr_name(name))).min()?;
+
+            (dist <= max_dist).then_some((dist, f.name))
+        })
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup_by_key(|(_, name)| *name);
+
+    candidates.into_iter().take(3).map(|(_, name)| name).collect()
+}
+
+/// ", did you mean "SSE4.2"?" for the unknown feature, empty if there is no close name
+pub(crate) fn did_you_mean(query: &str) -> String {
+    let names: Vec<String> = suggest_ftr(query).iter().map(|name| format!("\"{name}\"")).collect();
+
+    if names.is_empty() {
+        return String::new();
+    }
+
+    format!(", did you mean {}?", names.join(" or "))
+}
+
+impl CpuidDump {
+    /// Whether the feature (the name in this crate or the flag name of Linux) is set in the results,
+    /// `None` for the unknown feature
+    pub(crate) fn has_feature(&self, name: &str) -> Option<bool> {
+        let mut bits = ftr_bits_by_name(name).peekable();
+
+        bits.peek()?;
+
+        Some(bits.any(|f| {
+            let cpuid = self.get_cpuid(f.leaf, f.sub_leaf);
+            let reg = [cpuid.eax, cpuid.ebx, cpuid.ecx, cpuid.edx][f.reg.index()];
+
+            ((reg >> f.bit) & 0b1) == 0b1
+        }))
+    }
+}
+
+/// Description of the feature: Leaf/Bit, vendor applicability, what it means,
+/// the name in this crate and the flag name of Linux `/proc/cpuinfo` are accepted
+pub(crate) fn describe(query: &str) -> Option<String> {
+    let s: String = ftr_bits_by_name(query)
+        .map(|f| {
+            let linux = match kernel_flag_name(f.name) {
+                Some(flag) => format!(" (Linux: {flag})"),
+                None => String::new(),
+            };
+            let desc = if f.desc.is_empty() { "-" } else { f.desc };
+
+            format!(
+                "{}{linux}\n    \
+                    Location:    CPUID.(EAX={:X}h, ECX={:X}h):{}[{}]\n    \
+                    Vendor:      {}\n    \
+                    Description: {desc}\n",
+                f.name,
+                f.leaf,
+                f.sub_leaf,
+                f.reg.name(),
+                f.bit,
+                f.vendor,
+            )
+        })
+        .collect();
+
+    if s.is_empty() {
+        return None;
+    }
+
+    Some(s)
+}
+
+#[test]
+fn test_ftr_name_match() {
+    for query in ["avx512vnni", "AVX512_VNNI", "sse4_2", "sse4.2", "SSE4.2", "sha_ni", "lm", "x86-64"] {
+        assert!(ftr_bits_by_name(query).next().is_some(), "{}", query);
+    }
+
+    assert_eq!(suggest_ftr("AVX512_VNIN"), ["AVX512_VNNI"]);
+    assert_eq!(suggest_ftr("avx2").first(), Some(&"AVX2"));
+    assert!(suggest_ftr("NOT_A_FEATURE").is_empty());
+    assert_eq!(did_you_mean("sse4.3"), ", did you mean \"SSE3\" or \"SSE4.1\" or \"SSE4.2\"?");
+}
@@ -0,0 +1,79 @@
+use crate::{CpuidDump, CpuVendor, RawCpuid, RawCpuidFmt};
+use libcpuid_dump::{IntelExtTopo, Reg, TopoLevelType};
+
+impl CpuidDump {
+    /// Short description of the environment, like "KVM guest (8 vCPUs, flat topology)",
+    /// from the hypervisor bit, the signature of the hypervisor leaf (`hv_sig`),
+    /// the leaf maxima, the x2APIC ID layout, the CPUs of the OS (`os_cpus`) and the container runtime
+    pub(crate) fn environment(&self, hv_sig: Option<&str>, os_cpus: Option<usize>, container: Option<&str>) -> String {
+        let hypervisor = self.get_cpuid(0x1, 0x0).ecx.bit(31);
+        let container = container.map_or(String::new(), |name| format!(", {name} container"));
+
+        if !hypervisor {
+            return format!("Bare metal{container}");
+        }
+
+        let name = match hv_sig {
+            Some(sig) => match CpuVendor::from_id_str(sig) {
+                Some(hv) if hv.is_hypervisor() => hv.name().to_string(),
+                _ => format!("\"{sig}\""),
+            },
+            None => "Unknown hypervisor".to_string(),
+        };
+
+        /* the x2APIC ID without the SMT bits means no SMT sibling is exposed to the guest */
+        let smt = IntelExtTopo::from(&self.get_cpuid(0xB, 0x0));
+        let topology = if smt.level_type != TopoLevelType::SMT {
+            "no topology leaf"
+        } else if smt.next_level == 0 {
+            "flat topology"
+        } else {
+            "SMT topology"
+        };
+
+        let mut notes: Vec<String> = Vec::new();
+
+        if let Some(os_cpus) = os_cpus {
+            notes.push(format!("{os_cpus} vCPU{}", if os_cpus == 1 { "" } else { "s" }));
+        }
+
+        notes.push(topology.to_string());
+
+        if !self.leaf_limit_anomaly().is_empty() {
+            notes.push("clamped leaf maxima".to_string());
+        }
+
+        format!("{name} guest ({}){container}", notes.join(", "))
+    }
+
+    /// "[Environment: ...]" line of the dump header, only for the current CPU
+    pub(crate) fn environment_head(&self) -> String {
+        use libcpuid_dump::util;
+
+        let hv_sig = RawCpuid::exe(0x4000_0000, 0x0).ascii();
+        let env = self.environment(hv_sig.as_deref(), util::configured_cpu_count(), util::container_runtime());
+
+        format!("[Environment: {env}]\n")
+    }
+}
+
+#[test]
+fn test_environment() {
+    /* Ryzen 5 5600G, with the hypervisor bit */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+
+    assert_eq!(cpuid_dump.environment(None, Some(12), None), "Bare metal");
+    assert_eq!(cpuid_dump.environment(None, None, Some("Docker")), "Bare metal, Docker container");
+
+    let leaf_01h = cpuid_dump.get_cpuid(0x1, 0x0);
+    let dump = dump.replace(
+        &format!("0x00000001 0x00000000: {:#010X} {:#010X} {:#010X}", leaf_01h.eax, leaf_01h.ebx, leaf_01h.ecx),
+        &format!("0x00000001 0x00000000: {:#010X} {:#010X} {:#010X}", leaf_01h.eax, leaf_01h.ebx, leaf_01h.ecx | (1 << 31)),
+    );
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+
+    assert_eq!(cpuid_dump.environment(Some("KVMKVMKVM"), Some(12), None), "KVM guest (12 vCPUs, SMT topology)");
+}
@@ -0,0 +1,34 @@
+use crate::CpuidDump;
+use super::*;
+use libcpuid_dump::{CpuCodename, CpuStepping, Erratum, FamModStep, ProcInfo};
+
+impl CpuidDump {
+    pub fn errata_report(&self) -> String {
+        let fms = FamModStep::from(&self.get_cpuid(0x1, 0x0));
+        let proc_info = ProcInfo::from_fms(&fms, &self.cpu_vendor);
+
+        let codename = match (proc_info.codename, proc_info.step_info) {
+            (CpuCodename::Unknown(_, _, _), _) => "-".to_string(),
+            (codename, CpuStepping::Unknown(_)) => codename.to_string(),
+            (codename, step_info) => format!("{codename} ({step_info})"),
+        };
+
+        let mut s = [
+            report_head("Errata", &self.cpu_vendor),
+            format!("    Fam{:X}h Model{:X}h Stepping{:X}h, {codename}\n", fms.syn_fam, fms.syn_mod, fms.step),
+        ].concat();
+
+        let mut found = false;
+
+        for erratum in Erratum::lookup(&self.cpu_vendor, &fms) {
+            s.push_str(&format!("\n    {}: {}\n        {}\n", erratum.id, erratum.title, erratum.note));
+            found = true;
+        }
+
+        if !found {
+            s.push_str("\n    No known errata in the list\n");
+        }
+
+        s
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{CpuidDump, str_detect_ftr};
+use crate::parse::*;
+
+/* ref: https://github.com/torvalds/linux/blob/master/arch/x86/include/asm/cpufeatures.h */
+/// Translate the feature name of this crate to the Linux kernel flag name (`/proc/cpuinfo`).
+/// `None` is returned if the kernel does not show the feature in `/proc/cpuinfo`.
+pub(crate) fn kernel_flag_name(ftr: &str) -> Option<&'static str> {
+    let name = match ftr {
+        /* 00_01_EDX */
+        "FPU" => "fpu",
+        "VME" => "vme",
+        "DE" => "de",
+        "PSE" => "pse",
+        "TSC" => "tsc",
+        "MSR" => "msr",
+        "PAE" => "pae",
+        "MCE" => "mce",
+        "CX8" => "cx8",
+        "APIC" => "apic",
+        "SEP" => "sep",
+        "MTRR" => "mtrr",
+        "PGE" => "pge",
+        "MCA" => "mca",
+        "CMOV" => "cmov",
+        "PAT" => "pat",
+        "PSE36" => "pse36",
+        "PSN" => "pn",
+        "CLFLUSH" => "clflush",
+        "DS" => "dts",
+        "ACPI" => "acpi",
+        "MMX" => "mmx",
+        "FXSR" => "fxsr",
+        "SSE" => "sse",
+        "SSE2" => "sse2",
+        "SS" => "ss",
+        "HTT" => "ht",
+        "TM" => "tm",
+        "PBE" => "pbe",
+        /* 00_01_ECX */
+        "SSE3" => "pni",
+        "PCLMULQDQ" => "pclmulqdq",
+        "DTES64" => "dtes64",
+        "MONITOR" => "monitor",
+        "DS-CPL" => "ds_cpl",
+        "VMX" => "vmx",
+        "SMX" => "smx",
+        "EST" => "est",
+        "TM2" => "tm2",
+        "SSSE3" => "ssse3",
+        "CNXT-ID" => "cid",
+        "SDBG" => "sdbg",
+        "FMA" => "fma",
+        "CX16" => "cx16",
+        "xTPR Update Control" => "xtpr",
+        "PDCM" => "pdcm",
+        "PCID" => "pcid",
+        "DCA" => "dca",
+        "SSE4.1" => "sse4_1",
+        "SSE4.2" => "sse4_2",
+        "x2APIC" => "x2apic",
+        "MOVBE" => "movbe",
+        "POPCNT" => "popcnt",
+        "TSC-Deadline" => "tsc_deadline_timer",
+        "AES" => "aes",
+        "XSAVE" => "xsave",
+        "AVX" => "avx",
+        "F16C" => "f16c",
+        "RDRAND" => "rdrand",
+        /* 00_06_EAX */
+        "DigitalTempSensor" => "dtherm",
+        "TurboBoost" => "ida",
+        "ARAT" => "arat",
+        "PLN" => "pln",
+        "PTM" => "pts",
+        "HWP" => "hwp",
+        "HWP_Notification" => "hwp_notify",
+        "HWP_Activity_Window" => "hwp_act_window",
+        "HWP_Energy_Performance_Preference" => "hwp_epp",
+        "HWP_Package_Level_Request" => "hwp_pkg_req",
+        "HFI" => "hfi",
+        /* 00_07_EBX_x0 */
+        "FSGSBASE" => "fsgsbase",
+        "TSC_Adjust" => "tsc_adjust",
+        "SGX" => "sgx",
+        "BMI1" => "bmi1",
+        "HLE" => "hle",
+        "AVX2" => "avx2",
+        "FDP_EXCPTN_ONLY" => "fdp_excptn_only",
+        "SMEP" => "smep",
+        "BMI2" => "bmi2",
+        "ERMS" => "erms",
+        "INVPCID" => "invpcid",
+        "RTM" => "rtm",
+        "PQM" => "cqm",
+        "MemoryProtectionExtensions" => "mpx",
+        "PQE" => "rdt_a",
+        "AVX512F" => "avx512f",
+        "AVX512DQ" => "avx512dq",
+        "RDSEED" => "rdseed",
+        "ADX" => "adx",
+        "SMAP" => "smap",
+        "AVX512IFMA" => "avx512ifma",
+        "CLFLUSHOPT" => "clflushopt",
+        "CLWB" => "clwb",
+        "ProcessorTrace" => "intel_pt",
+        "AVX512PF" => "avx512pf",
+        "AVX512ER" => "avx512er",
+        "AVX512CD" => "avx512cd",
+        "SHA" => "sha_ni",
+        "AVX512BW" => "avx512bw",
+        "AVX512VL" => "avx512vl",
+        /* 00_07_ECX_x0 */
+        "AVX512_VBMI" => "avx512vbmi",
+        "UMIP" => "umip",
+        "PKU" => "pku",
+        "OSPKE" => "ospke",
+        "WAITPKG" => "waitpkg",
+        "AVX512_VBMI2" => "avx512_vbmi2",
+        "GFNI" => "gfni",
+        "VAES" => "vaes",
+        "VPCLMULQDQ" => "vpclmulqdq",
+        "AVX512_VNNI" => "avx512_vnni",
+        "AVX512_BITALG" => "avx512_bitalg",
+        "TME_EN" => "tme",
+        "AVX512_VPOPCNTDQ" => "avx512_vpopcntdq",
+        "LA57" => "la57",
+        "RDPID" => "rdpid",
+        "BUS_LOCK_DETECT" => "bus_lock_detect",
+        "CLDEMOTE" => "cldemote",
+        "MOVDIRI" => "movdiri",
+        "MOVDIRI64B" => "movdir64b",
+        "ENQCMD" => "enqcmd",
+        "SGX_LC" => "sgx_lc",
+        /* 00_07_EDX_x0 */
+        "AVX512_4VNNIW" => "avx512_4vnniw",
+        "AVX512_4FMAPS" => "avx512_4fmaps",
+        "FSRM" => "fsrm",
+        "AVX512_VP2INTERSECT" => "avx512_vp2intersect",
+        "MD_CLEAR" => "md_clear",
+        "SERIALIZE" => "serialize",
+        "Hybrid" => "hybrid_cpu",
+        "TSXLDTRK" => "tsxldtrk",
+        "PCONFIG" => "pconfig",
+        "ArchitecturalLBR" => "arch_lbr",
+        "CET_IBT" => "ibt",
+        "AMX-BF16" => "amx_bf16",
+        "AVX512_FP16" => "avx512_fp16",
+        "AMX-TILE" => "amx_tile",
+        "AMX-INT8" => "amx_int8",
+        "L1D_FLUSH" => "flush_l1d",
+        "SSBD" => "ssbd",
+        /* 00_07_EAX_x1 */
+        "AVX-VNNI" => "avx_vnni",
+        "AVX512_BF16" => "avx512_bf16",
+        "CMPCCXADD" => "cmpccxadd",
+        "ArchPerfmonExt" => "arch_perfmon_ext",
+        "FZRM" => "fzrm",
+        "FSRS" => "fsrs",
+        "FSRC" => "fsrc",
+        "LKGS" => "lkgs",
+        "AMX-FP16" => "amx_fp16",
+        "AVX-IFMA" => "avx_ifma",
+        "LAM" => "lam",
+        /* 00_0D_EAX_x1 */
+        "XSAVEOPT" => "xsaveopt",
+        "XSAVEC" => "xsavec",
+        "XGETBV" => "xgetbv1",
+        "XSAVES" => "xsaves",
+        "XFD" => "xfd",
+        /* 80_01_ECX */
+        "LAHF/SAHF" => "lahf_lm",
+        "CmpLegacy" => "cmp_legacy",
+        "SVM" => "svm",
+        "Ext_APIC_Space" => "extapic",
+        "Alt_MOV_CR8" => "cr8_legacy",
+        "ABM" => "abm",
+        "SSE4A" => "sse4a",
+        "MisAlignSSE" => "misalignsse",
+        "3DNowPrefetch" => "3dnowprefetch",
+        "OSVW" => "osvw",
+        "IBS" => "ibs",
+        "XOP" => "xop",
+        "SKINIT" => "skinit",
+        "WDT" => "wdt",
+        "LWP" => "lwp",
+        "FMA4" => "fma4",
+        "TCE" => "tce",
+        "TBM" => "tbm",
+        "TopologyExtensions" => "topoext",
+        "PerfCtrExtCore" => "perfctr_core",
+        "PerfCtrExtNB" => "perfctr_nb",
+        "DataBkptExt" => "bpext",
+        "PerfTSC" => "ptsc",
+        "PerfCtrExtLLC" => "perfctr_llc",
+        "MONITORX" => "mwaitx",
+        /* 80_01_EDX */
+        "SYSCALL/SYSRET" => "syscall",
+        "NXbit" => "nx",
+        "Page1GB" => "pdpe1gb",
+        "RDTSCP" => "rdtscp",
+        "LongMode" => "lm",
+        "3DNow!Ext" => "3dnowext",
+        "3DNow!" => "3dnow",
+        /* 80_08_EBX */
+        "CLZERO" => "clzero",
+        "InstRetCntMsr" => "irperf",
+        "RstrFpErrPtrs" => "xsaveerptr",
+        "RDPRU" => "rdpru",
+        "WBNOINVD" => "wbnoinvd",
+        "PPIN" => "amd_ppin",
+        "SSBD_Virt_Spec_Ctrl" => "virt_ssbd",
+        "CPPC" => "cppc",
+        /* 80_0A_EDX */
+        "NestedPaging" => "npt",
+        "LbrVirt" => "lbrv",
+        "SVM_Lock" => "svm_lock",
+        "NRIP_Save" => "nrip_save",
+        "TSC_Rate_MSR" => "tsc_scale",
+        "VMCB_Clean" => "vmcb_clean",
+        "FlushByASID" => "flushbyasid",
+        "DecodeAssists" => "decodeassists",
+        "PauseFilter" => "pausefilter",
+        "PauseFilterThreshold" => "pfthreshold",
+        "AVIC" => "avic",
+        "V_VMSAVE_VMLOAD" => "v_vmsave_vmload",
+        "vGIF" => "vgif",
+        "x2AVIC" => "x2avic",
+        "GuestSpecCtrl" => "v_spec_ctrl",
+        "VNMI" => "vnmi",
+        /* 80_1F_EAX */
+        "SME" => "sme",
+        "SEV" => "sev",
+        "SEV-ES" => "sev_es",
+        "SEV-SNP" => "sev_snp",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+impl CpuidDump {
+    /// Kernel flag names, ordered by leaf
+    pub fn kernel_flags(&self) -> Vec<&'static str> {
+        let leaf_01h = self.get_cpuid(0x1, 0x0);
+        let leaf_06h = self.get_cpuid(0x6, 0x0);
+        let leaf_07h_x0 = self.get_cpuid(0x7, 0x0);
+        let leaf_07h_x1 = self.get_cpuid(0x7, 0x1);
+        let leaf_0dh_x1 = self.get_cpuid(0xD, 0x1);
+        let leaf_80_01h = self.get_cpuid(0x8000_0001, 0x0);
+        let leaf_80_08h = self.get_cpuid(0x8000_0008, 0x0);
+        let leaf_80_0ah = self.get_cpuid(0x8000_000A, 0x0);
+        let leaf_80_1fh = self.get_cpuid(0x8000_001F, 0x0);
+
+        let ftrs = [
+            str_detect_ftr(leaf_01h.edx, &ftr_00_01_edx_x0()),
+            str_detect_ftr(leaf_01h.ecx, &ftr_00_01_ecx_x0()),
+            str_detect_ftr(leaf_06h.eax, &ftr_00_06_eax_x0()),
+            str_detect_ftr(leaf_07h_x0.ebx, &ftr_00_07_ebx_x0()),
+            str_detect_ftr(leaf_07h_x0.ecx, &ftr_00_07_ecx_x0()),
+            str_detect_ftr(leaf_07h_x0.edx, &ftr_00_07_edx_x0()),
+            str_detect_ftr(leaf_07h_x1.eax, &ftr_00_07_eax_x1()),
+            str_detect_ftr(leaf_0dh_x1.eax, &xsave_00_0d_eax_x1()),
+            str_detect_ftr(leaf_80_01h.ecx, &ftr_80_01_ecx_x0()),
+            str_detect_ftr(leaf_80_01h.edx, &ftr_80_01_edx_x0()),
+            str_detect_ftr(leaf_80_08h.ebx, &ftr_80_08_ebx_x0()),
+            str_detect_ftr(leaf_80_0ah.edx, &ftr_amd_80_0a_edx_x0()),
+            str_detect_ftr(leaf_80_1fh.eax, &ftr_amd_80_1f_eax_x0()),
+        ].concat();
+
+        let mut flags: Vec<&'static str> = Vec::with_capacity(ftrs.len());
+
+        for flag in ftrs.iter().filter_map(|ftr| kernel_flag_name(ftr)) {
+            if !flags.contains(&flag) {
+                flags.push(flag);
+            }
+        }
+
+        flags
+    }
+
+    /// The `flags` line like `/proc/cpuinfo`
+    pub fn kernel_flags_line(&self) -> String {
+        format!("flags\t\t: {}\n", self.kernel_flags().join(" "))
+    }
+}
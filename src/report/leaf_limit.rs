@@ -0,0 +1,155 @@
+use crate::{CpuidDump, CpuidResult, CpuVendor, RawCpuid};
+use libcpuid_dump::{FamModStep, Reg};
+
+/// Number of leaves probed above the reported max
+const PROBE_LEN: u32 = 0x20;
+const ZERO: CpuidResult = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+
+impl CpuidDump {
+    /// The reported maxima that look artificially limited,
+    /// e.g. "Limit CPUID Maxval" BIOS option or hypervisor masking
+    pub(crate) fn leaf_limit_anomaly(&self) -> Vec<String> {
+        let max_std_leaf = self.get_cpuid(0x0, 0x0).eax;
+        let max_ext_leaf = self.get_cpuid(0x8000_0000, 0x0).eax;
+        let leaf_01h = self.get_cpuid(0x1, 0x0);
+        let leaf_80_01h = self.get_cpuid(0x8000_0001, 0x0);
+        let fms = FamModStep::from(&leaf_01h);
+        let is_amd = self.cpu_vendor == CpuVendor::AuthenticAMD;
+
+        let list: Vec<(bool, String)> = vec![
+            (
+                self.cpu_vendor == CpuVendor::GenuineIntel && fms.syn_fam == 0x6 && max_std_leaf <= 0x3,
+                format!("max_std_leaf ({max_std_leaf:#X}) looks limited by \"Limit CPUID Maxval\""),
+            ),
+            (
+                leaf_01h.ecx.bit(21) && max_std_leaf < 0xB,
+                format!("x2APIC is supported, but max_std_leaf ({max_std_leaf:#X}) < 0xB"),
+            ),
+            (
+                leaf_01h.ecx.bit(26) && max_std_leaf < 0xD,
+                format!("XSAVE is supported, but max_std_leaf ({max_std_leaf:#X}) < 0xD"),
+            ),
+            (
+                leaf_80_01h.edx.bit(29) && max_ext_leaf < 0x8000_0008,
+                format!("LongMode is supported, but max_ext_leaf ({max_ext_leaf:#X}) < 0x80000008"),
+            ),
+            (
+                is_amd && leaf_80_01h.ecx.bit(2) && max_ext_leaf < 0x8000_000A,
+                format!("SVM is supported, but max_ext_leaf ({max_ext_leaf:#X}) < 0x8000000A"),
+            ),
+            (
+                is_amd && leaf_80_01h.ecx.bit(22) && max_ext_leaf < 0x8000_001E,
+                format!("TopologyExtensions is supported, but max_ext_leaf ({max_ext_leaf:#X}) < 0x8000001E"),
+            ),
+        ];
+
+        list.into_iter().filter_map(|(anomaly, msg)| anomaly.then_some(msg)).collect()
+    }
+
+    /// Maxima of the standard, extended, hypervisor (`hv_max`) and Centaur (`centaur_max`) ranges
+    /// that are not self-consistent, e.g. broken BIOS or hypervisor configurations
+    pub(crate) fn leaf_maxima_anomaly(&self, hv_max: u32, centaur_max: u32) -> Vec<String> {
+        let max_std_leaf = self.get_cpuid(0x0, 0x0).eax;
+        let max_ext_leaf = self.get_cpuid(0x8000_0000, 0x0).eax;
+        let leaf_01h = self.get_cpuid(0x1, 0x0);
+        let fms = FamModStep::from(&leaf_01h);
+        let hypervisor = leaf_01h.ecx.bit(31);
+        let is_amd = matches!(self.cpu_vendor, CpuVendor::AuthenticAMD | CpuVendor::HygonGenuine);
+        let is_centaur = matches!(self.cpu_vendor, CpuVendor::CentaurHauls | CpuVendor::Shanghai);
+        let in_range = |max: u32, base: u32| (base..=base + 0xFF).contains(&max);
+
+        let list: Vec<(bool, String)> = vec![
+            (
+                0xFF < max_std_leaf,
+                format!("max_std_leaf ({max_std_leaf:#X}) is out of 0x0..=0xFF"),
+            ),
+            (
+                max_ext_leaf != 0x0 && !in_range(max_ext_leaf, 0x8000_0000),
+                format!("max_ext_leaf ({max_ext_leaf:#X}) is out of 0x80000000..=0x800000FF"),
+            ),
+            (
+                is_amd && 0xF <= fms.syn_fam && max_ext_leaf < 0x8000_0008,
+                format!("Family {:#X}, but max_ext_leaf ({max_ext_leaf:#X}) < 0x80000008", fms.syn_fam),
+            ),
+            /* KVM of the old versions returns 0 for 0x40000001 */
+            (
+                hypervisor && hv_max != 0x0 && !in_range(hv_max, 0x4000_0000),
+                format!("Hypervisor is present, but the max hypervisor leaf ({hv_max:#X}) is out of 0x40000000..=0x400000FF"),
+            ),
+            (
+                is_centaur && !in_range(centaur_max, 0xC000_0000),
+                format!("max Centaur leaf ({centaur_max:#X}) is out of 0xC0000000..=0xC00000FF"),
+            ),
+        ];
+
+        list.into_iter().filter_map(|(anomaly, msg)| anomaly.then_some(msg)).collect()
+    }
+
+    /// Execute the leaves above the reported maxima,
+    /// and check whether they return the default alias (data of the highest basic leaf) or not
+    pub(crate) fn probe_above_limit() -> Vec<String> {
+        let max_std_leaf = RawCpuid::exe(0x0, 0x0).result.eax;
+        let max_ext_leaf = RawCpuid::exe(0x8000_0000, 0x0).result.eax;
+        let default = RawCpuid::exe(max_std_leaf, 0x0).result;
+
+        [(max_std_leaf, "max_std_leaf"), (max_ext_leaf, "max_ext_leaf")].iter().filter_map(|(max, name)| {
+            let leaves: Vec<String> = (max.saturating_add(1)..=max.saturating_add(PROBE_LEN)).filter_map(|leaf| {
+                let result = RawCpuid::exe(leaf, 0x0).result;
+
+                if result == ZERO || result == default {
+                    None
+                } else {
+                    Some(format!("{leaf:#X}"))
+                }
+            }).collect();
+
+            if leaves.is_empty() { return None }
+
+            Some(format!("leaves above {name} ({max:#X}) return non-default data: {}", leaves.join(", ")))
+        }).collect()
+    }
+
+    /// Warning lines for the dump header, only for the current CPU
+    pub(crate) fn leaf_limit_head(&self) -> String {
+        let mut warn = self.leaf_limit_anomaly();
+        let above = Self::probe_above_limit();
+
+        if !warn.is_empty() && above.is_empty() {
+            warn.push("leaves above the max return the default alias data".to_string());
+        }
+
+        warn.extend(above);
+        warn.extend(self.leaf_maxima_anomaly(
+            RawCpuid::exe(0x4000_0000, 0x0).result.eax,
+            RawCpuid::exe(0xC000_0000, 0x0).result.eax,
+        ));
+
+        if let Some(true) = libcpuid_dump::util::cpuid_faulting() {
+            warn.push("CPUID faulting is enabled, results may be emulated by the kernel".to_string());
+        }
+
+        warn.iter().map(|msg| format!("[Warning: {msg}]\n")).collect()
+    }
+}
+
+#[test]
+fn test_leaf_maxima_anomaly() {
+    /* Ryzen 5 5600G, LFuncExt 0x8000_0023 */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+
+    assert!(cpuid_dump.leaf_maxima_anomaly(0x0, 0x0).is_empty());
+
+    let dump = dump.replace(
+        "0x80000000 0x00000000: 0x80000023",
+        "0x80000000 0x00000000: 0x80000004",
+    );
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+
+    assert_eq!(
+        cpuid_dump.leaf_maxima_anomaly(0x0, 0x0),
+        ["Family 0x19, but max_ext_leaf (0x80000004) < 0x80000008"],
+    );
+}
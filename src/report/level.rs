@@ -0,0 +1,67 @@
+use crate::CpuidDump;
+use libcpuid_dump::MicroArchLevel;
+
+impl CpuidDump {
+    /// "--require-level": whether the dump meets the level, and the features of each level up to the target
+    /// ("+" supported, "-" missing) followed by the missing features
+    pub(crate) fn level_report(&self, target: MicroArchLevel) -> (bool, String) {
+        let src = |leaf, sub_leaf| self.get_cpuid(leaf, sub_leaf);
+        let cur = MicroArchLevel::from_source(&src);
+        let features = MicroArchLevel::features_from_source(&src);
+        let missing: Vec<&str> = features
+            .iter()
+            .filter(|ftr| ftr.level <= target && !ftr.present)
+            .map(|ftr| ftr.name)
+            .collect();
+        let meets = missing.is_empty();
+
+        let mut s = if meets {
+            format!("{cur}: meets {target}\n")
+        } else {
+            format!("{cur}: does not meet {target}\n")
+        };
+
+        for level in [MicroArchLevel::X86_64_V1, MicroArchLevel::X86_64_V2, MicroArchLevel::X86_64_V3, MicroArchLevel::X86_64_V4] {
+            if target < level { break }
+
+            let ftrs: Vec<String> = level
+                .level_features(&features)
+                .map(|ftr| format!("{}{}", if ftr.present { "+" } else { "-" }, ftr.name))
+                .collect();
+
+            s.push_str(&format!("    {level}: {}\n", ftrs.join(" ")));
+        }
+
+        if !meets {
+            s.push_str("Missing features:\n");
+
+            for ftr in missing {
+                s.push_str(&format!("    {ftr}\n"));
+            }
+        }
+
+        (meets, s)
+    }
+}
+
+#[test]
+fn test_level_report() {
+    /* Ryzen 5 5600G */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+
+    let (meets, report) = cpuid_dump.level_report(MicroArchLevel::X86_64_V3);
+
+    assert!(meets);
+    assert!(report.starts_with("x86-64-v3: meets x86-64-v3\n"), "{}", report);
+    assert!(!report.contains("x86-64-v4:"), "{}", report);
+
+    let (meets, report) = cpuid_dump.level_report(MicroArchLevel::X86_64_V4);
+
+    assert!(!meets);
+    assert!(report.contains("    x86-64-v2: +SSE3 +SSSE3 +CMPXCHG16B +SSE4_1 +SSE4_2 +POPCNT +LAHF-SAHF\n"), "{}", report);
+    assert!(report.contains("    x86-64-v4: -AVX512F -AVX512DQ -AVX512CD -AVX512BW -AVX512VL\n"), "{}", report);
+    assert!(report.ends_with("Missing features:\n    AVX512F\n    AVX512DQ\n    AVX512CD\n    AVX512BW\n    AVX512VL\n"), "{}", report);
+}
@@ -0,0 +1,52 @@
+use crate::{CpuidDump, CpuidResult, CpuVendor};
+
+mod security;
+mod summary;
+mod kernel_flags;
+mod leaf_limit;
+mod thread_count;
+mod environment;
+mod brand;
+mod xsave;
+mod tlb;
+mod level;
+mod describe;
+mod errata;
+mod boost;
+mod virt;
+mod confidential;
+mod strict;
+mod topology;
+pub(crate) use topology::topology_tree;
+mod ccx;
+pub(crate) use ccx::ccx_report;
+mod apic_id;
+pub(crate) use apic_id::apic_id_report;
+pub(crate) use describe::{describe, did_you_mean};
+pub(crate) use summary::codename_for;
+
+impl CpuidDump {
+    /// Look up the result of (leaf, sub_leaf) from the already-executed pool,
+    /// zeros are returned if the leaf is not in the pool
+    pub(crate) fn get_cpuid(&self, leaf: u32, sub_leaf: u32) -> CpuidResult {
+        self.rawcpuid_pool
+            .iter()
+            .find(|rawcpuid| rawcpuid.leaf == leaf && rawcpuid.sub_leaf == sub_leaf)
+            .map(|rawcpuid| rawcpuid.result)
+            .unwrap_or(CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 })
+    }
+}
+
+pub(crate) fn report_line(name: &str, supported: bool, source: &str) -> String {
+    let flag = if supported { "Yes" } else { "No" };
+
+    format!("    {name:<24} {flag:<4} ({source})\n")
+}
+
+pub(crate) fn report_head(title: &str, vendor: &CpuVendor) -> String {
+    format!("\n[{title}: {vendor}]\n")
+}
+
+pub(crate) fn summary_line(name: &str, value: &str) -> String {
+    format!("{:<24}{value}\n", format!("{name}:"))
+}
@@ -0,0 +1,56 @@
+use crate::{CpuidDump, CpuVendor};
+use super::*;
+use libcpuid_dump::SecurityFeatures;
+
+impl CpuidDump {
+    pub fn security_report(&self) -> String {
+        let ftr = SecurityFeatures::from_cpuid_array([
+            self.get_cpuid(0x7, 0x0),
+            self.get_cpuid(0x7, 0x2),
+            self.get_cpuid(0x8000_0008, 0x0),
+            self.get_cpuid(0x8000_0021, 0x0),
+        ]);
+
+        let list: Vec<(&str, bool, &str)> = match self.cpu_vendor {
+            CpuVendor::AuthenticAMD => vec![
+                ("IBPB", ftr.ibpb, "80_08_EBX[12]"),
+                ("IBRS", ftr.ibrs, "80_08_EBX[14]"),
+                ("STIBP", ftr.stibp, "80_08_EBX[15]"),
+                ("IBRS_Always_On", ftr.ibrs_always_on, "80_08_EBX[16]"),
+                ("STIBP_Always_On", ftr.stibp_always_on, "80_08_EBX[17]"),
+                ("IBRS_Preferred", ftr.ibrs_preferred, "80_08_EBX[18]"),
+                ("IBRS_Same_Mode", ftr.ibrs_same_mode, "80_08_EBX[19]"),
+                ("SSBD", ftr.ssbd, "80_08_EBX[24]"),
+                ("SSBD_Virt_Spec_Ctrl", ftr.virt_ssbd, "80_08_EBX[25]"),
+                ("SSBD_Not_Required", ftr.ssbd_not_required, "80_08_EBX[26]"),
+                ("PSFD", ftr.psfd, "80_08_EBX[28]"),
+                ("BTC_NO", ftr.btc_no, "80_08_EBX[29]"),
+                ("IBPB_RET", ftr.ibpb_ret, "80_08_EBX[30]"),
+                ("AutomaticIBRS", ftr.automatic_ibrs, "80_21_EAX[8]"),
+            ],
+            _ => vec![
+                ("SRBDS_CTRL", ftr.srbds_ctrl, "00_07_EDX_x0[9]"),
+                ("MD_CLEAR", ftr.md_clear, "00_07_EDX_x0[10]"),
+                ("IBRS/IBPB", ftr.ibrs, "00_07_EDX_x0[26]"),
+                ("STIBP", ftr.stibp, "00_07_EDX_x0[27]"),
+                ("L1D_FLUSH", ftr.l1d_flush, "00_07_EDX_x0[28]"),
+                ("IA32_ARCH_CAPABILITIES", ftr.arch_capabilities, "00_07_EDX_x0[29]"),
+                ("IA32_CORE_CAPABILITIES", ftr.core_capabilities, "00_07_EDX_x0[30]"),
+                ("SSBD", ftr.ssbd, "00_07_EDX_x0[31]"),
+                ("PSFD", ftr.psfd, "00_07_EDX_x2[0]"),
+                ("IPRED_CTRL", ftr.ipred_ctrl, "00_07_EDX_x2[1]"),
+                ("RRSBA_CTRL", ftr.rrsba_ctrl, "00_07_EDX_x2[2]"),
+                ("DDPD_U", ftr.ddpd_u, "00_07_EDX_x2[3]"),
+                ("BHI_CTRL", ftr.bhi_ctrl, "00_07_EDX_x2[4]"),
+                ("MCDT_NO", ftr.mcdt_no, "00_07_EDX_x2[5]"),
+            ],
+        };
+
+        let body: String = list
+            .iter()
+            .map(|(name, supported, source)| report_line(name, *supported, source))
+            .collect();
+
+        [report_head("Security features", &self.cpu_vendor), body].concat()
+    }
+}
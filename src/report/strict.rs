@@ -0,0 +1,38 @@
+use crate::{CpuidDump, reserved_bits};
+
+impl CpuidDump {
+    /// Registers with the reserved bits set, like
+    /// "0x00000001 0x0: ECX = 0x80010000, reserved bits 0x00010000"
+    pub(crate) fn reserved_bit_violations(&self) -> Vec<String> {
+        self.rawcpuid_pool.iter().flat_map(|rawcpuid| {
+            let regs = [rawcpuid.result.eax, rawcpuid.result.ebx, rawcpuid.result.ecx, rawcpuid.result.edx];
+
+            reserved_bits(rawcpuid.leaf, rawcpuid.sub_leaf, &self.cpu_vendor).filter_map(move |r| {
+                let reg = regs[r.reg.index()];
+                let set = reg & r.mask;
+
+                (set != 0).then(|| format!(
+                    "{:#010X} {:#X}: {} = {reg:#010X}, reserved bits {set:#010X}",
+                    rawcpuid.leaf,
+                    rawcpuid.sub_leaf,
+                    r.reg.name(),
+                ))
+            })
+        }).collect()
+    }
+}
+
+#[test]
+fn test_reserved_bit_violations() {
+    /* CPUID.(EAX=01h):ECX[16] is reserved */
+    let dump = "\
+        0x00000000 0x00000000: 0x00000020 0x756E6547 0x6C65746E 0x49656E69\n\
+        0x00000001 0x00000000: 0x000806F8 0x00000000 0x80010000 0x00000000\n\
+    ";
+    let cpuid_dump = &crate::load_raw::parse_raw(dump).unwrap()[0];
+
+    assert_eq!(
+        cpuid_dump.reserved_bit_violations(),
+        ["0x00000001 0x0: ECX = 0x80010000, reserved bits 0x00010000"],
+    );
+}
@@ -0,0 +1,441 @@
+use crate::{CpuidDump, CpuVendor};
+use super::*;
+use libcpuid_dump::{
+    AddressSize,
+    AmdMemEncrypt,
+    AmdProcTopo,
+    AmdSizeId,
+    CacheProp,
+    Codename,
+    CpuCodename,
+    CpuMicroArch,
+    CpuStepping,
+    FamModStep,
+    IntelExtTopo,
+    MemEncryptTme,
+    MicroArchLevel,
+    ProcInfo,
+    ProcName,
+    Reg,
+    SgxEpcSection,
+    TopoLevelType,
+};
+
+/* (Name, Leaf, SubLeaf, Register index of [EAX, EBX, ECX, EDX], Bit) */
+const HEADLINE_FTR: &[(&str, u32, u32, usize, u32)] = &[
+    ("SSE4.2", 0x1, 0x0, 2, 20),
+    ("AES", 0x1, 0x0, 2, 25),
+    ("AVX", 0x1, 0x0, 2, 28),
+    ("FMA", 0x1, 0x0, 2, 12),
+    ("AVX2", 0x7, 0x0, 1, 5),
+    ("BMI2", 0x7, 0x0, 1, 8),
+    ("SHA", 0x7, 0x0, 1, 29),
+    ("AVX512F", 0x7, 0x0, 1, 16),
+    ("AVX512_VNNI", 0x7, 0x0, 2, 11),
+    ("AVX512_BF16", 0x7, 0x1, 0, 5),
+    ("AVX512_FP16", 0x7, 0x0, 3, 23),
+    ("AVX-VNNI", 0x7, 0x1, 0, 4),
+    ("VAES", 0x7, 0x0, 2, 9),
+    ("GFNI", 0x7, 0x0, 2, 8),
+    ("AMX-TILE", 0x7, 0x0, 3, 24),
+    ("SGX", 0x7, 0x0, 1, 2),
+    ("VMX", 0x1, 0x0, 2, 5),
+    ("SVM", 0x8000_0001, 0x0, 2, 2),
+];
+
+impl CpuidDump {
+    pub(crate) fn summary_name(&self) -> String {
+        let name = ProcName::raw_bytes([
+            self.get_cpuid(0x8000_0002, 0x0),
+            self.get_cpuid(0x8000_0003, 0x0),
+            self.get_cpuid(0x8000_0004, 0x0),
+        ]);
+
+        ProcName::from_raw_bytes(&name).trim().to_string()
+    }
+
+    /* (Threads per package, Threads per core) */
+    fn summary_thread_count(&self) -> (u32, u32) {
+        for topo_leaf in [0x1F, 0xB] {
+            let [smt, core] = [0x0, 0x1].map(|sub_leaf| {
+                IntelExtTopo::from(&self.get_cpuid(topo_leaf, sub_leaf))
+            });
+
+            if smt.level_type == TopoLevelType::SMT && core.level_type == TopoLevelType::Core {
+                return (core.num_proc, smt.num_proc.max(1));
+            }
+        }
+
+        let max_apic_id = self.get_cpuid(0x1, 0x0).ebx.bits(16..24);
+
+        if let CpuVendor::AuthenticAMD = self.cpu_vendor {
+            let num_thread = AmdSizeId::from(&self.get_cpuid(0x8000_0008, 0x0)).num_thread;
+            let threads_per_core = AmdProcTopo::from(&self.get_cpuid(0x8000_001E, 0x0)).threads_per_core;
+
+            return (num_thread as u32, threads_per_core as u32);
+        }
+
+        (max_apic_id.max(1), 1)
+    }
+
+    /// Cross-check of the logical processors of `CPUID.(EAX=01h):EBX[23:16]`, the threads per core
+    /// of the topology leaves and the CPUs visible to the OS (`os_cpus`), like "SMT disabled in firmware"
+    pub(crate) fn smt_status(&self, os_cpus: Option<usize>) -> String {
+        let leaf_01h = self.get_cpuid(0x1, 0x0);
+        /* HTT: CPUID.(EAX=01h):EDX[28], Hypervisor: CPUID.(EAX=01h):ECX[31] */
+        let htt = leaf_01h.edx.bit(28);
+        let hypervisor = leaf_01h.ecx.bit(31);
+        let leaf_01h_logical = if htt { leaf_01h.ebx.bits(16..24) } else { 1 };
+        let (threads, threads_per_core) = self.summary_thread_count();
+        let cores = (threads / threads_per_core.max(1)) as usize;
+
+        match os_cpus {
+            Some(os_cpus) if 1 < threads_per_core && os_cpus <= cores => format!(
+                "SMT disabled by the OS or firmware ({os_cpus} CPUs visible to the OS for {cores} cores, {threads_per_core} threads/core)",
+            ),
+            _ if hypervisor && threads_per_core == 1 && 1 < threads => {
+                "Hypervisor reporting 1 thread/core".to_string()
+            },
+            _ if threads_per_core == 1 && threads < leaf_01h_logical => format!(
+                "SMT disabled in firmware (CPUID.(EAX=01h) reports {leaf_01h_logical} logical processors, the topology leaf 1 thread/core)",
+            ),
+            Some(os_cpus) if os_cpus < threads as usize => format!(
+                "{os_cpus} of {threads} threads visible to the OS",
+            ),
+            _ => format!("OK, {threads_per_core} threads/core"),
+        }
+    }
+
+    /// 5-level paging: LA57 (`CPUID.(EAX=07h, ECX=0):ECX[16]`) and the virtual address size
+    fn summary_la57(&self) -> String {
+        let la57 = self.get_cpuid(0x7, 0x0).ecx.bit(16);
+        let virtual_ = self.addr_size().virtual_;
+
+        match (la57, virtual_) {
+            (true, 57) => "Supported (LA57, 57-bit virtual)".to_string(),
+            (true, _) => format!("LA57, but {virtual_}-bit virtual reported"),
+            (false, _) => format!("No ({virtual_}-bit virtual)"),
+        }
+    }
+
+    /// Address sizes with Intel LAM/AMD UAI
+    pub(crate) fn addr_size(&self) -> AddressSize {
+        AddressSize::from_cpuid_array([
+            self.get_cpuid(0x8000_0008, 0x0),
+            self.get_cpuid(0x7, 0x1),
+            self.get_cpuid(0x8000_0021, 0x0),
+        ])
+    }
+
+    /// A line per cache with the instances and the total capacity per package,
+    /// like "L3U: 2 × 32 MiB = 64 MiB, 16-way, 1 per CCX (16 threads)"
+    fn summary_cache(&self) -> Vec<String> {
+        let leaf = match self.cpu_vendor {
+            CpuVendor::AuthenticAMD |
+            CpuVendor::HygonGenuine => 0x8000_001D,
+            _ => 0x4,
+        };
+        let (threads, threads_per_core) = self.summary_thread_count();
+        let threads = threads.max(1);
+        let cores = (threads / threads_per_core.max(1)).max(1);
+        /* the L3 is shared by the CCX on AMD, even if the package has only a CCX */
+        let amd = matches!(self.cpu_vendor, CpuVendor::AuthenticAMD | CpuVendor::HygonGenuine);
+        let cluster = if amd { "CCX" } else { "cluster" };
+
+        (0x0..=0x4).filter_map(|sub_leaf| {
+            let cache = CacheProp::option_from_cpuid(&self.get_cpuid(leaf, sub_leaf))?;
+            /* EAX[25:14] is the addressable IDs, rounded up to a power of 2 */
+            let per_core = cache.level <= 2 && cache.share_thread <= threads_per_core.max(1).next_power_of_two();
+            let share_thread = cache.share_thread.min(threads);
+            let plural = |n: u32| if n == 1 { "" } else { "s" };
+            let (count, scope) = match (per_core, threads.div_ceil(share_thread)) {
+                (true, _) => (cores, "core".to_string()),
+                (false, 1) if !amd => (1, format!("package ({threads} thread{})", plural(threads))),
+                (false, count) => (count, format!("{cluster} ({share_thread} thread{})", plural(share_thread))),
+            };
+
+            Some(format!(
+                "L{}{}: {count} × {} {} = {}, {}-way, 1 per {scope}",
+                cache.level,
+                &cache.cache_type.to_string()[..1],
+                cache.size_in_the_unit(),
+                cache.size_unit,
+                size_with_unit(cache.size as u64 * count as u64),
+                cache.way,
+            ))
+        }).collect()
+    }
+
+    /* (Total, per-section) EPC size, None if SGX is not supported */
+    pub(super) fn summary_sgx_epc(&self) -> Option<(u64, Vec<u64>)> {
+        /* SGX: CPUID.(EAX=07h, ECX=0):EBX[2] */
+        if !self.get_cpuid(0x7, 0x0).ebx.bit(2) {
+            return None;
+        }
+
+        let sections: Vec<u64> = self.rawcpuid_pool
+            .iter()
+            .filter(|rawcpuid| rawcpuid.leaf == 0x12 && 0x2 <= rawcpuid.sub_leaf)
+            .filter_map(|rawcpuid| SgxEpcSection::option_from_cpuid(&rawcpuid.result))
+            .map(|epc| epc.size_in_mib())
+            .collect();
+
+        Some((sections.iter().sum(), sections))
+    }
+
+    /* like "SEV-SNP, 509 ASIDs (ES min 1), C-bit 51, phys-addr reduction 1" */
+    pub(super) fn summary_sev(&self) -> Option<String> {
+        if self.cpu_vendor != CpuVendor::AuthenticAMD {
+            return None;
+        }
+
+        let enc = AmdMemEncrypt::from(&self.get_cpuid(0x8000_001F, 0x0));
+
+        if !enc.sev {
+            return None;
+        }
+
+        let mode = if enc.sev_snp {
+            "SEV-SNP"
+        } else if enc.sev_es {
+            "SEV-ES"
+        } else {
+            "SEV"
+        };
+
+        Some(format!(
+            "{mode}, {} ASIDs (ES min {}), C-bit {}, phys-addr reduction {}",
+            enc.num_encrypted_guests,
+            enc.min_sev_no_es_asid,
+            enc.c_bit,
+            enc.phys_addr_reduction,
+        ))
+    }
+
+    pub(crate) fn mem_encrypt_tme(&self) -> MemEncryptTme {
+        let pconfig_sub_leaves: Vec<_> = self.rawcpuid_pool
+            .iter()
+            .filter(|rawcpuid| rawcpuid.leaf == 0x1B)
+            .map(|rawcpuid| rawcpuid.result)
+            .collect();
+
+        MemEncryptTme::from_cpuid_array(
+            [self.get_cpuid(0x7, 0x0), self.get_cpuid(0x8000_0023, 0x0)],
+            &pconfig_sub_leaves,
+        )
+    }
+
+    pub(super) fn summary_tme(&self) -> Option<String> {
+        let tme = self.mem_encrypt_tme();
+
+        if tme.amd_mem_hmk {
+            return Some(format!("SME-HMK, Max KeyID {}", tme.amd_max_key_id));
+        }
+
+        if !tme.tme {
+            return None;
+        }
+
+        Some(if tme.pconfig_mktme {
+            "TME, MKTME (PCONFIG)".to_string()
+        } else {
+            "TME".to_string()
+        })
+    }
+
+    fn summary_headline_ftr(&self) -> String {
+        HEADLINE_FTR.iter().filter_map(|(name, leaf, sub_leaf, idx, bit)| {
+            let cpuid = self.get_cpuid(*leaf, *sub_leaf);
+            let reg = [cpuid.eax, cpuid.ebx, cpuid.ecx, cpuid.edx][*idx];
+
+            reg.bit(*bit).then_some(*name)
+        }).collect::<Vec<&str>>().join(" ")
+    }
+
+    pub fn summary(&self) -> String {
+        let leaf_01h = self.get_cpuid(0x1, 0x0);
+        let fms = FamModStep::from(&leaf_01h);
+        let proc_info = ProcInfo::from_fms(&fms, &self.cpu_vendor);
+
+        let codename = match proc_info.codename {
+            CpuCodename::Unknown(_, _, _) => "-".to_string(),
+            _ => match proc_info.step_info {
+                CpuStepping::Unknown(_) => proc_info.codename.to_string(),
+                _ => format!("{} ({})", proc_info.codename, proc_info.step_info),
+            },
+        };
+        let archname = match proc_info.archname {
+            CpuMicroArch::Unknown => "-".to_string(),
+            _ => proc_info.archname.to_string(),
+        };
+        let FamModStep { syn_fam, syn_mod, step, raw_eax } = fms;
+
+        let (threads, threads_per_core) = self.summary_thread_count();
+        let cores = threads / threads_per_core.max(1);
+
+        let freq = {
+            let leaf_16h = self.get_cpuid(0x16, 0x0);
+            let [base, max] = [leaf_16h.eax & 0xFFFF, leaf_16h.ebx & 0xFFFF];
+
+            if base != 0 {
+                format!("Base {base} MHz, Max {max} MHz")
+            } else {
+                "-".to_string()
+            }
+        };
+
+        let boost = match self.boost_capability() {
+            (true, true) => "Yes, HW P-states",
+            (true, false) => "Yes",
+            (false, true) => "No, HW P-states",
+            (false, false) => "No",
+        };
+
+        let addr_size = self.addr_size();
+        let guest_physical = match addr_size.guest_physical {
+            0 => String::new(),
+            guest => format!(", {guest} bits guest physical"),
+        };
+        let upper_addr_ignore = match (addr_size.lam, addr_size.uai) {
+            (true, _) => ", LAM",
+            (false, true) => ", UAI",
+            (false, false) => "",
+        };
+        let level = MicroArchLevel::from_cpuid_array([
+            leaf_01h,
+            self.get_cpuid(0x7, 0x0),
+            self.get_cpuid(0x8000_0001, 0x0),
+        ]);
+
+        let mut summary = [
+            ("Vendor", self.cpu_vendor.to_string()),
+            ("Model name", self.summary_name()),
+            ("Codename", codename),
+            ("Microarchitecture", archname),
+            ("Family/Model/Stepping", format!("{syn_fam:#X}/{syn_mod:#X}/{step:#X} ({raw_eax:#010X})")),
+            ("Microcode", self.microcode.map_or("-".to_string(), |rev| format!("{rev:#X}"))),
+            ("Threads per package", threads.to_string()),
+            ("Cores per package", cores.to_string()),
+            ("Threads per core", threads_per_core.to_string()),
+            ("Frequency", freq),
+            ("Boost", boost.to_string()),
+            ("Address sizes", format!(
+                "{} bits physical, {} bits virtual{guest_physical}{upper_addr_ignore}",
+                addr_size.physical,
+                addr_size.virtual_,
+            )),
+            ("5-level paging", self.summary_la57()),
+            ("Microarch level", level.to_string()),
+        ].iter().map(|(name, value)| summary_line(name, value)).collect::<String>();
+
+        for mismatch in self.brand_mismatch() {
+            summary.push_str(&summary_line("Brand mismatch", &mismatch));
+        }
+
+        for cache in self.summary_cache() {
+            summary.push_str(&summary_line("Cache", &cache));
+        }
+
+        if let Some((total, sections)) = self.summary_sgx_epc() {
+            let sections: Vec<String> = sections.iter().map(|size| format!("{size} MiB")).collect();
+
+            summary.push_str(&summary_line(
+                "SGX EPC",
+                &format!("{total} MiB total ({})", sections.join(" + ")),
+            ));
+        }
+
+        if let Some(tme) = self.summary_tme() {
+            summary.push_str(&summary_line("Memory encryption", &tme));
+        }
+
+        if let Some(sev) = self.summary_sev() {
+            summary.push_str(&summary_line("SEV", &sev));
+        }
+
+        summary.push_str(&summary_line("Features", &self.summary_headline_ftr()));
+
+        summary
+    }
+}
+
+/* like "1.5 MiB" */
+fn size_with_unit(byte: u64) -> String {
+    let (div, unit) = [(1 << 30, "GiB"), (1 << 20, "MiB"), (1 << 10, "KiB")]
+        .iter()
+        .copied()
+        .find(|(div, _)| *div <= byte)
+        .unwrap_or((1, "Byte"));
+
+    format!("{} {unit}", byte as f64 / div as f64)
+}
+
+/// The codename, the microarchitecture and the process node of the Family/Model/Stepping,
+/// without the CPU ("--codename-for")
+pub(crate) fn codename_for(fms: &FamModStep, vendor: &CpuVendor) -> String {
+    let proc_info = ProcInfo::from_fms(fms, vendor);
+    let codename = match proc_info.codename {
+        CpuCodename::Unknown(_, _, _) => "Unknown".to_string(),
+        _ => proc_info.codename.to_string(),
+    };
+    let archname = match proc_info.archname {
+        CpuMicroArch::Unknown => "Unknown".to_string(),
+        _ => proc_info.archname.to_string(),
+    };
+    let stepping = match proc_info.step_info {
+        CpuStepping::Unknown(_) => "-".to_string(),
+        step => step.to_string(),
+    };
+    let node = proc_info.node.map_or("-".to_string(), |node| node.to_string());
+    let segment = Codename::from(&proc_info).segment;
+
+    [
+        ("Vendor", vendor.to_string()),
+        ("Family/Model/Stepping", format!("{fms} ({:#010X})", fms.raw_eax())),
+        ("Codename", codename),
+        ("Microarchitecture", archname),
+        ("Segment", segment.to_string()),
+        ("Stepping", stepping),
+        ("Process node", node),
+    ].iter().map(|(name, value)| summary_line(name, value)).collect()
+}
+
+#[test]
+fn test_codename_for() {
+    let s = codename_for(&"19:50:0".parse().unwrap(), &CpuVendor::AuthenticAMD);
+
+    assert!(s.contains("Family/Model/Stepping:  19:50:0 (0x00A50F00)\n"), "{}", s);
+    assert!(s.contains("Codename:               AMD Cezanne/Barcelo\n"), "{}", s);
+    assert!(s.contains("Segment:                Client\n"), "{}", s);
+}
+
+#[test]
+fn test_smt_status() {
+    /* Ryzen 5 5600G, 6 cores, 12 threads */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+
+    assert_eq!(cpuid_dump.smt_status(Some(12)), "OK, 2 threads/core");
+    assert_eq!(cpuid_dump.smt_status(None), "OK, 2 threads/core");
+    assert!(cpuid_dump.smt_status(Some(6)).starts_with("SMT disabled by the OS or firmware"));
+    assert_eq!(cpuid_dump.smt_status(Some(8)), "8 of 12 threads visible to the OS");
+}
+
+#[test]
+fn test_summary_cache() {
+    /* Ryzen 5 5600G, 6 cores, 12 threads, a CCX */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+
+    assert_eq!(cpuid_dump.summary_cache(), [
+        "L1D: 6 × 32 KiB = 192 KiB, 8-way, 1 per core",
+        "L1I: 6 × 32 KiB = 192 KiB, 8-way, 1 per core",
+        "L2U: 6 × 512 KiB = 3 MiB, 8-way, 1 per core",
+        "L3U: 1 × 16 MiB = 16 MiB, 16-way, 1 per CCX (12 threads)",
+    ]);
+    assert_eq!(size_with_unit(3 << 19), "1.5 MiB");
+}
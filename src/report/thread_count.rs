@@ -0,0 +1,110 @@
+use crate::{CpuidDump, CpuVendor};
+use libcpuid_dump::{AmdProcTopo, AmdSizeId, IntelExtTopo, Reg, TopoLevelType};
+
+impl CpuidDump {
+    /// (Source leaf, logical processors per package) of the topology leaves
+    fn topo_thread_counts(&self) -> Vec<(&'static str, u32)> {
+        let max_ext_leaf = self.get_cpuid(0x8000_0000, 0x0).eax;
+        let is_amd = matches!(self.cpu_vendor, CpuVendor::AuthenticAMD | CpuVendor::HygonGenuine);
+        let mut counts: Vec<(&'static str, u32)> = Vec::new();
+
+        if let Some(core) = (0x0..=0x1)
+            .map(|sub_leaf| IntelExtTopo::from(&self.get_cpuid(0xB, sub_leaf)))
+            .find(|topo| topo.level_type == TopoLevelType::Core)
+        {
+            counts.push(("leaf 0xB", core.num_proc));
+        }
+
+        /* the number of the logical processors at the highest level */
+        if let Some(top) = (0x0..=0x4)
+            .map(|sub_leaf| IntelExtTopo::from(&self.get_cpuid(0x1F, sub_leaf)))
+            .take_while(|topo| topo.level_type != TopoLevelType::Invalid)
+            .last()
+        {
+            counts.push(("leaf 0x1F", top.num_proc));
+        }
+
+        if is_amd && 0x8000_0008 <= max_ext_leaf {
+            let num_thread = AmdSizeId::from(&self.get_cpuid(0x8000_0008, 0x0)).num_thread;
+
+            counts.push(("leaf 0x80000008", num_thread as u32));
+        }
+
+        counts.retain(|(_, count)| *count != 0);
+        counts
+    }
+
+    /// Disagreements between `LogicalProcessorCount` (`CPUID.(EAX=01h):EBX[23:16]`), the topology leaves
+    /// and the number of the CPUs of the OS (`os_cpus`), common in the VMs
+    pub(crate) fn thread_count_anomaly(&self, os_cpus: Option<usize>) -> Vec<String> {
+        let leaf_01h = self.get_cpuid(0x1, 0x0);
+        let htt = leaf_01h.edx.bit(28);
+        let leaf_01h_count = leaf_01h.ebx.bits(16..24);
+        let counts = self.topo_thread_counts();
+        let mut warn: Vec<String> = Vec::new();
+
+        for (src, count) in &counts {
+            if !htt && 1 < *count {
+                warn.push(format!("HTT is not set, but {src} reports {count} logical processors per package"));
+            } else if htt && leaf_01h_count < *count {
+                warn.push(format!(
+                    "leaf 0x1 reports {leaf_01h_count} logical processors per package, fewer than {count} of {src}",
+                ));
+            }
+        }
+
+        if counts.iter().any(|(_, count)| *count != counts[0].1) {
+            let counts: Vec<String> = counts.iter().map(|(src, count)| format!("{src}: {count}")).collect();
+
+            warn.push(format!("logical processors per package differ, {}", counts.join(", ")));
+        }
+
+        /* TopologyExtensions: CPUID.(EAX=8000_0001h):ECX[22] */
+        let smt = IntelExtTopo::from(&self.get_cpuid(0xB, 0x0));
+        let topoext = self.get_cpuid(0x8000_0001, 0x0).ecx.bit(22);
+
+        if topoext && smt.level_type == TopoLevelType::SMT {
+            let threads_per_core = AmdProcTopo::from(&self.get_cpuid(0x8000_001E, 0x0)).threads_per_core as u32;
+
+            if threads_per_core != smt.num_proc {
+                warn.push(format!(
+                    "threads per core differ, leaf 0xB: {}, leaf 0x8000001E: {threads_per_core}",
+                    smt.num_proc,
+                ));
+            }
+        }
+
+        if let (Some(os_cpus), Some((src, count))) = (os_cpus, counts.first()) {
+            if os_cpus % (*count as usize) != 0 {
+                warn.push(format!(
+                    "{os_cpus} CPUs of the OS is not a multiple of {count} logical processors per package of {src}",
+                ));
+            }
+        }
+
+        warn
+    }
+
+    /// Warning lines for the dump header, only for the current CPU
+    pub(crate) fn thread_count_head(&self, os_cpus: Option<usize>) -> String {
+        self.thread_count_anomaly(os_cpus)
+            .iter()
+            .map(|msg| format!("[Warning: {msg}]\n"))
+            .collect()
+    }
+}
+
+#[test]
+fn test_thread_count_anomaly() {
+    /* Ryzen 5 5600G, 12 threads */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+
+    assert!(cpuid_dump.thread_count_anomaly(Some(12)).is_empty(), "{:?}", cpuid_dump.thread_count_anomaly(Some(12)));
+    assert_eq!(
+        cpuid_dump.thread_count_anomaly(Some(4)),
+        ["4 CPUs of the OS is not a multiple of 12 logical processors per package of leaf 0xB"],
+    );
+}
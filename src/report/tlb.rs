@@ -0,0 +1,87 @@
+use crate::{CpuidDump, CpuVendor};
+use super::*;
+use libcpuid_dump::{TlbAssoc, TlbInfo};
+
+impl CpuidDump {
+    /* (TLBs sorted by the level and the kind, source leaves) */
+    fn tlb_info(&self) -> (Vec<TlbInfo>, &'static str) {
+        let (mut tlbs, source) = match self.cpu_vendor {
+            CpuVendor::AuthenticAMD |
+            CpuVendor::HygonGenuine => (
+                TlbInfo::from_amd_cpuid_array([
+                    self.get_cpuid(0x8000_0005, 0x0),
+                    self.get_cpuid(0x8000_0006, 0x0),
+                    self.get_cpuid(0x8000_0019, 0x0),
+                ]),
+                "CPUID.(EAX=8000_0005h/8000_0006h/8000_0019h)",
+            ),
+            _ => {
+                let max_sub_leaf = self.get_cpuid(0x18, 0x0).eax;
+                let leaf_18h: Vec<TlbInfo> = (0x0..=max_sub_leaf.min(0xFF))
+                    .filter_map(|sub_leaf| TlbInfo::from_intel_leaf_18h(&self.get_cpuid(0x18, sub_leaf)))
+                    .collect();
+
+                if leaf_18h.is_empty() {
+                    (TlbInfo::from_leaf_02h(&self.get_cpuid(0x2, 0x0)), "CPUID.(EAX=02h) descriptors")
+                } else {
+                    (leaf_18h, "CPUID.(EAX=18h)")
+                }
+            },
+        };
+
+        tlbs.sort_by_key(|tlb| (tlb.level, tlb.kind));
+
+        (tlbs, source)
+    }
+
+    /// A table of the TLBs in the same layout for all vendors:
+    /// the level, the kind, the page sizes, the entries and the associativity
+    pub fn tlb_report(&self) -> String {
+        let (tlbs, source) = self.tlb_info();
+
+        let table: String = if tlbs.is_empty() {
+            "    No TLB information\n".to_string()
+        } else {
+            tlbs.iter().map(|tlb| {
+                let assoc = match &tlb.assoc {
+                    TlbAssoc::Way(1) => "direct".to_string(),
+                    TlbAssoc::Way(way) => format!("{way}-way"),
+                    TlbAssoc::WayRange(range) => format!("{}-{}-way", range.start, range.end - 1),
+                    TlbAssoc::Full => "full".to_string(),
+                    TlbAssoc::Disabled |
+                    TlbAssoc::Invalid => "-".to_string(),
+                };
+                let level = format!("L{}", tlb.level);
+
+                format!(
+                    "    {level:<6} {:<12} {:<12} {:>7}  {assoc}\n",
+                    tlb.kind,
+                    tlb.pages,
+                    tlb.entries,
+                )
+            }).collect()
+        };
+
+        [
+            report_head("TLB", &self.cpu_vendor),
+            format!("    {:<6} {:<12} {:<12} {:>7}  {}\n", "Level", "Type", "Pages", "Entries", "Assoc"),
+            table,
+            "\n".to_string(),
+            summary_line("Source", source),
+        ].concat()
+    }
+}
+
+#[test]
+fn test_tlb_report() {
+    /* Ryzen 5 5600G */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/zen3_ryzen5_5600g.txt")
+    ).unwrap();
+    let cpuid_dump = &crate::load_raw::parse_raw(&dump).unwrap()[0];
+    let report = cpuid_dump.tlb_report();
+
+    assert!(report.contains("    L1     Instruction  4K                64  full\n"), "{}", report);
+    assert!(report.contains("    L2     Data         4K              2048  8-15-way\n"), "{}", report);
+    assert!(report.contains("    L2     Data         1G                64  full\n"), "{}", report);
+}
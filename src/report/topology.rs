@@ -0,0 +1,299 @@
+use crate::{CoreTypeInfo, CpuidDump, CpuVendor};
+use libcpuid_dump::{CacheProp, CacheType, TopoId};
+
+/* a thread of the tree, sorted by (pkg, node, core, smt) */
+struct TopoThread {
+    pkg: u32,
+    node: Option<u32>,
+    core: u32,
+    smt: u32,
+    x2apic: u32,
+    thread_id: Option<usize>,
+    core_type: Option<String>,
+    caches: Vec<TopoCache>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TopoCache {
+    level: u32,
+    /* like "L1d 48 KiB" */
+    label: String,
+    /* x2APIC ID without the bits of the sharing threads */
+    id: u32,
+}
+
+impl TopoThread {
+    fn from_cpuid_dump(i: usize, cpuid_dump: &CpuidDump) -> Self {
+        let TopoId { pkg_id, core_id, smt_id, x2apic_id, node_id } = cpuid_dump.topo_id
+            .unwrap_or(TopoId { smt_id: 0, core_id: i as u32, pkg_id: 0, x2apic_id: i as u32, node_id: None });
+        let core_type = cpuid_dump.core_type_info.map(|info| match info {
+            CoreTypeInfo::Intel(core_type, _) => core_type.to_string(),
+            CoreTypeInfo::Amd(core_type, _) => core_type.to_string(),
+        });
+
+        Self {
+            pkg: pkg_id,
+            node: node_id,
+            core: core_id,
+            smt: smt_id,
+            x2apic: x2apic_id,
+            thread_id: cpuid_dump.thread_id,
+            core_type,
+            caches: cpuid_dump.topo_caches(x2apic_id),
+        }
+    }
+
+    fn cpu_name(&self) -> String {
+        match self.thread_id {
+            Some(id) => format!("CPU {id}"),
+            None => format!("SMT {}", self.smt),
+        }
+    }
+}
+
+impl CpuidDump {
+    fn topo_caches(&self, x2apic_id: u32) -> Vec<TopoCache> {
+        let leaf = match self.cpu_vendor {
+            CpuVendor::AuthenticAMD |
+            CpuVendor::HygonGenuine => 0x8000_001D,
+            _ => 0x4,
+        };
+
+        (0x0..=0x4).filter_map(|sub_leaf| {
+            let cache = CacheProp::option_from_cpuid(&self.get_cpuid(leaf, sub_leaf))?;
+            let cache_type = match cache.cache_type {
+                CacheType::Data => "d",
+                CacheType::Instruction => "i",
+                _ => "",
+            };
+            let label = format!("L{}{cache_type} {} {}", cache.level, cache.size_in_the_unit(), cache.size_unit);
+            let shift = 32 - (cache.share_thread - 1).leading_zeros();
+
+            Some(TopoCache { level: cache.level, label, id: x2apic_id.checked_shr(shift).unwrap_or(0) })
+        }).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Package,
+    Node,
+    Core,
+    Cpu,
+}
+
+/* the threads of the same level as the thread */
+fn level_group(threads: &[TopoThread], i: usize, level: Level) -> Vec<usize> {
+    let t = &threads[i];
+
+    (0..threads.len()).filter(|j| {
+        let u = &threads[*j];
+
+        match level {
+            Level::Package => u.pkg == t.pkg,
+            Level::Node => (u.pkg, u.node) == (t.pkg, t.node),
+            Level::Core => (u.pkg, u.node, u.core) == (t.pkg, t.node, t.core),
+            Level::Cpu => *j == i,
+        }
+    }).collect()
+}
+
+/* the level of the threads sharing the cache, the lower level for L1/L2 if the levels have the same threads,
+   `None` for the caches shared by a part of the level (e.g. L3 of CCX, L2 of E-core module) */
+fn cache_level(threads: &[TopoThread], i: usize, cache: &TopoCache) -> Option<Level> {
+    let sharing: Vec<usize> = (0..threads.len())
+        .filter(|j| threads[*j].caches.contains(cache))
+        .collect();
+    let order = if cache.level <= 2 {
+        [Level::Core, Level::Cpu, Level::Node, Level::Package]
+    } else {
+        [Level::Package, Level::Node, Level::Core, Level::Cpu]
+    };
+
+    order.iter().copied().find(|level| {
+        (*level != Level::Node || threads[i].node.is_some()) && level_group(threads, i, *level) == sharing
+    })
+}
+
+fn level_caches(threads: &[TopoThread], i: usize, level: Option<Level>) -> String {
+    let caches: Vec<String> = threads[i].caches
+        .iter()
+        .filter(|cache| cache_level(threads, i, cache) == level)
+        .map(|cache| match level {
+            Some(_) => cache.label.clone(),
+            None => format!("{} #{}", cache.label, cache.id),
+        })
+        .collect();
+
+    if caches.is_empty() {
+        String::new()
+    } else {
+        format!("  [{}]", caches.join(", "))
+    }
+}
+
+/* split the sorted indices into the runs of the same key */
+fn runs<K: PartialEq>(group: &[usize], key: impl Fn(usize) -> K) -> Vec<Vec<usize>> {
+    let mut runs: Vec<Vec<usize>> = Vec::new();
+
+    for i in group {
+        match runs.last_mut() {
+            Some(run) if key(run[0]) == key(*i) => run.push(*i),
+            _ => runs.push(vec![*i]),
+        }
+    }
+
+    runs
+}
+
+fn branch(prefix: &str, last: bool) -> (String, String) {
+    if last {
+        (format!("{prefix}`-- "), format!("{prefix}    "))
+    } else {
+        (format!("{prefix}|-- "), format!("{prefix}|   "))
+    }
+}
+
+/* like "Atom: 4 cores per L2 (2 MiB), 2 L2" for each core type of the hybrid architecture */
+fn hybrid_cache_domains(threads: &[TopoThread]) -> String {
+    let mut core_types: Vec<&str> = Vec::new();
+
+    for core_type in threads.iter().filter_map(|t| t.core_type.as_deref()) {
+        if !core_types.contains(&core_type) {
+            core_types.push(core_type);
+        }
+    }
+
+    core_types.iter().filter_map(|core_type| {
+        let of_type: Vec<&TopoThread> = threads
+            .iter()
+            .filter(|t| t.core_type.as_deref() == Some(*core_type))
+            .collect();
+        let mut l2_list: Vec<&TopoCache> = Vec::new();
+
+        for cache in of_type.iter().flat_map(|t| t.caches.iter()).filter(|cache| cache.level == 2) {
+            if !l2_list.contains(&cache) {
+                l2_list.push(cache);
+            }
+        }
+
+        /* the number of the cores sharing each L2 */
+        let counts: Vec<usize> = l2_list.iter().map(|l2| {
+            let mut cores: Vec<(u32, Option<u32>, u32)> = of_type
+                .iter()
+                .filter(|t| t.caches.contains(l2))
+                .map(|t| (t.pkg, t.node, t.core))
+                .collect();
+            cores.dedup();
+
+            cores.len()
+        }).collect();
+
+        let (min, max) = (counts.iter().min()?, counts.iter().max()?);
+        let per = if min == max { min.to_string() } else { format!("{min}-{max}") };
+        let cores = if *max == 1 { "core" } else { "cores" };
+        let size = l2_list[0].label.trim_start_matches("L2 ");
+
+        Some(format!("{core_type}: {per} {cores} per L2 ({size}), {} L2\n", l2_list.len()))
+    }).collect()
+}
+
+/// "Package → Node (AMD) → Core → CPU" tree of the threads, with the core types
+/// and the caches attached to the level of the threads sharing them,
+/// followed by the cores per L2 of each core type for the hybrid architecture
+pub(crate) fn topology_tree(cpuid_dumps: &[CpuidDump]) -> String {
+    let mut threads: Vec<TopoThread> = cpuid_dumps
+        .iter()
+        .enumerate()
+        .map(|(i, cpuid_dump)| TopoThread::from_cpuid_dump(i, cpuid_dump))
+        .collect();
+    threads.sort_by_key(|t| (t.pkg, t.node, t.core, t.smt));
+
+    let all: Vec<usize> = (0..threads.len()).collect();
+    let mut s = String::new();
+
+    for pkg in runs(&all, |i| threads[i].pkg) {
+        let caches = level_caches(&threads, pkg[0], Some(Level::Package));
+        s.push_str(&format!("Package {}{caches}\n", threads[pkg[0]].pkg));
+
+        let nodes = runs(&pkg, |i| threads[i].node);
+
+        for (n, node) in nodes.iter().enumerate() {
+            let prefix = match threads[node[0]].node {
+                Some(node_id) => {
+                    let (head, prefix) = branch("", n + 1 == nodes.len());
+                    let caches = level_caches(&threads, node[0], Some(Level::Node));
+                    s.push_str(&format!("{head}Node {node_id}{caches}\n"));
+
+                    prefix
+                },
+                None => String::new(),
+            };
+
+            let cores = runs(node, |i| threads[i].core);
+
+            for (c, core) in cores.iter().enumerate() {
+                let (head, prefix) = branch(&prefix, c + 1 == cores.len());
+                let t = &threads[core[0]];
+                let core_type = t.core_type.as_ref().map_or(String::new(), |core_type| format!(" ({core_type})"));
+                let caches = level_caches(&threads, core[0], Some(Level::Core));
+                s.push_str(&format!("{head}Core {}{core_type}{caches}\n", t.core));
+
+                for (j, i) in core.iter().enumerate() {
+                    let (head, _) = branch(&prefix, j + 1 == core.len());
+                    let t = &threads[*i];
+                    let caches = level_caches(&threads, *i, Some(Level::Cpu));
+                    let partial = level_caches(&threads, *i, None);
+
+                    s.push_str(&format!("{head}{}  x2APIC: {:#X}{caches}{partial}\n", t.cpu_name(), t.x2apic));
+                }
+            }
+        }
+    }
+
+    let hybrid = hybrid_cache_domains(&threads);
+
+    if !hybrid.is_empty() {
+        s.push('\n');
+        s.push_str(&hybrid);
+    }
+
+    s
+}
+
+#[test]
+fn test_topology_tree() {
+    /* P-core and E-core of Core i9-12900K */
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/alderlake_core_i9_12900k.txt")
+    ).unwrap();
+    let tree = topology_tree(&crate::load_raw::parse_raw(&dump).unwrap());
+
+    assert_eq!(tree.lines().next(), Some("Package 0"));
+    assert!(tree.contains("|-- Core 0 (Core)\n|   `-- CPU 0"));
+    assert!(tree.contains("`-- Core 1 (Atom)\n    `-- CPU 16"));
+}
+
+#[test]
+fn test_hybrid_cache_domains() {
+    /* a P-core with 2 threads and 4 E-cores sharing a L2 */
+    let leaves = |x2apic: u32, l2: &str, hybrid: &str| format!("\
+        0x00000000 0x00000000: 0x00000020 0x756E6547 0x6C65746E 0x49656E69\n\
+        0x00000001 0x00000000: 0x00090672 0x00000000 0x00000000 0x00000000\n\
+        0x00000004 0x00000002: {l2} 0x000007FF 0x00000000\n\
+        0x0000000B 0x00000000: 0x00000001 0x00000002 0x00000100 {x2apic:#010X}\n\
+        0x0000000B 0x00000001: 0x00000007 0x00000018 0x00000201 {x2apic:#010X}\n\
+        0x0000001A 0x00000000: {hybrid} 0x00000000 0x00000000 0x00000000\n\
+    ");
+    let p_core = "0x00004043 0x0240003F";
+    let e_core = "0x0001C043 0x03C0003F";
+    let dump: String = [(0, 0x0, p_core, "0x40000001"), (1, 0x1, p_core, "0x40000001")]
+        .iter()
+        .chain([(16, 0x40, e_core, "0x20000001"), (17, 0x42, e_core, "0x20000001")].iter())
+        .chain([(18, 0x44, e_core, "0x20000001"), (19, 0x46, e_core, "0x20000001")].iter())
+        .map(|(thread, x2apic, l2, hybrid)| format!("# [Thread: {thread:03}]\n{}", leaves(*x2apic, l2, hybrid)))
+        .collect();
+    let tree = topology_tree(&crate::load_raw::parse_raw(&dump).unwrap());
+
+    assert!(tree.ends_with("\nCore: 1 core per L2 (1.25 MiB), 1 L2\nAtom: 4 cores per L2 (2 MiB), 1 L2\n"), "{}", tree);
+}
@@ -0,0 +1,58 @@
+use crate::{CpuidDump, CpuVendor};
+use super::*;
+use libcpuid_dump::Reg;
+
+impl CpuidDump {
+    /// VMX (Intel, `CPUID.(EAX=01h):ECX[5]`) or SVM (AMD, `CPUID.(EAX=8000_0001h):ECX[2]`)
+    /// and the SVM features of `CPUID.(EAX=8000_000Ah)` for the nested paging and the nested virtualization
+    pub fn virt_report(&self) -> String {
+        let leaf_01h = self.get_cpuid(0x1, 0x0);
+        let hypervisor = report_line("Hypervisor present", leaf_01h.ecx.bit(31), "01_ECX[31]");
+
+        let body = match self.cpu_vendor {
+            CpuVendor::AuthenticAMD |
+            CpuVendor::HygonGenuine => {
+                let svm = self.get_cpuid(0x8000_0001, 0x0).ecx.bit(2);
+                let leaf_0ah = self.get_cpuid(0x8000_000A, 0x0);
+                let edx = leaf_0ah.edx;
+
+                let list = [
+                    ("SVM", svm, "80_01_ECX[2]"),
+                    ("NPT (Nested Paging)", edx.bit(0), "80_0A_EDX[0] NP"),
+                    ("AVIC", edx.bit(13), "80_0A_EDX[13]"),
+                    ("x2AVIC", edx.bit(18), "80_0A_EDX[18]"),
+                    ("NRIPS", edx.bit(3), "80_0A_EDX[3] NRIPSave"),
+                    ("VMCB Clean Bits", edx.bit(5), "80_0A_EDX[5] VmcbClean"),
+                    ("Flush by ASID", edx.bit(6), "80_0A_EDX[6] FlushByAsid"),
+                    ("Decode Assists", edx.bit(7), "80_0A_EDX[7] DecodeAssists"),
+                    ("Pause Filter", edx.bit(10), "80_0A_EDX[10] PauseFilter"),
+                    ("Virtual VMSAVE/VMLOAD", edx.bit(15), "80_0A_EDX[15] VMSAVEvirt"),
+                    ("Virtual GIF", edx.bit(16), "80_0A_EDX[16] VGIF"),
+                    ("Virtual NMI", edx.bit(25), "80_0A_EDX[25] VNMI"),
+                ];
+                let lines: String = list
+                    .iter()
+                    .map(|(name, supported, source)| report_line(name, *supported, source))
+                    .collect();
+                /* SvmRev: EAX[7:0], NASID: EBX */
+                let rev = if svm {
+                    format!("    SVM revision {:#X}, {} ASIDs\n", leaf_0ah.eax & 0xFF, leaf_0ah.ebx)
+                } else {
+                    String::new()
+                };
+                let kvm = report_line("KVM with NPT", svm && edx.bit(0), "SVM + NP");
+
+                [lines, hypervisor, rev, kvm].concat()
+            },
+            _ => {
+                let vmx = report_line("VMX", leaf_01h.ecx.bit(5), "01_ECX[5]");
+                /* EPT, VPID and VMCS shadowing are enumerated by IA32_VMX_PROCBASED_CTLS2 (MSR 0x48B) */
+                let note = "    EPT, VPID and VMCS shadowing are not enumerated by CPUID (IA32_VMX_* MSRs)\n";
+
+                [vmx, hypervisor, note.to_string()].concat()
+            },
+        };
+
+        [report_head("Virtualization", &self.cpu_vendor), body].concat()
+    }
+}
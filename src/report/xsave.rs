@@ -0,0 +1,118 @@
+use crate::{CpuidDump, xfeature_mask_00_0d_eax_x0};
+use super::*;
+use libcpuid_dump::{Reg, XSaveInfo, XStateComponent, xgetbv};
+
+/* Legacy region (512B) + XSAVE header (64B) */
+const XSAVE_EXT_AREA_OFFSET: u32 = 576;
+
+impl CpuidDump {
+    fn xstate_components(&self, info: &XSaveInfo) -> Vec<XStateComponent> {
+        let mask = info.xcr0_supported | info.xss_supported;
+
+        (0..64u32).filter(|i| ((mask >> i) & 0b1) == 0b1).map(|index| match index {
+            /* x87 and SSE states are in the legacy region, not enumerated by sub-leaf */
+            0 => XStateComponent { index, size: 160, offset: 0, ..Default::default() },
+            1 => XStateComponent { index, size: 256, offset: 160, ..Default::default() },
+            _ => XStateComponent::from_cpuid(index, &self.get_cpuid(0xD, index)),
+        }).collect()
+    }
+
+    pub fn xsave_layout(&self) -> String {
+        let info = XSaveInfo::from_cpuid_array([
+            self.get_cpuid(0xD, 0x0),
+            self.get_cpuid(0xD, 0x1),
+        ]);
+        let names = xfeature_mask_00_0d_eax_x0();
+
+        /* Compacted format (XSAVEC/XSAVES) with all the supported components enabled */
+        let mut compacted = XSAVE_EXT_AREA_OFFSET;
+
+        let table: String = self.xstate_components(&info).iter().map(|c| {
+            let name = names.get(c.index as usize).copied().filter(|s| !s.is_empty()).unwrap_or("Unknown");
+            let state = if c.supervisor { "Supervisor" } else { "User" };
+            let align = if c.align_64 { "Yes" } else { "No" };
+            let offset = if c.supervisor { "-".to_string() } else { format!("{}", c.offset) };
+
+            let compacted_offset = if c.index < 2 {
+                c.offset
+            } else {
+                if c.align_64 {
+                    compacted = (compacted + 63) & !63;
+                }
+                let tmp = compacted;
+                compacted += c.size;
+
+                tmp
+            };
+
+            format!(
+                "    {:>3}  {name:<20} {state:<10} {:>6} {offset:>8} {compacted_offset:>10}  {align}\n",
+                c.index,
+                c.size,
+            )
+        }).collect();
+
+        [
+            report_head("XSAVE Layout", &self.cpu_vendor),
+            format!("    {:>3}  {:<20} {:<10} {:>6} {:>8} {:>10}  {}\n",
+                "Idx", "Component", "Type", "Size", "Offset", "Compacted", "Align64"),
+            table,
+            "\n".to_string(),
+            summary_line("Size, XCR0", &format!("{}B", info.size_xcr0_enabled)),
+            summary_line("Size, XCR0 max", &format!("{}B", info.size_xcr0_max)),
+            summary_line("Size, XCR0|XSS", &format!("{}B (compacted)", info.size_xcr0_xss_enabled)),
+            summary_line("Size, all compacted", &format!("{compacted}B")),
+        ].concat()
+    }
+
+    /// XCR0 enabled by the OS versus the user state components supported by the CPU
+    pub fn xcr0_report(&self) -> String {
+        let info = XSaveInfo::from_cpuid_array([
+            self.get_cpuid(0xD, 0x0),
+            self.get_cpuid(0xD, 0x1),
+        ]);
+        let names = xfeature_mask_00_0d_eax_x0();
+        let name_list = |mask: u64| -> String {
+            let list: Vec<&str> = (0..64usize)
+                .filter(|i| ((mask >> i) & 0b1) == 0b1)
+                .map(|i| names.get(i).copied().filter(|s| !s.is_empty()).unwrap_or("Unknown"))
+                .collect();
+
+            if list.is_empty() { "-".to_string() } else { list.join(", ") }
+        };
+
+        let Some(xcr0) = xgetbv(0) else {
+            return [
+                report_head("XCR0", &self.cpu_vendor),
+                "    OSXSAVE is not set, XSAVE is not enabled by the OS\n".to_string(),
+            ].concat();
+        };
+
+        let xinuse = if info.xgetbv_ecx1 { xgetbv(1) } else { None };
+        let [leaf_01h, leaf_07h] = [self.get_cpuid(0x1, 0x0), self.get_cpuid(0x7, 0x0)];
+        /* (Feature, supported by the CPU, required XCR0 bits) */
+        let usable: String = [
+            ("AVX", leaf_01h.ecx.bit(28), 0b110u64),
+            ("AVX512F", leaf_07h.ebx.bit(16), 0b1110_0110),
+            ("AMX-TILE", leaf_07h.edx.bit(24), 0b11 << 17),
+        ].iter().filter(|(_, supported, _)| *supported).map(|(name, _, mask)| {
+            let usable = if (xcr0 & mask) == *mask { "Usable" } else { "Not usable, disabled in XCR0" };
+
+            summary_line(name, usable)
+        }).collect();
+
+        [
+            report_head("XCR0", &self.cpu_vendor),
+            summary_line("XCR0 supported", &format!("{:#X}", info.xcr0_supported)),
+            summary_line("XCR0 enabled", &format!("{xcr0:#X}")),
+            summary_line("Supported, not enabled", &name_list(info.xcr0_supported & !xcr0)),
+            summary_line("Enabled, not supported", &name_list(xcr0 & !info.xcr0_supported)),
+            match xinuse {
+                Some(xinuse) => summary_line("XINUSE", &format!("{xinuse:#X}")),
+                None => String::new(),
+            },
+            summary_line("IA32_XSS supported", &format!("{:#X} (not readable from user mode)", info.xss_supported)),
+            usable,
+        ].concat()
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{heuristic_hints, CpuidResult, CpuVendor, FormatConfig, RawCpuid, RawCpuidFmt, LN_PAD};
+use crate::progress::Progress;
+use std::io::{self, Write};
+
+/// (base leaf, name), each range is probed up to `base + SCAN_LEN`
+const SCAN_RANGE: [(u32, &str); 4] = [
+    (0x0000_0000, "Standard"),
+    (0x4000_0000, "Hypervisor"),
+    (0x8000_0000, "Extended"),
+    (0xC000_0000, "Centaur"),
+];
+const SCAN_LEN: u32 = 0x100;
+const ZERO: CpuidResult = CpuidResult { eax: 0x0, ebx: 0x0, ecx: 0x0, edx: 0x0 };
+
+/// Probe the leaves beyond the reported maxima,
+/// leaves returning non-default data beyond the max are flagged as undocumented.
+/// Each range is written as soon as it is probed.
+pub(crate) fn scan_leaf(vendor: &CpuVendor, cfg: &FormatConfig, out: &mut dyn Write) -> io::Result<()> {
+    let max_std_leaf = RawCpuid::exe(0x0, 0x0).result.eax;
+    /* Intel returns the data of the highest basic leaf for the leaf above the max */
+    let default = RawCpuid::exe(max_std_leaf, 0x0).result;
+    let progress = Progress::new("leaves", SCAN_RANGE.len() * SCAN_LEN as usize);
+
+    for (base, name) in SCAN_RANGE.iter() {
+        let end = base + SCAN_LEN - 1;
+        let max = RawCpuid::exe(*base, 0x0).result.eax;
+        /* the max leaf is out of range or not implemented */
+        let max = if (*base..=base + 0xFFFF).contains(&max) {
+            Some(max)
+        } else {
+            None
+        };
+
+        let head = match max {
+            Some(max) => format!("\n[{name}: {base:#010X}..={end:#010X}, Max: {max:#010X}]\n"),
+            None => format!("\n[{name}: {base:#010X}..={end:#010X}, Max: -]\n"),
+        };
+
+        let s: String = (*base..=end).filter_map(|leaf| {
+            let rawcpuid = RawCpuid::exe(leaf, 0x0);
+            let in_range = max.is_some_and(|max| leaf <= max);
+
+            progress.inc();
+
+            if rawcpuid.result == ZERO {
+                return None;
+            }
+
+            if in_range {
+                return Some(rawcpuid.parse_fmt(vendor, cfg));
+            }
+
+            if rawcpuid.result == default {
+                return None;
+            }
+
+            let hints = heuristic_hints(&rawcpuid.result);
+            let end_str = if hints.is_empty() {
+                "[Beyond Max, Undocumented?]".to_string()
+            } else {
+                format!("[Beyond Max, Undocumented?]{LN_PAD}{hints}")
+            };
+
+            Some(rawcpuid.result(&end_str))
+        }).collect();
+
+        progress.clear();
+        out.write_all([head, s].concat().as_bytes())?;
+        out.flush()?;
+    }
+
+    Ok(())
+}
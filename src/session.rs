@@ -0,0 +1,181 @@
+use crate::{cpu_ranges, disp_all_threads, header, skipped_cpus_msg_of, CpuidDump, DumpFormat};
+use crate::load_raw;
+use libcpuid_dump::util::OsCpuTopology;
+use libcpuid_dump::FormatConfig;
+
+/// The first line of the session file of "record", with the version of the format
+const SESSION_HEAD: &str = "# CPUID Dump Session: ";
+const SESSION_VERSION: u32 = 1;
+
+/// Metadata of the recorded session, "# <key>: <value>" lines after the first line.
+/// The body is the "-a -r" dump of all threads, so that "--load" also accepts the session file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SessionMeta {
+    /// "<version> (<commit>)" of the recorder
+    pub recorder: String,
+    /// "YYYY-MM-DDThh:mm:ssZ"
+    pub recorded: String,
+    /// "<os> <arch>" of the recorder
+    pub os: String,
+    /// The CPUs dumped, like "0-7, 16-23"
+    pub cpus: String,
+    /// The CPUs configured in the OS, including the CPUs offline
+    pub configured_cpus: Option<usize>,
+    /// The topology of each CPU dumped, seen by the OS (sysfs), empty if it is not available
+    pub os_topology: Vec<(usize, OsCpuTopology)>,
+}
+
+/* "# OS CPU <cpu>: package <id>, die <id>, core <id>, node <id>", "-" for the unknown die/node */
+const OS_CPU: &str = "OS CPU ";
+
+fn os_topology_fmt(topo: &OsCpuTopology) -> String {
+    let id = |id: Option<u32>| id.map_or("-".to_string(), |id| id.to_string());
+
+    format!("package {}, die {}, core {}, node {}", topo.package_id, id(topo.die_id), topo.core_id, id(topo.node_id))
+}
+
+fn parse_os_topology(s: &str) -> Option<OsCpuTopology> {
+    let mut ids = s.split(", ").map(|field| field.split_once(' ').map(|(_, id)| id));
+    let mut next = || -> Option<Option<u32>> {
+        match ids.next()?? {
+            "-" => Some(None),
+            id => id.parse().ok().map(Some),
+        }
+    };
+
+    Some(OsCpuTopology { package_id: next()??, die_id: next()?, core_id: next()??, node_id: next()? })
+}
+
+impl SessionMeta {
+    fn current(cpuid_dumps: &[CpuidDump]) -> Self {
+        let mut cpus: Vec<usize> = cpuid_dumps.iter().filter_map(|cpuid_dump| cpuid_dump.thread_id).collect();
+        cpus.sort_unstable();
+
+        Self {
+            recorder: format!("{} ({})", env!("CARGO_PKG_VERSION"), env!("CPUID_DUMP_GIT_COMMIT")),
+            recorded: load_raw::utc_timestamp(std::time::SystemTime::now()),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            cpus: cpu_ranges(&cpus),
+            configured_cpus: libcpuid_dump::util::configured_cpu_count(),
+            os_topology: cpus.iter().filter_map(|&cpu| Some((cpu, libcpuid_dump::util::os_cpu_topology(cpu)?))).collect(),
+        }
+    }
+
+    fn head(&self) -> String {
+        let configured = self.configured_cpus.map_or("-".to_string(), |n| n.to_string());
+
+        let os_topology: String = self.os_topology
+            .iter()
+            .map(|(cpu, topo)| format!("# {OS_CPU}{cpu}: {}\n", os_topology_fmt(topo)))
+            .collect();
+
+        format!(
+            "{SESSION_HEAD}{SESSION_VERSION}\n\
+            # Recorder: {}\n\
+            # Recorded: {}\n\
+            # OS: {}\n\
+            # CPUs: {}\n\
+            # Configured CPUs: {configured}\n\
+            {os_topology}",
+            self.recorder, self.recorded, self.os, self.cpus,
+        )
+    }
+
+    /// The metadata lines up to the first line not starting with '#'
+    fn parse(session: &str) -> Result<Self, String> {
+        let mut lines = session.lines();
+        let version = lines.next()
+            .and_then(|line| line.strip_prefix(SESSION_HEAD))
+            .ok_or_else(|| "not a session file recorded with \"record\"".to_string())?;
+
+        if version.trim().parse::<u32>() != Ok(SESSION_VERSION) {
+            return Err(format!("unsupported session version \"{version}\", expected {SESSION_VERSION}"));
+        }
+
+        let fields: Vec<(&str, &str)> = lines
+            .map_while(|line| line.strip_prefix("# "))
+            .filter_map(|line| line.split_once(": "))
+            .collect();
+        let field = |key: &str| -> Result<String, String> {
+            fields.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.trim().to_string())
+                .ok_or_else(|| format!("missing \"{key}\" in the session metadata"))
+        };
+
+        Ok(Self {
+            recorder: field("Recorder")?,
+            recorded: field("Recorded")?,
+            os: field("OS")?,
+            cpus: field("CPUs")?,
+            configured_cpus: field("Configured CPUs")?.parse().ok(),
+            /* optional, not in the sessions recorded without sysfs */
+            os_topology: fields.iter().filter_map(|(key, value)| {
+                Some((key.strip_prefix(OS_CPU)?.parse().ok()?, parse_os_topology(value.trim())?))
+            }).collect(),
+        })
+    }
+}
+
+/// "record <path>": the session file of all threads
+pub(crate) fn record_fmt(cpuid_dumps: &[CpuidDump]) -> String {
+    let meta = SessionMeta::current(cpuid_dumps);
+    let body: String = cpuid_dumps.iter().map(|cpuid_dump| cpuid_dump.disp(DumpFormat::Raw, &FormatConfig::default())).collect();
+
+    meta.head() + &body
+}
+
+/// The threads of the session, checking its version
+pub(crate) fn parse_session(session: &str) -> Result<Vec<CpuidDump>, String> {
+    SessionMeta::parse(session)?;
+    load_raw::parse_raw(session)
+}
+
+/// "replay <path>": the session in the format, the same as "-a" on the recorded host
+pub(crate) fn replay_fmt(session: &str, dump_fmt: DumpFormat, cfg: &FormatConfig, diff: bool) -> Result<String, String> {
+    let meta = SessionMeta::parse(session)?;
+    let cpuid_dumps = load_raw::parse_raw(session)?;
+    let summary = format!("recorded {} on {}, CPUs {}, cpuid_dump {}", meta.recorded, meta.os, meta.cpus, meta.recorder);
+    let head = match dump_fmt {
+        _ if !header() => String::new(),
+        f if f.comment_head() => format!("# Session: {summary}\n"),
+        _ => format!("[Session: {summary}]\n"),
+    };
+    let skipped = skipped_cpus_msg_of(cpuid_dumps.len(), meta.configured_cpus);
+
+    Ok(head + &disp_all_threads(&cpuid_dumps, dump_fmt, cfg, diff, skipped))
+}
+
+#[test]
+fn test_session() {
+    let dump = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/alderlake_core_i9_12900k.txt")
+    ).unwrap();
+    let cpuid_dumps = load_raw::parse_raw(&dump).unwrap();
+    let session = record_fmt(&cpuid_dumps);
+    let meta = SessionMeta::parse(&session).unwrap();
+
+    assert_eq!(meta.cpus, "0, 16");
+    assert_eq!(load_raw::parse_raw(&session).unwrap().len(), 2);
+
+    let replay = replay_fmt(&session, DumpFormat::Raw, &FormatConfig::default(), false).unwrap();
+
+    assert!(replay.starts_with(&format!("# Session: recorded {} on {}", meta.recorded, meta.os)));
+    assert_eq!(load_raw::parse_raw(&replay).unwrap()[1].get_cpuid(0x0, 0x0), cpuid_dumps[1].get_cpuid(0x0, 0x0));
+
+    let topo = OsCpuTopology { package_id: 1, die_id: None, core_id: 3, node_id: Some(1) };
+    let meta = SessionMeta { os_topology: vec![(16, topo)], ..meta };
+
+    assert_eq!(SessionMeta::parse(&meta.head()).unwrap(), meta);
+    assert!(meta.head().contains("# OS CPU 16: package 1, die -, core 3, node 1\n"));
+
+    /* the session file is always the raw dump with the headers */
+    let args = |args: &[&str]| crate::MainOpt::parse_args(Default::default(), &args.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+
+    assert!(!args(&["--record", "s.txt", "-no-header"]).unwrap().no_header);
+    assert!(args(&["--record", "s.txt", "-json"]).is_err());
+    assert!(args(&["--record", "s.txt", "-print0"]).is_err());
+
+    assert!(replay_fmt(&dump, DumpFormat::Raw, &FormatConfig::default(), false).is_err());
+    assert!(SessionMeta::parse(&session.replacen("Session: 1", "Session: 2", 1)).is_err());
+}